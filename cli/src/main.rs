@@ -1,15 +1,28 @@
-use batrun::error::Result;
+use batrun::comparison::PreviousRun;
+use batrun::error::{Error, Result};
 use batrun::execution_strategy::ExecutionStrategy;
-use batrun::settings::Settings;
+use batrun::exit_code::{ExitCodes, RunOutcome};
+use batrun::fault_injection::FaultSpec;
+use batrun::interrupt;
+use batrun::paused_run::PausedRun;
+use batrun::regex::Regex;
+use batrun::reporter::json::JsonReporter;
+use batrun::reporter::locale::Locale;
+use batrun::reporter::porcelain::PorcelainReporter;
+use batrun::reporter::tap::TapReporter;
+use batrun::settings::global_config::GlobalConfig;
+use batrun::settings::{ColorChoice, GithubAnnotations, Settings, ShowOutput, Verbosity};
 use batrun::test_runner::TestRunner;
-use batrun::time::format as format_duration;
+use batrun::test_suite::config::TestSuiteConfig;
+use batrun::test_suite::filter::{
+    PathFilter, PathPattern, SpeedFilter, TagFilter, TestFilter, TestPattern,
+};
 
-use clap::Parser;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
 
-use std::path::PathBuf;
-use std::time::Instant;
-
-const DEFAULT_OUT_DIR: &str = "out";
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use clap::builder::styling::{AnsiColor, Color, Style};
 
@@ -47,37 +60,217 @@ pub fn batrun_cli_styles() -> clap::builder::Styles {
         .placeholder(Style::new().fg_color(Some(Color::Ansi(AnsiColor::White))))
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum ListFormat {
+    Human,
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Rewrite the config files of test suites in place, renaming their deprecated keys
+    MigrateConfig {
+        /// Directory where the test suite is located
+        #[arg(required = true, value_name = "TEST_SUITE_DIR")]
+        test_suite: Vec<PathBuf>,
+    },
+    /// Resume a run which was paused, interrupted or stopped by a crash, running the test cases
+    /// which did not complete with the options of the run
+    Resume {
+        /// Output directory of the paused run
+        #[arg(value_name = "OUT_DIR")]
+        out_dir: PathBuf,
+    },
+}
+
 #[derive(Parser, Debug)]
 #[clap(name = "batrun", styles = batrun_cli_styles(), version)]
+#[command(
+    group(ArgGroup::new("list").multiple(true)),
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Directory where the test suite is located
     #[arg(required = true, value_name = "TEST_SUITE_DIR")]
     test_suite: Vec<PathBuf>,
 
-    /// Output directory for logs and data
-    #[arg(short = 'o', long = "out-dir", default_value = DEFAULT_OUT_DIR)]
-    out_dir: PathBuf,
+    /// Output directory for logs and data, each test suite writing to a subdirectory named after
+    /// it (suffixed with `-2`, `-3`... for test suites sharing a name) [default: out]
+    #[arg(short = 'o', long = "out-dir")]
+    out_dir: Option<PathBuf>,
+
+    /// JSON file setting the defaults of some options (out-dir, targets, exec-strategy, retries,
+    /// timeouts, show-output, ...), e.g. '{ "exec-strategy": "parallel" }'; the closest
+    /// `batrun.json` in the current directory and its parents, or in ~/.config/batrun, is used if
+    /// not provided
+    #[arg(long = "config", value_name = "FILE", conflicts_with = "no_config")]
+    config: Option<PathBuf>,
+
+    /// Ignore the config files of batrun, only using the options given on the command line
+    #[arg(long = "no-config")]
+    no_config: bool,
+
+    /// Write the output of each run into its own timestamped directory under the test suite
+    /// output directory, the `latest` symbolic link pointing to the last one, instead of
+    /// overwriting the output of the previous run
+    #[arg(long = "keep-history")]
+    keep_history: bool,
+
+    /// Metadata of the run as KEY=VALUE (e.g. 'build=1234'), recorded in the run manifest and
+    /// the reports so that the results can be joined with the build metadata; may be repeated
+    #[arg(long = "meta", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    run_metadata: Vec<(String, String)>,
 
     /// Targets to run the tests on; select all available targets if not provided
     #[arg(short = 't', long = "target", num_args(0..))]
     targets: Vec<String>,
 
+    /// Only select the test files whose path relative to the test suite directory matches GLOB
+    /// (e.g. 'network/**.sh'); exclude them if GLOB starts with '!'; a directory selects all its
+    /// files
+    #[arg(long = "path", value_name = "GLOB")]
+    paths: Vec<PathPattern>,
+
+    /// Only select the test cases whose id (`path::name`) contains PATTERN, or matches it if it
+    /// is a glob (e.g. '*::test_dhcp_*'); exclude them if PATTERN starts with '!'
+    #[arg(short = 'f', long = "filter", value_name = "PATTERN")]
+    filters: Vec<TestPattern>,
+
+    /// Only select the test cases tagged with TAG (with a `# @tags:` annotation); may be
+    /// repeated to select the test cases having any of the tags
+    #[arg(long = "include-tag", value_name = "TAG")]
+    include_tags: Vec<String>,
+
+    /// Skip the test cases tagged with TAG; may be repeated
+    #[arg(long = "exclude-tag", value_name = "TAG")]
+    exclude_tags: Vec<String>,
+
+    /// Skip the test cases usually taking longer than the slow threshold on previous runs
+    #[arg(long = "skip-slow", conflicts_with = "only_slow")]
+    skip_slow: bool,
+
+    /// Only select the test cases usually taking longer than the slow threshold on previous runs
+    #[arg(long = "only-slow")]
+    only_slow: bool,
+
+    /// Duration beyond which test cases are considered slow by --skip-slow and --only-slow,
+    /// compared to their median duration on previous runs
+    #[arg(long = "slow-threshold", value_name = "DURATION", default_value = "1m", value_parser = parse_duration)]
+    slow_threshold: Duration,
+
     /// List targets supported by the specified test suite
-    #[arg(short = 'L', long = "list-targets")]
+    #[arg(short = 'L', long = "list-targets", group = "list")]
     list_targets: bool,
 
     /// List tests available in the specified test suite
-    #[arg(short = 'l', long = "list-tests")]
+    #[arg(short = 'l', long = "list-tests", group = "list")]
     list_tests: bool,
 
-    /// Select the test cases execution strategy for each target
-    #[arg(value_enum, short = 's', long = "exec-strategy", default_value_t = ExecutionStrategy::RoundRobin)]
-    exec_strategy: ExecutionStrategy,
+    /// Also list the setup and teardown test cases, and the test files they cover
+    #[arg(long = "include-fixtures", requires = "list_tests")]
+    include_fixtures: bool,
+
+    /// Format of the lists of targets and tests: 'human' for the console, or 'json' for tools,
+    /// printing one JSON object per line and per test suite
+    #[arg(value_enum, long = "list-format", value_name = "FORMAT", default_value_t = ListFormat::Human, requires = "list")]
+    list_format: ListFormat,
+
+    /// Print strictly formatted, versioned, tab-separated records instead of the console output,
+    /// for scripts: lists, results and statistics, but no durations
+    #[arg(long = "porcelain", conflicts_with = "list_format")]
+    porcelain: bool,
 
-    /// Go through all tests but execute nothing
+    /// Print the results in the Test Anything Protocol (TAP) version 13 instead of the console
+    /// output, as the test cases complete, for TAP consumers such as prove
+    #[arg(long = "report-tap", conflicts_with_all = ["porcelain", "list"])]
+    report_tap: bool,
+
+    /// When to also print the failures, warnings and errors as GitHub Actions annotations:
+    /// 'auto' when the GITHUB_ACTIONS environment variable is 'true' and the console output is
+    /// used, 'always' or 'never' [default: auto]
+    #[arg(value_enum, long = "github-annotations", value_name = "WHEN")]
+    github_annotations: Option<GithubAnnotations>,
+
+    /// Select the test cases execution strategy for each target [default: round-robin]
+    #[arg(value_enum, short = 's', long = "exec-strategy")]
+    exec_strategy: Option<ExecutionStrategy>,
+
+    /// Maximum number of targets run at the same time with the parallel execution strategy;
+    /// remaining targets are queued until a running one is done (default: all targets at once)
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+
+    /// Maximum number of test files annotated `# @independent: true` run at the same time on each
+    /// target, test cases of a file still running in order between its setup and teardown; ignored
+    /// by the round-robin execution strategy [default: 1]
+    #[arg(long = "file-jobs", value_name = "N")]
+    file_jobs: Option<NonZeroUsize>,
+
+    /// Go through all tests but execute nothing, estimating how long the run would take from the
+    /// expected durations of the test cases
     #[arg(short = 'n', long = "dry-run")]
     dry_run: bool,
 
+    /// Stop at the first failed test case, skipping the remaining ones (teardowns of what was
+    /// already set up still run)
+    #[arg(long = "fail-fast")]
+    fail_fast: bool,
+
+    /// Pause the run as soon as FILE exists, as when batrun receives SIGUSR1: the test cases
+    /// running finish but no new one starts (teardowns of what was already set up still run),
+    /// and the progress is saved in the output directory for `batrun resume`
+    #[arg(long = "pause-file", value_name = "FILE")]
+    pause_file: Option<PathBuf>,
+
+    /// Fail the run when a test case expected to fail (`xfail` in the test suite config, or
+    /// BATRUN_XFAIL set by the test case) passes
+    #[arg(long = "strict-xfail")]
+    strict_xfail: bool,
+
+    /// Fail the run when a test case is skipped by itself (e.g. BATRUN_SKIPPED) or because files
+    /// it needs are missing, e.g. for release runs where all test cases must actually run; the
+    /// skips declared in the test suite config are still allowed
+    #[arg(long = "strict-skips")]
+    strict_skips: bool,
+
+    /// With '--strict-skips', still allow the test cases tagged with TAG to skip themselves
+    #[arg(long = "allow-skips", value_name = "TAG", requires = "strict_skips")]
+    allowed_skip_tags: Vec<String>,
+
+    /// Fail the test cases which leave processes running once done (on Linux, where they are
+    /// detected and reported as warnings); setups are expected to leave services running for the
+    /// next test cases
+    #[arg(long = "strict-orphans")]
+    strict_orphans: bool,
+
+    /// Kill the processes left running by the test cases once done, setups excepted
+    #[arg(long = "kill-orphans")]
+    kill_orphans: bool,
+
+    /// Compare the results with the ones of a previous run of the test suite of the same name,
+    /// given by its run manifest (e.g. 'out/my-suite/run.json'), reporting the test cases which
+    /// newly fail, are fixed or are newly skipped
+    #[arg(long = "compare-with", value_name = "MANIFEST", value_parser = parse_previous_run)]
+    compare_with: Option<PreviousRun>,
+
+    /// Only fail the run for the test cases which fail while they did not in the run compared
+    /// with
+    #[arg(long = "fail-only-on-regressions", requires = "compare_with")]
+    fail_only_on_regressions: bool,
+
+    /// Only print the failures, warnings, errors and summaries, e.g. on CI
+    #[arg(short = 'q', long = "quiet", conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Also print the log files, artifacts and timings of all test cases, and stream their
+    /// output unless '--show-output' is given
+    #[arg(short = 'v', long = "verbose")]
+    verbose: bool,
+
     /// Output additional logs helping to debug batrun itself
     #[arg(short = 'd', long = "debug")]
     debug: bool,
@@ -85,54 +278,403 @@ struct Cli {
     /// Output the summary using a matrix format with test cases in rows and targets in columns
     #[arg(short = 'm', long = "matrix-summary")]
     matrix_summary: bool,
+
+    /// Print the output of each target as a single block once the target is done, instead of
+    /// interleaving the output of targets run concurrently
+    #[arg(short = 'g', long = "group-output")]
+    group_output: bool,
+
+    /// Print plain text for screen readers and dumb terminals: no colors, no box-drawing
+    /// characters, no progress status line, and a matrix summary made of aligned status words
+    #[arg(long = "plain")]
+    plain: bool,
+
+    /// When to color the messages: 'auto' when printing to a terminal and the NO_COLOR
+    /// environment variable is not set, 'always' or 'never' [default: auto]
+    #[arg(value_enum, long = "color", value_name = "WHEN")]
+    color: Option<ColorChoice>,
+
+    /// Which outputs of the test cases to print besides writing them to their log files: 'never',
+    /// 'failed' to print the last lines of the output of failed test cases once they are done, or
+    /// 'all' to stream the output of all test cases as they run [default: failed]
+    #[arg(value_enum, long = "show-output", value_name = "WHEN")]
+    show_output: Option<ShowOutput>,
+
+    /// Number of lines of the output of a failed test case printed with '--show-output failed',
+    /// 0 to print all of them [default: 20]
+    #[arg(long = "output-lines", value_name = "N")]
+    output_lines: Option<usize>,
+
+    /// Also write the execution summaries to FILE, e.g. for wrapper scripts
+    #[arg(long = "summary-file", value_name = "FILE")]
+    summary_file: Option<PathBuf>,
+
+    /// Also write the execution summaries to the file descriptor N opened by the caller, e.g.
+    /// `--summary-fd 3 3>summary.txt`
+    #[arg(long = "summary-fd", value_name = "N", conflicts_with = "summary_file")]
+    summary_fd: Option<u32>,
+
+    /// Write the events of the run to FILE as JSON lines as they happen (test-suite-started,
+    /// test-case-started, test-case-finished, test-suite-finished...), e.g. for dashboards and IDEs
+    /// following the run; a file descriptor opened by the caller is given as /dev/fd/N
+    #[arg(long = "events", value_name = "FILE")]
+    events_file: Option<PathBuf>,
+
+    /// Also write the results in the format of Allure to the 'allure-results' directory of the
+    /// output directory, one file per test case and target with its setups and teardowns as steps
+    /// and its log files as attachments, e.g. for 'allure generate'
+    #[arg(long = "allure-results")]
+    allure_results: bool,
+
+    /// JSON file mapping the outcome of the run (passed, failed, runner-failed, paused,
+    /// interrupted, error) to the exit code of batrun, e.g. '{ "failed": 0, "runner-failed": 2 }'
+    #[arg(long = "exit-codes", value_name = "FILE", value_parser = parse_exit_codes)]
+    exit_codes: Option<ExitCodes>,
+
+    /// Exit with the exit code of passed runs even if test cases failed or could not be run, e.g.
+    /// when the results are checked from the reports only
+    #[arg(long = "no-fail-exit-code")]
+    no_fail_exit_code: bool,
+
+    /// Language of the messages, e.g. 'en' or 'fr' (default: from the LC_ALL, LC_MESSAGES or LANG
+    /// environment variables, English if not supported)
+    #[arg(long = "locale", value_name = "LOCALE")]
+    locale: Option<Locale>,
+
+    /// Warn about test cases taking longer than FACTOR times their expected duration (declared
+    /// with `# @expected-duration:` or the 95th percentile of previous runs)
+    #[arg(long = "slow-factor", value_name = "FACTOR", default_value_t = Settings::DEFAULT_SLOW_FACTOR)]
+    slow_factor: f64,
+
+    /// Run failed test cases again up to N times, reporting those passing on a retry as flaky
+    /// (overrides the `retries` of the test suite config)
+    #[arg(long = "retries", value_name = "N")]
+    retries: Option<u32>,
+
+    /// Kill test cases running for longer than DURATION, overriding the timeouts declared with
+    /// `# @timeout:` in test cases and test files, and with `timeout` in the test suite config
+    #[arg(long = "timeout", value_name = "DURATION", value_parser = parse_duration)]
+    timeout: Option<Duration>,
+
+    /// Kill setups and teardowns running for longer than DURATION, overriding the `# @timeout:`
+    /// of the fixtures and the `fixture-timeout` of the test suite config (the test case timeouts
+    /// apply to fixtures if not set)
+    #[arg(long = "fixture-timeout", value_name = "DURATION", value_parser = parse_duration)]
+    fixture_timeout: Option<Duration>,
+
+    /// Give the test processes killed after their timeout (or because the run was interrupted)
+    /// DURATION to exit once sent SIGTERM, before sending them SIGKILL, 0 sending SIGKILL right
+    /// away [default: 5s]
+    #[arg(long = "kill-grace-period", value_name = "DURATION", value_parser = parse_duration)]
+    kill_grace_period: Option<Duration>,
+
+    /// Run the test processes with only the environment variables given with '--env-pass' or
+    /// listed in the `env-pass` of the test suite config (and PATH), instead of the whole
+    /// environment of batrun
+    #[arg(long = "clean-env")]
+    clean_env: bool,
+
+    /// Forward the environment variable VAR to the test processes in clean environment mode; may
+    /// be repeated
+    #[arg(long = "env-pass", value_name = "VAR", value_parser = parse_env_var_name)]
+    env_pass: Vec<String>,
+
+    /// Set the environment variable KEY to VALUE in all the test processes, overriding the
+    /// environment of batrun and the `env` of the test suite config; may be repeated
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    env: Vec<(String, String)>,
+
+    /// Redact whatever matches the regular expression REGEX (e.g. 'token=\w+') from the logs of
+    /// the test cases, in addition to the `redact` rules of the test suite config; may be repeated
+    #[arg(long = "redact", value_name = "REGEX")]
+    redact: Vec<Regex>,
+
+    /// File listing additional targets, one per line, polled during the run so that targets
+    /// attached later on (e.g. hotplugged boards) join it
+    #[arg(long = "targets-file", value_name = "FILE")]
+    targets_file: Option<PathBuf>,
+
+    /// How long to keep waiting for new targets in the targets file once all known targets are
+    /// done (e.g. 30s, 5m)
+    #[arg(long = "targets-wait", value_name = "DURATION", default_value = "0s", value_parser = parse_duration)]
+    targets_wait: Duration,
+
+    /// Shuffle the order in which targets are serviced, using SEED or a random seed if not
+    /// provided; the seed is printed so that the order can be reproduced
+    #[arg(long = "shuffle-targets", value_name = "SEED", num_args(0..=1))]
+    shuffle_targets: Option<Option<u64>>,
+
+    /// Developer mode injecting a fault to exercise the error paths of batrun, as
+    /// <fault>@<n>: 'driver-io-error' or 'timeout' for the n-th test case run by the test driver,
+    /// or 'reporter-panic' for the n-th test case result reported
+    #[arg(long = "inject-fault", value_name = "SPEC", num_args(1..), hide = true)]
+    inject_faults: Vec<FaultSpec>,
+
+    /// Arguments of the command line, saved if the run is paused
+    #[arg(skip)]
+    command_line: Vec<String>,
+
+    /// The paused run resumed by this one, if any
+    #[arg(skip)]
+    resumed_run: Option<PausedRun>,
 }
 
-impl From<&Cli> for Settings {
-    fn from(cli: &Cli) -> Self {
+/// The path of a file descriptor opened by the parent process (e.g. `3>summary.txt`)
+fn fd_path(fd: u32) -> PathBuf {
+    PathBuf::from(format!("/dev/fd/{fd}"))
+}
+
+fn parse_exit_codes(path: &str) -> std::result::Result<ExitCodes, String> {
+    ExitCodes::load(path.as_ref()).map_err(|error| match error {
+        Error::InvalidExitCodes(error) => format!("{error}: {}", error.details),
+        error => error.to_string(),
+    })
+}
+
+fn parse_previous_run(path: &str) -> std::result::Result<PreviousRun, String> {
+    PreviousRun::load(path.as_ref()).map_err(|error| match error {
+        Error::InvalidPreviousRun(error) => format!("{error}: {}", error.details),
+        error => error.to_string(),
+    })
+}
+
+fn parse_env_var_name(name: &str) -> std::result::Result<String, String> {
+    if name.is_empty() || name.contains('=') {
+        return Err(format!("invalid environment variable name `{name}`"));
+    }
+    Ok(name.to_string())
+}
+
+fn parse_key_value(key_value: &str) -> std::result::Result<(String, String), String> {
+    match key_value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got `{key_value}`")),
+    }
+}
+
+fn parse_duration(duration: &str) -> std::result::Result<Duration, String> {
+    batrun::time::parse(duration).ok_or_else(|| format!("invalid duration `{duration}`"))
+}
+
+impl Cli {
+    /// The config file of batrun applying to the run, if any
+    fn global_config(&self) -> Result<GlobalConfig> {
+        if self.no_config {
+            return Ok(GlobalConfig::default());
+        }
+        let path = match &self.config {
+            Some(path) => Some(path.clone()),
+            None => std::env::current_dir()
+                .ok()
+                .and_then(|dir| GlobalConfig::discover(&dir)),
+        };
+        path.map_or_else(
+            || Ok(GlobalConfig::default()),
+            |path| GlobalConfig::load(&path),
+        )
+    }
+
+    /// The settings of the run: the options given on the command line, or else the ones of the
+    /// config file of batrun, or else the defaults
+    fn settings(&self, global_config: &GlobalConfig) -> Settings {
+        let defaults = Settings::new(self.test_suite.clone()).with_global_config(global_config);
         Settings {
-            test_suite_dirs: cli.test_suite.clone(),
-            out_dir: cli.out_dir.clone(),
-            targets: cli.targets.clone(),
-            exec_strategy: cli.exec_strategy,
-            dry_run: cli.dry_run,
-            test_filter: None,
-            debug: cli.debug,
-            matrix_summary: cli.matrix_summary,
+            out_dir: self.out_dir.clone().unwrap_or(defaults.out_dir),
+            keep_history: self.keep_history || defaults.keep_history,
+            run_metadata: self.run_metadata.iter().cloned().collect(),
+            targets: if self.targets.is_empty() {
+                defaults.targets
+            } else {
+                self.targets.clone()
+            },
+            exec_strategy: self.exec_strategy.unwrap_or(defaults.exec_strategy),
+            jobs: self.jobs.or(defaults.jobs),
+            file_jobs: self.file_jobs.unwrap_or(defaults.file_jobs),
+            dry_run: self.dry_run,
+            fail_fast: self.fail_fast,
+            strict_xfail: self.strict_xfail,
+            strict_skips: self.strict_skips,
+            allowed_skip_tags: self.allowed_skip_tags.clone(),
+            strict_orphans: self.strict_orphans,
+            kill_orphans: self.kill_orphans,
+            compare_with: self.compare_with.clone(),
+            fail_only_on_regressions: self.fail_only_on_regressions,
+            test_filter: TestFilter::new(self.filters.clone()),
+            path_filter: PathFilter::new(self.paths.clone()),
+            tag_filter: TagFilter::new(self.include_tags.clone(), self.exclude_tags.clone()),
+            speed_filter: match (self.skip_slow, self.only_slow) {
+                (true, _) => SpeedFilter::Quick,
+                (_, true) => SpeedFilter::Slow,
+                _ => SpeedFilter::All,
+            },
+            slow_threshold: self.slow_threshold,
+            debug: self.debug,
+            matrix_summary: self.matrix_summary || defaults.matrix_summary,
+            group_output: self.group_output || defaults.group_output,
+            plain: self.plain || defaults.plain,
+            color: self.color.unwrap_or(defaults.color),
+            github_annotations: self
+                .github_annotations
+                .unwrap_or(defaults.github_annotations),
+            verbosity: match (self.quiet, self.verbose) {
+                (true, _) => Verbosity::Quiet,
+                (_, true) => Verbosity::Verbose,
+                _ => Verbosity::Normal,
+            },
+            show_output: self
+                .show_output
+                .or(self.verbose.then_some(ShowOutput::All))
+                .unwrap_or(defaults.show_output),
+            output_lines: self.output_lines.unwrap_or(defaults.output_lines),
+            summary_file: self
+                .summary_file
+                .clone()
+                .or_else(|| self.summary_fd.map(fd_path)),
+            events_file: self.events_file.clone(),
+            allure_results: self.allure_results,
+            locale: self.locale.unwrap_or(defaults.locale),
+            slow_factor: self.slow_factor,
+            retries: self.retries.or(defaults.retries),
+            timeout: self.timeout.or(defaults.timeout),
+            fixture_timeout: self.fixture_timeout.or(defaults.fixture_timeout),
+            kill_grace_period: self.kill_grace_period.unwrap_or(defaults.kill_grace_period),
+            clean_env: self.clean_env,
+            env_pass: self.env_pass.clone(),
+            env: self.env.iter().cloned().collect(),
+            redact: self.redact.clone(),
+            targets_file: self.targets_file.clone(),
+            targets_wait: self.targets_wait,
+            target_shuffle_seed: self
+                .shuffle_targets
+                .map(|seed| seed.unwrap_or_else(batrun::rng::random_seed)),
+            pause_file: self.pause_file.clone(),
+            command_line: self.command_line.clone(),
+            resumed_run: self.resumed_run.clone(),
+            inject_faults: self.inject_faults.clone(),
+            ..defaults
+        }
+    }
+}
+
+/// Migrate the config files of the given test suites, printing the keys renamed in each of them
+fn migrate_config(test_suite_dirs: &[PathBuf]) -> RunOutcome {
+    let mut outcome = RunOutcome::Passed;
+    for test_suite_dir in test_suite_dirs {
+        let config_path = TestSuiteConfig::path_in(test_suite_dir);
+        match TestSuiteConfig::migrate(test_suite_dir) {
+            Ok(deprecations) if deprecations.is_empty() => {
+                println!("`{}` is up to date", config_path.display());
+            }
+            Ok(deprecations) => {
+                println!("`{}` migrated:", config_path.display());
+                for deprecation in deprecations {
+                    println!(
+                        "  `{}` renamed to `{}`",
+                        deprecation.key, deprecation.replacement
+                    );
+                }
+            }
+            Err(error) => {
+                let details = std::error::Error::source(&error)
+                    .map(|source| format!(": {source}"))
+                    .unwrap_or_default();
+                eprintln!("Error: {error}{details}");
+                outcome = RunOutcome::Error;
+            }
         }
     }
+    outcome
 }
 
-fn main_impl() -> Result<()> {
-    let cli = Cli::parse();
-    let mut test_runner = TestRunner::new(Settings::from(&cli))?;
+/// The command line of the run paused with the given output directory, from the directory the
+/// run was started from, to resume it
+fn resumed_cli(out_dir: &Path) -> std::result::Result<Cli, String> {
+    let out_dir = std::path::absolute(out_dir).map_err(|io_err| io_err.to_string())?;
+    let paused_run = PausedRun::load(&out_dir).map_err(|error| match error {
+        Error::InvalidPausedRun(error) => format!("{error}: {}", error.details),
+        error => error.to_string(),
+    })?;
+    std::env::set_current_dir(&paused_run.working_dir).map_err(|io_err| {
+        format!(
+            "cannot resume the run from `{}`: {io_err}",
+            paused_run.working_dir.display()
+        )
+    })?;
+    let args = std::iter::once("batrun".to_string()).chain(paused_run.args.iter().cloned());
+    let mut cli = Cli::try_parse_from(args).map_err(|clap_err| clap_err.to_string())?;
+    cli.out_dir = Some(out_dir);
+    cli.command_line = paused_run.args.clone();
+    cli.resumed_run = Some(paused_run);
+    Ok(cli)
+}
+
+fn main_impl(cli: &Cli) -> Result<RunOutcome> {
+    if let Some(Command::MigrateConfig { test_suite }) = &cli.command {
+        return Ok(migrate_config(test_suite));
+    }
+
+    let global_config = match cli.global_config() {
+        Ok(global_config) => global_config,
+        Err(error) => {
+            let details = match &error {
+                Error::InvalidGlobalConfig(error) => format!(": {}", error.details),
+                _ => String::new(),
+            };
+            eprintln!("Error: {error}{details}");
+            return Ok(RunOutcome::Error);
+        }
+    };
+    let settings = cli.settings(&global_config);
+    let mut test_runner = if cli.porcelain {
+        TestRunner::with_reporter(settings, Box::new(PorcelainReporter::new()))?
+    } else if cli.report_tap {
+        TestRunner::with_reporter(settings, Box::new(TapReporter::new()))?
+    } else {
+        match cli.list_format {
+            ListFormat::Human => TestRunner::new(settings)?,
+            ListFormat::Json => TestRunner::with_reporter(settings, Box::new(JsonReporter::new()))?,
+        }
+    };
 
     let start = Instant::now();
-    {
-        for test_suite_dir in test_runner.settings().test_suite_dirs.clone() {
-            let mut run_tests = true;
+    let outcome = if cli.list_targets || cli.list_tests {
+        for test_suite_dir in &test_runner.settings().test_suite_dirs {
             if cli.list_targets {
-                run_tests = false;
-                test_runner.list_targets(&test_suite_dir)?
+                test_runner.list_targets(test_suite_dir)?
             }
             if cli.list_tests {
-                run_tests = false;
-                test_runner.list_tests(&test_suite_dir)?
-            }
-            if run_tests {
-                test_runner.run_tests(&test_suite_dir)?
+                test_runner.list_tests(test_suite_dir, cli.include_fixtures)?
             }
         }
-    }
-    let duration = start.elapsed();
-    println!();
-    println!("Time elapsed: {}", format_duration(duration));
+        RunOutcome::Passed
+    } else {
+        test_runner.run_all()?
+    };
+    test_runner.report_total_time(start.elapsed());
 
-    Ok(())
+    Ok(outcome)
 }
 
 fn main() -> std::process::ExitCode {
-    match main_impl() {
-        Err(_) => std::process::ExitCode::FAILURE,
-        Ok(_) => std::process::ExitCode::SUCCESS,
+    let mut cli = Cli::parse();
+    cli.command_line = std::env::args_os()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    if let Some(Command::Resume { out_dir }) = &cli.command {
+        match resumed_cli(out_dir) {
+            Ok(resumed_cli) => cli = resumed_cli,
+            Err(message) => {
+                eprintln!("Error: {message}");
+                return std::process::ExitCode::from(ExitCodes::default().error);
+            }
+        }
+    }
+    interrupt::install_handler();
+    let outcome = main_impl(&cli).unwrap_or(RunOutcome::Error);
+    let mut exit_codes = cli.exit_codes.unwrap_or_default();
+    if cli.no_fail_exit_code {
+        exit_codes = exit_codes.ignoring_failures();
     }
+    std::process::ExitCode::from(exit_codes.code(outcome))
 }
@@ -0,0 +1,1690 @@
+//! End-to-end tests running the batrun binary on the sample test suites of `tests/fixtures`
+//!
+//! The runs are checked through the `--porcelain` output, the exit code and the run manifest,
+//! their output directories being written to the temporary directory of Cargo.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Outcome of a run of batrun on a sample test suite
+struct Run {
+    exit_code: Option<i32>,
+    /// The porcelain records, without the version line
+    records: Vec<String>,
+    out_dir: PathBuf,
+}
+
+impl Run {
+    /// The records of the given kind, without their kind
+    fn records(&self, kind: &str) -> Vec<&str> {
+        self.records
+            .iter()
+            .filter_map(|record| record.strip_prefix(kind)?.strip_prefix('\t'))
+            .collect()
+    }
+
+    /// The run manifest of the test suite with the given name
+    fn manifest(&self, test_suite_name: &str) -> serde_json::Value {
+        let path = self.out_dir.join(test_suite_name).join("run.json");
+        let contents = std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("cannot read {}: {err}", path.display()));
+        serde_json::from_str(&contents).unwrap()
+    }
+}
+
+/// The root of the repository, the sample test suites being given relative to it so that the
+/// records are the same wherever the repository is
+fn repo_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap()
+}
+
+/// The directory of the files written by the tests, created if missing so that the tests can
+/// write their inputs there before running batrun
+fn e2e_dir() -> PathBuf {
+    let e2e_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("e2e");
+    std::fs::create_dir_all(&e2e_dir).unwrap();
+    e2e_dir
+}
+
+/// The emptied output directory of the current test, each test having its own one, the test
+/// threads being named after them
+fn out_dir(test_suite: &str) -> PathBuf {
+    let test_name = std::thread::current()
+        .name()
+        .unwrap_or(test_suite)
+        .to_string();
+    let out_dir = e2e_dir().join(test_name);
+    let _ = std::fs::remove_dir_all(&out_dir);
+    out_dir
+}
+
+/// Run batrun in porcelain mode on the given sample test suite, on the `local` target
+fn batrun(test_suite: &str, args: &[&str]) -> Run {
+    batrun_on(test_suite, &["local"], args)
+}
+
+/// Run batrun in porcelain mode on the given sample test suite and targets
+fn batrun_on(test_suite: &str, targets: &[&str], args: &[&str]) -> Run {
+    let out_dir = out_dir(test_suite);
+    let output = Command::new(env!("CARGO_BIN_EXE_batrun"))
+        .current_dir(repo_dir())
+        .arg(Path::new("tests/fixtures").join(test_suite))
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .arg("--target")
+        .args(targets)
+        .arg("--porcelain")
+        .args(args)
+        .output()
+        .expect("cannot run batrun");
+    let stdout = String::from_utf8(output.stdout).expect("porcelain output is not UTF-8");
+    let mut lines = stdout.lines();
+    // Nothing at all is printed if batrun stops before reporting anything
+    if !stdout.is_empty() {
+        assert_eq!(lines.next(), Some("version\t1"), "output: {stdout}");
+    }
+    Run {
+        exit_code: output.status.code(),
+        records: lines.map(String::from).collect(),
+        out_dir,
+    }
+}
+
+/// Run batrun on the given sample test suite and targets, returning its exit code and the
+/// console output, uncolored unless asked to as it is not printed to a terminal
+fn batrun_console(test_suite: &str, targets: &[&str], args: &[&str]) -> (Option<i32>, String) {
+    let out_dir = out_dir(test_suite);
+    let output = Command::new(env!("CARGO_BIN_EXE_batrun"))
+        .current_dir(repo_dir())
+        .arg(Path::new("tests/fixtures").join(test_suite))
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .arg("--target")
+        .args(targets)
+        .args(args)
+        .output()
+        .expect("cannot run batrun");
+    let stdout = String::from_utf8(output.stdout).expect("console output is not UTF-8");
+    (output.status.code(), stdout)
+}
+
+/// The contents of the log file of the given kind of a test case, from the run manifest
+fn log_file(manifest: &serde_json::Value, test_case: usize, kind: &str) -> String {
+    let log_files = manifest["targets"][0]["test-cases"][test_case]["log-files"]
+        .as_array()
+        .unwrap();
+    let log_file = log_files
+        .iter()
+        .find(|log_file| log_file["kind"] == kind)
+        .unwrap_or_else(|| panic!("no {kind} log in {log_files:?}"));
+    std::fs::read_to_string(log_file["path"].as_str().unwrap()).unwrap()
+}
+
+#[test]
+fn passing_test_suite() {
+    let run = batrun("passing", &[]);
+    assert_eq!(run.exit_code, Some(0));
+    assert_eq!(
+        run.records("suite"),
+        ["tests/fixtures/passing\tbatrun-fixture-passing"]
+    );
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\ttests.sh::test_01_ok\tpassed\t1\t",
+            "local\ttests.sh::test_02_ok\tpassed\t1\t",
+        ]
+    );
+    assert_eq!(
+        run.records("stats"),
+        ["local\tpassed=2\tfailed=0\trunner-failed=0\tskipped=0\txfailed=0\txpassed=0"]
+    );
+    assert_eq!(run.records("runner-health"), ["warnings=0\terrors=0"]);
+    let manifest = run.manifest("batrun-fixture-passing");
+    assert_eq!(manifest["targets"][0]["statistics"]["passed"], 2);
+}
+
+#[test]
+fn failing_test_suite() {
+    let run = batrun("failing", &[]);
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\ttests.sh::test_01_ok\tpassed\t1\t",
+            "local\ttests.sh::test_02_fail\tfailed\t1\ttest-case-failure",
+        ]
+    );
+    let manifest = run.manifest("batrun-fixture-failing");
+    let log = log_file(&manifest, 1, "test");
+    assert!(
+        log.contains("Test 02 failing on target local"),
+        "log: {log}"
+    );
+}
+
+#[test]
+fn comparison_with_previous_run() {
+    let run = batrun("failing", &[]);
+    let mut manifest = run.manifest("batrun-fixture-failing");
+    let previous_run = e2e_dir().join("previous-run.json");
+    std::fs::write(&previous_run, manifest.to_string()).unwrap();
+    let previous_run = previous_run.to_str().unwrap();
+    let run = batrun("failing", &["--compare-with", previous_run]);
+    assert_eq!(run.exit_code, Some(2));
+    assert!(
+        run.records("change").is_empty(),
+        "records: {:?}",
+        run.records
+    );
+    let run = batrun(
+        "failing",
+        &["--compare-with", previous_run, "--fail-only-on-regressions"],
+    );
+    assert_eq!(run.exit_code, Some(0));
+
+    // The first test case failed and the second one passed in the previous run
+    let test_cases = &mut manifest["targets"][0]["test-cases"];
+    test_cases[0]["status"] = "failed".into();
+    test_cases[1]["status"] = "passed".into();
+    let previous_run = e2e_dir().join("regressed-run.json");
+    std::fs::write(&previous_run, manifest.to_string()).unwrap();
+    let previous_run = previous_run.to_str().unwrap();
+    let run = batrun(
+        "failing",
+        &["--compare-with", previous_run, "--fail-only-on-regressions"],
+    );
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("change"),
+        [
+            "local\ttests.sh::test_01_ok\tfixed\tfailed",
+            "local\ttests.sh::test_02_fail\tnew-failure\tpassed",
+        ]
+    );
+    let comparison = &run.manifest("batrun-fixture-failing")["comparison"];
+    assert_eq!(comparison["changes"][1]["kind"], "new-failure");
+    assert_eq!(comparison["changes"][1]["id"], "tests.sh::test_02_fail");
+}
+
+#[test]
+fn failing_test_suite_ignoring_failures() {
+    let run = batrun("failing", &["--no-fail-exit-code"]);
+    assert_eq!(run.exit_code, Some(0));
+}
+
+#[test]
+fn skipping_test_suite() {
+    let run = batrun("skipping", &[]);
+    assert_eq!(run.exit_code, Some(0));
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\ttests.sh::test_01_ok\tpassed\t1\t",
+            "local\ttests.sh::test_02_skipped\tskipped\t1\t\
+             test-case-specific-reason: Nothing to test on this target",
+            "local\ttests.sh::test_03_skipped_by_config\tskipped\t1\t\
+             incompatible-with-target: not supported by the local target",
+        ]
+    );
+    let manifest = run.manifest("batrun-fixture-skipping");
+    let test_cases = &manifest["targets"][0]["test-cases"];
+    assert_eq!(
+        test_cases[1]["reason"],
+        "test-case-specific-reason: Nothing to test on this target"
+    );
+}
+
+#[test]
+fn strict_skips() {
+    let run = batrun("skipping", &["--strict-skips"]);
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("result")[1..],
+        [
+            "local\ttests.sh::test_02_skipped\tfailed\t1\t\
+             unexpected-skip: Nothing to test on this target",
+            "local\ttests.sh::test_03_skipped_by_config\tskipped\t1\t\
+             incompatible-with-target: not supported by the local target",
+        ]
+    );
+    let run = batrun("skipping", &["--strict-skips", "--allow-skips", "optional"]);
+    assert_eq!(run.exit_code, Some(0));
+    assert_eq!(
+        run.records("result")[1],
+        "local\ttests.sh::test_02_skipped\tskipped\t1\t\
+         test-case-specific-reason: Nothing to test on this target"
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn orphans() {
+    let run = batrun("orphans", &["--kill-orphans"]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    let warnings = run.records("warning");
+    assert_eq!(warnings.len(), 1, "records: {:?}", run.records);
+    assert!(
+        warnings[0].starts_with("Test case `tests.sh::test_01_leaves_process` left process(es) `"),
+        "{}",
+        warnings[0]
+    );
+    // The process may not have executed `sleep` yet when found
+    assert!(warnings[0].ends_with("` running on target `local`, killed\t"));
+    let run = batrun("orphans", &["--strict-orphans", "--kill-orphans"]);
+    assert_eq!(run.exit_code, Some(2));
+    let results = run.records("result");
+    assert!(
+        results[0].starts_with(
+            "local\ttests.sh::test_01_leaves_process\tfailed\t1\torphaned-processes: "
+        ),
+        "{}",
+        results[0]
+    );
+    assert_eq!(
+        results[1],
+        "local\ttests.sh::test_02_cleans_up\tpassed\t1\t"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn timeouts() {
+    let run = batrun("timeouts", &["--kill-grace-period", "1s"]);
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\ttests.sh::test_01_exits_on_sigterm\tfailed\t1\ttimeout: 1s",
+            "local\ttests.sh::test_02_ignores_sigterm\tfailed\t1\ttimeout: 1s",
+            "local\ttests.sh::test_03_leaves_process_ignoring_sigterm\tfailed\t1\ttimeout: 1s",
+        ]
+    );
+    let manifest = run.manifest("batrun-fixture-timeouts");
+    let killed_by = |index: usize| manifest["targets"][0]["test-cases"][index]["killed-by"].clone();
+    assert_eq!(killed_by(0), "SIGTERM");
+    assert_eq!(killed_by(1), "SIGKILL");
+    // The process it started in the background ignored SIGTERM
+    assert_eq!(killed_by(2), "SIGKILL");
+    let run = batrun(
+        "timeouts",
+        &["--kill-grace-period", "0", "--filter", "test_01"],
+    );
+    let manifest = run.manifest("batrun-fixture-timeouts");
+    assert_eq!(
+        manifest["targets"][0]["test-cases"][0]["killed-by"],
+        "SIGKILL"
+    );
+}
+
+#[test]
+fn fixture_failure_test_suite() {
+    let run = batrun("fixture-failure", &[]);
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\tfixture.sh::setup\tfailed\t1\ttest-case-failure",
+            "local\ttests.sh::test_01_ok\tskipped\t1\ttest-suite-setup-error",
+            "local\tfixture.sh::teardown\tskipped\t1\ttest-suite-setup-error",
+        ]
+    );
+}
+
+#[test]
+fn unicode_test_suite() {
+    let run = batrun("unicode", &[]);
+    assert_eq!(run.exit_code, Some(0));
+    assert_eq!(
+        run.records("result"),
+        ["local\tdossier été/tëst ✓.sh::test_01_ok\tpassed\t1\t"]
+    );
+}
+
+#[test]
+fn filtered_test_suite() {
+    let run = batrun("passing", &["--filter", "tests.sh::test_02_*"]);
+    assert_eq!(run.exit_code, Some(0));
+    assert_eq!(
+        run.records("result"),
+        ["local\ttests.sh::test_02_ok\tpassed\t1\t"]
+    );
+    // Not counted as skipped
+    assert_eq!(
+        run.records("filtered-out"),
+        ["tests/fixtures/passing\ttests.sh::test_01_ok"]
+    );
+    assert_eq!(
+        run.records("stats"),
+        ["local\tpassed=1\tfailed=0\trunner-failed=0\tskipped=0\txfailed=0\txpassed=0"]
+    );
+    let statistics = &run.manifest("batrun-fixture-passing")["targets"][0]["statistics"];
+    assert_eq!(statistics["filtered-out"], 1);
+    assert_eq!(statistics["skipped"], 0);
+}
+
+#[test]
+fn list_tests() {
+    let run = batrun("unicode", &["--list-tests"]);
+    assert_eq!(run.exit_code, Some(0));
+    assert_eq!(
+        run.records("test"),
+        ["tests/fixtures/unicode\ttest\tdossier été/tëst ✓.sh::test_01_ok"]
+    );
+}
+
+#[test]
+fn missing_test_suite() {
+    let run = batrun("missing", &[]);
+    assert_eq!(run.exit_code, Some(1));
+    assert!(
+        !run.records("error").is_empty(),
+        "records: {:?}",
+        run.records
+    );
+}
+
+#[test]
+fn injected_driver_io_error() {
+    let run = batrun("passing", &["--inject-fault", "driver-io-error@2"]);
+    assert_eq!(run.exit_code, Some(3));
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\ttests.sh::test_01_ok\tpassed\t1\t",
+            "local\ttests.sh::test_02_ok\trunner-failed\t1\t\
+             cannot execute test driver command `driver-io-error`",
+        ]
+    );
+}
+
+#[test]
+fn fail_fast_tears_down() {
+    let run = batrun("fail-fast", &["--fail-fast"]);
+    assert_eq!(run.exit_code, Some(2));
+    // What was set up before the failure is torn down, the rest being skipped
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\tfixture.sh::setup\tpassed\t1\t",
+            "local\ta.sh::setup\tpassed\t1\t",
+            "local\ta.sh::test_01_fail\tfailed\t1\ttest-case-failure",
+            "local\ta.sh::test_02_ok\tskipped\t1\taborted-by-fail-fast",
+            "local\ta.sh::teardown\tpassed\t1\t",
+            "local\tb.sh::setup\tskipped\t1\taborted-by-fail-fast",
+            "local\tb.sh::test_01_ok\tskipped\t1\taborted-by-fail-fast",
+            "local\tb.sh::teardown\tskipped\t1\taborted-by-fail-fast",
+            "local\tfixture.sh::teardown\tpassed\t1\t",
+        ]
+    );
+}
+
+#[test]
+fn on_failure_command_timeout() {
+    let run = batrun("on-failure", &[]);
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("warning"),
+        [
+            "On-failure command `sh` of test case `tests.sh::test_01_fail` killed with SIGTERM \
+          after 1s\t"
+        ]
+    );
+    let manifest = run.manifest("batrun-fixture-on-failure");
+    let log_files = &manifest["targets"][0]["test-cases"][0]["log-files"];
+    let on_failure_log = log_files
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|log_file| log_file["kind"] == "on-failure")
+        .unwrap();
+    let log = std::fs::read_to_string(on_failure_log["path"].as_str().unwrap()).unwrap();
+    assert!(
+        log.starts_with("collecting\nbatrun: On-failure command"),
+        "{log}"
+    );
+}
+
+#[test]
+fn injected_timeout() {
+    let run = batrun("passing", &["--inject-fault", "timeout@1", "--fail-fast"]);
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\ttests.sh::test_01_ok\tfailed\t1\ttimeout: 0s",
+            "local\ttests.sh::test_02_ok\tskipped\t1\taborted-by-fail-fast",
+        ]
+    );
+}
+
+#[test]
+fn injected_reporter_panic() {
+    let run = batrun("passing", &["--inject-fault", "reporter-panic@1"]);
+    assert_eq!(run.exit_code, Some(101));
+    assert!(run.records("result").is_empty());
+}
+
+#[test]
+fn xtrace_in_debug_log() {
+    let run = batrun("failing", &[]);
+    let manifest = run.manifest("batrun-fixture-failing");
+    let test_log = log_file(&manifest, 1, "test");
+    assert!(!test_log.contains("+ "), "test log: {test_log}");
+    let debug_log = log_file(&manifest, 1, "debug");
+    assert!(
+        debug_log.contains("+ tests/fixtures/failing/tests.sh:9 (test_02_fail): return 1"),
+        "debug log: {debug_log}"
+    );
+}
+
+#[test]
+fn xtrace_disabled() {
+    let run = batrun("no-xtrace", &[]);
+    assert_eq!(run.exit_code, Some(2));
+    let manifest = run.manifest("batrun-fixture-no-xtrace");
+    let test_log = log_file(&manifest, 1, "test");
+    assert!(test_log.contains("Test 02 failing"), "test log: {test_log}");
+    let debug_log = log_file(&manifest, 1, "debug");
+    assert!(!debug_log.contains("+ "), "debug log: {debug_log}");
+}
+
+#[test]
+fn output_vars() {
+    let run = batrun("output-vars", &[]);
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("message"),
+        ["local\ttests.sh::test_01_message_and_metrics\tBooted in 8.5s"]
+    );
+    assert_eq!(
+        run.records("warning").len(),
+        1,
+        "records: {:?}",
+        run.records
+    );
+    let manifest = run.manifest("batrun-fixture-output-vars");
+    let test_cases = &manifest["targets"][0]["test-cases"];
+    assert_eq!(test_cases[0]["message"], "Booted in 8.5s");
+    assert_eq!(test_cases[0]["metrics"]["boot_time"], 8.5);
+    assert_eq!(test_cases[0]["metrics"]["memory"], 512.0);
+    let artifacts = test_cases[1]["log-files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|log_file| log_file["kind"] == "artifact")
+        .map(|log_file| std::fs::read_to_string(log_file["path"].as_str().unwrap()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(artifacts, ["result\n", "report\n"]);
+}
+
+#[test]
+fn runner_health() {
+    let run = batrun("output-vars", &["--filter", "test_04"]);
+    assert_eq!(run.exit_code, Some(0));
+    assert_eq!(run.records("runner-health"), ["warnings=2\terrors=0"]);
+    let manifest = run.manifest("batrun-fixture-output-vars");
+    assert_eq!(manifest["runner-health"]["warnings"], 2);
+    assert_eq!(manifest["runner-health"]["errors"], 0);
+}
+
+#[test]
+fn late_targets() {
+    let targets_file = e2e_dir().join("late-targets.txt");
+    let _ = std::fs::remove_file(&targets_file);
+    let _ = std::fs::remove_file(targets_file.with_extension("txt.late"));
+    let run = batrun(
+        "late-targets",
+        &[
+            "--targets-file",
+            targets_file.to_str().unwrap(),
+            "--env",
+            &format!("TARGETS_FILE={}", targets_file.display()),
+        ],
+    );
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    // The targets joining the run start while the first ones are still running, each one once
+    let mut targets = run
+        .records("result")
+        .iter()
+        .map(|result| result.split('\t').next().unwrap())
+        .collect::<Vec<_>>();
+    targets.sort();
+    assert_eq!(
+        targets,
+        ["late", "late", "later", "later", "local", "local"]
+    );
+}
+
+#[test]
+fn summary_fd() {
+    // The summary is only written to a file descriptor when asked to, here the standard output
+    let run = batrun("passing", &[]);
+    assert!(
+        !run.records
+            .iter()
+            .any(|line| line.contains("execution summary"))
+    );
+    let run = batrun("passing", &["--summary-fd", "1"]);
+    assert_eq!(run.exit_code, Some(0));
+    assert!(
+        run.records
+            .iter()
+            .any(|line| line == "Test suite `tests/fixtures/passing` execution summary"),
+        "{:?}",
+        run.records
+    );
+}
+
+#[test]
+fn summary_file() {
+    let summary_file = e2e_dir().join("summary.txt");
+    let _ = std::fs::remove_file(&summary_file);
+    let run = batrun(
+        "failing",
+        &["--summary-file", summary_file.to_str().unwrap()],
+    );
+    assert_eq!(run.exit_code, Some(2));
+    let summary = std::fs::read_to_string(&summary_file).unwrap();
+    // Leaving out the timings
+    let lines = summary
+        .lines()
+        .filter(|line| !line.is_empty() && !line.ends_with("ms"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "Test suite `tests/fixtures/failing` execution summary",
+            "  Target: local",
+            "  Statistics: 1 passed, 1 failed, 0 runner failed, 0 skipped",
+        ],
+        "summary: {summary}"
+    );
+}
+
+#[test]
+fn artifacts_dir() {
+    let run = batrun("output-vars", &["--filter", "test_03"]);
+    assert_eq!(run.exit_code, Some(2));
+    let manifest = run.manifest("batrun-fixture-output-vars");
+    let artifacts = manifest["targets"][0]["test-cases"][0]["log-files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|log_file| log_file["kind"] == "artifact")
+        .map(|log_file| {
+            let path = Path::new(log_file["path"].as_str().unwrap());
+            let dir = path.parent().unwrap().file_name().unwrap();
+            let file_name = path.file_name().unwrap();
+            format!("{}/{}", dir.to_string_lossy(), file_name.to_string_lossy())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        artifacts,
+        ["test_03_artifacts_dir.artifacts/core", "traces/trace.txt"]
+    );
+}
+
+#[test]
+fn deprecated_config() {
+    let run = batrun("deprecated-config", &[]);
+    assert_eq!(run.exit_code, Some(0));
+    assert_eq!(
+        run.records("warning"),
+        [
+            "Test suite config `tests/fixtures/deprecated-config/test-suite.json`: \
+             `test-file-pattern` is deprecated since batrun 2.0.0, use `test-file-patterns` \
+             instead.\tRun `batrun migrate-config tests/fixtures/deprecated-config` to update it."
+        ]
+    );
+    // The test file patterns are still taken from the deprecated key
+    assert_eq!(
+        run.records("result"),
+        ["local\ttests.test.sh::test_01_ok\tpassed\t1\t"]
+    );
+    let manifest = run.manifest("batrun-fixture-deprecated-config");
+    assert_eq!(
+        manifest["test-suite"]["deprecations"][0]["key"],
+        "test-file-pattern"
+    );
+}
+
+#[test]
+fn migrate_config() {
+    let test_suite_dir = e2e_dir().join("migrate-config");
+    let _ = std::fs::remove_dir_all(&test_suite_dir);
+    std::fs::create_dir_all(&test_suite_dir).unwrap();
+    let config_path = test_suite_dir.join("test-suite.json");
+    let config = repo_dir().join("tests/fixtures/deprecated-config/test-suite.json");
+    std::fs::copy(config, &config_path).unwrap();
+    let migrate_config = || {
+        Command::new(env!("CARGO_BIN_EXE_batrun"))
+            .arg("migrate-config")
+            .arg(&test_suite_dir)
+            .output()
+            .expect("cannot run batrun")
+    };
+
+    let output = migrate_config();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("`test-file-pattern` renamed to `test-file-patterns`"),
+        "output: {stdout}"
+    );
+    let config = std::fs::read_to_string(&config_path).unwrap();
+    assert!(
+        config.contains("    \"test-file-patterns\": [\"*.test.sh\"],\n"),
+        "config: {config}"
+    );
+
+    let output = migrate_config();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("is up to date"), "output: {stdout}");
+}
+
+#[test]
+fn invalid_driver_options() {
+    let run = batrun("invalid-driver-options", &[]);
+    assert_eq!(run.exit_code, Some(1));
+    let errors = run.records("error");
+    assert_eq!(errors.len(), 1, "records: {:?}", run.records);
+    assert!(
+        errors[0].contains(
+            "invalid `driver-options` for the `bash` driver: unknown field `interpreter`"
+        ),
+        "error: {}",
+        errors[0]
+    );
+}
+
+#[test]
+fn invalid_config() {
+    let run = batrun("invalid-config", &[]);
+    assert_eq!(run.exit_code, Some(1));
+    let errors = run.records("error");
+    assert_eq!(errors.len(), 1, "records: {:?}", run.records);
+    let issues = errors[0].split('\t').nth(1).unwrap_or_default();
+    assert_eq!(
+        issues.split("\\n").collect::<Vec<_>>(),
+        [
+            "line 6, column 5: unknown key `test-file-patern` (did you mean `test-file-patterns`?)",
+            "line 7, column 23: global fixture `fixture.sh` not found (its path is relative to \
+             the test suite directory)",
+            "line 8, column 36: target `local` declared twice in `targets` (remove one of the \
+             declarations)",
+            "line 9, column 24: invalid pattern `tests.sh::[test_01` in `skip`: Pattern syntax \
+             error near position 10: invalid range pattern (special characters are escaped with \
+             brackets, e.g. `[*]`)",
+            "line 10, column 58: invalid pattern `smoke/[a` in `stages`: Pattern syntax error \
+             near position 6: invalid range pattern (special characters are escaped with \
+             brackets, e.g. `[*]`)",
+        ]
+    );
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml_config() {
+    let run = batrun("toml-config", &[]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("suite"),
+        ["tests/fixtures/toml-config\tbatrun-fixture-toml-config"]
+    );
+    assert_eq!(
+        run.records("stats"),
+        ["local\tpassed=2\tfailed=0\trunner-failed=0\tskipped=0\txfailed=0\txpassed=0"]
+    );
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml_config() {
+    let run = batrun("yaml-config", &[]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("suite"),
+        ["tests/fixtures/yaml-config\tbatrun-fixture-yaml-config"]
+    );
+    assert_eq!(
+        run.records("stats"),
+        ["local\tpassed=2\tfailed=0\trunner-failed=0\tskipped=0\txfailed=0\txpassed=0"]
+    );
+}
+
+#[cfg(not(feature = "toml"))]
+#[test]
+fn unsupported_config_format() {
+    let run = batrun("toml-config", &[]);
+    assert_eq!(run.exit_code, Some(1));
+    assert_eq!(
+        run.records("error"),
+        [
+            "invalid test suite config file `tests/fixtures/toml-config/test-suite.toml`\tTOML test \
+          suite configs are not supported by this build of batrun (build batrun with the `toml` \
+          feature, or convert it to JSON as `test-suite.json`)"
+        ]
+    );
+}
+
+#[test]
+fn global_config() {
+    let config = ["--config", "tests/fixtures/global-config/batrun.json"];
+    let run = batrun("failing", &config);
+    assert_eq!(
+        run.records("result")[1],
+        "local\ttests.sh::test_02_fail\tfailed\t2\ttest-case-failure"
+    );
+    // The command line overrides the config file
+    let run = batrun("failing", &[&config[..], &["--retries", "0"]].concat());
+    assert_eq!(
+        run.records("result")[1],
+        "local\ttests.sh::test_02_fail\tfailed\t1\ttest-case-failure"
+    );
+}
+
+#[test]
+fn invalid_global_config() {
+    let run = batrun(
+        "failing",
+        &["--config", "tests/fixtures/global-config/invalid.json"],
+    );
+    assert_eq!(run.exit_code, Some(1));
+    assert!(run.records.is_empty(), "records: {:?}", run.records);
+}
+
+#[test]
+fn setup_exports() {
+    let run = batrun("setup-exports", &[]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    assert_eq!(run.records("result").len(), 6);
+    assert_eq!(
+        run.records("warning"),
+        [
+            "Values exported by test case `a.sh::test_02_export_from_test_case` ignored, only \
+          setups can export values\t"
+        ]
+    );
+}
+
+#[test]
+fn independent_files() {
+    // Each test file waits for the other one to be set up, passing only if both run at once
+    for strategy in ["sequential", "parallel"] {
+        let run = batrun("independent-files", &["--file-jobs", "2", "-s", strategy]);
+        assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+        assert_eq!(run.records("result").len(), 8);
+        let manifest = run.manifest("batrun-fixture-independent-files");
+        assert_eq!(manifest["settings"]["file-jobs"], 2);
+    }
+}
+
+#[test]
+fn same_test_suite_names() {
+    let run = batrun("passing", &["tests/fixtures/same-name"]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    let out_dir = |name: &str| run.out_dir.join(name).display().to_string();
+    assert_eq!(
+        run.records("out-dir"),
+        [
+            format!(
+                "tests/fixtures/passing\t{}",
+                out_dir("batrun-fixture-passing")
+            ),
+            format!(
+                "tests/fixtures/same-name\t{}",
+                out_dir("batrun-fixture-passing-2")
+            ),
+        ]
+    );
+    assert_eq!(run.records("warning").len(), 1);
+    let manifest = run.manifest("batrun-fixture-passing-2");
+    assert_eq!(manifest["test-suite"]["path"], "tests/fixtures/same-name");
+    assert_eq!(
+        manifest["test-suite"]["out-dir"],
+        out_dir("batrun-fixture-passing-2")
+    );
+}
+
+#[test]
+fn telemetry() {
+    let run = batrun("telemetry", &[]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    let manifest = run.manifest("batrun-fixture-telemetry");
+    let telemetry = &manifest["targets"][0]["test-cases"][0]["telemetry"];
+    assert_eq!(telemetry["temperature"]["max"], 61.5);
+    assert_eq!(telemetry["load"]["avg"], 0.8);
+    // Sampled when the test case starts, then every 100ms while it sleeps for 500ms
+    assert!(telemetry["load"]["samples"].as_u64().unwrap() >= 2);
+    let samples = log_file(&manifest, 0, "telemetry");
+    assert!(samples.starts_with("time,load,temperature\n0.000,0.8,61.5\n"));
+}
+
+#[test]
+fn services() {
+    let run = batrun("services", &[]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("result"),
+        ["local\ttests.test.sh::test_01_ok\tpassed\t1\t"]
+    );
+    // Ready before the test suite ran, stopped gracefully once it ran
+    let log = std::fs::read_to_string(run.out_dir.join("batrun-fixture-services/echo.service.log"))
+        .unwrap();
+    assert_eq!(log, "listening\nstopping\n");
+}
+
+#[test]
+fn pause_and_resume() {
+    // The first test case creates the pause file in the output directory
+    let out_dir = e2e_dir().join("pause_and_resume");
+    let pause_file = out_dir.join("pause");
+    let run = batrun("pausing", &["--pause-file", pause_file.to_str().unwrap()]);
+    assert_eq!(run.exit_code, Some(75), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("result")[1..3],
+        [
+            "local\ttests.sh::test_01_pause\tpassed\t1\t",
+            "local\ttests.sh::test_02_after_pause\tskipped\t1\tpaused",
+        ]
+    );
+    assert!(run.out_dir.join("paused-run.json").exists());
+
+    std::fs::remove_file(&pause_file).unwrap();
+    // Resumed from elsewhere, in the directory the run was started from
+    let output = Command::new(env!("CARGO_BIN_EXE_batrun"))
+        .current_dir(&run.out_dir)
+        .args(["resume", "."])
+        .output()
+        .expect("cannot run batrun");
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let results = stdout
+        .lines()
+        .filter(|line| line.starts_with("result\t"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        results[1..3],
+        [
+            "result\tlocal\ttests.sh::test_01_pause\tpassed\t1\t",
+            "result\tlocal\ttests.sh::test_02_after_pause\tpassed\t1\t",
+        ]
+    );
+    // The test case which paused the run did not run again
+    assert!(!pause_file.exists());
+    assert!(!run.out_dir.join("paused-run.json").exists());
+}
+
+#[test]
+fn locks() {
+    // Both test files use the bus, failing if they do at the same time
+    for strategy in ["sequential", "parallel"] {
+        let run = batrun("locks", &["--file-jobs", "2", "-s", strategy]);
+        assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+        assert_eq!(run.records("result").len(), 2);
+    }
+}
+
+#[test]
+fn ports() {
+    // Both test files run at the same time, each with its own ports
+    let run = batrun("ports", &["--file-jobs", "2", "-s", "sequential"]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    let ports = run
+        .records("message")
+        .iter()
+        .flat_map(|record| record.rsplit('\t').next().unwrap().split(' '))
+        .map(|port| port.parse::<u16>().unwrap())
+        .collect::<std::collections::BTreeSet<_>>();
+    assert_eq!(ports.len(), 4, "records: {:?}", run.records);
+}
+
+#[test]
+fn resume_after_crash() {
+    let out_dir = e2e_dir().join("resume_after_crash");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let mut batrun = Command::new(env!("CARGO_BIN_EXE_batrun"))
+        .current_dir(repo_dir())
+        .arg("tests/fixtures/crashing")
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .args(["--target", "local", "--porcelain"])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .expect("cannot run batrun");
+    // Killed while the second test case runs, the first one being saved
+    let killed = out_dir.join("batrun-fixture-crashing/local/killed");
+    for _ in 0..100 {
+        if killed.exists() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    batrun.kill().unwrap();
+    batrun.wait().unwrap();
+    assert!(killed.exists());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_batrun"))
+        .args(["resume"])
+        .arg(&out_dir)
+        .output()
+        .expect("cannot run batrun");
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let results = stdout
+        .lines()
+        .filter(|line| line.starts_with("result\t"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        results,
+        [
+            "result\tlocal\ttests.sh::test_01_ok\tpassed\t1\t",
+            "result\tlocal\ttests.sh::test_02_killed\tpassed\t1\t",
+        ]
+    );
+    assert!(!out_dir.join("paused-run.json").exists());
+}
+
+#[test]
+fn dry_run_estimate() {
+    // The test cases fail if executed
+    let run = batrun("estimating", &["--dry-run"]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\ttests.sh::test_01_known_duration\tdry-run\t1\t",
+            "local\ttests.sh::test_02_unknown_duration\tdry-run\t1\t",
+        ]
+    );
+    assert_eq!(run.records("estimate"), ["local\t2\t1"]);
+    assert_eq!(
+        run.records("no-estimate"),
+        ["local\ttests.sh::test_02_unknown_duration"]
+    );
+}
+
+#[test]
+fn event_stream() {
+    let events_file = e2e_dir().join("events.jsonl");
+    let run = batrun(
+        "passing",
+        &[
+            "--events",
+            events_file.to_str().unwrap(),
+            "--meta",
+            "BUILD=42",
+        ],
+    );
+    assert_eq!(run.exit_code, Some(0));
+    let events = std::fs::read_to_string(&events_file)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|event| !["message", "target-progress"].contains(&event["event"].as_str().unwrap()))
+        .collect::<Vec<_>>();
+    let kinds = events
+        .iter()
+        .map(|event| event["event"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            "run-started",
+            "test-suite-started",
+            "test-case-started",
+            "test-case-finished",
+            "test-case-started",
+            "test-case-finished",
+            "target-finished",
+            "test-suite-finished",
+            "runner-health",
+        ]
+    );
+    assert_eq!(events[8]["warnings"], 0);
+    assert_eq!(events[8]["errors"], 0);
+    assert_eq!(events[0]["metadata"], serde_json::json!({"BUILD": "42"}));
+    assert_eq!(events[1]["test-suite"], "tests/fixtures/passing");
+    assert_eq!(events[3]["test-case"], "tests.sh::test_01_ok");
+    assert_eq!(events[3]["target"], "local");
+    assert_eq!(events[3]["status"], "passed");
+    assert!(events[3]["duration"].is_f64());
+    assert!(events[3]["time"].is_string());
+}
+
+#[test]
+fn terminated() {
+    let out_dir = e2e_dir().join("terminated");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let started_file = out_dir.join("started");
+    let batrun = Command::new(env!("CARGO_BIN_EXE_batrun"))
+        .current_dir(repo_dir())
+        .arg("tests/fixtures/terminated")
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .args(["--target", "local", "--porcelain", "--env"])
+        .arg(format!("STARTED_FILE={}", started_file.display()))
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("cannot run batrun");
+    while !started_file.exists() {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    let kill = Command::new("kill")
+        .args(["-TERM", &batrun.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(kill.success());
+    let start = std::time::Instant::now();
+    let output = batrun.wait_with_output().unwrap();
+    // The test case without timeout is killed right away, with the process it started
+    assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    assert_eq!(output.status.code(), Some(130));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let records = stdout.lines().collect::<Vec<_>>();
+    let results = records
+        .iter()
+        .filter_map(|record| record.strip_prefix("result\t"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        results,
+        [
+            "local\ttests.sh::setup\tpassed\t1\t",
+            "local\ttests.sh::test_01_long\tskipped\t1\tinterrupted",
+            "local\ttests.sh::test_02_after\tskipped\t1\tinterrupted",
+            "local\ttests.sh::teardown\tpassed\t1\t",
+        ]
+    );
+    assert!(
+        !records.iter().any(|record| record.contains("left process")),
+        "records: {records:?}"
+    );
+}
+
+#[test]
+fn simulator_stopped_with_its_processes() {
+    let run = batrun("simulator", &[]);
+    assert_eq!(run.exit_code, Some(0));
+    let console_log = run
+        .out_dir
+        .join("batrun-fixture-simulator/local/simulator.console.log");
+    let console = std::fs::read_to_string(&console_log).unwrap();
+    let board_pid = console
+        .lines()
+        .find_map(|line| line.strip_prefix("board pid: "))
+        .unwrap();
+    // The process started by the simulator is killed along with it
+    assert!(!Path::new("/proc").join(board_pid).exists(), "{console}");
+}
+
+#[test]
+fn tap_report() {
+    let out_dir = e2e_dir().join("tap_report");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let output = Command::new(env!("CARGO_BIN_EXE_batrun"))
+        .current_dir(repo_dir())
+        .arg("tests/fixtures/skipping")
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .args(["--target", "local", "--report-tap", "--meta", "BUILD=42"])
+        .output()
+        .expect("cannot run batrun");
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines = stdout
+        .lines()
+        .filter(|line| !line.starts_with("  "))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "TAP version 13",
+            "# meta: BUILD=42",
+            "ok 1 - tests.sh::test_01_ok on local",
+            "ok 2 - tests.sh::test_02_skipped on local \
+             # SKIP test-case-specific-reason: Nothing to test on this target",
+            "ok 3 - tests.sh::test_03_skipped_by_config on local \
+             # SKIP incompatible-with-target: not supported by the local target",
+            "1..3",
+            "# runner-health: warnings=0 errors=0",
+        ]
+    );
+    assert!(stdout.contains("  target: \"local\"\n  duration_ms: "));
+}
+
+#[test]
+fn shell_options() {
+    let run = batrun("legacy-shell", &[]);
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\ttests.sh::test_01_legacy\tpassed\t1\t",
+            "local\ttests.sh::test_02_errexit\tfailed\t1\ttest-case-failure",
+        ]
+    );
+    let manifest = run.manifest("batrun-fixture-legacy-shell");
+    let test_cases = &manifest["targets"][0]["test-cases"];
+    assert_eq!(
+        test_cases[0]["shell-options"],
+        serde_json::json!(["pipefail"])
+    );
+    assert_eq!(
+        test_cases[1]["shell-options"],
+        serde_json::json!(["errexit"])
+    );
+}
+
+#[test]
+fn github_annotations() {
+    let out_dir = e2e_dir().join("github_annotations");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let output = Command::new(env!("CARGO_BIN_EXE_batrun"))
+        .current_dir(repo_dir())
+        .arg("tests/fixtures/failing")
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .args(["--target", "local"])
+        .env("GITHUB_ACTIONS", "true")
+        .output()
+        .expect("cannot run batrun");
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let annotations = stdout
+        .lines()
+        .filter(|line| line.starts_with("::"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        annotations,
+        [format!(
+            "::error file=tests/fixtures/failing/tests.sh,\
+             title=tests.sh%3A%3Atest_02_fail failed on local::Reason: test-case-failure%0A\
+             Log: {}",
+            out_dir
+                .join("batrun-fixture-failing/local/tests.sh/test_02_fail.test.log")
+                .display()
+        )]
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn network_isolation() {
+    // Network namespaces need batrun to run as root
+    let supported = Command::new("unshare")
+        .args(["--net", "python3", "--version"])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !supported {
+        eprintln!("network namespaces not supported, skipped");
+        return;
+    }
+    // Both test files bind the same port at the same time, and reach the service of the host
+    // through the forwarded port
+    let run = batrun(
+        "network-isolation",
+        &["--file-jobs", "2", "-s", "sequential"],
+    );
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    assert_eq!(run.records("result").len(), 4);
+}
+
+#[test]
+fn network_namespace_not_taken_from_env() {
+    // Only the namespaces created by batrun are entered, not one given in the environment
+    let run = batrun("passing", &["--env", "BATRUN_NETNS=/nonexistent"]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+}
+
+#[test]
+fn allure_results() {
+    let run = batrun(
+        "fixture-failure",
+        &[
+            "--allure-results",
+            "--meta",
+            "BUILD=42",
+            "--meta",
+            "BRANCH=main",
+        ],
+    );
+    assert_eq!(run.exit_code, Some(2));
+    let results_dir = run.out_dir.join("allure-results");
+    let results = std::fs::read_dir(&results_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_string_lossy().ends_with("-result.json"))
+        .map(|path| {
+            serde_json::from_str::<serde_json::Value>(&std::fs::read_to_string(path).unwrap())
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    // The setup and teardown are steps of the only test case
+    assert_eq!(results.len(), 1);
+    let result = &results[0];
+    assert_eq!(
+        result["fullName"],
+        "batrun-fixture-fixture-failure/tests.sh::test_01_ok"
+    );
+    assert_eq!(result["status"], "skipped");
+    assert_eq!(result["statusDetails"]["message"], "test-suite-setup-error");
+    assert_eq!(result["parameters"][0]["value"], "local");
+    let steps = result["steps"].as_array().unwrap();
+    assert_eq!(steps[0]["name"], "Setup `fixture.sh::setup`");
+    assert_eq!(steps[0]["status"], "failed");
+    assert_eq!(steps[1]["name"], "Teardown `fixture.sh::teardown`");
+    assert_eq!(steps[1]["status"], "skipped");
+    // The log files of the setup are copied next to the results
+    let attachment = &steps[0]["attachments"][0];
+    assert_eq!(attachment["name"], "test");
+    let log = std::fs::read_to_string(results_dir.join(attachment["source"].as_str().unwrap()));
+    assert!(log.unwrap().contains("fixture.sh"));
+    // The metadata of the run is the environment of the results
+    assert_eq!(
+        std::fs::read_to_string(results_dir.join("environment.properties")).unwrap(),
+        "BRANCH=main\nBUILD=42\n"
+    );
+}
+
+/// The directory where the targets of the `parallel` sample test suite meet, emptied beforehand
+fn meet_dir(name: &str) -> String {
+    let meet_dir = e2e_dir().join(name);
+    let _ = std::fs::remove_dir_all(&meet_dir);
+    format!("MEET_DIR={}", meet_dir.display())
+}
+
+#[test]
+fn parallel_targets() {
+    let meet_dir = meet_dir("parallel-targets-meet");
+    let run = batrun_on(
+        "parallel",
+        &["a", "b"],
+        &["-s", "parallel-targets", "--env", &meet_dir],
+    );
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    let mut results = run.records("result");
+    results.sort();
+    assert_eq!(
+        results,
+        [
+            "a\ttests.sh::test_01_meet\tpassed\t1\t",
+            "a\ttests.sh::test_02_output\tpassed\t1\t",
+            "b\ttests.sh::test_01_meet\tpassed\t1\t",
+            "b\ttests.sh::test_02_output\tpassed\t1\t",
+        ]
+    );
+}
+
+#[test]
+fn parallel_worker_pool() {
+    let meet_dir = meet_dir("parallel-worker-pool-meet");
+    let run = batrun_on(
+        "parallel",
+        &["a", "b"],
+        &["-s", "parallel", "--env", &meet_dir],
+    );
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("stats"),
+        [
+            "a\tpassed=2\tfailed=0\trunner-failed=0\tskipped=0\txfailed=0\txpassed=0",
+            "b\tpassed=2\tfailed=0\trunner-failed=0\tskipped=0\txfailed=0\txpassed=0",
+        ]
+    );
+}
+
+#[test]
+fn concurrent_console_output() {
+    // The long lines printed at the same time by both targets are never mixed up
+    let meet_dir = meet_dir("concurrent-console-output-meet");
+    let (exit_code, stdout) = batrun_console(
+        "parallel",
+        &["a", "b"],
+        &[
+            "-s",
+            "parallel-targets",
+            "--show-output",
+            "all",
+            "--env",
+            &meet_dir,
+        ],
+    );
+    assert_eq!(exit_code, Some(0), "output: {stdout}");
+    let padding = "0".repeat(200);
+    let output_lines = stdout
+        .lines()
+        .filter(|line| line.contains(" line "))
+        .collect::<Vec<_>>();
+    assert_eq!(output_lines.len(), 400, "output: {stdout}");
+    for line in output_lines {
+        let (prefix, output) = line.split_once(" │ ").unwrap();
+        let target = prefix.trim();
+        let (number, rest) = output
+            .strip_prefix(&format!("target {target} line "))
+            .and_then(|output| output.split_once(' '))
+            .unwrap_or_else(|| panic!("mixed up line: {line}"));
+        assert!(number.parse::<u32>().is_ok(), "mixed up line: {line}");
+        assert_eq!(rest, padding, "mixed up line: {line}");
+    }
+}
+
+#[test]
+fn grouped_console_output() {
+    // Everything printed about a target comes in a single block
+    let meet_dir = meet_dir("grouped-console-output-meet");
+    let (exit_code, stdout) = batrun_console(
+        "parallel",
+        &["a", "b"],
+        &[
+            "-s",
+            "parallel-targets",
+            "--show-output",
+            "all",
+            "-g",
+            "--env",
+            &meet_dir,
+        ],
+    );
+    assert_eq!(exit_code, Some(0), "output: {stdout}");
+    let mut targets = stdout
+        .lines()
+        .filter_map(|line| {
+            let (_, target) = line.split_once(" for target `")?;
+            target.split_once('`').map(|(target, _)| target)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(targets.len(), 8, "output: {stdout}");
+    targets.dedup();
+    assert_eq!(targets.len(), 2, "output: {stdout}");
+    let mut output_targets = stdout
+        .lines()
+        .filter_map(|line| line.split_once(" │ ").map(|(target, _)| target.trim()))
+        .collect::<Vec<_>>();
+    output_targets.dedup();
+    assert_eq!(output_targets, targets, "output: {stdout}");
+}
+
+#[test]
+fn parallel_jobs() {
+    // With a single job, the first target gives up meeting the second one before it starts
+    let meet_dir = meet_dir("parallel-jobs-meet");
+    let run = batrun_on(
+        "parallel",
+        &["a", "b"],
+        &[
+            "-s",
+            "parallel",
+            "-j",
+            "1",
+            "--env",
+            &meet_dir,
+            "--env",
+            "MEET_TRIES=5",
+        ],
+    );
+    assert_eq!(run.exit_code, Some(2), "records: {:?}", run.records);
+    let mut meet_results = run
+        .records("result")
+        .into_iter()
+        .filter(|record| record.contains("test_01_meet"))
+        .map(|record| record.split('\t').nth(2).unwrap())
+        .collect::<Vec<_>>();
+    meet_results.sort();
+    assert_eq!(meet_results, ["failed", "passed"]);
+}
+
+#[test]
+fn only_targets() {
+    let run = batrun_on("only-targets", &["a", "b"], &[]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("result"),
+        [
+            "a\ttests.sh::test_01_only_on_a\tpassed\t1\t",
+            "a\ttests.sh::test_02_only_on_b\tskipped\t1\t\
+             not-selected-for-target: only runs on targets `b`",
+            "a\ttests.sh::test_03_everywhere\tpassed\t1\t",
+            "b\ttests.sh::test_01_only_on_a\tskipped\t1\t\
+             not-selected-for-target: only runs on targets `a`",
+            "b\ttests.sh::test_02_only_on_b\tpassed\t1\t",
+            "b\ttests.sh::test_03_everywhere\tpassed\t1\t",
+        ]
+    );
+}
+
+#[test]
+fn kubernetes_targets() {
+    let run = batrun_on("kubernetes", &["k8s-ok", "k8s-broken-image"], &[]);
+    assert_eq!(run.exit_code, Some(3), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("result"),
+        [
+            "k8s-ok\tkubernetes.test.sh::test_01_env_points_to_pod\tpassed\t1\t",
+            "k8s-ok\tkubernetes.test.sh::test_02_fail\tfailed\t1\ttest-case-failure",
+            "k8s-broken-image\tkubernetes.test.sh::test_01_env_points_to_pod\trunner-failed\t1\t\
+             target `k8s-broken-image` is unavailable",
+            "k8s-broken-image\tkubernetes.test.sh::test_02_fail\trunner-failed\t1\t\
+             target `k8s-broken-image` is unavailable",
+        ]
+    );
+    // The end of the pod logs comes with the failed test case
+    let manifest = run.manifest("batrun-fixture-kubernetes");
+    let pod_log = log_file(&manifest, 1, "pod");
+    assert!(pod_log.contains("fake simulator ready"), "log: {pod_log}");
+}
+
+#[test]
+fn device_farm_targets() {
+    let run = batrun_on("device-farm", &["farm-ok", "farm-exhausted"], &[]);
+    assert_eq!(run.exit_code, Some(3), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("result"),
+        [
+            "farm-ok\tdevice-farm.test.sh::test_01_env_points_to_device\tpassed\t1\t",
+            "farm-ok\tdevice-farm.test.sh::test_02_fail\tfailed\t1\ttest-case-failure",
+            "farm-exhausted\tdevice-farm.test.sh::test_01_env_points_to_device\trunner-failed\t1\t\
+             target `farm-exhausted` is unavailable",
+            "farm-exhausted\tdevice-farm.test.sh::test_02_fail\trunner-failed\t1\t\
+             target `farm-exhausted` is unavailable",
+        ]
+    );
+    // The release command is given the lease, failing otherwise
+    assert_eq!(run.records("runner-health"), ["warnings=0\terrors=0"]);
+    let target_out_dir = run.out_dir.join("batrun-fixture-device-farm/farm-ok");
+    let lease = std::fs::read_to_string(target_out_dir.join("device-farm.lease.json")).unwrap();
+    assert!(lease.contains("lease-42"), "lease: {lease}");
+    // The capture command collects the state of the device when a test case fails
+    let screenshot = target_out_dir.join("device-farm.test.sh/test_02_fail.screenshot.png");
+    assert_eq!(
+        std::fs::read_to_string(screenshot).unwrap(),
+        "fake screenshot of 192.0.2.42\n"
+    );
+}
+
+#[test]
+fn several_reporters() {
+    // The porcelain output, the event stream and the Allure results all get every result
+    let events_file = e2e_dir().join("several-reporters.jsonl");
+    let run = batrun(
+        "failing",
+        &[
+            "--events",
+            events_file.to_str().unwrap(),
+            "--allure-results",
+        ],
+    );
+    assert_eq!(run.exit_code, Some(2));
+    let expected_statuses = ["passed", "failed"];
+    let porcelain_statuses = run
+        .records("result")
+        .iter()
+        .map(|record| record.split('\t').nth(2).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(porcelain_statuses, expected_statuses);
+    let event_statuses = std::fs::read_to_string(&events_file)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|event| event["event"] == "test-case-finished")
+        .map(|event| event["status"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(event_statuses, expected_statuses);
+    let mut allure_statuses = std::fs::read_dir(run.out_dir.join("allure-results"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_string_lossy().ends_with("-result.json"))
+        .map(|path| {
+            let result =
+                serde_json::from_str::<serde_json::Value>(&std::fs::read_to_string(path).unwrap())
+                    .unwrap();
+            result["status"].as_str().unwrap().to_string()
+        })
+        .collect::<Vec<_>>();
+    allure_statuses.sort();
+    assert_eq!(allure_statuses, ["failed", "passed"]);
+}
+
+#[test]
+fn lab_locks() {
+    // The lock directory is next to the output directory of the run
+    let lock_dir = e2e_dir().join("lab-locks");
+    let lock_file = lock_dir.join("bench.lock");
+    let _ = std::fs::remove_dir_all(&lock_dir);
+    let run = batrun("lab-locks", &[]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    assert!(!lock_file.exists());
+    // Another batrun instance holds the bench for longer than the lock timeout
+    std::fs::create_dir_all(&lock_dir).unwrap();
+    std::fs::write(&lock_file, "local on lab-host (pid 1)").unwrap();
+    let run = batrun("lab-locks", &[]);
+    assert_eq!(run.exit_code, Some(3), "records: {:?}", run.records);
+    assert_eq!(
+        run.records("result"),
+        ["local\ttests.sh::test_01_bench_locked\trunner-failed\t1\ttarget `local` is unavailable"]
+    );
+    assert_eq!(
+        std::fs::read_to_string(&lock_file).unwrap(),
+        "local on lab-host (pid 1)"
+    );
+}
+
+#[test]
+fn executable_test_files() {
+    let run = batrun("executable", &[]);
+    assert_eq!(run.exit_code, Some(2));
+    assert_eq!(
+        run.records("result"),
+        [
+            "local\t01-pass.test::01-pass.test\tpassed\t1\t",
+            "local\t02-fail.test::02-fail.test\tfailed\t1\ttest-case-failure",
+            "local\t03-skip.test.sh::03-skip.test.sh\tskipped\t1\t\
+             test-case-specific-reason: exited with code 77",
+        ]
+    );
+    let manifest = run.manifest("batrun-fixture-executable");
+    let log = log_file(&manifest, 1, "test");
+    assert!(
+        log.contains("Failing on purpose for target local"),
+        "log: {log}"
+    );
+}
+
+#[test]
+fn plain_output() {
+    // Even when asked to color, the plain output is only made of ASCII text
+    let (exit_code, stdout) = batrun_console(
+        "failing",
+        &["local"],
+        &["--plain", "--color", "always", "--show-output", "all"],
+    );
+    assert_eq!(exit_code, Some(2), "output: {stdout}");
+    assert!(
+        stdout
+            .chars()
+            .all(|c| c.is_ascii() && (!c.is_ascii_control() || c == '\n')),
+        "output: {stdout}"
+    );
+    assert!(
+        stdout.contains("\n  local | Test 02 failing on target local\n"),
+        "output: {stdout}"
+    );
+    assert!(
+        stdout.contains("Test case `tests.sh::test_02_fail` for target `local` FAILED"),
+        "output: {stdout}"
+    );
+}
+
+#[test]
+fn exit_codes() {
+    let exit_codes = e2e_dir().join("exit-codes.json");
+    std::fs::write(&exit_codes, r#"{ "failed": 0, "error": 9 }"#).unwrap();
+    let exit_codes = exit_codes.to_str().unwrap();
+    let run = batrun("failing", &["--exit-codes", exit_codes]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    let run = batrun("missing", &["--exit-codes", exit_codes]);
+    assert_eq!(run.exit_code, Some(9), "records: {:?}", run.records);
+    // The outcomes not listed keep their default exit code
+    let run = batrun_on(
+        "kubernetes",
+        &["k8s-broken-image"],
+        &["--exit-codes", exit_codes],
+    );
+    assert_eq!(run.exit_code, Some(3), "records: {:?}", run.records);
+}
+
+#[test]
+fn excluded_per_target() {
+    let run = batrun_on("excluded", &["board-a", "board-b", "emulator"], &[]);
+    assert_eq!(run.exit_code, Some(0), "records: {:?}", run.records);
+    let skipped = "skipped\t1\tincompatible-with-target: needs the debug port of the emulator";
+    assert_eq!(
+        run.records("result"),
+        [
+            "board-a\ttests.sh::test_01_everywhere\tpassed\t1\t".to_string(),
+            format!("board-a\ttests.sh::test_02_emulator_only\t{skipped}"),
+            "board-b\ttests.sh::test_01_everywhere\tpassed\t1\t".to_string(),
+            format!("board-b\ttests.sh::test_02_emulator_only\t{skipped}"),
+            "emulator\ttests.sh::test_01_everywhere\tpassed\t1\t".to_string(),
+            "emulator\ttests.sh::test_02_emulator_only\tpassed\t1\t".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn local_artifact_storage() {
+    // By default the log files are only kept in the output directory
+    let run = batrun("failing", &[]);
+    assert_eq!(run.exit_code, Some(2));
+    let manifest = run.manifest("batrun-fixture-failing");
+    for log_file in manifest["targets"][0]["test-cases"][1]["log-files"]
+        .as_array()
+        .unwrap()
+    {
+        assert!(log_file.get("location").is_none(), "{log_file}");
+        assert!(Path::new(log_file["path"].as_str().unwrap()).is_file());
+    }
+}
+
+#[cfg(feature = "s3")]
+#[test]
+fn s3_artifact_storage() {
+    // The fake `aws` CLI uploads the files to a bucket directory in the output directory
+    let run = batrun("artifact-storage", &[]);
+    assert_eq!(run.exit_code, Some(2), "records: {:?}", run.records);
+    let manifest = run.manifest("batrun-fixture-artifact-storage");
+    let log_file = &manifest["targets"][0]["test-cases"][0]["log-files"][0];
+    let object = "nightly/batrun-fixture-artifact-storage/local/artifact-storage.test.sh/\
+                  test_01_uploaded.test.log";
+    assert_eq!(
+        log_file["location"],
+        format!("s3://batrun-fixture/{object}")
+    );
+    // The local copy is removed once uploaded
+    assert!(!Path::new(log_file["path"].as_str().unwrap()).exists());
+    let uploaded =
+        std::fs::read_to_string(run.out_dir.join("bucket/batrun-fixture").join(object)).unwrap();
+    assert!(uploaded.contains("This log is stored in the bucket"));
+}
@@ -0,0 +1,25 @@
+//! Run a test suite on the given targets from another program, e.g.
+//! `cargo run -p batrun --example embed -- tests/ivts foo bar`
+
+use batrun::{ExitCodes, RunOutcome, Settings, TestRunner, interrupt};
+
+use std::path::PathBuf;
+
+fn main() -> std::process::ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(test_suite_dir) = args.next() else {
+        eprintln!("usage: embed <test suite dir> <target>...");
+        return std::process::ExitCode::FAILURE;
+    };
+    let settings = Settings::new(vec![PathBuf::from(test_suite_dir)])
+        .with_out_dir(std::env::temp_dir().join("batrun-embed"))
+        .with_targets(args.collect());
+
+    interrupt::install_handler();
+    let outcome = TestRunner::builder(settings)
+        .build()
+        .and_then(|mut test_runner| test_runner.run_all())
+        .unwrap_or(RunOutcome::Error);
+    println!("Outcome: {outcome:?}");
+    std::process::ExitCode::from(ExitCodes::default().code(outcome))
+}
@@ -0,0 +1,56 @@
+/*
+ * Run a test suite on the given targets through the C API of batrun, e.g.
+ *   cargo build -p batrun-ffi
+ *   cc ffi/examples/run_suite.c -Iffi/include -Ltarget/debug -lbatrun_ffi -o run_suite
+ *   LD_LIBRARY_PATH=target/debug ./run_suite tests/ivts foo bar
+ */
+
+#include <batrun.h>
+
+#include <stdio.h>
+
+static void print_test_case(const char *test_case, void *user_data)
+{
+    (void)user_data;
+    printf("  %s\n", test_case);
+}
+
+static void print_event(const batrun_event *event, void *user_data)
+{
+    int *failures = user_data;
+    switch (event->kind) {
+    case BATRUN_EVENT_MESSAGE:
+        fprintf(stderr, "%s: %s\n", event->level, event->message);
+        break;
+    case BATRUN_EVENT_TEST_CASE_FINISHED:
+        printf("%s on %s: %s (%.3fs)\n", event->test_case, event->target, event->status,
+               event->duration);
+        *failures += event->status[0] == 'f' || event->status[0] == 'r';
+        break;
+    default:
+        break;
+    }
+}
+
+int main(int argc, const char *argv[])
+{
+    if (argc < 2) {
+        fprintf(stderr, "usage: %s <test suite dir> <target>...\n", argv[0]);
+        return 1;
+    }
+    int failures = 0;
+    printf("batrun %s, test cases of %s:\n", batrun_version(), argv[1]);
+    if (batrun_list_tests(argv[1], print_test_case, print_event, &failures)
+        != BATRUN_OUTCOME_PASSED) {
+        return 1;
+    }
+    batrun_run_options options = {
+        .test_suite_dir = argv[1],
+        .out_dir = NULL,
+        .targets = &argv[2],
+        .targets_len = (size_t)(argc - 2),
+    };
+    batrun_outcome outcome = batrun_run_suite(&options, print_event, &failures);
+    printf("outcome %d, %d failures\n", (int)outcome, failures);
+    return outcome == BATRUN_OUTCOME_PASSED ? 0 : 2;
+}
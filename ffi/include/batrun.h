@@ -0,0 +1,108 @@
+/*
+ * C API of batrun, to embed the runner in C and C++ lab frameworks, or in Python through cffi
+ *
+ * All strings are NUL-terminated UTF-8, the ones given to the callbacks being only valid during
+ * the call. The callbacks are called from one thread at a time.
+ */
+
+#ifndef BATRUN_H
+#define BATRUN_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Overall outcome of a run */
+typedef enum {
+    BATRUN_OUTCOME_PASSED,
+    BATRUN_OUTCOME_FAILED,
+    BATRUN_OUTCOME_RUNNER_FAILED,
+    BATRUN_OUTCOME_INTERRUPTED,
+    BATRUN_OUTCOME_ERROR,
+    BATRUN_OUTCOME_PAUSED,
+} batrun_outcome;
+
+typedef enum {
+    /* A message for the user, with its level and message */
+    BATRUN_EVENT_MESSAGE,
+    /* A test case started on a target, with its test_case id and target */
+    BATRUN_EVENT_TEST_CASE_STARTED,
+    /* A test case finished on a target, with its test_case id, target, status, duration and as
+     * message the error preventing it from running, or the message it attached to its result, if
+     * any */
+    BATRUN_EVENT_TEST_CASE_FINISHED,
+    /* All the test cases of a test suite ran on a target, with the target */
+    BATRUN_EVENT_TARGET_FINISHED,
+    /* A test suite ran on all its targets, with the test_suite path and duration */
+    BATRUN_EVENT_TEST_SUITE_FINISHED,
+    /* The last lines of the log of a failed test case, right after it finished, with its test_case
+     * id, target and the lines as message */
+    BATRUN_EVENT_TEST_CASE_FAILURE_OUTPUT,
+    /* A test case is done on a target, with the target, the percentage of the test cases of the
+     * test suite done as progress and the estimated time left as duration, if known */
+    BATRUN_EVENT_TARGET_PROGRESS,
+    /* A test suite is about to run, with its test_suite path */
+    BATRUN_EVENT_TEST_SUITE_STARTED,
+    /* The test suites are about to run, with the metadata of the run as message, one KEY=VALUE
+     * line per entry */
+    BATRUN_EVENT_RUN_STARTED,
+    /* All the test suites ran, with how many warnings and errors the runner reported as message,
+     * e.g. warnings=0 errors=1 */
+    BATRUN_EVENT_RUNNER_HEALTH,
+} batrun_event_kind;
+
+/* Event of a run, the fields not relevant to its kind being NULL (or negative for the duration
+ * and the progress) */
+typedef struct {
+    batrun_event_kind kind;
+    /* Level of a message: "notice", "info", "warning" or "error" */
+    const char *level;
+    const char *message;
+    const char *test_suite;
+    const char *test_case;
+    const char *target;
+    /* Status of a finished test case, as in the run manifest (e.g. "passed" or "runner-failed") */
+    const char *status;
+    /* Duration in seconds */
+    double duration;
+    /* Percentage of the test cases of the test suite done on a target */
+    double progress;
+} batrun_event;
+
+typedef void (*batrun_event_callback)(const batrun_event *event, void *user_data);
+typedef void (*batrun_test_callback)(const char *test_case, void *user_data);
+
+/* Settings of a run, the other settings keeping the defaults of the command line interface */
+typedef struct {
+    const char *test_suite_dir;
+    /* Output directory, "out" if NULL */
+    const char *out_dir;
+    const char *const *targets;
+    size_t targets_len;
+    /* Test filters, as given to --filter */
+    const char *const *filters;
+    size_t filters_len;
+    bool fail_fast;
+    bool dry_run;
+} batrun_run_options;
+
+/* Version of batrun */
+const char *batrun_version(void);
+
+/* Run a test suite, reporting the events of the run to on_event, or on the console if NULL */
+batrun_outcome batrun_run_suite(const batrun_run_options *options, batrun_event_callback on_event,
+                                void *user_data);
+
+/* List the test cases of a test suite, calling on_test with the id of each of them, and reporting
+ * the errors to on_event if not NULL */
+batrun_outcome batrun_list_tests(const char *test_suite_dir, batrun_test_callback on_test,
+                                 batrun_event_callback on_event, void *user_data);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BATRUN_H */
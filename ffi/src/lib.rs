@@ -0,0 +1,362 @@
+//! C API of batrun, so that C and C++ lab frameworks, or Python through cffi, can embed the runner
+//!
+//! The declarations are in `include/batrun.h`. All strings are NUL-terminated UTF-8, the ones
+//! given to the callbacks being only valid during the call.
+
+use batrun::reporter::Reporter;
+use batrun::reporter::events::{Event, EventKind, EventReporter};
+use batrun::test_suite::filter::{TestFilter, TestPattern};
+use batrun::{RunOutcome, Settings, TestRunner};
+
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::path::PathBuf;
+use std::ptr;
+use std::time::Instant;
+
+/// Overall outcome of a run, see [`RunOutcome`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatrunOutcome {
+    Passed,
+    Failed,
+    RunnerFailed,
+    Interrupted,
+    Error,
+    Paused,
+}
+
+impl From<RunOutcome> for BatrunOutcome {
+    fn from(outcome: RunOutcome) -> Self {
+        match outcome {
+            RunOutcome::Passed => BatrunOutcome::Passed,
+            RunOutcome::Failed => BatrunOutcome::Failed,
+            RunOutcome::RunnerFailed => BatrunOutcome::RunnerFailed,
+            RunOutcome::Interrupted => BatrunOutcome::Interrupted,
+            RunOutcome::Error => BatrunOutcome::Error,
+            RunOutcome::Paused => BatrunOutcome::Paused,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatrunEventKind {
+    /// A message for the user, with its `level` and `message`
+    Message,
+    /// A test case started on a target, with its `test_case` id and `target`
+    TestCaseStarted,
+    /// A test case finished on a target, with its `test_case` id, `target`, `status`, `duration`
+    /// and as `message` the error preventing it from running, or the message it attached to its
+    /// result, if any
+    TestCaseFinished,
+    /// All the test cases of a test suite ran on a target, with the `target`
+    TargetFinished,
+    /// A test suite ran on all its targets, with the `test_suite` path and `duration`
+    TestSuiteFinished,
+    /// The last lines of the log of a failed test case, right after it finished, with its
+    /// `test_case` id, `target` and the lines as `message`
+    TestCaseFailureOutput,
+    /// A test case is done on a target, with the `target`, the percentage of the test cases of
+    /// the test suite done as `progress` and the estimated time left as `duration`, if known
+    TargetProgress,
+    /// A test suite is about to run, with its `test_suite` path
+    TestSuiteStarted,
+    /// The test suites are about to run, with the metadata of the run as `message`, one
+    /// `KEY=VALUE` line per entry
+    RunStarted,
+    /// All the test suites ran, with how many warnings and errors the runner reported as
+    /// `message`, e.g. `warnings=0 errors=1`
+    RunnerHealth,
+}
+
+/// Event of a run, the fields not relevant to its kind being null (or negative for the duration
+/// and the progress)
+#[repr(C)]
+pub struct BatrunEvent {
+    pub kind: BatrunEventKind,
+    /// Level of a message: `notice`, `info`, `warning` or `error`
+    pub level: *const c_char,
+    pub message: *const c_char,
+    pub test_suite: *const c_char,
+    pub test_case: *const c_char,
+    pub target: *const c_char,
+    /// Status of a finished test case, as in the run manifest (e.g. `passed` or `runner-failed`)
+    pub status: *const c_char,
+    /// Duration in seconds
+    pub duration: f64,
+    /// Percentage of the test cases of the test suite done on a target
+    pub progress: f64,
+}
+
+pub type BatrunEventCallback =
+    Option<unsafe extern "C" fn(event: *const BatrunEvent, user_data: *mut c_void)>;
+pub type BatrunTestCallback =
+    Option<unsafe extern "C" fn(test_case: *const c_char, user_data: *mut c_void)>;
+
+/// Settings of a run, the other settings keeping the defaults of the command line interface
+#[repr(C)]
+pub struct BatrunRunOptions {
+    pub test_suite_dir: *const c_char,
+    /// Output directory, `out` if null
+    pub out_dir: *const c_char,
+    pub targets: *const *const c_char,
+    pub targets_len: usize,
+    /// Test filters, as given to `--filter`
+    pub filters: *const *const c_char,
+    pub filters_len: usize,
+    pub fail_fast: bool,
+    pub dry_run: bool,
+}
+
+/// Version of batrun
+#[unsafe(no_mangle)]
+pub extern "C" fn batrun_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Run a test suite, reporting the events of the run to `on_event`, or on the console if null
+///
+/// # Safety
+///
+/// `options` must point to valid options, whose strings and arrays stay valid during the call.
+/// `on_event` is called from one thread at a time, with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn batrun_run_suite(
+    options: *const BatrunRunOptions,
+    on_event: BatrunEventCallback,
+    user_data: *mut c_void,
+) -> BatrunOutcome {
+    let reporter = Callbacks {
+        on_event,
+        on_test: None,
+        user_data,
+    }
+    .reporter();
+    // SAFETY: the caller guarantees that the options are valid if not null
+    let Some(options) = (unsafe { options.as_ref() }) else {
+        reporter.error("batrun_run_suite: no options given");
+        return BatrunOutcome::Error;
+    };
+    // SAFETY: the caller guarantees that the strings and arrays of the options are valid
+    let settings = match unsafe { settings(options) } {
+        Ok(settings) => settings,
+        Err(details) => {
+            reporter.error(&format!("batrun_run_suite: {details}"));
+            return BatrunOutcome::Error;
+        }
+    };
+    let mut builder = TestRunner::builder(settings);
+    if on_event.is_some() {
+        builder = builder.with_reporter(Box::new(reporter));
+    }
+    builder
+        .build()
+        .and_then(|mut test_runner| {
+            let start = Instant::now();
+            let outcome = test_runner.run_all();
+            test_runner.report_total_time(start.elapsed());
+            outcome
+        })
+        .unwrap_or(RunOutcome::Error)
+        .into()
+}
+
+/// List the test cases of a test suite, calling `on_test` with the id of each of them, and
+/// reporting the errors to `on_event` if not null
+///
+/// # Safety
+///
+/// `test_suite_dir` must be a valid string. The callbacks are called with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn batrun_list_tests(
+    test_suite_dir: *const c_char,
+    on_test: BatrunTestCallback,
+    on_event: BatrunEventCallback,
+    user_data: *mut c_void,
+) -> BatrunOutcome {
+    let reporter = Callbacks {
+        on_event,
+        on_test,
+        user_data,
+    }
+    .reporter();
+    // SAFETY: the caller guarantees that the string is valid if not null
+    let test_suite_dir = match unsafe { path(test_suite_dir) } {
+        Ok(test_suite_dir) => test_suite_dir,
+        Err(details) => {
+            reporter.error(&format!("batrun_list_tests: {details}"));
+            return BatrunOutcome::Error;
+        }
+    };
+    let settings = Settings::new(vec![test_suite_dir.clone()]);
+    let result = TestRunner::builder(settings)
+        .with_reporter(Box::new(reporter))
+        .build()
+        .and_then(|test_runner| test_runner.list_tests(&test_suite_dir, false));
+    match result {
+        Ok(()) => BatrunOutcome::Passed,
+        Err(_) => BatrunOutcome::Error,
+    }
+}
+
+/// Read a string given through the C API
+///
+/// # Safety
+///
+/// `string` must be null or point to a valid NUL-terminated string.
+unsafe fn string(string: *const c_char) -> Result<String, String> {
+    if string.is_null() {
+        return Err("null string".to_string());
+    }
+    // SAFETY: the caller guarantees that the string is valid
+    unsafe { CStr::from_ptr(string) }
+        .to_str()
+        .map(String::from)
+        .map_err(|_| "invalid UTF-8 string".to_string())
+}
+
+/// # Safety
+///
+/// See [`string`].
+unsafe fn path(path: *const c_char) -> Result<PathBuf, String> {
+    // SAFETY: guaranteed by the caller
+    unsafe { string(path) }.map(PathBuf::from)
+}
+
+/// Read an array of strings given through the C API
+///
+/// # Safety
+///
+/// `strings` must point to `len` valid strings, or be null if `len` is 0.
+unsafe fn strings(strings: *const *const c_char, len: usize) -> Result<Vec<String>, String> {
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    if strings.is_null() {
+        return Err("null array".to_string());
+    }
+    // SAFETY: the caller guarantees that the array holds `len` strings
+    unsafe { std::slice::from_raw_parts(strings, len) }
+        .iter()
+        // SAFETY: the caller guarantees that the strings are valid
+        .map(|&s| unsafe { string(s) })
+        .collect()
+}
+
+/// Settings of a run given through the C API
+///
+/// # Safety
+///
+/// The strings and arrays of the options must be valid.
+unsafe fn settings(options: &BatrunRunOptions) -> Result<Settings, String> {
+    // SAFETY: guaranteed by the caller
+    let test_suite_dir = unsafe { path(options.test_suite_dir) }?;
+    // SAFETY: guaranteed by the caller
+    let targets = unsafe { strings(options.targets, options.targets_len) }?;
+    // SAFETY: guaranteed by the caller
+    let filters = unsafe { strings(options.filters, options.filters_len) }?
+        .iter()
+        .map(|filter| filter.parse::<TestPattern>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut settings = Settings::new(vec![test_suite_dir])
+        .with_targets(targets)
+        .with_test_filter(TestFilter::new(filters));
+    if !options.out_dir.is_null() {
+        // SAFETY: guaranteed by the caller
+        settings = settings.with_out_dir(unsafe { path(options.out_dir) }?);
+    }
+    settings.fail_fast = options.fail_fast;
+    settings.dry_run = options.dry_run;
+    Ok(settings)
+}
+
+/// A string given to the callbacks, without the NUL characters it cannot hold
+fn c_string(string: &str) -> CString {
+    // UNWRAP: the NUL characters are removed
+    CString::new(string.replace('\0', "")).unwrap()
+}
+
+/// Callbacks given through the C API, along with their user data
+#[derive(Clone, Copy)]
+struct Callbacks {
+    on_event: BatrunEventCallback,
+    on_test: BatrunTestCallback,
+    user_data: *mut c_void,
+}
+
+// SAFETY: the user data is only given to the callbacks, which the event reporter calls one at a
+// time
+unsafe impl Send for Callbacks {}
+
+impl Callbacks {
+    fn reporter(self) -> EventReporter {
+        EventReporter::new(move |event| self.call(event))
+    }
+
+    fn call(&self, event: Event) {
+        let kind = match event.kind {
+            EventKind::TestCaseListed => {
+                if let (Some(on_test), Some(test_case)) = (self.on_test, &event.test_case) {
+                    let test_case = c_string(test_case);
+                    // SAFETY: the id lives until the end of the call, and the user data is the
+                    // one given along with the callback
+                    unsafe { on_test(test_case.as_ptr(), self.user_data) };
+                }
+                return;
+            }
+            EventKind::Message => BatrunEventKind::Message,
+            EventKind::TestCaseStarted => BatrunEventKind::TestCaseStarted,
+            EventKind::TestCaseFinished => BatrunEventKind::TestCaseFinished,
+            EventKind::TargetFinished => BatrunEventKind::TargetFinished,
+            EventKind::TestSuiteFinished => BatrunEventKind::TestSuiteFinished,
+            EventKind::TestCaseFailureOutput => BatrunEventKind::TestCaseFailureOutput,
+            EventKind::TargetProgress => BatrunEventKind::TargetProgress,
+            EventKind::TestSuiteStarted => BatrunEventKind::TestSuiteStarted,
+            EventKind::RunStarted => BatrunEventKind::RunStarted,
+            EventKind::RunnerHealth => BatrunEventKind::RunnerHealth,
+        };
+        let Some(on_event) = self.on_event else {
+            return;
+        };
+        let level = event.level.map(c_string);
+        let message = match &event.metadata {
+            Some(metadata) => Some(c_string(
+                &metadata
+                    .iter()
+                    .map(|(key, value)| format!("{key}={value}"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )),
+            None => match event.health {
+                Some(health) => Some(c_string(&format!(
+                    "warnings={} errors={}",
+                    health.warnings, health.errors
+                ))),
+                None => event.message.as_deref().map(c_string),
+            },
+        };
+        let test_suite = event
+            .test_suite
+            .map(|test_suite| c_string(&test_suite.display().to_string()));
+        let test_case = event.test_case.as_deref().map(c_string);
+        let target = event.target.as_deref().map(c_string);
+        let status = event.status.map(c_string);
+        let as_ptr = |string: &Option<CString>| string.as_ref().map_or(ptr::null(), |s| s.as_ptr());
+        let event = BatrunEvent {
+            kind,
+            level: as_ptr(&level),
+            message: as_ptr(&message),
+            test_suite: as_ptr(&test_suite),
+            test_case: as_ptr(&test_case),
+            target: as_ptr(&target),
+            status: as_ptr(&status),
+            duration: event
+                .duration
+                .map_or(-1.0, |duration| duration.as_secs_f64()),
+            progress: event.progress.unwrap_or(-1.0),
+        };
+        // SAFETY: the strings of the event live until the end of the call, and the user data is
+        // the one given along with the callback
+        unsafe { on_event(&event, self.user_data) };
+    }
+}
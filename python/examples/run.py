@@ -0,0 +1,31 @@
+"""Run a test suite on the given targets from Python, e.g.
+
+    maturin develop -m python/Cargo.toml
+    python python/examples/run.py tests/ivts foo bar
+"""
+
+import sys
+
+import batrun
+
+
+def main():
+    if len(sys.argv) < 2:
+        sys.exit(f"usage: {sys.argv[0]} <test suite dir> <target>...")
+    settings = batrun.Settings([sys.argv[1]], targets=sys.argv[2:])
+    runner = batrun.TestRunner(settings)
+    print(f"batrun {batrun.__version__}, test cases of {sys.argv[1]}:")
+    for test_case in runner.list_tests():
+        print(f"  {test_case}")
+    run = runner.run()
+    for event in run:
+        if event.kind == "message" and event.level in ("warning", "error"):
+            print(f"{event.level}: {event.message}", file=sys.stderr)
+        elif event.kind == "test-case-finished":
+            print(f"{event.test_case} on {event.target}: {event.status} ({event.duration or 0:.3f}s)")
+    print(f"outcome: {run.outcome}")
+    sys.exit(run.exit_code)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,329 @@
+//! Python bindings of batrun, so that Python lab tooling can orchestrate runs and follow their
+//! events without parsing the output of the command line interface
+//!
+//! ```python
+//! import batrun
+//!
+//! runner = batrun.TestRunner(batrun.Settings(["tests/ivts"], targets=["foo", "bar"]))
+//! run = runner.run()
+//! for event in run:
+//!     if event.kind == "test-case-finished":
+//!         print(event.test_case, event.target, event.status)
+//! print(run.outcome)
+//! ```
+
+use batrun::reporter::events::{Event, EventKind, EventReporter};
+use batrun::test_suite::filter::{TestFilter, TestPattern};
+use batrun::{ExitCodes, RunOutcome, TestRunner};
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+/// Settings of a run, the other settings keeping the defaults of the command line interface
+#[pyclass(name = "Settings", module = "batrun", get_all, set_all)]
+#[derive(Clone)]
+struct PySettings {
+    test_suite_dirs: Vec<PathBuf>,
+    /// Output directory, `out` if None
+    out_dir: Option<PathBuf>,
+    targets: Vec<String>,
+    /// Test filters, as given to `--filter`
+    filters: Vec<String>,
+    /// Environment variables set in all the test processes, as given to `--env`
+    env: BTreeMap<String, String>,
+    fail_fast: bool,
+    dry_run: bool,
+}
+
+#[pymethods]
+impl PySettings {
+    #[new]
+    #[pyo3(signature = (
+        test_suite_dirs,
+        *,
+        out_dir = None,
+        targets = Vec::new(),
+        filters = Vec::new(),
+        env = BTreeMap::new(),
+        fail_fast = false,
+        dry_run = false
+    ))]
+    fn new(
+        test_suite_dirs: Vec<PathBuf>,
+        out_dir: Option<PathBuf>,
+        targets: Vec<String>,
+        filters: Vec<String>,
+        env: BTreeMap<String, String>,
+        fail_fast: bool,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            test_suite_dirs,
+            out_dir,
+            targets,
+            filters,
+            env,
+            fail_fast,
+            dry_run,
+        }
+    }
+}
+
+impl PySettings {
+    fn to_settings(&self) -> PyResult<batrun::Settings> {
+        let filters = self
+            .filters
+            .iter()
+            .map(|filter| filter.parse::<TestPattern>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(PyValueError::new_err)?;
+        let mut settings = batrun::Settings::new(self.test_suite_dirs.clone())
+            .with_targets(self.targets.clone())
+            .with_test_filter(TestFilter::new(filters));
+        if let Some(out_dir) = &self.out_dir {
+            settings = settings.with_out_dir(out_dir);
+        }
+        settings.env = self.env.clone();
+        settings.fail_fast = self.fail_fast;
+        settings.dry_run = self.dry_run;
+        Ok(settings)
+    }
+}
+
+/// Event of a run, the attributes not relevant to its kind being None
+#[pyclass(name = "Event", module = "batrun", frozen, get_all)]
+struct PyEvent {
+    /// `message`, `run-started`, `test-case-started`, `test-case-finished`,
+    /// `test-case-failure-output`, `target-finished`, `test-suite-finished` or `runner-health`
+    kind: &'static str,
+    /// Level of a message: `notice`, `info`, `warning` or `error`
+    level: Option<&'static str>,
+    message: Option<String>,
+    test_suite: Option<PathBuf>,
+    test_case: Option<String>,
+    target: Option<String>,
+    /// Status of a finished test case, as in the run manifest (e.g. `passed` or `runner-failed`)
+    status: Option<&'static str>,
+    /// Duration in seconds
+    duration: Option<f64>,
+    /// Metadata of the run, for `run-started` events
+    metadata: Option<BTreeMap<String, String>>,
+    /// How many warnings and errors the runner reported, for `runner-health` events
+    warnings: Option<usize>,
+    errors: Option<usize>,
+}
+
+#[pymethods]
+impl PyEvent {
+    fn __repr__(&self) -> String {
+        let mut fields = vec![format!("kind={:?}", self.kind)];
+        let mut field = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                fields.push(format!("{name}={value:?}"));
+            }
+        };
+        field("level", self.level.map(String::from));
+        field("message", self.message.clone());
+        field(
+            "test_suite",
+            self.test_suite
+                .as_ref()
+                .map(|path| path.display().to_string()),
+        );
+        field("test_case", self.test_case.clone());
+        field("target", self.target.clone());
+        field("status", self.status.map(String::from));
+        if let Some(duration) = self.duration {
+            fields.push(format!("duration={duration}"));
+        }
+        if let Some(metadata) = &self.metadata {
+            fields.push(format!("metadata={metadata:?}"));
+        }
+        if let (Some(warnings), Some(errors)) = (self.warnings, self.errors) {
+            fields.push(format!("warnings={warnings}, errors={errors}"));
+        }
+        format!("Event({})", fields.join(", "))
+    }
+}
+
+impl From<Event> for PyEvent {
+    fn from(event: Event) -> Self {
+        Self {
+            kind: event.kind.name(),
+            level: event.level,
+            message: event.message,
+            test_suite: event.test_suite,
+            test_case: event.test_case,
+            target: event.target,
+            status: event.status,
+            duration: event.duration.map(|duration| duration.as_secs_f64()),
+            metadata: event.metadata,
+            warnings: event.health.map(|health| health.warnings),
+            errors: event.health.map(|health| health.errors),
+        }
+    }
+}
+
+/// A run in progress, iterating over its events until it is done
+#[pyclass(name = "Run", module = "batrun")]
+struct PyRun {
+    events: Mutex<Receiver<Event>>,
+    thread: Option<JoinHandle<RunOutcome>>,
+    outcome: Option<RunOutcome>,
+}
+
+#[pymethods]
+impl PyRun {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<PyEvent>> {
+        let event = {
+            let events = &slf.events;
+            // The GIL is released while waiting, so that other Python threads keep running
+            py.allow_threads(|| {
+                events
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .recv()
+                    .ok()
+            })
+        };
+        match event {
+            Some(event) => Ok(Some(event.into())),
+            None => {
+                slf.finish()?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Outcome of the run (`passed`, `failed`, `runner-failed`, `paused`, `interrupted` or
+    /// `error`), None until all its events were iterated over
+    #[getter]
+    fn outcome(&self) -> Option<&'static str> {
+        self.outcome.map(|outcome| match outcome {
+            RunOutcome::Passed => "passed",
+            RunOutcome::Failed => "failed",
+            RunOutcome::RunnerFailed => "runner-failed",
+            RunOutcome::Paused => "paused",
+            RunOutcome::Interrupted => "interrupted",
+            RunOutcome::Error => "error",
+        })
+    }
+
+    /// Exit code of the command line interface for the outcome of the run
+    #[getter]
+    fn exit_code(&self) -> Option<u8> {
+        self.outcome
+            .map(|outcome| ExitCodes::default().code(outcome))
+    }
+}
+
+impl PyRun {
+    fn finish(&mut self) -> PyResult<()> {
+        if let Some(thread) = self.thread.take() {
+            let outcome = thread
+                .join()
+                .map_err(|_| PyRuntimeError::new_err("the run panicked"))?;
+            self.outcome = Some(outcome);
+        }
+        Ok(())
+    }
+}
+
+/// Runs test suites, see `batrun::TestRunner`
+#[pyclass(name = "TestRunner", module = "batrun")]
+struct PyTestRunner {
+    settings: PySettings,
+}
+
+#[pymethods]
+impl PyTestRunner {
+    #[new]
+    fn new(settings: PySettings) -> Self {
+        Self { settings }
+    }
+
+    /// The ids of the test cases of the test suites, raising a RuntimeError with the errors
+    /// reported if they cannot be loaded
+    fn list_tests(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        let settings = self.settings.to_settings()?;
+        let (events, result) = py.allow_threads(|| {
+            let (sender, receiver) = mpsc::channel();
+            let reporter = EventReporter::new(move |event| {
+                let _ = sender.send(event);
+            });
+            let result = TestRunner::builder(settings)
+                .with_reporter(Box::new(reporter))
+                .build()
+                .and_then(|test_runner| {
+                    for test_suite_dir in &test_runner.settings().test_suite_dirs {
+                        test_runner.list_tests(test_suite_dir, false)?;
+                    }
+                    Ok(())
+                });
+            (receiver.try_iter().collect::<Vec<_>>(), result)
+        });
+        let mut test_cases = Vec::new();
+        let mut errors = Vec::new();
+        for event in events {
+            match event.kind {
+                EventKind::TestCaseListed => test_cases.extend(event.test_case),
+                EventKind::Message if event.level == Some("error") => errors.extend(event.message),
+                _ => {}
+            }
+        }
+        match result {
+            Ok(()) => Ok(test_cases),
+            Err(error) if errors.is_empty() => Err(PyRuntimeError::new_err(error.to_string())),
+            Err(_) => Err(PyRuntimeError::new_err(errors.join("\n"))),
+        }
+    }
+
+    /// Start running the test suites on their targets in a background thread, returning the run
+    /// to iterate over its events
+    fn run(&self) -> PyResult<PyRun> {
+        let settings = self.settings.to_settings()?;
+        let (sender, receiver) = mpsc::channel();
+        let thread = std::thread::spawn(move || {
+            let reporter = EventReporter::new(move |event| {
+                let _ = sender.send(event);
+            });
+            TestRunner::builder(settings)
+                .with_reporter(Box::new(reporter))
+                .build()
+                .and_then(|mut test_runner| {
+                    let start = Instant::now();
+                    let outcome = test_runner.run_all();
+                    test_runner.report_total_time(start.elapsed());
+                    outcome
+                })
+                .unwrap_or(RunOutcome::Error)
+        });
+        Ok(PyRun {
+            events: Mutex::new(receiver),
+            thread: Some(thread),
+            outcome: None,
+        })
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "batrun")]
+fn batrun_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    module.add_class::<PySettings>()?;
+    module.add_class::<PyTestRunner>()?;
+    module.add_class::<PyRun>()?;
+    module.add_class::<PyEvent>()?;
+    Ok(())
+}
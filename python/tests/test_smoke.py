@@ -0,0 +1,48 @@
+"""Smoke test of the Python bindings, running the sample test suites of tests/fixtures, e.g.
+
+    maturin develop -m python/Cargo.toml
+    pytest python/tests
+"""
+
+from pathlib import Path
+
+import batrun
+
+FIXTURES = Path(__file__).resolve().parents[2] / "tests" / "fixtures"
+
+
+def run_suite(test_suite, tmp_path, **kwargs):
+    settings = batrun.Settings(
+        [str(FIXTURES / test_suite)], out_dir=str(tmp_path), targets=["local"], **kwargs
+    )
+    run = batrun.TestRunner(settings).run()
+    return run, list(run)
+
+
+def test_version():
+    assert batrun.__version__
+
+
+def test_list_tests():
+    settings = batrun.Settings([str(FIXTURES / "passing")], targets=["local"])
+    assert batrun.TestRunner(settings).list_tests() == [
+        "tests.sh::test_01_ok",
+        "tests.sh::test_02_ok",
+    ]
+
+
+def test_passing_run(tmp_path):
+    run, events = run_suite("passing", tmp_path)
+    finished = [event for event in events if event.kind == "test-case-finished"]
+    assert [(event.test_case, event.status) for event in finished] == [
+        ("tests.sh::test_01_ok", "passed"),
+        ("tests.sh::test_02_ok", "passed"),
+    ]
+    assert run.outcome == "passed"
+    assert run.exit_code == 0
+
+
+def test_failing_run(tmp_path):
+    run, _ = run_suite("failing", tmp_path, filters=["test_02_fail"])
+    assert run.outcome == "failed"
+    assert run.exit_code != 0
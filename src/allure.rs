@@ -0,0 +1,395 @@
+use crate::error::{self, Result};
+use crate::rng::{self, Rng};
+use crate::test_driver::{Env, LogFile};
+use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
+use crate::test_suite::status::TestCaseStatus;
+use crate::test_suite::{TestCase, TestSuite};
+
+use serde::Serialize;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Writes the results of the test suites in the format of Allure (https://allurereport.org), one
+/// `<uuid>-result.json` file per test case and target, for teams browsing their results in Allure
+/// reports
+/// The setups and teardowns of a test case are steps of its result rather than results of their
+/// own, and the log files are copied next to the results as attachments, so that the results
+/// directory can be archived on its own. The metadata of the run is written to the
+/// `environment.properties` file, shown by Allure in the Environment section of its reports.
+pub(crate) struct AllureResults {
+    dir: PathBuf,
+    rng: Rng,
+}
+
+/// Result of a test case on a target, in the format of Allure
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AllureResult {
+    uuid: String,
+    /// Identifies the test case across runs, so that Allure shows its history
+    history_id: String,
+    full_name: String,
+    name: String,
+    #[serde(flatten)]
+    execution: Execution,
+    labels: Vec<NameValue>,
+    parameters: Vec<NameValue>,
+    steps: Vec<Step>,
+}
+
+/// How a test case or a step went
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Execution {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status_details: Option<StatusDetails>,
+    stage: &'static str,
+    /// Milliseconds since the Unix epoch
+    start: u64,
+    stop: u64,
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusDetails {
+    message: String,
+    /// The test case is expected to fail
+    known: bool,
+    flaky: bool,
+}
+
+#[derive(Serialize)]
+struct NameValue {
+    name: &'static str,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Step {
+    name: String,
+    #[serde(flatten)]
+    execution: Execution,
+}
+
+#[derive(Clone, Serialize)]
+struct Attachment {
+    name: String,
+    /// Name of the file in the results directory
+    source: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    mime_type: Option<&'static str>,
+}
+
+impl AllureResults {
+    pub(crate) const DIR_NAME: &str = "allure-results";
+
+    const ENVIRONMENT_FILE_NAME: &str = "environment.properties";
+
+    /// Results written to the given directory along with the metadata of the run, the results of
+    /// previous runs being removed
+    pub(crate) fn new(dir: PathBuf, run_metadata: &Env) -> Result<Self> {
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).map_err(|io_err| error::kind::ReportIo {
+            filename: dir.clone(),
+            source: io_err,
+        })?;
+        if !run_metadata.is_empty() {
+            let filename = dir.join(Self::ENVIRONMENT_FILE_NAME);
+            std::fs::write(&filename, environment_properties(run_metadata)).map_err(|io_err| {
+                error::kind::ReportIo {
+                    filename,
+                    source: io_err,
+                }
+            })?;
+        }
+        Ok(Self {
+            dir,
+            rng: Rng::new(rng::random_seed() ^ u64::from(std::process::id())),
+        })
+    }
+
+    /// Write the results of the test cases of a test suite on all its targets, fixtures excepted
+    pub(crate) fn write(
+        &mut self,
+        test_suite: &TestSuite,
+        exec_contexts: &[ExecutionContext],
+    ) -> Result<()> {
+        for exec_context in exec_contexts {
+            // The setups and teardowns are steps of several test cases, their log files being
+            // attached once
+            let mut fixture_executions = HashMap::new();
+            for (test_case, exec_info) in exec_context.iter_exec_info() {
+                if test_suite.is_fixture(test_case) {
+                    continue;
+                }
+                let mut steps = Vec::new();
+                for (kind, fixture) in fixtures_of(test_suite, test_case) {
+                    let Some(fixture_exec_info) = exec_context.exec_info(fixture) else {
+                        continue;
+                    };
+                    let execution = match fixture_executions.get(&fixture.id()) {
+                        Some(execution) => Execution::clone(execution),
+                        None => {
+                            let execution = self.execution(fixture_exec_info)?;
+                            fixture_executions.insert(fixture.id(), execution.clone());
+                            execution
+                        }
+                    };
+                    steps.push(Step {
+                        name: format!("{kind} `{}`", fixture.id()),
+                        execution,
+                    });
+                }
+                let uuid = self.uuid();
+                let result = AllureResult {
+                    history_id: format!(
+                        "{}/{}/{}",
+                        test_suite.config().name,
+                        test_case.id(),
+                        exec_context.target()
+                    ),
+                    full_name: format!("{}/{}", test_suite.config().name, test_case.id()),
+                    name: test_case.instance_name(),
+                    execution: self.execution(exec_info)?,
+                    labels: labels(test_suite, test_case),
+                    parameters: parameters(test_case, exec_context.target()),
+                    steps,
+                    uuid,
+                };
+                let path = self.dir.join(format!("{}-result.json", result.uuid));
+                // UNWRAP: the result only contains strings, numbers and booleans
+                let contents = serde_json::to_string_pretty(&result).unwrap();
+                std::fs::write(&path, contents).map_err(|io_err| error::kind::ReportIo {
+                    filename: path,
+                    source: io_err,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// How a test case went, copying its log files to the results directory
+    fn execution(&mut self, exec_info: &TestCaseExecInfo) -> Result<Execution> {
+        let (status, message, known) = match exec_info.result() {
+            Ok(output) => {
+                let (status, reason, known) = match &output.test_case_status {
+                    TestCaseStatus::Passed => ("passed", None, false),
+                    TestCaseStatus::Failed(reason) => ("failed", Some(reason.to_string()), false),
+                    TestCaseStatus::Skipped(reason) => ("skipped", Some(reason.to_string()), false),
+                    TestCaseStatus::XFailed(reason) => {
+                        ("skipped", Some(format!("expected to fail: {reason}")), true)
+                    }
+                    TestCaseStatus::XPassed(reason) => {
+                        ("passed", Some(format!("expected to fail: {reason}")), true)
+                    }
+                    TestCaseStatus::DryRun => ("skipped", Some("dry run".to_string()), false),
+                    TestCaseStatus::NotRun | TestCaseStatus::Running => ("unknown", None, false),
+                };
+                let message = [reason, output.message.clone()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>();
+                (status, message, known)
+            }
+            Err(error) => ("broken", vec![error.to_string()], false),
+        };
+        let flaky = exec_info.is_flaky();
+        let status_details = (!message.is_empty() || known || flaky).then(|| StatusDetails {
+            message: message.join("\n"),
+            known,
+            flaky,
+        });
+        let start = exec_info.started_at();
+        let stop = start + exec_info.duration().unwrap_or_default();
+        let attachments = exec_info
+            .log_files()
+            .iter()
+            .chain(exec_info.failure_artifacts())
+            .filter(|log_file| log_file.path.is_file())
+            .map(|log_file| self.attach(log_file))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Execution {
+            status,
+            status_details,
+            stage: "finished",
+            start: epoch_millis(start),
+            stop: epoch_millis(stop),
+            attachments,
+        })
+    }
+
+    /// Copy a log file to the results directory
+    fn attach(&mut self, log_file: &LogFile) -> Result<Attachment> {
+        let extension = log_file
+            .path
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+            .unwrap_or_default();
+        let source = format!("{}-attachment{extension}", self.uuid());
+        let path = self.dir.join(&source);
+        std::fs::copy(&log_file.path, &path).map_err(|io_err| error::kind::ReportIo {
+            filename: path,
+            source: io_err,
+        })?;
+        Ok(Attachment {
+            name: log_file.kind.to_string(),
+            source,
+            mime_type: mime_type(&extension),
+        })
+    }
+
+    /// A random UUID (version 4), naming the results and attachments
+    fn uuid(&mut self) -> String {
+        let high = self.rng.next_u64() & !0xf000 | 0x4000;
+        let low = self.rng.next_u64() & !(0b11 << 62) | (0b10 << 62);
+        format!(
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xffff,
+            high & 0xffff,
+            low >> 48,
+            low & 0xffff_ffff_ffff
+        )
+    }
+}
+
+/// The setups and teardowns of a test case, in the order they run, with their kind
+fn fixtures_of<'a>(
+    test_suite: &'a TestSuite,
+    test_case: &TestCase,
+) -> Vec<(&'static str, &'a TestCase)> {
+    let test_file = test_suite
+        .test_files()
+        .iter()
+        .find(|test_file| test_file.path == test_case.path());
+    let fixture = test_suite.fixture();
+    [
+        ("Setup", fixture.setup_test_case.as_ref()),
+        (
+            "Setup",
+            test_file.and_then(|file| file.setup_test_case.as_ref()),
+        ),
+        (
+            "Teardown",
+            test_file.and_then(|file| file.teardown_test_case.as_ref()),
+        ),
+        ("Teardown", fixture.teardown_test_case.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(kind, fixture)| Some((kind, fixture?)))
+    .collect()
+}
+
+/// The test suite and test file of a test case as suites, and its tags
+fn labels(test_suite: &TestSuite, test_case: &TestCase) -> Vec<NameValue> {
+    let mut labels = vec![
+        NameValue {
+            name: "framework",
+            value: "batrun".to_string(),
+        },
+        NameValue {
+            name: "suite",
+            value: test_suite.config().name.clone(),
+        },
+        NameValue {
+            name: "subSuite",
+            value: test_case.path().display().to_string(),
+        },
+    ];
+    labels.extend(test_case.metadata().tags.iter().map(|tag| NameValue {
+        name: "tag",
+        value: tag.clone(),
+    }));
+    labels
+}
+
+fn parameters(test_case: &TestCase, target: &str) -> Vec<NameValue> {
+    let mut parameters = vec![NameValue {
+        name: "target",
+        value: target.to_string(),
+    }];
+    if let Some(params) = test_case.params() {
+        parameters.push(NameValue {
+            name: "params",
+            value: params.to_string(),
+        });
+    }
+    parameters
+}
+
+fn mime_type(extension: &str) -> Option<&'static str> {
+    match extension {
+        ".log" | ".txt" => Some("text/plain"),
+        ".csv" => Some("text/csv"),
+        ".json" => Some("application/json"),
+        _ => None,
+    }
+}
+
+fn epoch_millis(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis() as u64
+}
+
+/// The metadata of the run as a Java properties file, the format Allure reads its environment in
+fn environment_properties(run_metadata: &Env) -> String {
+    let escape = |text: &str, is_key: bool| {
+        let mut escaped = String::new();
+        for c in text.chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '=' | ':' | ' ' if is_key => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    };
+    run_metadata
+        .iter()
+        .map(|(key, value)| format!("{}={}\n", escape(key, true), escape(value, false)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_as_properties() {
+        let run_metadata = Env::from([
+            ("BUILD".to_string(), "42".to_string()),
+            ("branch name".to_string(), "feat=x\\y\nz".to_string()),
+        ]);
+        assert_eq!(
+            environment_properties(&run_metadata),
+            "BUILD=42\nbranch\\ name=feat=x\\\\y\\nz\n"
+        );
+    }
+
+    #[test]
+    fn uuids_are_version_4() {
+        let mut results = AllureResults {
+            dir: PathBuf::new(),
+            rng: Rng::new(42),
+        };
+        let uuid = results.uuid();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(
+            uuid.split('-').map(str::len).collect::<Vec<_>>(),
+            [8, 4, 4, 4, 12]
+        );
+        assert_eq!(&uuid[14..15], "4");
+        assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(results.uuid(), uuid);
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(feature = "gcs")]
+pub(crate) mod gcs;
+#[cfg(feature = "s3")]
+pub(crate) mod s3;
+
+use crate::error::Result;
+use crate::test_suite::config::ArtifactStorageConfig;
+
+use std::path::Path;
+
+/// An artifact storage keeps the log files and artifacts of the test cases once they ran (e.g.
+/// in an object storage, so that huge lab runs do not fill the disk of the host)
+/// It must be `Send` and `Sync` as it is shared between threads by parallel executors
+pub trait ArtifactStorage: Send + Sync {
+    /// Store a file written in the output directory, returning where it was stored if not only
+    /// in the output directory
+    fn store(&self, path: &Path) -> Result<Option<String>>;
+}
+
+/// Keep the files in the output directory only
+#[derive(Default)]
+pub struct LocalStorage;
+
+impl ArtifactStorage for LocalStorage {
+    fn store(&self, _path: &Path) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// Create the artifact storage described in the config of the test suite located in
+/// `test_suite_dir`, files being named after their path relative to `out_dir`
+#[cfg_attr(not(any(feature = "s3", feature = "gcs")), allow(unused_variables))]
+pub(crate) fn from_config(
+    config: &ArtifactStorageConfig,
+    test_suite_dir: &Path,
+    out_dir: &Path,
+) -> Box<dyn ArtifactStorage> {
+    match config {
+        ArtifactStorageConfig::Local => Box::new(LocalStorage),
+        #[cfg(feature = "s3")]
+        ArtifactStorageConfig::S3(config) => {
+            Box::new(s3::S3Storage::new(config.clone(), test_suite_dir, out_dir))
+        }
+        #[cfg(feature = "gcs")]
+        ArtifactStorageConfig::Gcs(config) => Box::new(gcs::GcsStorage::new(
+            config.clone(),
+            test_suite_dir,
+            out_dir,
+        )),
+    }
+}
+
+#[cfg(any(feature = "s3", feature = "gcs"))]
+fn error(path: &Path, details: impl Into<String>) -> crate::error::Error {
+    crate::error::kind::ArtifactStorage {
+        filename: path.to_path_buf(),
+        details: details.into(),
+    }
+    .into()
+}
+
+/// Name of the object a file is uploaded to: its path relative to the output directory, after
+/// the prefix of the config
+#[cfg(any(feature = "s3", feature = "gcs"))]
+fn object_name(prefix: &str, out_dir: &Path, path: &Path) -> String {
+    let relative_path = path.strip_prefix(out_dir).unwrap_or(path);
+    let components = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    format!("{prefix}{}", components.join("/"))
+}
+
+/// Upload a file with the CLI of an object storage, removing the local copy afterwards unless it
+/// is kept
+#[cfg(any(feature = "s3", feature = "gcs"))]
+fn upload(
+    mut command: std::process::Command,
+    path: &Path,
+    url: String,
+    keep_local: bool,
+) -> Result<Option<String>> {
+    let program = std::path::PathBuf::from(command.get_program());
+    let output = command
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|io_err| {
+            error(
+                path,
+                format!("cannot execute `{}`: {io_err}", program.display()),
+            )
+        })?;
+    if !output.status.success() {
+        return Err(error(
+            path,
+            format!(
+                "`{}` exited with {}: {}",
+                program.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ));
+    }
+    if !keep_local {
+        std::fs::remove_file(path).map_err(|io_err| error(path, io_err.to_string()))?;
+    }
+    Ok(Some(url))
+}
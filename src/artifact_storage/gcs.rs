@@ -0,0 +1,46 @@
+use crate::artifact_storage::{self, ArtifactStorage};
+use crate::error::Result;
+use crate::test_suite::config::ObjectStorageConfig;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Upload the files to a Google Cloud Storage bucket with `gcloud storage cp`, the credentials
+/// and project being the ones the `gcloud` CLI is configured with
+pub(crate) struct GcsStorage {
+    config: ObjectStorageConfig,
+    /// Directory the `gcloud` CLI is run from
+    working_dir: PathBuf,
+    out_dir: PathBuf,
+}
+
+impl GcsStorage {
+    const DEFAULT_PROGRAM: &str = "gcloud";
+
+    pub(crate) fn new(config: ObjectStorageConfig, working_dir: &Path, out_dir: &Path) -> Self {
+        Self {
+            config,
+            working_dir: working_dir.to_path_buf(),
+            out_dir: out_dir.to_path_buf(),
+        }
+    }
+}
+
+impl ArtifactStorage for GcsStorage {
+    fn store(&self, path: &Path) -> Result<Option<String>> {
+        let object = artifact_storage::object_name(&self.config.prefix, &self.out_dir, path);
+        let url = format!("gs://{}/{object}", self.config.bucket);
+        let mut command = Command::new(
+            self.config
+                .program
+                .as_deref()
+                .unwrap_or(Self::DEFAULT_PROGRAM),
+        );
+        command
+            .current_dir(&self.working_dir)
+            .args(["storage", "cp", "--quiet"])
+            .arg(path)
+            .arg(&url);
+        artifact_storage::upload(command, path, url, self.config.keep_local)
+    }
+}
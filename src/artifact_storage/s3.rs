@@ -0,0 +1,46 @@
+use crate::artifact_storage::{self, ArtifactStorage};
+use crate::error::Result;
+use crate::test_suite::config::ObjectStorageConfig;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Upload the files to an Amazon S3 bucket with `aws s3 cp`, the credentials and region being
+/// the ones the `aws` CLI is configured with
+pub(crate) struct S3Storage {
+    config: ObjectStorageConfig,
+    /// Directory the `aws` CLI is run from
+    working_dir: PathBuf,
+    out_dir: PathBuf,
+}
+
+impl S3Storage {
+    const DEFAULT_PROGRAM: &str = "aws";
+
+    pub(crate) fn new(config: ObjectStorageConfig, working_dir: &Path, out_dir: &Path) -> Self {
+        Self {
+            config,
+            working_dir: working_dir.to_path_buf(),
+            out_dir: out_dir.to_path_buf(),
+        }
+    }
+}
+
+impl ArtifactStorage for S3Storage {
+    fn store(&self, path: &Path) -> Result<Option<String>> {
+        let object = artifact_storage::object_name(&self.config.prefix, &self.out_dir, path);
+        let url = format!("s3://{}/{object}", self.config.bucket);
+        let mut command = Command::new(
+            self.config
+                .program
+                .as_deref()
+                .unwrap_or(Self::DEFAULT_PROGRAM),
+        );
+        command
+            .current_dir(&self.working_dir)
+            .args(["s3", "cp", "--only-show-errors"])
+            .arg(path)
+            .arg(&url);
+        artifact_storage::upload(command, path, url, self.config.keep_local)
+    }
+}
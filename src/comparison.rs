@@ -0,0 +1,202 @@
+use crate::error::{self, Result};
+use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
+use crate::test_suite::TestSuite;
+use crate::test_suite::id::TestCaseId;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Results of a previous run of a test suite, loaded from its run manifest, for the results of
+/// the run to be compared with so that regressions stand out from the failures already known
+#[derive(Debug, Clone)]
+pub struct PreviousRun {
+    path: PathBuf,
+    test_suite: String,
+    /// Status of the test cases in the manifest (e.g. `passed` or `runner-failed`), by target and
+    /// test case id
+    statuses: HashMap<(String, TestCaseId), String>,
+}
+
+/// The parts of a run manifest the results are compared with
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ManifestContents {
+    test_suite: TestSuiteContents,
+    targets: Vec<TargetContents>,
+}
+
+#[derive(Deserialize)]
+struct TestSuiteContents {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct TargetContents {
+    name: String,
+    test_cases: Vec<TestCaseContents>,
+}
+
+#[derive(Deserialize)]
+struct TestCaseContents {
+    id: TestCaseId,
+    status: String,
+}
+
+/// How the results of a test suite changed since a previous run
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Comparison {
+    /// Run manifest of the previous run
+    pub previous_run: PathBuf,
+    pub changes: Vec<Change>,
+}
+
+/// A test case whose result changed on a target since the previous run
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub target: String,
+    #[serde(rename = "id")]
+    pub test_case_id: TestCaseId,
+    pub status: &'static str,
+    /// Status in the previous run, `None` for a test case which did not run on the target then
+    pub previous_status: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeKind {
+    /// The test case failed (or could not be run), while it did not in the previous run
+    NewFailure,
+    /// The test case passed, while it failed (or could not be run) in the previous run
+    Fixed,
+    /// The test case was skipped, while it was not in the previous run
+    NewlySkipped,
+}
+
+impl ChangeKind {
+    /// Name of the change in the machine-readable outputs (e.g. `new-failure`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChangeKind::NewFailure => "new-failure",
+            ChangeKind::Fixed => "fixed",
+            ChangeKind::NewlySkipped => "newly-skipped",
+        }
+    }
+}
+
+impl PreviousRun {
+    /// Load the results of a previous run from its run manifest (`run.json`)
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |details: String| error::kind::InvalidPreviousRun {
+            filename: path.to_path_buf(),
+            details,
+        };
+        let contents =
+            std::fs::read_to_string(path).map_err(|io_err| invalid(io_err.to_string()))?;
+        let manifest: ManifestContents =
+            serde_json::from_str(&contents).map_err(|serde_err| invalid(serde_err.to_string()))?;
+        let statuses = manifest
+            .targets
+            .into_iter()
+            .flat_map(|target| {
+                target
+                    .test_cases
+                    .into_iter()
+                    .map(move |test_case| ((target.name.clone(), test_case.id), test_case.status))
+            })
+            .collect();
+        Ok(Self {
+            path: path.to_path_buf(),
+            test_suite: manifest.test_suite.name,
+            statuses,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Name of the test suite which ran
+    pub fn test_suite(&self) -> &str {
+        &self.test_suite
+    }
+
+    /// Compare the results of a test suite with the ones of the previous run, `None` if the
+    /// previous run is not one of this test suite
+    pub fn compare(
+        &self,
+        test_suite: &TestSuite,
+        exec_contexts: &[ExecutionContext],
+    ) -> Option<Comparison> {
+        if test_suite.config().name != self.test_suite {
+            return None;
+        }
+        let mut changes = Vec::new();
+        for exec_context in exec_contexts {
+            for (test_case, exec_info) in exec_context.iter_exec_info() {
+                let status = status_name(exec_info);
+                let previous_status = self
+                    .statuses
+                    .get(&(exec_context.target().to_string(), test_case.id()))
+                    .cloned();
+                let previous = previous_status.as_deref().unwrap_or_default();
+                let kind = if is_failure(status) && !is_failure(previous) {
+                    ChangeKind::NewFailure
+                } else if is_pass(status) && is_failure(previous) {
+                    ChangeKind::Fixed
+                } else if status == "skipped" && previous != "skipped" {
+                    ChangeKind::NewlySkipped
+                } else {
+                    continue;
+                };
+                changes.push(Change {
+                    kind,
+                    target: exec_context.target().to_string(),
+                    test_case_id: test_case.id(),
+                    status,
+                    previous_status,
+                });
+            }
+        }
+        Some(Comparison {
+            previous_run: self.path.clone(),
+            changes,
+        })
+    }
+}
+
+impl Comparison {
+    pub fn changes_of_kind(&self, kind: ChangeKind) -> impl Iterator<Item = &Change> {
+        self.changes
+            .iter()
+            .filter(move |change| change.kind == kind)
+    }
+
+    /// Whether test cases fail which did not in the previous run
+    pub fn has_regressions(&self) -> bool {
+        self.changes_of_kind(ChangeKind::NewFailure)
+            .next()
+            .is_some()
+    }
+}
+
+/// The status of a test case as named in the run manifest
+fn status_name(exec_info: &TestCaseExecInfo) -> &'static str {
+    match exec_info.result() {
+        Ok(output) => output.test_case_status.name(),
+        Err(_) => "runner-failed",
+    }
+}
+
+fn is_failure(status: &str) -> bool {
+    matches!(status, "failed" | "runner-failed")
+}
+
+fn is_pass(status: &str) -> bool {
+    matches!(status, "passed" | "xpassed")
+}
@@ -8,6 +8,9 @@ pub enum Error {
     #[error(transparent)]
     InvalidSuiteConfig(#[from] kind::InvalidSuiteConfig),
 
+    #[error(transparent)]
+    SuiteConfigValidation(#[from] kind::SuiteConfigValidation),
+
     #[error("unknown test driver `{0}`")]
     UnknownTestDriver(String),
 
@@ -20,6 +23,48 @@ pub enum Error {
     #[error(transparent)]
     TestFileExec(#[from] kind::TestFileExec),
 
+    #[error(transparent)]
+    InvalidTestMetadata(#[from] kind::InvalidTestMetadata),
+
+    #[error(transparent)]
+    HistoryIo(#[from] kind::HistoryIo),
+
+    #[error(transparent)]
+    InvalidHistory(#[from] kind::InvalidHistory),
+
+    #[error(transparent)]
+    TargetBackend(#[from] kind::TargetBackend),
+
+    #[error(transparent)]
+    ReportIo(#[from] kind::ReportIo),
+
+    #[error(transparent)]
+    InvalidExitCodes(#[from] kind::InvalidExitCodes),
+
+    #[error(transparent)]
+    InvalidGlobalConfig(#[from] kind::InvalidGlobalConfig),
+
+    #[error(transparent)]
+    InvalidPausedRun(#[from] kind::InvalidPausedRun),
+
+    #[error(transparent)]
+    InvalidPreviousRun(#[from] kind::InvalidPreviousRun),
+
+    #[error(transparent)]
+    Secrets(#[from] kind::Secrets),
+
+    #[error(transparent)]
+    ArtifactStorage(#[from] kind::ArtifactStorage),
+
+    #[error(transparent)]
+    Service(#[from] kind::Service),
+
+    #[error(transparent)]
+    PortAllocation(#[from] kind::PortAllocation),
+
+    #[error(transparent)]
+    NetworkIsolation(#[from] kind::NetworkIsolation),
+
     #[error("no test found in file `{0}`")]
     NoTestFound(PathBuf),
 
@@ -49,6 +94,36 @@ pub mod kind {
         pub source: serde_json::Error,
     }
 
+    /// A test suite config file with issues, all of them being reported at once
+    #[derive(thiserror::Error, Debug)]
+    #[error("invalid test suite config file `{}`", .filename.display())]
+    pub struct SuiteConfigValidation {
+        pub filename: PathBuf,
+        pub issues: Vec<ConfigIssue>,
+    }
+
+    /// An issue found in a test suite config file, with how to fix it if not obvious
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ConfigIssue {
+        pub message: String,
+        pub hint: Option<String>,
+        /// Line and column of the issue in the config file, both starting from 1
+        pub position: Option<(usize, usize)>,
+    }
+
+    impl std::fmt::Display for ConfigIssue {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if let Some((line, column)) = self.position {
+                write!(f, "line {line}, column {column}: ")?;
+            }
+            write!(f, "{}", self.message)?;
+            if let Some(hint) = &self.hint {
+                write!(f, " ({hint})")?;
+            }
+            Ok(())
+        }
+    }
+
     #[derive(thiserror::Error, Debug)]
     #[error("cannot execute test driver command `{}`", .filename.display())]
     pub struct TestDriverIo {
@@ -62,4 +137,101 @@ pub mod kind {
         pub filename: PathBuf,
         pub details: String,
     }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("cannot access the history file `{}`", .filename.display())]
+    pub struct HistoryIo {
+        pub filename: PathBuf,
+        pub source: std::io::Error,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("invalid history file `{}`", .filename.display())]
+    pub struct InvalidHistory {
+        pub filename: PathBuf,
+        pub source: serde_json::Error,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("invalid test metadata in file `{}`", .filename.display())]
+    pub struct InvalidTestMetadata {
+        pub filename: PathBuf,
+        pub details: String,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("cannot write the report file `{}`", .filename.display())]
+    pub struct ReportIo {
+        pub filename: PathBuf,
+        pub source: std::io::Error,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("invalid exit code mapping file `{}`", .filename.display())]
+    pub struct InvalidExitCodes {
+        pub filename: PathBuf,
+        pub details: String,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("invalid batrun config file `{}`", .filename.display())]
+    pub struct InvalidGlobalConfig {
+        pub filename: PathBuf,
+        pub details: String,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("cannot resume the run paused in `{}`", .filename.display())]
+    pub struct InvalidPausedRun {
+        pub filename: PathBuf,
+        pub details: String,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("cannot compare with the run manifest `{}`", .filename.display())]
+    pub struct InvalidPreviousRun {
+        pub filename: PathBuf,
+        pub details: String,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("cannot load the secrets of test suite `{}`", .test_suite.display())]
+    pub struct Secrets {
+        pub test_suite: PathBuf,
+        pub details: String,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("cannot store `{}`", .filename.display())]
+    pub struct ArtifactStorage {
+        pub filename: PathBuf,
+        pub details: String,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("service `{service}` failed")]
+    pub struct Service {
+        pub service: String,
+        pub details: String,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("cannot allocate {count} port(s)")]
+    pub struct PortAllocation {
+        pub count: usize,
+        pub source: std::io::Error,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("cannot isolate the test case from the network of the host")]
+    pub struct NetworkIsolation {
+        pub details: String,
+    }
+
+    #[derive(thiserror::Error, Debug)]
+    #[error("target `{target}` is unavailable")]
+    pub struct TargetBackend {
+        pub target: String,
+        pub details: String,
+    }
 }
@@ -1,15 +1,91 @@
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
 /// Aliases are currently not showed in help message.
 /// Wait for https://github.com/clap-rs/clap/pull/5480 to be merged to make aliases visible in help message.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum ExecutionStrategy {
     /// Run all test cases sequentially for a target before passing to the next target
-    #[clap(aliases = &["s", "seq"])]
+    #[cfg_attr(feature = "clap", clap(aliases = &["s", "seq"]))]
     Sequential,
     /// Run each test case for all targets before passing to the next test case
-    #[clap(aliases = &["r", "rr"])]
+    #[cfg_attr(feature = "clap", clap(aliases = &["r", "rr"]))]
     RoundRobin,
-    /// Run all test cases for each targets in parallel
-    #[clap(aliases = &["p", "par"])]
+    /// Run all test cases for each targets in parallel, on a pool of workers servicing one target
+    /// at a time
+    #[cfg_attr(feature = "clap", clap(aliases = &["p", "par"]))]
     Parallel,
+    /// Run all test cases sequentially for each target, all targets being run in parallel
+    #[cfg_attr(feature = "clap", clap(aliases = &["pt", "par-targets"]))]
+    ParallelTargets,
+}
+
+impl ExecutionStrategy {
+    /// How long running a test suite is expected to take given how long it is expected to take
+    /// on each target, the targets being run on at most `jobs` workers at the same time by the
+    /// parallel strategy (all of them if not set)
+    /// Independent test files running concurrently on a target are not accounted for, the
+    /// estimate being an upper bound then.
+    pub fn estimate_duration(
+        self,
+        target_durations: &[Duration],
+        jobs: Option<NonZeroUsize>,
+    ) -> Duration {
+        let workers = match self {
+            Self::Sequential | Self::RoundRobin => 1,
+            Self::Parallel => jobs.map_or(target_durations.len(), NonZeroUsize::get),
+            Self::ParallelTargets => target_durations.len(),
+        };
+        // Each target goes to the first worker available, in target order
+        let mut busy_until = vec![Duration::ZERO; workers.clamp(1, target_durations.len().max(1))];
+        for duration in target_durations {
+            // UNWRAP: there is always at least one worker
+            let first_available = busy_until.iter_mut().min().unwrap();
+            *first_available += *duration;
+        }
+        busy_until.into_iter().max().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DURATIONS: [Duration; 3] = [
+        Duration::from_secs(30),
+        Duration::from_secs(20),
+        Duration::from_secs(40),
+    ];
+
+    #[test]
+    fn targets_one_after_the_other() {
+        for strategy in [ExecutionStrategy::Sequential, ExecutionStrategy::RoundRobin] {
+            assert_eq!(
+                strategy.estimate_duration(&DURATIONS, None),
+                Duration::from_secs(90)
+            );
+        }
+    }
+
+    #[test]
+    fn targets_in_parallel() {
+        assert_eq!(
+            ExecutionStrategy::ParallelTargets.estimate_duration(&DURATIONS, None),
+            Duration::from_secs(40)
+        );
+        assert_eq!(
+            ExecutionStrategy::Parallel.estimate_duration(&DURATIONS, None),
+            Duration::from_secs(40)
+        );
+        assert_eq!(
+            ExecutionStrategy::Parallel.estimate_duration(&DURATIONS, NonZeroUsize::new(2)),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            ExecutionStrategy::Parallel.estimate_duration(&[], NonZeroUsize::new(2)),
+            Duration::ZERO
+        );
+    }
 }
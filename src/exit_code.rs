@@ -0,0 +1,192 @@
+use crate::error::{self, Result};
+use crate::test_suite::status::Statistics;
+
+use serde::Deserialize;
+
+use std::path::Path;
+
+/// Overall outcome of a run, from the best to the worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RunOutcome {
+    /// No test case failed (some may have been skipped or have failed as expected)
+    Passed,
+    /// Some test cases failed, but all could be run
+    Failed,
+    /// Some test cases could not be run (e.g. a target was unavailable)
+    RunnerFailed,
+    /// The run was paused before all test cases were run, its progress being saved so that it
+    /// can be resumed
+    Paused,
+    /// The run was interrupted (e.g. with Ctrl-C) before all test cases were run
+    Interrupted,
+    /// batrun itself failed (e.g. invalid test suite config)
+    Error,
+}
+
+impl RunOutcome {
+    /// Test cases expected to fail which pass only fail the run if `strict_xfail` is set
+    pub fn from_statistics(statistics: &Statistics, strict_xfail: bool) -> Self {
+        if statistics.runner_failed > 0 {
+            RunOutcome::RunnerFailed
+        } else if statistics.failed > 0 || (strict_xfail && statistics.xpassed > 0) {
+            RunOutcome::Failed
+        } else {
+            RunOutcome::Passed
+        }
+    }
+}
+
+/// Exit code of batrun for each run outcome, so that batrun fits into scripts expecting specific
+/// exit codes
+/// By default, failed runs exit with a nonzero code so that CI jobs fail. It is loaded from a JSON
+/// file such as `{ "failed": 0, "runner-failed": 2 }`, the outcomes not listed keeping their
+/// default exit code.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct ExitCodes {
+    pub passed: u8,
+    pub failed: u8,
+    pub runner_failed: u8,
+    pub paused: u8,
+    pub interrupted: u8,
+    pub error: u8,
+}
+
+impl Default for ExitCodes {
+    fn default() -> Self {
+        Self {
+            passed: 0,
+            failed: 2,
+            runner_failed: 3,
+            // EX_TEMPFAIL, as the run is to be resumed later
+            paused: 75,
+            // As shells do for commands killed by SIGINT
+            interrupted: 130,
+            error: 1,
+        }
+    }
+}
+
+impl ExitCodes {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|io_err| Self::invalid(path, io_err.to_string()))?;
+        Self::parse(path, &contents)
+    }
+
+    /// Parse the contents of the JSON file at `path`
+    fn parse(path: &Path, contents: &str) -> Result<Self> {
+        Ok(serde_json::from_str(contents)
+            .map_err(|serde_err| Self::invalid(path, serde_err.to_string()))?)
+    }
+
+    fn invalid(path: &Path, details: String) -> error::kind::InvalidExitCodes {
+        error::kind::InvalidExitCodes {
+            filename: path.to_path_buf(),
+            details,
+        }
+    }
+
+    /// Exit with the same code whether test cases failed or not, only pauses, interrupts and
+    /// errors of batrun itself being reported through the exit code
+    pub fn ignoring_failures(self) -> Self {
+        Self {
+            failed: self.passed,
+            runner_failed: self.passed,
+            ..self
+        }
+    }
+
+    pub fn code(&self, outcome: RunOutcome) -> u8 {
+        match outcome {
+            RunOutcome::Passed => self.passed,
+            RunOutcome::Failed => self.failed,
+            RunOutcome::RunnerFailed => self.runner_failed,
+            RunOutcome::Paused => self.paused,
+            RunOutcome::Interrupted => self.interrupted,
+            RunOutcome::Error => self.error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn outcomes_to_default_codes() {
+        let exit_codes = ExitCodes::default();
+        let codes = [
+            RunOutcome::Passed,
+            RunOutcome::Failed,
+            RunOutcome::RunnerFailed,
+            RunOutcome::Paused,
+            RunOutcome::Interrupted,
+            RunOutcome::Error,
+        ]
+        .map(|outcome| exit_codes.code(outcome));
+        assert_eq!(codes, [0, 2, 3, 75, 130, 1]);
+        let exit_codes = exit_codes.ignoring_failures();
+        assert_eq!(exit_codes.code(RunOutcome::Failed), 0);
+        assert_eq!(exit_codes.code(RunOutcome::RunnerFailed), 0);
+        assert_eq!(exit_codes.code(RunOutcome::Interrupted), 130);
+    }
+
+    #[test]
+    fn outcome_from_statistics() {
+        let statistics = Statistics {
+            passed: 1,
+            xpassed: 1,
+            ..Statistics::default()
+        };
+        assert_eq!(
+            RunOutcome::from_statistics(&statistics, false),
+            RunOutcome::Passed
+        );
+        assert_eq!(
+            RunOutcome::from_statistics(&statistics, true),
+            RunOutcome::Failed
+        );
+        let statistics = Statistics {
+            failed: 1,
+            runner_failed: 1,
+            ..Statistics::default()
+        };
+        assert_eq!(
+            RunOutcome::from_statistics(&statistics, false),
+            RunOutcome::RunnerFailed
+        );
+    }
+
+    #[test]
+    fn load_unlisted_outcomes_keep_default() {
+        let path = Path::new("exit-codes.json");
+        let exit_codes = ExitCodes::parse(path, r#"{ "failed": 0, "runner-failed": 4 }"#).unwrap();
+        assert_eq!(exit_codes.code(RunOutcome::Failed), 0);
+        assert_eq!(exit_codes.code(RunOutcome::RunnerFailed), 4);
+        assert_eq!(exit_codes.code(RunOutcome::Passed), 0);
+        assert_eq!(exit_codes.code(RunOutcome::Error), 1);
+    }
+
+    #[test]
+    fn load_invalid() {
+        let path = Path::new("exit-codes.json");
+        let Err(Error::InvalidExitCodes(error)) = ExitCodes::parse(path, r#"{ "failled": 0 }"#)
+        else {
+            panic!("unknown outcome accepted");
+        };
+        assert_eq!(error.filename, path);
+        assert!(
+            error.details.contains("unknown field `failled`"),
+            "{}",
+            error.details
+        );
+        assert!(ExitCodes::parse(path, r#"{ "failed": 256 }"#).is_err());
+        let Err(Error::InvalidExitCodes(error)) = ExitCodes::load(Path::new("/nonexistent.json"))
+        else {
+            panic!("missing file loaded");
+        };
+        assert_eq!(error.filename, Path::new("/nonexistent.json"));
+    }
+}
@@ -0,0 +1,291 @@
+use crate::error::{self, Error, Result};
+use crate::reporter::Reporter;
+use crate::test_driver::{DriverOptions, Env, LogFile, RunTestOutput, TestDriver};
+use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
+use crate::test_suite::config::TestSuiteConfig;
+use crate::test_suite::filter::PathFilter;
+use crate::test_suite::{TestCase, TestSuite};
+
+use std::fmt::Display;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Fault injected into a run to exercise the error paths of batrun, for its developers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The test driver fails to start the test case, which is reported as runner failed
+    DriverIoError,
+    /// The test case times out without being run
+    Timeout,
+    /// The reporters panic when reporting the result of the test case
+    ReporterPanic,
+}
+
+impl Fault {
+    const ALL: [Fault; 3] = [Fault::DriverIoError, Fault::Timeout, Fault::ReporterPanic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Fault::DriverIoError => "driver-io-error",
+            Fault::Timeout => "timeout",
+            Fault::ReporterPanic => "reporter-panic",
+        }
+    }
+}
+
+/// A fault and the occurrence it is injected into, written `<fault>@<n>`
+/// The occurrences are counted from 1 over the whole run, in the order they happen on any target:
+/// the test cases run by the test driver (retries included) for the driver faults, and the
+/// results reported for `reporter-panic`. E.g. `driver-io-error@2` makes the test driver fail to
+/// start the second test case it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultSpec {
+    pub fault: Fault,
+    pub occurrence: NonZeroUsize,
+}
+
+impl FromStr for FaultSpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> std::result::Result<Self, Self::Err> {
+        let names = || {
+            Fault::ALL
+                .iter()
+                .map(|fault| fault.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let (name, occurrence) = spec
+            .split_once('@')
+            .ok_or_else(|| format!("`{spec}` is not of the form <fault>@<n>"))?;
+        let fault = Fault::ALL
+            .into_iter()
+            .find(|fault| fault.name() == name)
+            .ok_or_else(|| format!("unknown fault `{name}`, expected one of: {}", names()))?;
+        let occurrence = occurrence
+            .parse()
+            .map_err(|_| format!("`{occurrence}` is not a positive number"))?;
+        Ok(Self { fault, occurrence })
+    }
+}
+
+impl Display for FaultSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.fault.name(), self.occurrence)
+    }
+}
+
+/// Injects the faults of a run, counting their occurrences across test suites and targets
+#[derive(Debug, Default)]
+pub(crate) struct FaultInjector {
+    specs: Vec<FaultSpec>,
+    test_runs: AtomicUsize,
+    results: AtomicUsize,
+}
+
+impl FaultInjector {
+    pub(crate) fn new(specs: Vec<FaultSpec>) -> Self {
+        Self {
+            specs,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+
+    fn injects(&self, fault: Fault, occurrence: usize) -> bool {
+        self.specs
+            .iter()
+            .any(|spec| spec.fault == fault && spec.occurrence.get() == occurrence)
+    }
+
+    /// The driver fault to inject into the test case about to be run, if any
+    fn next_test_run(&self) -> Option<Fault> {
+        let occurrence = self.test_runs.fetch_add(1, Ordering::Relaxed) + 1;
+        [Fault::DriverIoError, Fault::Timeout]
+            .into_iter()
+            .find(|&fault| self.injects(fault, occurrence))
+    }
+
+    /// Whether to panic when reporting the result about to be reported
+    fn next_result(&self) -> bool {
+        let occurrence = self.results.fetch_add(1, Ordering::Relaxed) + 1;
+        self.injects(Fault::ReporterPanic, occurrence)
+    }
+}
+
+/// Test driver injecting the driver faults into the test cases run by another one
+pub(crate) struct FaultyTestDriver<'a> {
+    test_driver: &'a dyn TestDriver,
+    injector: &'a FaultInjector,
+}
+
+impl<'a> FaultyTestDriver<'a> {
+    pub(crate) fn new(test_driver: &'a dyn TestDriver, injector: &'a FaultInjector) -> Self {
+        Self {
+            test_driver,
+            injector,
+        }
+    }
+}
+
+impl TestDriver for FaultyTestDriver<'_> {
+    fn test_file_patterns_default(&self) -> Vec<String> {
+        self.test_driver.test_file_patterns_default()
+    }
+
+    fn check_options(&self, options: &DriverOptions) -> serde_json::Result<()> {
+        self.test_driver.check_options(options)
+    }
+
+    fn discover_tests(
+        &self,
+        test_suite_dir: &Path,
+        test_suite_config: &TestSuiteConfig,
+        path_filter: &PathFilter,
+    ) -> Result<TestSuite> {
+        self.test_driver
+            .discover_tests(test_suite_dir, test_suite_config, path_filter)
+    }
+
+    fn run_test(
+        &self,
+        test_suite_dir: &Path,
+        test_suite_config: &TestSuiteConfig,
+        target: &str,
+        test_case: &TestCase,
+        test_case_out_dir: &Path,
+        env: &Env,
+        timeout: Option<Duration>,
+        network_namespace: Option<&Path>,
+    ) -> Result<RunTestOutput> {
+        match self.injector.next_test_run() {
+            Some(Fault::DriverIoError) => Err(error::kind::TestDriverIo {
+                filename: PathBuf::from(Fault::DriverIoError.name()),
+                source: std::io::Error::other("injected fault"),
+            }
+            .into()),
+            Some(Fault::Timeout) => Ok(RunTestOutput::timed_out(timeout, None)),
+            Some(Fault::ReporterPanic) | None => self.test_driver.run_test(
+                test_suite_dir,
+                test_suite_config,
+                target,
+                test_case,
+                test_case_out_dir,
+                env,
+                timeout,
+                network_namespace,
+            ),
+        }
+    }
+
+    fn log_files(&self, test_case: &TestCase, test_case_out_dir: &Path) -> Vec<LogFile> {
+        self.test_driver.log_files(test_case, test_case_out_dir)
+    }
+}
+
+/// Reporter panicking when reporting the results selected by the `reporter-panic` faults
+pub(crate) struct FaultyReporter {
+    injector: Arc<FaultInjector>,
+}
+
+impl FaultyReporter {
+    pub(crate) fn new(injector: Arc<FaultInjector>) -> Self {
+        Self { injector }
+    }
+}
+
+impl Reporter for FaultyReporter {
+    fn notice_detailed(&self, _message: &str, _details: &str) {}
+    fn info_detailed(&self, _message: &str, _details: &str) {}
+    fn warning_detailed(&self, _message: &str, _details: &str) {}
+    fn error_detailed(&self, _message: &str, _details: &str) {}
+    fn error_from(&self, _error: &Error) {}
+    fn report_target_list(&self, _test_suite: &TestSuite) {}
+    fn report_test_list(&self, _test_suite: &TestSuite, _include_fixtures: bool) {}
+    fn report_test_suite_time(&self, _test_suite: &TestSuite, _duration: Duration) {}
+    fn report_test_suite_execution_summary(
+        &self,
+        _test_suite: &TestSuite,
+        _exec_context: &[ExecutionContext],
+    ) {
+    }
+    fn report_total_time(&self, _duration: Duration) {}
+
+    fn report_test_case_execution_result(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        _exec_info: &TestCaseExecInfo,
+    ) {
+        if self.injector.next_result() {
+            panic!(
+                "injected fault: {} while reporting `{}` on target `{target}`",
+                Fault::ReporterPanic.name(),
+                test_case.id()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(fault: Fault, occurrence: usize) -> FaultSpec {
+        FaultSpec {
+            fault,
+            occurrence: NonZeroUsize::new(occurrence).unwrap(),
+        }
+    }
+
+    #[test]
+    fn parse_fault_spec() {
+        assert_eq!(
+            "driver-io-error@2".parse::<FaultSpec>(),
+            Ok(spec(Fault::DriverIoError, 2))
+        );
+        assert_eq!(
+            "timeout@1".parse::<FaultSpec>(),
+            Ok(spec(Fault::Timeout, 1))
+        );
+        assert_eq!(
+            "reporter-panic@10".parse::<FaultSpec>(),
+            Ok(spec(Fault::ReporterPanic, 10))
+        );
+    }
+
+    #[test]
+    fn parse_invalid_fault_spec() {
+        assert!("timeout".parse::<FaultSpec>().is_err());
+        assert!("timeout@0".parse::<FaultSpec>().is_err());
+        assert!("timeout@x".parse::<FaultSpec>().is_err());
+        assert!("crash@1".parse::<FaultSpec>().is_err());
+    }
+
+    #[test]
+    fn display_fault_spec() {
+        assert_eq!(spec(Fault::Timeout, 3).to_string(), "timeout@3");
+    }
+
+    #[test]
+    fn injector_counts_occurrences() {
+        let injector = FaultInjector::new(vec![
+            spec(Fault::Timeout, 2),
+            spec(Fault::DriverIoError, 3),
+            spec(Fault::ReporterPanic, 1),
+        ]);
+        assert_eq!(injector.next_test_run(), None);
+        assert_eq!(injector.next_test_run(), Some(Fault::Timeout));
+        assert_eq!(injector.next_test_run(), Some(Fault::DriverIoError));
+        assert_eq!(injector.next_test_run(), None);
+        assert!(injector.next_result());
+        assert!(!injector.next_result());
+    }
+}
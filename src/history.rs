@@ -0,0 +1,177 @@
+use crate::error::{self, Result};
+use crate::test_suite::id::TestCaseId;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Durations and results of past runs of a test suite, persisted as JSON in the output directory
+/// and used to detect test cases becoming slower than usual
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    /// Samples keyed by test case id, then by target
+    test_cases: BTreeMap<TestCaseId, BTreeMap<String, TestCaseHistory>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TestCaseHistory {
+    /// Durations in seconds of the last successful runs, oldest first
+    #[serde(default)]
+    durations: Vec<f64>,
+    /// Values of the metrics emitted during the last runs, oldest first
+    #[serde(default)]
+    metrics: BTreeMap<String, Vec<f64>>,
+}
+
+impl History {
+    pub const FILE_NAME: &str = "history.json";
+
+    /// Number of samples kept for each test case and target
+    const MAX_SAMPLES: usize = 50;
+    /// Number of samples required before the history is considered meaningful
+    const MIN_SAMPLES: usize = 3;
+
+    /// Load the history file, returning an empty history if it does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path).map_err(|io_err| error::kind::HistoryIo {
+            filename: path.to_path_buf(),
+            source: io_err,
+        })?;
+        serde_json::from_str(&contents).map_err(|serde_err| {
+            error::kind::InvalidHistory {
+                filename: path.to_path_buf(),
+                source: serde_err,
+            }
+            .into()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        // UNWRAP: History only contains maps with string keys and numbers, serialization cannot fail
+        let contents = serde_json::to_string_pretty(self).unwrap();
+        std::fs::write(path, contents).map_err(|io_err| {
+            error::kind::HistoryIo {
+                filename: path.to_path_buf(),
+                source: io_err,
+            }
+            .into()
+        })
+    }
+
+    pub fn get(&self, test_case_id: &TestCaseId, target: &str) -> Option<&TestCaseHistory> {
+        self.test_cases.get(test_case_id)?.get(target)
+    }
+
+    /// How long a test case usually takes: the median of its recorded durations on the target
+    /// where it is the slowest, if it ever passed
+    pub fn typical_duration(&self, test_case_id: &TestCaseId) -> Option<Duration> {
+        self.test_cases
+            .get(test_case_id)?
+            .values()
+            .filter_map(TestCaseHistory::duration_median)
+            .max()
+    }
+
+    /// Whether a test case usually takes longer than `threshold`, test cases which never passed
+    /// being considered quick
+    pub fn is_slow(&self, test_case_id: &TestCaseId, threshold: Duration) -> bool {
+        self.typical_duration(test_case_id)
+            .is_some_and(|duration| duration > threshold)
+    }
+
+    pub fn record_duration(&mut self, test_case_id: &TestCaseId, target: &str, duration: Duration) {
+        let durations = &mut self.entry(test_case_id, target).durations;
+        Self::push_sample(durations, duration.as_secs_f64());
+    }
+
+    pub fn record_metric(
+        &mut self,
+        test_case_id: &TestCaseId,
+        target: &str,
+        metric: &str,
+        value: f64,
+    ) {
+        let samples = self
+            .entry(test_case_id, target)
+            .metrics
+            .entry(metric.to_string())
+            .or_default();
+        Self::push_sample(samples, value);
+    }
+
+    fn entry(&mut self, test_case_id: &TestCaseId, target: &str) -> &mut TestCaseHistory {
+        self.test_cases
+            .entry(test_case_id.clone())
+            .or_default()
+            .entry(target.to_string())
+            .or_default()
+    }
+
+    fn push_sample(samples: &mut Vec<f64>, sample: f64) {
+        samples.push(sample);
+        if samples.len() > Self::MAX_SAMPLES {
+            samples.drain(..samples.len() - Self::MAX_SAMPLES);
+        }
+    }
+}
+
+impl TestCaseHistory {
+    /// The 95th percentile of the recorded durations (nearest-rank method), if enough samples
+    /// were recorded
+    pub fn duration_p95(&self) -> Option<Duration> {
+        if self.durations.len() < History::MIN_SAMPLES {
+            return None;
+        }
+        let mut durations = self.durations.clone();
+        durations.sort_by(f64::total_cmp);
+        let rank = (durations.len() as f64 * 0.95).ceil() as usize;
+        Some(Duration::from_secs_f64(durations[rank - 1]))
+    }
+
+    /// The median of the recorded durations, if any
+    pub fn duration_median(&self) -> Option<Duration> {
+        let mut durations = self.durations.clone();
+        durations.sort_by(f64::total_cmp);
+        let median = match durations.len() {
+            0 => return None,
+            len if len % 2 == 0 => (durations[len / 2 - 1] + durations[len / 2]) / 2.0,
+            len => durations[len / 2],
+        };
+        Some(Duration::from_secs_f64(median))
+    }
+
+    /// The recorded values of a metric, oldest first
+    pub fn metric_trend(&self, metric: &str) -> &[f64] {
+        self.metrics
+            .get(metric)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typical_duration() {
+        let id = TestCaseId::new(Path::new("a.sh"), "test_a");
+        let mut history = History::default();
+        assert_eq!(history.typical_duration(&id), None);
+        for secs in [1, 9, 2] {
+            history.record_duration(&id, "foo", Duration::from_secs(secs));
+        }
+        for secs in [3, 5] {
+            history.record_duration(&id, "bar", Duration::from_secs(secs));
+        }
+        // Median of 4s on bar, slower than the 2s on foo
+        assert_eq!(history.typical_duration(&id), Some(Duration::from_secs(4)));
+        assert!(history.is_slow(&id, Duration::from_secs(3)));
+        assert!(!history.is_slow(&id, Duration::from_secs(4)));
+    }
+}
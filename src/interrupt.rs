@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static PAUSE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Handle interrupts (Ctrl-C) and termination requests (SIGTERM, e.g. from a CI runner cancelling
+/// its job) by stopping the run gracefully instead of exiting right away: the test cases not run
+/// yet are skipped, the teardowns of what was already set up still run and the summary is printed
+/// A second interrupt or termination request exits right away. Pause requests (SIGUSR1) are handled too, see
+/// [`is_pause_requested`]. Neither is handled on non-Unix platforms.
+pub fn install_handler() {
+    #[cfg(unix)]
+    unix::install_handler();
+}
+
+/// Whether the run was interrupted
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Whether pausing the run was requested (with SIGUSR1): the test cases running finish, but no
+/// new one starts
+pub fn is_pause_requested() -> bool {
+    PAUSE_REQUESTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+mod unix {
+    use libc::{SIG_DFL, SIGINT, SIGTERM, SIGUSR1, c_int, sighandler_t, signal};
+
+    use std::sync::atomic::Ordering;
+
+    extern "C" fn on_interrupt(_signum: c_int) {
+        // Only async-signal-safe operations are allowed here
+        super::INTERRUPTED.store(true, Ordering::SeqCst);
+        // SAFETY: restoring the default handlers is async-signal-safe
+        unsafe {
+            signal(SIGINT, SIG_DFL);
+            signal(SIGTERM, SIG_DFL);
+        }
+    }
+
+    extern "C" fn on_pause_request(_signum: c_int) {
+        // Only async-signal-safe operations are allowed here
+        super::PAUSE_REQUESTED.store(true, Ordering::SeqCst);
+    }
+
+    pub(super) fn install_handler() {
+        let on_interrupt = on_interrupt as extern "C" fn(c_int) as sighandler_t;
+        let on_pause_request = on_pause_request as extern "C" fn(c_int) as sighandler_t;
+        // SAFETY: the handlers only store an atomic and call an async-signal-safe function
+        unsafe {
+            signal(SIGINT, on_interrupt);
+            signal(SIGTERM, on_interrupt);
+            signal(SIGUSR1, on_pause_request);
+        }
+    }
+}
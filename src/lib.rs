@@ -1,9 +1,54 @@
+//! Engine of batrun, running test suites on a set of targets
+//!
+//! The `batrun` command line interface is a thin layer over this library, which other tools can
+//! embed the same way:
+//! - [`Settings`] describe the run (test suites, targets, filters...), built with
+//!   [`Settings::new`] and its `with_*` methods;
+//! - [`TestRunner`] loads the test suites and runs them, its [`builder`](TestRunner::builder)
+//!   accepting custom [`Reporter`]s receiving the events of the run and custom [`TestDriver`]s
+//!   discovering and running the test cases;
+//! - the results are reported as [`Statistics`] per test suite and as a [`RunOutcome`] for the
+//!   whole run, from which an exit code can be computed with [`ExitCodes`].
+//!
+//! See `examples/embed.rs` for a minimal embedding.
+
+pub(crate) mod allure;
+pub(crate) mod artifact_storage;
+pub mod comparison;
 pub mod error;
 pub mod execution_strategy;
+pub mod exit_code;
+pub mod fault_injection;
+pub(crate) mod history;
+pub mod interrupt;
+pub(crate) mod manifest;
+pub(crate) mod network_isolation;
+pub(crate) mod orphans;
+pub mod paused_run;
+pub mod ports;
+pub mod progress;
+pub(crate) mod redaction;
 pub mod reporter;
+pub mod rng;
+pub(crate) mod secrets;
+pub(crate) mod services;
 pub mod settings;
+pub(crate) mod simple_executor;
+pub(crate) mod target_backend;
+pub(crate) mod target_watcher;
+pub mod telemetry;
 pub mod test_driver;
 pub mod test_executor;
 pub mod test_runner;
 pub mod test_suite;
 pub mod time;
+
+pub use error::{Error, Result};
+pub use execution_strategy::ExecutionStrategy;
+pub use exit_code::{ExitCodes, RunOutcome};
+pub use regex;
+pub use reporter::Reporter;
+pub use settings::Settings;
+pub use test_driver::TestDriver;
+pub use test_runner::{TestRunner, TestRunnerBuilder};
+pub use test_suite::status::{Statistics, TestCaseStatus};
@@ -0,0 +1,299 @@
+use crate::comparison::Comparison;
+use crate::error::{self, Result};
+use crate::execution_strategy::ExecutionStrategy;
+use crate::reporter::composite::RunnerHealth;
+use crate::settings::Settings;
+use crate::telemetry::Telemetry;
+use crate::test_driver::{Env, KillSignal, LogFile, Metrics};
+use crate::test_executor::{ExecutionContext, TestCaseExecInfo, TimeoutSource};
+use crate::test_suite::config::TestSuiteConfig;
+use crate::test_suite::config::migration::Deprecation;
+use crate::test_suite::id::TestCaseId;
+use crate::test_suite::status::{Statistics, TestCaseStatus};
+use crate::test_suite::{TestCase, TestSuite};
+use crate::time;
+
+use serde::Serialize;
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Description of a run of a test suite, written as JSON in its output directory so that the
+/// results can be archived and inspected later without parsing the console output
+/// Durations are in seconds.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RunManifest<'a> {
+    batrun_version: &'static str,
+    started_at: String,
+    finished_at: String,
+    metadata: &'a Env,
+    settings: SettingsManifest<'a>,
+    test_suite: TestSuiteManifest<'a>,
+    targets: Vec<TargetManifest<'a>>,
+    /// How the results changed since the previous run compared with, if compared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comparison: Option<&'a Comparison>,
+    /// Warnings and errors reported while the test suite ran, up to the writing of the manifest
+    runner_health: RunnerHealth,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct SettingsManifest<'a> {
+    out_dir: &'a Path,
+    targets: &'a [String],
+    exec_strategy: ExecutionStrategy,
+    jobs: Option<usize>,
+    file_jobs: usize,
+    dry_run: bool,
+    fail_fast: bool,
+    strict_xfail: bool,
+    strict_skips: bool,
+    allowed_skip_tags: &'a [String],
+    strict_orphans: bool,
+    kill_orphans: bool,
+    filters: Vec<String>,
+    paths: Vec<String>,
+    include_tags: &'a [String],
+    exclude_tags: &'a [String],
+    retries: Option<u32>,
+    timeout: Option<f64>,
+    fixture_timeout: Option<f64>,
+    kill_grace_period: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TestSuiteManifest<'a> {
+    name: &'a str,
+    path: &'a Path,
+    /// Output directory of the test suite in the one of the run, named after the test suite,
+    /// holding its history and the output of its runs
+    out_dir: &'a Path,
+    /// The config file as written, `null` if it cannot be read anymore
+    config: serde_json::Value,
+    /// The deprecated keys of the config file
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    deprecations: &'a [Deprecation],
+    /// The test cases deselected by the filters of the run
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    filtered_out: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TargetManifest<'a> {
+    name: &'a str,
+    joined_late: bool,
+    lease_metadata: &'a Env,
+    statistics: Statistics,
+    /// Time spent running the test cases, failed attempts included
+    duration: f64,
+    test_cases: Vec<TestCaseManifest<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TestCaseManifest<'a> {
+    id: TestCaseId,
+    tags: &'a [String],
+    /// Options of the shell the test case ran in, for the test drivers using one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell_options: Option<&'a [String]>,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    /// Message the test case attached to its result, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+    duration: Option<f64>,
+    attempts: u32,
+    flaky: bool,
+    slow: bool,
+    timeout: Option<TimeoutManifest>,
+    /// Signal which ended the test case, if it was killed after its timeout or because the run
+    /// was interrupted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    killed_by: Option<KillSignal>,
+    metrics: Option<&'a Metrics>,
+    /// Summary of the telemetry sampled while the test case ran, by value
+    #[serde(skip_serializing_if = "Telemetry::is_empty")]
+    telemetry: &'a Telemetry,
+    /// Log files and failure artifacts of the last attempt, then of the failed attempts before it
+    log_files: Vec<&'a LogFile>,
+}
+
+#[derive(Serialize)]
+struct TimeoutManifest {
+    duration: f64,
+    source: TimeoutSource,
+}
+
+impl<'a> RunManifest<'a> {
+    pub const FILE_NAME: &'static str = "run.json";
+
+    pub fn new(
+        settings: &'a Settings,
+        test_suite: &'a TestSuite,
+        test_suite_out_dir: &'a Path,
+        exec_contexts: &'a [ExecutionContext],
+        started_at: SystemTime,
+    ) -> Self {
+        let config = std::fs::read_to_string(test_suite.path().join(TestSuiteConfig::FILE_NAME))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            batrun_version: env!("CARGO_PKG_VERSION"),
+            started_at: time::format_timestamp(started_at),
+            finished_at: time::format_timestamp(SystemTime::now()),
+            metadata: &settings.run_metadata,
+            settings: SettingsManifest {
+                out_dir: &settings.out_dir,
+                targets: &settings.targets,
+                exec_strategy: settings.exec_strategy,
+                jobs: settings.jobs.map(usize::from),
+                file_jobs: settings.file_jobs.get(),
+                dry_run: settings.dry_run,
+                fail_fast: settings.fail_fast,
+                strict_xfail: settings.strict_xfail,
+                strict_skips: settings.strict_skips,
+                allowed_skip_tags: &settings.allowed_skip_tags,
+                strict_orphans: settings.strict_orphans,
+                kill_orphans: settings.kill_orphans,
+                filters: settings
+                    .test_filter
+                    .patterns()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                paths: settings
+                    .path_filter
+                    .patterns()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                include_tags: settings.tag_filter.include(),
+                exclude_tags: settings.tag_filter.exclude(),
+                retries: settings.retries,
+                timeout: settings.timeout.as_ref().map(Duration::as_secs_f64),
+                fixture_timeout: settings.fixture_timeout.as_ref().map(Duration::as_secs_f64),
+                kill_grace_period: settings.kill_grace_period.as_secs_f64(),
+            },
+            test_suite: TestSuiteManifest {
+                name: &test_suite.config().name,
+                path: test_suite.path(),
+                out_dir: test_suite_out_dir,
+                config,
+                deprecations: &test_suite.config().deprecations,
+                filtered_out: test_suite
+                    .filtered_out()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            },
+            targets: exec_contexts.iter().map(TargetManifest::new).collect(),
+            comparison: None,
+            runner_health: RunnerHealth::default(),
+        }
+    }
+
+    pub fn with_comparison(mut self, comparison: Option<&'a Comparison>) -> Self {
+        self.comparison = comparison;
+        self
+    }
+
+    pub fn with_runner_health(mut self, runner_health: RunnerHealth) -> Self {
+        self.runner_health = runner_health;
+        self
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        // UNWRAP: the manifest only contains strings, numbers and maps with string keys
+        let contents = serde_json::to_string_pretty(self).unwrap();
+        std::fs::write(path, contents).map_err(|io_err| {
+            error::kind::ReportIo {
+                filename: PathBuf::from(path),
+                source: io_err,
+            }
+            .into()
+        })
+    }
+}
+
+impl<'a> TargetManifest<'a> {
+    fn new(exec_context: &'a ExecutionContext) -> Self {
+        Self {
+            name: exec_context.target(),
+            joined_late: exec_context.is_late_joined(),
+            lease_metadata: exec_context.lease_metadata(),
+            statistics: exec_context.get_statistics(),
+            duration: exec_context.total_duration().as_secs_f64(),
+            test_cases: exec_context
+                .iter_exec_info()
+                .map(|(test_case, exec_info)| TestCaseManifest::new(test_case, exec_info))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> TestCaseManifest<'a> {
+    fn new(test_case: &'a TestCase, exec_info: &'a TestCaseExecInfo) -> Self {
+        let (status, reason) = match exec_info.result() {
+            Ok(output) => {
+                let reason = match &output.test_case_status {
+                    TestCaseStatus::Failed(reason) => Some(reason.to_string()),
+                    TestCaseStatus::Skipped(reason) => Some(reason.to_string()),
+                    TestCaseStatus::XFailed(reason) | TestCaseStatus::XPassed(reason) => {
+                        Some(reason.clone())
+                    }
+                    _ => None,
+                };
+                (output.test_case_status.name(), reason)
+            }
+            Err(error) => ("runner-failed", Some(error.to_string())),
+        };
+        let failed_attempts_log_files = exec_info
+            .failed_attempts()
+            .iter()
+            .flat_map(|attempt| &attempt.log_files);
+        Self {
+            id: test_case.id(),
+            tags: &test_case.metadata().tags,
+            shell_options: test_case.metadata().shell_options.as_deref(),
+            status,
+            reason,
+            message: exec_info
+                .result()
+                .as_ref()
+                .ok()
+                .and_then(|output| output.message.as_deref()),
+            duration: exec_info.duration().as_ref().map(Duration::as_secs_f64),
+            attempts: exec_info.attempt(),
+            flaky: exec_info.is_flaky(),
+            slow: exec_info.is_slow(),
+            timeout: exec_info.timeout().map(|timeout| TimeoutManifest {
+                duration: timeout.duration.as_secs_f64(),
+                source: timeout.source,
+            }),
+            killed_by: exec_info
+                .result()
+                .as_ref()
+                .ok()
+                .and_then(|output| output.killed_by),
+            metrics: exec_info
+                .result()
+                .as_ref()
+                .ok()
+                .map(|output| &output.metrics),
+            telemetry: exec_info.telemetry(),
+            log_files: exec_info
+                .log_files()
+                .iter()
+                .chain(exec_info.failure_artifacts())
+                .chain(failed_attempts_log_files)
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use crate::test_suite::config::NetworkIsolationConfig;
+
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+
+/// Set in the environment of the test processes of a test case isolated from the network of the
+/// host to its network namespace (e.g. `/proc/1234/ns/net`), for information only: the test
+/// drivers are given the namespace to start the test processes in explicitly
+pub(crate) const NETNS_ENV_VAR: &str = "BATRUN_NETNS";
+
+/// A network namespace of its own for a test case, with only a loopback interface, so that test
+/// cases binding fixed ports can run at the same time
+/// The namespace is kept alive by a process started with `unshare` (which needs batrun to run as
+/// root), and the forwarded ports by threads relaying the connections to the host. Both are
+/// stopped once dropped, the connections still open being shut down.
+pub(crate) struct NetworkNamespace {
+    holder: Child,
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    forwarders: Vec<JoinHandle<()>>,
+}
+
+impl NetworkNamespace {
+    /// Create a network namespace with its loopback interface up, forwarding the given ports to
+    /// the host
+    pub(crate) fn create(config: &NetworkIsolationConfig) -> Result<Self, String> {
+        if !cfg!(target_os = "linux") {
+            return Err("network namespaces are only supported on Linux".to_string());
+        }
+        let mut holder = Command::new("unshare")
+            .args(["--net", "--", "sh", "-c"])
+            .arg("ip link set lo up && echo ready && exec cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|io_err| format!("cannot execute `unshare`: {io_err}"))?;
+        let mut line = String::new();
+        // UNWRAP: the output of the holder is piped
+        let _ = BufReader::new(holder.stdout.take().unwrap()).read_line(&mut line);
+        if line.trim_end() != "ready" {
+            let _ = holder.kill();
+            let _ = holder.wait();
+            let mut stderr = String::new();
+            // UNWRAP: the errors of the holder are piped
+            let _ = holder.stderr.take().unwrap().read_to_string(&mut stderr);
+            return Err(format!(
+                "cannot create a network namespace: {}",
+                stderr.trim_end()
+            ));
+        }
+        let mut namespace = Self {
+            path: PathBuf::from(format!("/proc/{}/ns/net", holder.id())),
+            holder,
+            stop: Arc::default(),
+            forwarders: Vec::new(),
+        };
+        for &port in &config.forward_ports {
+            let forwarder = forward(&namespace.path, port, namespace.stop.clone())
+                .map_err(|io_err| format!("cannot forward port {port}: {io_err}"))?;
+            namespace.forwarders.push(forwarder);
+        }
+        Ok(namespace)
+    }
+
+    /// The namespace as a file, to be entered with [`enter`]
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for NetworkNamespace {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        for forwarder in self.forwarders.drain(..) {
+            let _ = forwarder.join();
+        }
+        let _ = self.holder.kill();
+        let _ = self.holder.wait();
+    }
+}
+
+/// Move the calling thread into the given network namespace, only affecting this thread (or the
+/// process about to be executed when called from [`std::os::unix::process::CommandExt::pre_exec`])
+#[cfg(target_os = "linux")]
+pub(crate) fn enter(netns: &std::fs::File) -> io::Result<()> {
+    use std::ffi::c_int;
+    use std::os::fd::AsRawFd;
+
+    const CLONE_NEWNET: c_int = 0x4000_0000;
+
+    unsafe extern "C" {
+        fn setns(fd: c_int, nstype: c_int) -> c_int;
+    }
+
+    // SAFETY: setns only reads the file descriptor, kept open by `netns`, and is
+    // async-signal-safe
+    match unsafe { setns(netns.as_raw_fd(), CLONE_NEWNET) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Relay the connections to a port of the loopback interface of a network namespace to the same
+/// port of the loopback interface of the host, until stopped
+/// The connections still open when stopped are shut down, and their relaying threads joined.
+#[cfg(target_os = "linux")]
+fn forward(netns: &Path, port: u16, stop: Arc<AtomicBool>) -> io::Result<JoinHandle<()>> {
+    use std::fs::File;
+    use std::net::{Ipv4Addr, Shutdown, TcpListener, TcpStream};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    const POLL_PERIOD: Duration = Duration::from_millis(10);
+
+    let netns = File::open(netns)?;
+    let host_netns = File::open("/proc/thread-self/ns/net")?;
+    let (bound, listening) = mpsc::channel();
+    let forwarder = thread::spawn(move || {
+        // The listener belongs to the namespace it is created in, and the connections to the host
+        // to the one the thread is back in
+        let listener = enter(&netns)
+            .and_then(|()| TcpListener::bind((Ipv4Addr::LOCALHOST, port)))
+            .and_then(|listener| {
+                enter(&host_netns)?;
+                listener.set_nonblocking(true)?;
+                Ok(listener)
+            });
+        let listener = match listener {
+            Ok(listener) => {
+                let _ = bound.send(Ok(()));
+                listener
+            }
+            Err(io_err) => {
+                let _ = bound.send(Err(io_err));
+                return;
+            }
+        };
+        /// A connection relayed to the host, by a thread per direction
+        struct Connection {
+            inbound: TcpStream,
+            outbound: TcpStream,
+            relays: [JoinHandle<()>; 2],
+        }
+        let relay = |mut from: TcpStream, mut to: TcpStream| {
+            thread::spawn(move || {
+                let _ = io::copy(&mut from, &mut to);
+                let _ = to.shutdown(Shutdown::Write);
+            })
+        };
+        let mut connections = Vec::<Connection>::new();
+        while !stop.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((inbound, _)) => {
+                    let connection = inbound.set_nonblocking(false).and_then(|()| {
+                        let outbound = TcpStream::connect((Ipv4Addr::LOCALHOST, port))?;
+                        let streams = [
+                            (inbound.try_clone()?, outbound.try_clone()?),
+                            (outbound.try_clone()?, inbound.try_clone()?),
+                        ];
+                        Ok(Connection {
+                            inbound,
+                            outbound,
+                            relays: streams.map(|(from, to)| relay(from, to)),
+                        })
+                    });
+                    // The connection is closed if the port of the host cannot be reached
+                    if let Ok(connection) = connection {
+                        connections.push(connection);
+                    }
+                }
+                Err(io_err) if io_err.kind() == io::ErrorKind::WouldBlock => {
+                    connections.retain(|connection| {
+                        !connection.relays.iter().all(JoinHandle::is_finished)
+                    });
+                    thread::sleep(POLL_PERIOD);
+                }
+                Err(_) => break,
+            }
+        }
+        // The relaying threads are blocked on their connections until these are shut down
+        for connection in connections {
+            let _ = connection.inbound.shutdown(Shutdown::Both);
+            let _ = connection.outbound.shutdown(Shutdown::Both);
+            for relay in connection.relays {
+                let _ = relay.join();
+            }
+        }
+    });
+    match listening.recv() {
+        Ok(Ok(())) => Ok(forwarder),
+        Ok(Err(io_err)) => Err(io_err),
+        Err(_) => Err(io::Error::other("the forwarding thread panicked")),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn forward(_netns: &Path, _port: u16, _stop: Arc<AtomicBool>) -> io::Result<JoinHandle<()>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Set in the environment of the processes of each test case with a value unique to it, so that
+/// the processes they start, which inherit it, can be found once the test case is done, even if
+/// they left its process group
+pub(crate) const MARKER_ENV_VAR: &str = "BATRUN_PROCESS_MARKER";
+
+/// A process still running once the test case which started it is done
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Orphan {
+    pub(crate) pid: u32,
+    pub(crate) command: String,
+}
+
+impl fmt::Display for Orphan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.pid, self.command)
+    }
+}
+
+/// A new value for [`MARKER_ENV_VAR`], unique to this batrun instance and test case
+pub(crate) fn new_marker() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// The processes running with the given marker in their environment, by pid
+/// Only supported on Linux, where the environment of the processes is read from `/proc`.
+pub(crate) fn find(marker: &str) -> Vec<Orphan> {
+    #[cfg(target_os = "linux")]
+    {
+        let variable = format!("{MARKER_ENV_VAR}={marker}");
+        let Ok(entries) = std::fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+        let mut orphans = entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+            .filter(|pid| {
+                // The processes which exited meanwhile, or of other users, cannot be read
+                std::fs::read(format!("/proc/{pid}/environ")).is_ok_and(|environ| {
+                    environ
+                        .split(|&byte| byte == 0)
+                        .any(|entry| entry == variable.as_bytes())
+                }) && !is_zombie(*pid)
+            })
+            .map(|pid| Orphan {
+                pid,
+                command: std::fs::read(format!("/proc/{pid}/cmdline"))
+                    .map(|cmdline| {
+                        String::from_utf8_lossy(&cmdline)
+                            .trim_end_matches('\0')
+                            .replace('\0', " ")
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        orphans.sort_by_key(|orphan| orphan.pid);
+        orphans
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = marker;
+        Vec::new()
+    }
+}
+
+/// Whether a process exited but was not reaped yet by its parent, e.g. a process killed with the
+/// test case which started it
+#[cfg(target_os = "linux")]
+fn is_zombie(pid: u32) -> bool {
+    // The state follows the command name, which is in parentheses and may contain any character
+    std::fs::read_to_string(format!("/proc/{pid}/stat")).is_ok_and(|stat| {
+        stat.rsplit_once(')').is_some_and(|(_, fields)| {
+            matches!(fields.trim_start().chars().next(), Some('Z' | 'X'))
+        })
+    })
+}
+
+/// Kill the given processes, best effort as they may have exited meanwhile
+pub(crate) fn kill(orphans: &[Orphan]) {
+    let _ = Command::new("kill")
+        .args(["-KILL", "--"])
+        .args(orphans.iter().map(|orphan| orphan.pid.to_string()))
+        .status();
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_and_kill_marked_processes() {
+        let marker = new_marker();
+        let mut child = Command::new("sleep")
+            .arg("10")
+            .env(MARKER_ENV_VAR, &marker)
+            .spawn()
+            .unwrap();
+        // The environment of a process just spawned may not be readable yet
+        let mut orphans = find(&marker);
+        for _ in 0..100 {
+            if !orphans.is_empty() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            orphans = find(&marker);
+        }
+        assert_eq!(
+            orphans,
+            [Orphan {
+                pid: child.id(),
+                command: "sleep 10".to_string(),
+            }]
+        );
+        assert!(find(&new_marker()).is_empty());
+        kill(&orphans);
+        child.wait().unwrap();
+        assert!(find(&marker).is_empty());
+    }
+}
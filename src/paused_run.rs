@@ -0,0 +1,227 @@
+use crate::error::{self, Result};
+use crate::settings::Settings;
+use crate::test_executor::TestCaseExecInfo;
+use crate::test_suite::id::TestCaseId;
+use crate::test_suite::status::{SkipReason, TestCaseStatus};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Result of a test case which ran before the run was paused or stopped, reported again instead
+/// of running it when the run is resumed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SavedResult {
+    pub status: TestCaseStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl SavedResult {
+    /// Whether a test case with this status ran to completion, so that it does not need to run
+    /// again once the run is resumed
+    pub fn is_saved(status: &TestCaseStatus) -> bool {
+        match status {
+            TestCaseStatus::Passed
+            | TestCaseStatus::Failed(_)
+            | TestCaseStatus::XFailed(_)
+            | TestCaseStatus::XPassed(_) => true,
+            TestCaseStatus::Skipped(reason) => !matches!(
+                reason,
+                SkipReason::Paused | SkipReason::Interrupted | SkipReason::AbortedByFailFast
+            ),
+            TestCaseStatus::NotRun | TestCaseStatus::Running | TestCaseStatus::DryRun => false,
+        }
+    }
+
+    /// The result to save of a test case which just ran, if it ran to completion
+    pub fn of(exec_info: &TestCaseExecInfo) -> Option<Self> {
+        let output = exec_info.result().as_ref().ok()?;
+        Self::is_saved(&output.test_case_status).then(|| Self {
+            status: output.test_case_status.clone(),
+            duration: exec_info.duration(),
+            message: output.message.clone(),
+        })
+    }
+}
+
+/// The saved results of the test cases of a target, by test case id
+pub type SavedResults = BTreeMap<TestCaseId, SavedResult>;
+
+/// Progress of a run, saved in its output directory as the test cases complete so that
+/// `batrun resume` can run the remaining test cases later on with the same options, whether the
+/// run was paused (with a pause file or SIGUSR1), interrupted, or batrun or the host crashed
+/// The file is removed once the run completes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PausedRun {
+    /// Directory batrun was run from, the relative paths of the command line being relative to it
+    pub working_dir: PathBuf,
+    /// Command line arguments of the run, without the program name
+    pub args: Vec<String>,
+    /// Results of the test cases which ran before the pause, by test suite directory and target
+    pub results: BTreeMap<PathBuf, BTreeMap<String, SavedResults>>,
+}
+
+impl PausedRun {
+    pub const FILE_NAME: &str = "paused-run.json";
+
+    /// Load the progress of the run with the given output directory
+    pub fn load(out_dir: &Path) -> Result<Self> {
+        let path = out_dir.join(Self::FILE_NAME);
+        let invalid = |details: String| error::kind::InvalidPausedRun {
+            filename: path.clone(),
+            details,
+        };
+        let contents =
+            std::fs::read_to_string(&path).map_err(|io_err| invalid(io_err.to_string()))?;
+        Ok(serde_json::from_str(&contents).map_err(|serde_err| invalid(serde_err.to_string()))?)
+    }
+
+    /// Save the progress through a temporary file, so that a crash while saving does not lose
+    /// the previous progress
+    pub(crate) fn save(&self, out_dir: &Path) -> Result<PathBuf> {
+        let path = out_dir.join(Self::FILE_NAME);
+        let temp_path = path.with_extension("json.tmp");
+        // UNWRAP: serializing strings, paths and statuses cannot fail
+        let contents = serde_json::to_string_pretty(self).unwrap();
+        std::fs::write(&temp_path, contents)
+            .and_then(|()| std::fs::rename(&temp_path, &path))
+            .map_err(|io_err| error::kind::ReportIo {
+                filename: path.clone(),
+                source: io_err,
+            })?;
+        Ok(path)
+    }
+
+    /// Remove the saved progress once the run completed
+    pub(crate) fn remove(out_dir: &Path) {
+        let _ = std::fs::remove_file(out_dir.join(Self::FILE_NAME));
+    }
+
+    /// The saved results of a test suite on a target, if any test case of it ran before the pause
+    pub(crate) fn results(&self, test_suite_dir: &Path, target: &str) -> Option<&SavedResults> {
+        self.results.get(test_suite_dir)?.get(target)
+    }
+}
+
+/// The progress of a run, shared by the test runner and the reporter saving it after each test
+/// case
+pub(crate) struct Checkpoint {
+    out_dir: PathBuf,
+    progress: Mutex<PausedRun>,
+}
+
+impl Checkpoint {
+    /// The progress of a new run, starting from the one of the run it resumes if any
+    pub(crate) fn new(settings: &Settings) -> Self {
+        Self {
+            out_dir: settings.out_dir.clone(),
+            progress: Mutex::new(PausedRun {
+                working_dir: std::env::current_dir().unwrap_or_default(),
+                args: settings.command_line.clone(),
+                results: settings
+                    .resumed_run
+                    .as_ref()
+                    .map(|resumed_run| resumed_run.results.clone())
+                    .unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// Record the result of a test case and save the progress
+    /// Saving it is best effort, as it only matters if batrun crashes: the progress is saved
+    /// again, reporting errors, when the run is paused.
+    pub(crate) fn record(
+        &self,
+        test_suite_dir: &Path,
+        target: &str,
+        test_case_id: TestCaseId,
+        result: SavedResult,
+    ) {
+        // UNWRAP: the lock is never held while panicking
+        let mut progress = self.progress.lock().unwrap();
+        progress
+            .results
+            .entry(test_suite_dir.to_path_buf())
+            .or_default()
+            .entry(target.to_string())
+            .or_default()
+            .insert(test_case_id, result);
+        let _ = progress.save(&self.out_dir);
+    }
+
+    pub(crate) fn save(&self) -> Result<PathBuf> {
+        // UNWRAP: the lock is never held while panicking
+        self.progress.lock().unwrap().save(&self.out_dir)
+    }
+
+    /// Remove the saved progress once the run completed
+    pub(crate) fn remove(&self) {
+        PausedRun::remove(&self.out_dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_suite::status::FailReason;
+
+    #[test]
+    fn only_completed_test_cases_are_saved() {
+        assert!(SavedResult::is_saved(&TestCaseStatus::Passed));
+        assert!(SavedResult::is_saved(&TestCaseStatus::Failed(
+            FailReason::TestCaseFailure
+        )));
+        assert!(SavedResult::is_saved(&TestCaseStatus::Skipped(
+            SkipReason::TestSuiteSetupError
+        )));
+        assert!(!SavedResult::is_saved(&TestCaseStatus::Skipped(
+            SkipReason::Paused
+        )));
+        assert!(!SavedResult::is_saved(&TestCaseStatus::Skipped(
+            SkipReason::Interrupted
+        )));
+        assert!(!SavedResult::is_saved(&TestCaseStatus::NotRun));
+        assert!(!SavedResult::is_saved(&TestCaseStatus::DryRun));
+    }
+
+    #[test]
+    fn saved_progress_round_trip() {
+        let result = SavedResult {
+            status: TestCaseStatus::Failed(FailReason::Timeout(Duration::from_secs(3))),
+            duration: Some(Duration::from_millis(3010)),
+            message: Some("killed".to_string()),
+        };
+        let test_case_id = TestCaseId::new(Path::new("tests.sh"), "test_01").with_params("fast");
+        let paused_run = PausedRun {
+            working_dir: PathBuf::from("/work"),
+            args: vec!["suite".to_string(), "--target".to_string(), "a".to_string()],
+            results: BTreeMap::from([(
+                PathBuf::from("suite"),
+                BTreeMap::from([(
+                    "a".to_string(),
+                    SavedResults::from([(test_case_id.clone(), result.clone())]),
+                )]),
+            )]),
+        };
+        let contents = serde_json::to_string(&paused_run).unwrap();
+        assert!(
+            contents.contains(r#""tests.sh::test_01[fast]":"#),
+            "{contents}"
+        );
+        let loaded: PausedRun = serde_json::from_str(&contents).unwrap();
+        assert_eq!(loaded, paused_run);
+        assert_eq!(
+            loaded.results(Path::new("suite"), "a").unwrap()[&test_case_id],
+            result
+        );
+        assert!(loaded.results(Path::new("suite"), "b").is_none());
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+use std::io;
+use std::net::{Ipv4Addr, TcpListener};
+use std::sync::Mutex;
+
+/// Allocates the TCP ports needed by the test cases, shared by all the execution contexts of a
+/// run so that no two test cases running at the same time get the same port
+/// The ports are free ones picked by the system, not handed out again by batrun until the test
+/// case they were allocated to is done.
+#[derive(Debug, Default)]
+pub struct PortAllocator {
+    allocated: Mutex<BTreeSet<u16>>,
+}
+
+impl PortAllocator {
+    /// Number of ports picked by the system which are allocated already tolerated before giving
+    /// up
+    const MAX_COLLISIONS: usize = 100;
+
+    /// Allocate the given number of ports, released once the returned ports are dropped
+    pub(crate) fn allocate(&self, count: usize) -> io::Result<AllocatedPorts<'_>> {
+        // UNWRAP: the lock is never held while panicking
+        let mut allocated = self.allocated.lock().unwrap();
+        // Kept bound until all the ports are picked, so that the system picks distinct ones
+        let mut listeners = Vec::new();
+        let mut ports = Vec::new();
+        let mut collisions = 0;
+        while ports.len() < count {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+            let port = listener.local_addr()?.port();
+            if allocated.contains(&port) {
+                collisions += 1;
+                if collisions > Self::MAX_COLLISIONS {
+                    return Err(io::Error::other("no free port left"));
+                }
+            } else {
+                ports.push(port);
+            }
+            listeners.push(listener);
+        }
+        allocated.extend(&ports);
+        Ok(AllocatedPorts {
+            allocator: self,
+            ports,
+        })
+    }
+}
+
+/// Ports allocated to a test case, released once dropped
+pub(crate) struct AllocatedPorts<'a> {
+    allocator: &'a PortAllocator,
+    ports: Vec<u16>,
+}
+
+impl AllocatedPorts<'_> {
+    pub(crate) fn ports(&self) -> &[u16] {
+        &self.ports
+    }
+}
+
+impl Drop for AllocatedPorts<'_> {
+    fn drop(&mut self) {
+        if self.ports.is_empty() {
+            return;
+        }
+        // UNWRAP: the lock is never held while panicking
+        let mut allocated = self.allocator.allocated.lock().unwrap();
+        for port in &self.ports {
+            allocated.remove(port);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ports_allocated_once_until_released() {
+        let allocator = PortAllocator::default();
+        let first = allocator.allocate(3).unwrap();
+        let second = allocator.allocate(2).unwrap();
+        let ports = first
+            .ports()
+            .iter()
+            .chain(second.ports())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(ports.len(), 5);
+        drop(first);
+        drop(second);
+        assert!(allocator.allocated.lock().unwrap().is_empty());
+        assert!(allocator.allocate(0).unwrap().ports().is_empty());
+    }
+}
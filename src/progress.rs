@@ -0,0 +1,158 @@
+use crate::test_suite::id::TestCaseId;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How far a target went through the test cases of a test suite
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetProgress {
+    /// Number of test cases done, fixtures included
+    pub done: usize,
+    pub total: usize,
+    /// Estimated time left until the target is done, unknown as long as the duration of one of
+    /// the test cases left cannot be estimated
+    pub eta: Option<Duration>,
+}
+
+impl TargetProgress {
+    /// Percentage of the test cases done
+    pub fn percent(&self) -> f64 {
+        match self.total {
+            0 => 100.0,
+            total => self.done as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+/// The test cases a target has left, and how long the ones which ran took
+#[derive(Debug, Default)]
+struct TargetState {
+    /// Expected duration of each test case left, declared or from the history, if known
+    left: HashMap<TestCaseId, Option<Duration>>,
+    total: usize,
+    ran: u32,
+    ran_duration: Duration,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    targets: HashMap<String, TargetState>,
+    /// Last duration observed of each test case which ran, on any target
+    observed: HashMap<TestCaseId, Duration>,
+}
+
+/// Follows the progress of the targets running a test suite, shared by their execution contexts
+/// The time left on a target sums the expected durations of its test cases left, or else the
+/// duration they took on another target, or else the average duration of the test cases which
+/// ran on the target.
+#[derive(Debug, Default)]
+pub struct ProgressTracker {
+    state: Mutex<State>,
+}
+
+impl ProgressTracker {
+    /// Follow a target running the given test cases, with their expected duration if known
+    pub(crate) fn add_target(
+        &self,
+        target: &str,
+        test_cases: impl IntoIterator<Item = (TestCaseId, Option<Duration>)>,
+    ) {
+        let left = test_cases.into_iter().collect::<HashMap<_, _>>();
+        // UNWRAP: the lock is never held while panicking
+        self.state.lock().unwrap().targets.insert(
+            target.to_string(),
+            TargetState {
+                total: left.len(),
+                left,
+                ..TargetState::default()
+            },
+        );
+    }
+
+    /// Mark a test case done on a target, with the time it took if it actually ran, returning
+    /// the progress of the target
+    pub(crate) fn finish(
+        &self,
+        target: &str,
+        test_case: &TestCaseId,
+        duration: Option<Duration>,
+    ) -> TargetProgress {
+        // UNWRAP: the lock is never held while panicking
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let target_state = state.targets.entry(target.to_string()).or_default();
+        target_state.left.remove(test_case);
+        if let Some(duration) = duration {
+            target_state.ran += 1;
+            target_state.ran_duration += duration;
+            state.observed.insert(test_case.clone(), duration);
+        }
+        let average = (target_state.ran > 0).then(|| target_state.ran_duration / target_state.ran);
+        let eta = target_state
+            .left
+            .iter()
+            .map(|(test_case, expected)| {
+                expected
+                    .or_else(|| state.observed.get(test_case).copied())
+                    .or(average)
+            })
+            .sum::<Option<Duration>>();
+        TargetProgress {
+            done: target_state.total - target_state.left.len(),
+            total: target_state.total,
+            eta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn id(name: &str) -> TestCaseId {
+        TestCaseId::new(Path::new("tests.sh"), name)
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn eta_from_expected_durations() {
+        let tracker = ProgressTracker::default();
+        tracker.add_target(
+            "a",
+            [(id("t1"), Some(secs(10))), (id("t2"), Some(secs(20)))],
+        );
+        assert_eq!(
+            tracker.finish("a", &id("t1"), Some(secs(12))),
+            TargetProgress {
+                done: 1,
+                total: 2,
+                eta: Some(secs(20)),
+            }
+        );
+        let progress = tracker.finish("a", &id("t2"), None);
+        assert_eq!(progress.eta, Some(Duration::ZERO));
+        assert_eq!(progress.percent(), 100.0);
+    }
+
+    #[test]
+    fn eta_from_observed_durations() {
+        let tracker = ProgressTracker::default();
+        let test_cases = [(id("t1"), None), (id("t2"), None), (id("t3"), None)];
+        tracker.add_target("a", test_cases.clone());
+        tracker.add_target("b", test_cases);
+        // Nothing ran yet on b, nor t3 anywhere
+        tracker.finish("a", &id("t2"), Some(secs(30)));
+        assert_eq!(tracker.finish("b", &id("t1"), None).eta, None);
+        // t2 took 30s on a
+        let progress = tracker.finish("b", &id("t3"), Some(secs(10)));
+        assert_eq!(progress.eta, Some(secs(30)));
+        assert_eq!(progress.done, 2);
+        // t3 took 10s on b
+        assert_eq!(tracker.finish("a", &id("t1"), None).eta, Some(secs(10)));
+    }
+}
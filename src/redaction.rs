@@ -0,0 +1,136 @@
+use crate::secrets::Secrets;
+
+use regex::{NoExpand, Regex};
+
+use std::path::Path;
+
+/// Removes sensitive data from the logs of the test cases and from the reports: the values of
+/// the secrets, and whatever matches the redaction rules (e.g. tokens or serial numbers)
+#[derive(Debug, Default)]
+pub struct Redactor {
+    /// Values replaced wherever they appear, longest first so that a value containing another one
+    /// is redacted as a whole
+    values: Vec<String>,
+    /// Patterns matched against each line
+    rules: Vec<Regex>,
+}
+
+impl Redactor {
+    /// What the sensitive data is replaced with
+    pub const REDACTED: &str = "[redacted]";
+
+    pub fn new(secrets: &Secrets, rules: Vec<Regex>) -> Self {
+        let mut values = secrets
+            .env()
+            .values()
+            .filter(|value| !value.is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        values.dedup();
+        Self { values, rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.rules.is_empty()
+    }
+
+    /// Remove the sensitive data from a text
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for value in &self.values {
+            text = text.replace(value.as_str(), Self::REDACTED);
+        }
+        if self.rules.is_empty() {
+            return text;
+        }
+        text.split_inclusive('\n')
+            .map(|line| {
+                let (line, end) = match line.strip_suffix('\n') {
+                    Some(line) => (line, "\n"),
+                    None => (line, ""),
+                };
+                let mut line = line.to_string();
+                for rule in &self.rules {
+                    line = rule
+                        .replace_all(&line, NoExpand(Self::REDACTED))
+                        .into_owned();
+                }
+                line + end
+            })
+            .collect()
+    }
+
+    /// Remove the sensitive data from a file
+    /// The values of the secrets are removed from any file, the redaction rules are only applied
+    /// to text files.
+    pub fn redact_file(&self, path: &Path) -> std::io::Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let contents = std::fs::read(path)?;
+        let redacted = match std::str::from_utf8(&contents) {
+            Ok(text) => self.redact(text).into_bytes(),
+            Err(_) => {
+                let mut redacted = contents.clone();
+                for value in &self.values {
+                    redacted =
+                        replace_bytes(&redacted, value.as_bytes(), Self::REDACTED.as_bytes());
+                }
+                redacted
+            }
+        };
+        if redacted != contents {
+            std::fs::write(path, redacted)?;
+        }
+        Ok(())
+    }
+}
+
+/// Replace all the occurrences of a non-empty byte string
+fn replace_bytes(haystack: &[u8], needle: &[u8], replacement: &[u8]) -> Vec<u8> {
+    let mut replaced = Vec::with_capacity(haystack.len());
+    let mut rest = haystack;
+    while !rest.is_empty() {
+        if rest.starts_with(needle) {
+            replaced.extend_from_slice(replacement);
+            rest = &rest[needle.len()..];
+        } else {
+            replaced.push(rest[0]);
+            rest = &rest[1..];
+        }
+    }
+    replaced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_secrets() {
+        let secrets = Secrets::parse("SHORT=abc\nLONG=abcdef\nEMPTY=\n").unwrap();
+        let redactor = Redactor::new(&secrets, Vec::new());
+        assert_eq!(
+            redactor.redact("token abcdef, then abc"),
+            "token [redacted], then [redacted]"
+        );
+        assert_eq!(
+            replace_bytes(b"\xffabc\xff", b"abc", b"***"),
+            b"\xff***\xff".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_redact_rules() {
+        let rules = vec![
+            Regex::new(r"token=\w+").unwrap(),
+            Regex::new("^SN.*$").unwrap(),
+        ];
+        let redactor = Redactor::new(&Secrets::default(), rules);
+        assert_eq!(
+            redactor.redact("login token=abc ok\nSN 1234\nend"),
+            "login [redacted] ok\n[redacted]\nend"
+        );
+    }
+}
@@ -1,8 +1,18 @@
+use crate::comparison::Comparison;
 use crate::error::Error;
-use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
+use crate::progress::TargetProgress;
+use crate::reporter::composite::RunnerHealth;
+use crate::test_driver::Env;
+use crate::test_executor::{CapturedOutput, ExecutionContext, TargetEstimate, TestCaseExecInfo};
 use crate::test_suite::{TestCase, TestSuite};
 
-pub trait Reporter {
+use std::path::Path;
+use std::time::Duration;
+
+/// A reporter receives the events of a run and presents them to the user
+/// It must be `Send` and `Sync` as it is shared between threads by parallel executors, so
+/// implementations writing to the console must make sure concurrent reports do not interleave
+pub trait Reporter: Send + Sync {
     #[track_caller]
     fn notice(&self, message: &str) {
         self.notice_detailed(message, "");
@@ -31,15 +41,46 @@ pub trait Reporter {
     #[track_caller]
     fn error_from(&self, error: &Error);
 
+    /// Called before the test suites run, with the metadata of the run given by `--meta`
+    fn report_run_started(&self, _run_metadata: &Env) {}
     fn report_target_list(&self, test_suite: &TestSuite);
-    fn report_test_list(&self, test_suite: &TestSuite);
-    fn report_test_suite_time(&self);
+    /// List the test cases of a test suite, along with the fixtures (setup and teardown test
+    /// cases) and the test files they cover if `include_fixtures` is set
+    fn report_test_list(&self, test_suite: &TestSuite, include_fixtures: bool);
+    /// Called before a test suite runs, with the output directory of the test suite
+    fn report_test_suite_out_dir(&self, _test_suite: &TestSuite, _out_dir: &Path) {}
+    /// Called before a test suite runs on a batch of targets, the targets joining the run later
+    /// on coming in batches of their own
+    fn report_targets_execution_started(
+        &self,
+        _test_suite: &TestSuite,
+        _exec_contexts: &[ExecutionContext],
+    ) {
+    }
+    /// Called once a test suite ran on all its targets, with the time it took
+    fn report_test_suite_time(&self, test_suite: &TestSuite, duration: Duration);
     fn report_test_suite_execution_summary(
         &self,
         test_suite: &TestSuite,
         exec_context: &[ExecutionContext],
     );
-    fn report_total_time(&self);
+    /// Called in dry-run mode once a test suite went through all its targets, with how long the
+    /// test cases which would run are expected to take on each target and overall
+    fn report_dry_run_estimate(
+        &self,
+        _test_suite: &TestSuite,
+        _estimates: &[TargetEstimate],
+        _total: Duration,
+    ) {
+    }
+    /// Called once a test suite ran on all its targets, with how its results changed since the
+    /// previous run compared with, when compared
+    fn report_comparison(&self, _test_suite: &TestSuite, _comparison: &Comparison) {}
+    /// Called once all the test suites ran, with the time the whole run took
+    fn report_total_time(&self, duration: Duration);
+    /// Called once all the test suites ran, right after the total time, with how many warnings
+    /// and errors were reported during the whole run
+    fn report_runner_health(&self, _health: &RunnerHealth) {}
     fn report_test_case_execution_started(
         &self,
         _test_case: &TestCase,
@@ -53,9 +94,44 @@ pub trait Reporter {
         target: &str,
         exec_info: &TestCaseExecInfo,
     );
+    /// Called with each line a test case writes to its main log file while it runs, redacted,
+    /// when the output of the test cases is streamed
+    fn report_test_case_output(&self, _test_case: &TestCase, _target: &str, _line: &str) {}
+    /// Called after the result of a failed test case with the last lines of its main log file,
+    /// when the output of the failed test cases is shown
+    fn report_test_case_failure_output(
+        &self,
+        _test_case: &TestCase,
+        _target: &str,
+        _output: &CapturedOutput,
+    ) {
+    }
+    /// Called when a test case failed and is about to be run again, the failed attempt being the
+    /// last of `exec_info.failed_attempts()`
+    fn report_test_case_execution_retried(
+        &self,
+        _test_case: &TestCase,
+        _target: &str,
+        _exec_info: &TestCaseExecInfo,
+    ) {
+    }
+    /// Called each time a test case is done on a target, with how far the target went through
+    /// the test suite and the estimated time left
+    fn report_target_progress(&self, _target: &str, _progress: &TargetProgress) {}
+    /// Called once an executor ran all the test cases it was given for a target
+    fn report_target_execution_finished(&self, _target: &str) {}
 }
 
+pub(crate) mod checkpoint;
+pub mod composite;
+mod console;
+pub mod events;
+pub mod github;
 pub(crate) mod human_friendly;
-// pub(crate) mod json;
+pub mod json;
+pub mod locale;
+pub mod porcelain;
+pub(crate) mod summary_file;
+pub mod tap;
 // pub(crate) mod logging;
 // pub(crate) mod null;
@@ -0,0 +1,65 @@
+use crate::error::Error;
+use crate::paused_run::{Checkpoint, SavedResult};
+use crate::reporter::Reporter;
+use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
+use crate::test_suite::{TestCase, TestSuite};
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Save the progress of the run as the test cases complete, so that the run can be resumed even
+/// if batrun or the host crashes
+pub(crate) struct CheckpointReporter {
+    checkpoint: Arc<Checkpoint>,
+    /// Directory of the test suite running
+    test_suite_dir: Mutex<PathBuf>,
+}
+
+impl CheckpointReporter {
+    pub(crate) fn new(checkpoint: Arc<Checkpoint>) -> Self {
+        Self {
+            checkpoint,
+            test_suite_dir: Mutex::default(),
+        }
+    }
+}
+
+impl Reporter for CheckpointReporter {
+    fn notice_detailed(&self, _message: &str, _details: &str) {}
+    fn info_detailed(&self, _message: &str, _details: &str) {}
+    fn warning_detailed(&self, _message: &str, _details: &str) {}
+    fn error_detailed(&self, _message: &str, _details: &str) {}
+    fn error_from(&self, _error: &Error) {}
+
+    fn report_target_list(&self, _test_suite: &TestSuite) {}
+    fn report_test_list(&self, _test_suite: &TestSuite, _include_fixtures: bool) {}
+
+    fn report_test_suite_out_dir(&self, test_suite: &TestSuite, _out_dir: &Path) {
+        // UNWRAP: the lock is never held while panicking
+        *self.test_suite_dir.lock().unwrap() = test_suite.path().to_path_buf();
+    }
+
+    fn report_test_suite_time(&self, _test_suite: &TestSuite, _duration: Duration) {}
+    fn report_test_suite_execution_summary(
+        &self,
+        _test_suite: &TestSuite,
+        _exec_context: &[ExecutionContext],
+    ) {
+    }
+    fn report_total_time(&self, _duration: Duration) {}
+
+    fn report_test_case_execution_result(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        exec_info: &TestCaseExecInfo,
+    ) {
+        if let Some(result) = SavedResult::of(exec_info) {
+            // UNWRAP: the lock is never held while panicking
+            let test_suite_dir = self.test_suite_dir.lock().unwrap().clone();
+            self.checkpoint
+                .record(&test_suite_dir, target, test_case.id(), result);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::comparison::Comparison;
+use crate::error::Error;
+use crate::progress::TargetProgress;
+use crate::reporter::Reporter;
+use crate::test_driver::Env;
+use crate::test_executor::{CapturedOutput, ExecutionContext, TargetEstimate, TestCaseExecInfo};
+use crate::test_suite::{TestCase, TestSuite};
+
+use serde::Serialize;
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Dispatch the events of a run to several reporters (e.g. console and JSON), in the order they
+/// were added, counting the warnings and errors reported along the way
+#[derive(Default)]
+pub struct CompositeReporter {
+    reporters: Vec<Box<dyn Reporter>>,
+    warnings: AtomicUsize,
+    errors: AtomicUsize,
+}
+
+/// How many warnings and errors the runner reported (e.g. IO issues or unknown output env vars),
+/// so that problems of the infrastructure do not hide behind passing test cases
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RunnerHealth {
+    pub warnings: usize,
+    pub errors: usize,
+}
+
+impl RunnerHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.warnings == 0 && self.errors == 0
+    }
+
+    /// The warnings and errors reported since an earlier count
+    pub fn since(&self, earlier: &RunnerHealth) -> RunnerHealth {
+        RunnerHealth {
+            warnings: self.warnings.saturating_sub(earlier.warnings),
+            errors: self.errors.saturating_sub(earlier.errors),
+        }
+    }
+}
+
+impl CompositeReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, reporter: Box<dyn Reporter>) {
+        self.reporters.push(reporter);
+    }
+
+    pub fn len(&self) -> usize {
+        self.reporters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reporters.is_empty()
+    }
+
+    /// The warnings and errors reported so far, the warnings of the test drivers about the output
+    /// of the test cases included
+    pub fn health(&self) -> RunnerHealth {
+        RunnerHealth {
+            warnings: self.warnings.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Reporter for CompositeReporter {
+    fn notice_detailed(&self, message: &str, details: &str) {
+        for reporter in &self.reporters {
+            reporter.notice_detailed(message, details);
+        }
+    }
+
+    fn info_detailed(&self, message: &str, details: &str) {
+        for reporter in &self.reporters {
+            reporter.info_detailed(message, details);
+        }
+    }
+
+    fn warning_detailed(&self, message: &str, details: &str) {
+        self.warnings.fetch_add(1, Ordering::Relaxed);
+        for reporter in &self.reporters {
+            reporter.warning_detailed(message, details);
+        }
+    }
+
+    fn error_detailed(&self, message: &str, details: &str) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        for reporter in &self.reporters {
+            reporter.error_detailed(message, details);
+        }
+    }
+
+    fn error_from(&self, error: &Error) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        for reporter in &self.reporters {
+            reporter.error_from(error);
+        }
+    }
+
+    fn report_run_started(&self, run_metadata: &Env) {
+        for reporter in &self.reporters {
+            reporter.report_run_started(run_metadata);
+        }
+    }
+
+    fn report_target_list(&self, test_suite: &TestSuite) {
+        for reporter in &self.reporters {
+            reporter.report_target_list(test_suite);
+        }
+    }
+
+    fn report_test_list(&self, test_suite: &TestSuite, include_fixtures: bool) {
+        for reporter in &self.reporters {
+            reporter.report_test_list(test_suite, include_fixtures);
+        }
+    }
+
+    fn report_test_suite_out_dir(&self, test_suite: &TestSuite, out_dir: &Path) {
+        for reporter in &self.reporters {
+            reporter.report_test_suite_out_dir(test_suite, out_dir);
+        }
+    }
+
+    fn report_targets_execution_started(
+        &self,
+        test_suite: &TestSuite,
+        exec_contexts: &[ExecutionContext],
+    ) {
+        for reporter in &self.reporters {
+            reporter.report_targets_execution_started(test_suite, exec_contexts);
+        }
+    }
+
+    fn report_test_suite_time(&self, test_suite: &TestSuite, duration: Duration) {
+        for reporter in &self.reporters {
+            reporter.report_test_suite_time(test_suite, duration);
+        }
+    }
+
+    fn report_test_suite_execution_summary(
+        &self,
+        test_suite: &TestSuite,
+        exec_context: &[ExecutionContext],
+    ) {
+        for reporter in &self.reporters {
+            reporter.report_test_suite_execution_summary(test_suite, exec_context);
+        }
+    }
+
+    fn report_dry_run_estimate(
+        &self,
+        test_suite: &TestSuite,
+        estimates: &[TargetEstimate],
+        total: Duration,
+    ) {
+        for reporter in &self.reporters {
+            reporter.report_dry_run_estimate(test_suite, estimates, total);
+        }
+    }
+
+    fn report_comparison(&self, test_suite: &TestSuite, comparison: &Comparison) {
+        for reporter in &self.reporters {
+            reporter.report_comparison(test_suite, comparison);
+        }
+    }
+
+    fn report_total_time(&self, duration: Duration) {
+        for reporter in &self.reporters {
+            reporter.report_total_time(duration);
+        }
+    }
+
+    fn report_runner_health(&self, health: &RunnerHealth) {
+        for reporter in &self.reporters {
+            reporter.report_runner_health(health);
+        }
+    }
+
+    fn report_test_case_execution_started(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        exec_info: &TestCaseExecInfo,
+    ) {
+        for reporter in &self.reporters {
+            reporter.report_test_case_execution_started(test_case, target, exec_info);
+        }
+    }
+
+    fn report_test_case_execution_result(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        exec_info: &TestCaseExecInfo,
+    ) {
+        // The test drivers warn about the output of the test cases through their result
+        if let Ok(Some(driver_output)) = exec_info
+            .result()
+            .as_ref()
+            .map(|output| &output.driver_output)
+        {
+            self.warnings
+                .fetch_add(driver_output.warnings().len(), Ordering::Relaxed);
+        }
+        for reporter in &self.reporters {
+            reporter.report_test_case_execution_result(test_case, target, exec_info);
+        }
+    }
+
+    fn report_test_case_output(&self, test_case: &TestCase, target: &str, line: &str) {
+        for reporter in &self.reporters {
+            reporter.report_test_case_output(test_case, target, line);
+        }
+    }
+
+    fn report_test_case_failure_output(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        output: &CapturedOutput,
+    ) {
+        for reporter in &self.reporters {
+            reporter.report_test_case_failure_output(test_case, target, output);
+        }
+    }
+
+    fn report_test_case_execution_retried(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        exec_info: &TestCaseExecInfo,
+    ) {
+        for reporter in &self.reporters {
+            reporter.report_test_case_execution_retried(test_case, target, exec_info);
+        }
+    }
+
+    fn report_target_progress(&self, target: &str, progress: &TargetProgress) {
+        for reporter in &self.reporters {
+            reporter.report_target_progress(target, progress);
+        }
+    }
+
+    fn report_target_execution_finished(&self, target: &str) {
+        for reporter in &self.reporters {
+            reporter.report_target_execution_finished(target);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{IsTerminal, Write};
+use std::sync::Mutex;
+use std::thread::ThreadId;
+
+/// Output manager serializing the console writes of concurrent threads
+/// Text written by a thread is kept aside until it forms complete lines, which are then written
+/// to stdout at once, so that the output of concurrent threads never interleaves within a line.
+/// When grouping is enabled, the complete lines related to a target are additionally kept aside
+/// until the target is done, so that it is printed as a single block, even when several threads
+/// run test cases of the same target.
+/// The output can be redirected to a file instead of stdout, colors being removed.
+/// When stdout is a terminal, a status line (e.g. the progress of the run) can be kept below the
+/// output, redrawn after each write.
+#[derive(Default)]
+pub(crate) struct Console {
+    grouped: bool,
+    /// Whether the status line is shown, only when writing to a terminal
+    status_line: bool,
+    /// The status line currently shown, empty if none
+    status: Mutex<String>,
+    /// The incomplete lines of each thread, by target for the grouped output
+    pending: Mutex<HashMap<(ThreadId, Option<String>), String>>,
+    groups: Mutex<HashMap<String, String>>,
+    file: Option<Mutex<File>>,
+}
+
+/// Writer for the output related to a target, see [`Console::group`]
+pub(crate) struct ConsoleGroup<'a> {
+    console: &'a Console,
+    target: &'a str,
+}
+
+impl Console {
+    pub(crate) fn new(grouped: bool) -> Self {
+        Self {
+            grouped,
+            status_line: false,
+            status: Mutex::default(),
+            pending: Mutex::default(),
+            groups: Mutex::default(),
+            file: None,
+        }
+    }
+
+    /// Write to the given file instead of stdout, without status line
+    pub(crate) fn with_file(mut self, file: File) -> Self {
+        self.file = Some(Mutex::new(file));
+        self.status_line = false;
+        self
+    }
+
+    /// Show the status line set with [`Console::set_status`] if stdout is a terminal, the status
+    /// being ignored otherwise so that the output stays made of plain lines
+    pub(crate) fn with_status_line(mut self) -> Self {
+        self.status_line = self.file.is_none() && std::io::stdout().is_terminal();
+        self
+    }
+
+    /// Replace the status line, truncated to the width of the terminal so that it can be erased
+    pub(crate) fn set_status(&self, status: &str) {
+        if !self.status_line {
+            return;
+        }
+        // UNWRAP: the lock is never held while panicking
+        let mut current = self.status.lock().unwrap();
+        if current.is_empty() && status.is_empty() {
+            return;
+        }
+        *current = Self::truncate(status, Self::terminal_width());
+        let _ = write!(std::io::stdout().lock(), "{}{current}", Self::ERASE_LINE);
+        let _ = std::io::stdout().flush();
+    }
+
+    /// Erase the status line, if shown
+    pub(crate) fn clear_status(&self) {
+        self.set_status("");
+    }
+
+    /// Entry point of the `write!` and `writeln!` macros
+    pub(crate) fn write_fmt(&self, args: std::fmt::Arguments) {
+        self.write_lines(None, args);
+    }
+
+    /// Write the complete lines of the calling thread, to the buffered output of the given target
+    /// if any, or else to the output
+    fn write_lines(&self, group: Option<&str>, args: std::fmt::Arguments) {
+        // UNWRAP: the lock is never held while panicking
+        let mut pending = self.pending.lock().unwrap();
+        let key = (std::thread::current().id(), group.map(str::to_string));
+        let buffer = pending.entry(key.clone()).or_default();
+        Self::append(buffer, args);
+        if let Some(end_of_lines) = buffer.rfind('\n') {
+            let lines = buffer.drain(..=end_of_lines).collect::<String>();
+            match group {
+                Some(target) => {
+                    // UNWRAP: the lock is never held while panicking
+                    let mut groups = self.groups.lock().unwrap();
+                    groups
+                        .entry(target.to_string())
+                        .or_default()
+                        .push_str(&lines);
+                }
+                None => self.output(&lines),
+            }
+        }
+        if buffer.is_empty() {
+            pending.remove(&key);
+        }
+    }
+
+    /// Writer for the output related to the given target, buffered until [`Console::end_group`]
+    /// is called for this target if grouping is enabled
+    pub(crate) fn group<'a>(&'a self, target: &'a str) -> ConsoleGroup<'a> {
+        ConsoleGroup {
+            console: self,
+            target,
+        }
+    }
+
+    /// Print the output buffered for the given target, if any
+    pub(crate) fn end_group(&self, target: &str) {
+        // UNWRAP: the lock is never held while panicking
+        let group = self.groups.lock().unwrap().remove(target);
+        if let Some(group) = group {
+            self.output(&group);
+        }
+    }
+
+    fn append(buffer: &mut String, args: std::fmt::Arguments) {
+        // UNWRAP: formatting into a String cannot fail
+        std::fmt::Write::write_fmt(buffer, args).unwrap();
+    }
+
+    fn output(&self, text: &str) {
+        // Nothing sensible can be done if the output is closed, just drop it
+        match &self.file {
+            Some(file) => {
+                // UNWRAP: the lock is never held while panicking
+                let _ = file
+                    .lock()
+                    .unwrap()
+                    .write_all(Self::strip_colors(text).as_bytes());
+            }
+            // UNWRAP: the lock is never held while panicking
+            None if self.status_line && !self.status.lock().unwrap().is_empty() => {
+                // UNWRAP: the lock is never held while panicking
+                let status = self.status.lock().unwrap();
+                let mut stdout = std::io::stdout().lock();
+                let _ = write!(stdout, "{}{text}{status}", Self::ERASE_LINE);
+                let _ = stdout.flush();
+            }
+            None => {
+                let _ = std::io::stdout().lock().write_all(text.as_bytes());
+            }
+        }
+    }
+
+    /// Move to the start of the line and erase it
+    const ERASE_LINE: &str = "\r\x1b[K";
+
+    /// Number of columns of the terminal, from `COLUMNS` as set by most shells, 80 otherwise
+    fn terminal_width() -> usize {
+        std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .filter(|columns| *columns > 0)
+            .unwrap_or(80)
+    }
+
+    /// The text cut to fit in the given number of columns, keeping a column free for the cursor
+    fn truncate(text: &str, columns: usize) -> String {
+        text.chars().take(columns.saturating_sub(1)).collect()
+    }
+
+    /// Remove the ANSI escape sequences setting colors and styles
+    fn strip_colors(text: &str) -> String {
+        let mut stripped = String::with_capacity(text.len());
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                // Skip the control sequence up to its final byte, e.g. `m` in `\x1b[1;31m`
+                chars.by_ref().find(|c| c.is_ascii_alphabetic());
+            } else {
+                stripped.push(c);
+            }
+        }
+        stripped
+    }
+}
+
+impl Drop for Console {
+    /// Leave the terminal without status line, e.g. when the run stops on an error
+    fn drop(&mut self) {
+        self.clear_status();
+    }
+}
+
+impl ConsoleGroup<'_> {
+    /// Entry point of the `write!` and `writeln!` macros
+    pub(crate) fn write_fmt(&self, args: std::fmt::Arguments) {
+        let group = Some(self.target).filter(|_| self.console.grouped);
+        self.console.write_lines(group, args);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grouped_lines_of_concurrent_threads_do_not_interleave() {
+        let console = Console::new(true);
+        write!(console.group("local"), "Running a");
+        std::thread::scope(|scope| {
+            scope.spawn(|| writeln!(console.group("local"), "Running b PASSED"));
+        });
+        writeln!(console.group("local"), " FAILED");
+        assert_eq!(
+            console.groups.lock().unwrap()["local"],
+            "Running b PASSED\nRunning a FAILED\n"
+        );
+        assert!(console.pending.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn status_truncated_to_terminal_width() {
+        assert_eq!(Console::truncate("12/48 tests", 80), "12/48 tests");
+        assert_eq!(Console::truncate("12/48 tests", 6), "12/48");
+        assert_eq!(Console::truncate("été", 3), "ét");
+    }
+}
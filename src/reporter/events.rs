@@ -0,0 +1,393 @@
+use crate::error::Error;
+use crate::progress::TargetProgress;
+use crate::reporter::Reporter;
+use crate::reporter::composite::RunnerHealth;
+use crate::test_driver::Env;
+use crate::test_executor::{CapturedOutput, ExecutionContext, TestCaseExecInfo};
+use crate::test_suite::{TestCase, TestSuite};
+use crate::time;
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// A message for the user, with its `level` and `message`
+    Message,
+    /// A test case of a listed test suite, with its `test_suite` and `test_case` id
+    TestCaseListed,
+    /// The test suites are about to run, with the `metadata` of the run
+    RunStarted,
+    /// A test suite is about to run, with its `test_suite` path
+    TestSuiteStarted,
+    /// A test case started on a target, with its `test_case` id and `target`
+    TestCaseStarted,
+    /// A test case finished on a target, with its `test_case` id, `target`, `status`, `duration`
+    /// and as `message` the error preventing it from running, or the message it attached to its
+    /// result, if any
+    TestCaseFinished,
+    /// The last lines of the log of a failed test case, right after it finished, with its
+    /// `test_case` id, `target` and the lines as `message`
+    TestCaseFailureOutput,
+    /// All the test cases of a test suite ran on a target, with the `target`
+    TargetFinished,
+    /// A test case is done on a target, with the `target`, the percentage of the test cases of
+    /// the test suite done as `progress` and the estimated time left as `duration`, if known
+    TargetProgress,
+    /// A test suite ran on all its targets, with the `test_suite` path and `duration`
+    TestSuiteFinished,
+    /// All the test suites ran, with the `health` of the runner
+    RunnerHealth,
+}
+
+impl EventKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::Message => "message",
+            EventKind::TestCaseListed => "test-case-listed",
+            EventKind::RunStarted => "run-started",
+            EventKind::TestSuiteStarted => "test-suite-started",
+            EventKind::TestCaseStarted => "test-case-started",
+            EventKind::TestCaseFinished => "test-case-finished",
+            EventKind::TestCaseFailureOutput => "test-case-failure-output",
+            EventKind::TargetFinished => "target-finished",
+            EventKind::TargetProgress => "target-progress",
+            EventKind::TestSuiteFinished => "test-suite-finished",
+            EventKind::RunnerHealth => "runner-health",
+        }
+    }
+}
+
+/// Event of a run, the fields not relevant to its kind being `None`
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub kind: EventKind,
+    /// Level of a message: `notice`, `info`, `warning` or `error`
+    pub level: Option<&'static str>,
+    pub message: Option<String>,
+    pub test_suite: Option<PathBuf>,
+    pub test_case: Option<String>,
+    pub target: Option<String>,
+    /// Status of a finished test case, as in the run manifest (e.g. `passed` or `runner-failed`)
+    pub status: Option<&'static str>,
+    pub duration: Option<Duration>,
+    /// Percentage of the test cases of the test suite done on a target
+    pub progress: Option<f64>,
+    /// Metadata of the run given by `--meta`
+    pub metadata: Option<Env>,
+    /// How many warnings and errors the runner reported, given as the `warnings` and `errors`
+    /// fields in JSON
+    pub health: Option<RunnerHealth>,
+}
+
+impl Event {
+    fn new(kind: EventKind) -> Self {
+        Self {
+            kind,
+            level: None,
+            message: None,
+            test_suite: None,
+            test_case: None,
+            target: None,
+            status: None,
+            duration: None,
+            progress: None,
+            metadata: None,
+            health: None,
+        }
+    }
+
+    /// The event as a JSON object, its kind being the `event` field, the fields not relevant to
+    /// its kind being left out and the duration being in seconds
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut object = serde_json::Map::new();
+        object.insert("event".to_string(), self.kind.name().into());
+        let fields = [
+            ("level", self.level.map(Into::into)),
+            ("message", self.message.clone().map(Into::into)),
+            (
+                "test-suite",
+                self.test_suite
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().into()),
+            ),
+            ("test-case", self.test_case.clone().map(Into::into)),
+            ("target", self.target.clone().map(Into::into)),
+            ("status", self.status.map(Into::into)),
+            (
+                "duration",
+                self.duration.map(|duration| duration.as_secs_f64().into()),
+            ),
+            ("progress", self.progress.map(Into::into)),
+            ("warnings", self.health.map(|health| health.warnings.into())),
+            ("errors", self.health.map(|health| health.errors.into())),
+            (
+                "metadata",
+                self.metadata.as_ref().map(|metadata| {
+                    metadata
+                        .iter()
+                        .map(|(key, value)| (key.clone(), value.clone().into()))
+                        .collect::<serde_json::Map<_, _>>()
+                        .into()
+                }),
+            ),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                object.insert(name.to_string(), value);
+            }
+        }
+        object.into()
+    }
+}
+
+/// Turn the reports of a run into [`Event`]s given to a handler, for applications embedding
+/// batrun through bindings to other languages
+/// The handler is called from one thread at a time, so that it does not need to be thread-safe.
+pub struct EventReporter {
+    on_event: Mutex<Box<dyn FnMut(Event) + Send>>,
+}
+
+impl EventReporter {
+    pub fn new(on_event: impl FnMut(Event) + Send + 'static) -> Self {
+        Self {
+            on_event: Mutex::new(Box::new(on_event)),
+        }
+    }
+
+    /// Write the events as JSON lines as they happen, along with the `time` they happened at, e.g.
+    /// for dashboards and IDEs following the run live
+    /// Writing is best effort, so that a reader going away does not stop the run.
+    pub fn json_lines(mut writer: impl Write + Send + 'static) -> Self {
+        Self::new(move |event| {
+            let mut json = event.to_json();
+            json["time"] = time::format_timestamp(SystemTime::now()).into();
+            let line = format!("{json}\n");
+            let _ = writer
+                .write_all(line.as_bytes())
+                .and_then(|()| writer.flush());
+        })
+    }
+
+    fn emit(&self, event: Event) {
+        let mut on_event = self
+            .on_event
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        on_event(event);
+    }
+
+    fn message(&self, level: &'static str, message: &str, details: &str) {
+        // Empty messages only space out the console output
+        if message.is_empty() && details.is_empty() {
+            return;
+        }
+        let message = if details.is_empty() {
+            message.to_string()
+        } else {
+            format!("{message}\n{details}")
+        };
+        self.emit(Event {
+            level: Some(level),
+            message: Some(message),
+            ..Event::new(EventKind::Message)
+        });
+    }
+}
+
+impl Reporter for EventReporter {
+    fn notice_detailed(&self, message: &str, details: &str) {
+        self.message("notice", message, details);
+    }
+
+    fn info_detailed(&self, message: &str, details: &str) {
+        self.message("info", message, details);
+    }
+
+    fn warning_detailed(&self, message: &str, details: &str) {
+        self.message("warning", message, details);
+    }
+
+    fn error_detailed(&self, message: &str, details: &str) {
+        self.message("error", message, details);
+    }
+
+    fn error_from(&self, error: &Error) {
+        self.message("error", &error.to_string(), "");
+    }
+
+    fn report_run_started(&self, run_metadata: &Env) {
+        self.emit(Event {
+            metadata: Some(run_metadata.clone()),
+            ..Event::new(EventKind::RunStarted)
+        });
+    }
+
+    fn report_target_list(&self, _test_suite: &TestSuite) {}
+
+    fn report_test_list(&self, test_suite: &TestSuite, _include_fixtures: bool) {
+        for test_case in test_suite
+            .test_files()
+            .iter()
+            .flat_map(|test_file| &test_file.test_cases)
+        {
+            self.emit(Event {
+                test_suite: Some(test_suite.path().to_path_buf()),
+                test_case: Some(test_case.id().to_string()),
+                ..Event::new(EventKind::TestCaseListed)
+            });
+        }
+    }
+
+    fn report_test_suite_out_dir(&self, test_suite: &TestSuite, _out_dir: &Path) {
+        self.emit(Event {
+            test_suite: Some(test_suite.path().to_path_buf()),
+            ..Event::new(EventKind::TestSuiteStarted)
+        });
+    }
+
+    fn report_test_suite_time(&self, test_suite: &TestSuite, duration: Duration) {
+        self.emit(Event {
+            test_suite: Some(test_suite.path().to_path_buf()),
+            duration: Some(duration),
+            ..Event::new(EventKind::TestSuiteFinished)
+        });
+    }
+
+    fn report_test_suite_execution_summary(
+        &self,
+        _test_suite: &TestSuite,
+        _exec_context: &[ExecutionContext],
+    ) {
+    }
+
+    fn report_total_time(&self, _duration: Duration) {}
+
+    fn report_runner_health(&self, health: &RunnerHealth) {
+        self.emit(Event {
+            health: Some(*health),
+            ..Event::new(EventKind::RunnerHealth)
+        });
+    }
+
+    fn report_test_case_execution_started(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        _exec_info: &TestCaseExecInfo,
+    ) {
+        self.emit(Event {
+            test_case: Some(test_case.id().to_string()),
+            target: Some(target.to_string()),
+            ..Event::new(EventKind::TestCaseStarted)
+        });
+    }
+
+    fn report_test_case_execution_result(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        exec_info: &TestCaseExecInfo,
+    ) {
+        let (status, message) = match exec_info.result() {
+            Ok(output) => (output.test_case_status.name(), output.message.clone()),
+            Err(error) => ("runner-failed", Some(error.to_string())),
+        };
+        self.emit(Event {
+            message,
+            test_case: Some(test_case.id().to_string()),
+            target: Some(target.to_string()),
+            status: Some(status),
+            duration: exec_info.duration(),
+            ..Event::new(EventKind::TestCaseFinished)
+        });
+    }
+
+    fn report_test_case_failure_output(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        output: &CapturedOutput,
+    ) {
+        self.emit(Event {
+            message: Some(output.lines.join("\n")),
+            test_case: Some(test_case.id().to_string()),
+            target: Some(target.to_string()),
+            ..Event::new(EventKind::TestCaseFailureOutput)
+        });
+    }
+
+    fn report_target_progress(&self, target: &str, progress: &TargetProgress) {
+        self.emit(Event {
+            target: Some(target.to_string()),
+            duration: progress.eta,
+            progress: Some(progress.percent()),
+            ..Event::new(EventKind::TargetProgress)
+        });
+    }
+
+    fn report_target_execution_finished(&self, target: &str) {
+        self.emit(Event {
+            target: Some(target.to_string()),
+            ..Event::new(EventKind::TargetFinished)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_as_json() {
+        let event = Event {
+            test_case: Some("tests.sh::test_01".to_string()),
+            target: Some("a".to_string()),
+            status: Some("passed"),
+            duration: Some(Duration::from_millis(1500)),
+            ..Event::new(EventKind::TestCaseFinished)
+        };
+        assert_eq!(
+            event.to_json(),
+            serde_json::json!({
+                "event": "test-case-finished",
+                "test-case": "tests.sh::test_01",
+                "target": "a",
+                "status": "passed",
+                "duration": 1.5,
+            })
+        );
+    }
+
+    #[test]
+    fn run_started_event_as_json() {
+        let event = Event {
+            metadata: Some(Env::from([("BUILD".to_string(), "42".to_string())])),
+            ..Event::new(EventKind::RunStarted)
+        };
+        assert_eq!(
+            event.to_json(),
+            serde_json::json!({
+                "event": "run-started",
+                "metadata": {"BUILD": "42"},
+            })
+        );
+    }
+
+    #[test]
+    fn runner_health_event_as_json() {
+        let event = Event {
+            health: Some(RunnerHealth {
+                warnings: 2,
+                errors: 0,
+            }),
+            ..Event::new(EventKind::RunnerHealth)
+        };
+        assert_eq!(
+            event.to_json(),
+            serde_json::json!({"event": "runner-health", "warnings": 2, "errors": 0})
+        );
+    }
+}
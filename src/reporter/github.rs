@@ -0,0 +1,189 @@
+use crate::error::Error;
+use crate::reporter::Reporter;
+use crate::reporter::human_friendly::HumanFriendlyReporter;
+use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
+use crate::test_suite::status::TestCaseStatus;
+use crate::test_suite::{TestCase, TestSuite};
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Print GitHub Actions workflow commands on the standard output for the failed test cases, the
+/// warnings and the errors of a run, so that they surface as annotations of the workflow run
+/// The annotations of the test cases point to their test file, the other ones to no file. It
+/// comes in addition to the main reporter, which keeps printing the rest of the run.
+pub struct GithubReporter {
+    /// Directory of the test suite running, the paths of its test cases being relative to it
+    test_suite_dir: Mutex<PathBuf>,
+}
+
+impl Default for GithubReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GithubReporter {
+    pub fn new() -> Self {
+        Self {
+            test_suite_dir: Mutex::new(PathBuf::new()),
+        }
+    }
+}
+
+/// A workflow command creating an annotation, e.g. `::error file=a.sh,title=...::message`
+fn annotation(level: &str, file: Option<&Path>, title: Option<&str>, message: &str) -> String {
+    let mut properties = Vec::new();
+    if let Some(file) = file {
+        properties.push(format!(
+            "file={}",
+            escape_property(&file.display().to_string())
+        ));
+    }
+    if let Some(title) = title {
+        properties.push(format!("title={}", escape_property(title)));
+    }
+    let mut command = format!("::{level}");
+    if !properties.is_empty() {
+        let _ = write!(command, " {}", properties.join(","));
+    }
+    let _ = write!(command, "::{}", escape_data(message));
+    command
+}
+
+/// The message of a workflow command ends at the end of its line
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// The properties of a workflow command are also separated by commas and end at a colon
+fn escape_property(property: &str) -> String {
+    escape_data(property)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+/// The level, title and message of the annotation of a test case result, if it deserves one
+fn test_case_annotation(
+    test_case: &TestCase,
+    target: &str,
+    exec_info: &TestCaseExecInfo,
+) -> Option<(&'static str, String, String)> {
+    let mut message = String::new();
+    let (level, title) = match exec_info.result() {
+        Ok(output) => {
+            let title = match &output.test_case_status {
+                TestCaseStatus::Failed(reason) => {
+                    let _ = writeln!(message, "Reason: {reason}");
+                    ("error", "failed")
+                }
+                TestCaseStatus::XPassed(reason) => {
+                    let _ = writeln!(message, "Expected to fail: {reason}");
+                    ("warning", "passed unexpectedly")
+                }
+                _ => return None,
+            };
+            if let Some(output_message) = &output.message {
+                let _ = writeln!(message, "Message: {output_message}");
+            }
+            title
+        }
+        Err(error) => {
+            let (error_message, details) = HumanFriendlyReporter::error_message(error);
+            let _ = writeln!(message, "{error_message}");
+            if !details.is_empty() {
+                let _ = writeln!(message, "{details}");
+            }
+            ("error", "could not run")
+        }
+    };
+    if let Some(log_file) = exec_info.main_log_file() {
+        let _ = writeln!(message, "Log: {}", log_file.location());
+    }
+    let title = format!("{} {title} on {target}", test_case.id());
+    Some((level, title, message.trim_end().to_string()))
+}
+
+impl Reporter for GithubReporter {
+    fn notice_detailed(&self, _message: &str, _details: &str) {}
+    fn info_detailed(&self, _message: &str, _details: &str) {}
+
+    fn warning_detailed(&self, message: &str, details: &str) {
+        let message = [message, details].join("\n");
+        println!("{}", annotation("warning", None, None, message.trim_end()));
+    }
+
+    fn error_detailed(&self, message: &str, details: &str) {
+        let message = [message, details].join("\n");
+        println!("{}", annotation("error", None, None, message.trim_end()));
+    }
+
+    fn error_from(&self, error: &Error) {
+        let (message, details) = HumanFriendlyReporter::error_message(error);
+        self.error_detailed(&message, &details);
+    }
+
+    fn report_target_list(&self, _test_suite: &TestSuite) {}
+
+    fn report_test_list(&self, _test_suite: &TestSuite, _include_fixtures: bool) {}
+
+    fn report_test_suite_out_dir(&self, test_suite: &TestSuite, _out_dir: &Path) {
+        // UNWRAP: the lock is never held while panicking
+        *self.test_suite_dir.lock().unwrap() = test_suite.path().to_path_buf();
+    }
+
+    fn report_test_suite_time(&self, _test_suite: &TestSuite, _duration: Duration) {}
+
+    fn report_test_suite_execution_summary(
+        &self,
+        _test_suite: &TestSuite,
+        _exec_contexts: &[ExecutionContext],
+    ) {
+    }
+
+    fn report_total_time(&self, _duration: Duration) {}
+
+    fn report_test_case_execution_result(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        exec_info: &TestCaseExecInfo,
+    ) {
+        if let Some((level, title, message)) = test_case_annotation(test_case, target, exec_info) {
+            // UNWRAP: the lock is never held while panicking
+            let file = self.test_suite_dir.lock().unwrap().join(test_case.path());
+            println!("{}", annotation(level, Some(&file), Some(&title), &message));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotation_without_properties() {
+        assert_eq!(
+            annotation("warning", None, None, "Target `b` left the run"),
+            "::warning::Target `b` left the run"
+        );
+    }
+
+    #[test]
+    fn annotation_escaped() {
+        assert_eq!(
+            annotation(
+                "error",
+                Some(Path::new("suite/a.sh")),
+                Some("a.sh::t[x=1,y=2] failed on foo:0"),
+                "100% failed\nReason: TestCaseFailure"
+            ),
+            "::error file=suite/a.sh,title=a.sh%3A%3At[x=1%2Cy=2] failed on foo%3A0::\
+             100%25 failed%0AReason: TestCaseFailure"
+        );
+    }
+}
@@ -1,138 +1,747 @@
+use crate::comparison::{ChangeKind, Comparison};
 use crate::error::Error;
+use crate::progress::TargetProgress;
 use crate::reporter::Reporter;
-use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
-use crate::test_suite::status::TestCaseStatus;
+use crate::reporter::composite::RunnerHealth;
+use crate::reporter::console::Console;
+use crate::reporter::locale::{Catalog, Locale, fill};
+use crate::settings::{ColorChoice, ShowOutput, Verbosity};
+use crate::test_driver::{Env, RunTestOutput};
+use crate::test_executor::{
+    CapturedOutput, ExecutionContext, ExpectedDuration, ExpectedDurationSource, TargetEstimate,
+    TestCaseExecInfo, TimeoutSource,
+};
+use crate::test_suite::id::TestCaseId;
+use crate::test_suite::status::{FailReason, Statistics, TestCaseStatus};
 use crate::test_suite::visitor::Visitor;
 use crate::test_suite::{TestCase, TestSuite};
+use crate::time;
 
 use colored::{ColoredString, Colorize};
 
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
 pub(crate) struct HumanFriendlyReporter {
+    console: Console,
     debug_enabled: bool,
     matrix_summary: bool,
+    /// No colors nor box-drawing characters, for screen readers and dumb terminals
+    plain: bool,
+    messages: &'static Catalog,
+    /// Metadata of the run given by the user (e.g. the build number), printed with the summaries
+    run_metadata: Env,
+    show_output: ShowOutput,
+    verbosity: Verbosity,
+    /// Progress of the test suite running, shown on the status line of the console
+    progress: Mutex<Progress>,
+}
+
+/// How many test cases of the test suite running are done, fixtures excluded, and on which
+/// targets test cases are running
+#[derive(Default)]
+struct Progress {
+    tests: HashSet<TestCaseId>,
+    total: usize,
+    done: usize,
+    failed: usize,
+    /// Number of test cases running on each target, fixtures included
+    running: BTreeMap<String, usize>,
+    /// How far each target went through the test suite, until done
+    targets: BTreeMap<String, TargetProgress>,
+}
+
+impl Progress {
+    fn status(&self, messages: &Catalog) -> String {
+        let mut status = fill(messages.progress, &[&self.done, &self.total, &self.failed]);
+        let targets = self
+            .running
+            .keys()
+            .chain(self.targets.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|target| match self.targets.get(target) {
+                Some(progress) => {
+                    let percent = format!("{:.0}", progress.percent());
+                    match progress.eta {
+                        Some(eta) => {
+                            fill(messages.target_eta, &[target, &percent, &time::format(eta)])
+                        }
+                        None => fill(messages.target_progress, &[target, &percent]),
+                    }
+                }
+                None => target.clone(),
+            })
+            .collect::<Vec<_>>();
+        if !targets.is_empty() {
+            status += &fill(messages.progress_targets, &[&targets.join(", ")]);
+        }
+        status
+    }
 }
 
 impl HumanFriendlyReporter {
-    pub(crate) fn new(debug_enabled: bool, matrix_summary: bool) -> Self {
+    pub(crate) fn new(
+        debug_enabled: bool,
+        matrix_summary: bool,
+        group_output: bool,
+        plain: bool,
+        color: ColorChoice,
+        locale: Locale,
+    ) -> Self {
+        colored::control::set_override(!plain && color.enabled());
+        let console = Console::new(group_output);
         Self {
+            // The status line is redrawn with control characters screen readers would spell out
+            console: if plain {
+                console
+            } else {
+                console.with_status_line()
+            },
             debug_enabled,
             matrix_summary,
+            plain,
+            messages: locale.catalog(),
+            run_metadata: Env::new(),
+            show_output: ShowOutput::Never,
+            verbosity: Verbosity::Normal,
+            progress: Mutex::default(),
         }
     }
 
+    /// Update the progress of the test suite running and show it on the status line
+    fn update_progress(&self, update: impl FnOnce(&mut Progress)) {
+        // UNWRAP: the lock is never held while panicking
+        let mut progress = self.progress.lock().unwrap();
+        update(&mut progress);
+        self.console.set_status(&progress.status(self.messages));
+    }
+
+    pub(crate) fn with_show_output(mut self, show_output: ShowOutput) -> Self {
+        self.show_output = show_output;
+        self
+    }
+
+    pub(crate) fn with_verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    pub(crate) fn with_run_metadata(mut self, run_metadata: Env) -> Self {
+        self.run_metadata = run_metadata;
+        self
+    }
+
+    /// Write to the given file instead of stdout, without colors
+    pub(crate) fn with_file(mut self, file: File) -> Self {
+        self.console = std::mem::take(&mut self.console).with_file(file);
+        self
+    }
+
     #[track_caller]
     fn print_with_details(&self, prefix: ColoredString, message: &str, details: &str) {
-        println!("{}{}", prefix, message.bright_white());
-        if !details.is_empty() {
-            println!("  {}", details.white());
-        }
-        self.print_source_location();
+        write!(
+            self.console,
+            "{}",
+            self.format_with_details(prefix, message, details)
+        );
     }
 
+    /// Format a message with its details as a block of lines, so that it is printed at once
     #[track_caller]
-    fn print_source_location(&self) {
+    fn format_with_details(&self, prefix: ColoredString, message: &str, details: &str) -> String {
+        let mut text = format!("{}{}\n", prefix, message.bright_white());
+        for line in details.lines() {
+            text += &format!("  {}\n", line.white());
+        }
         if self.debug_enabled {
             let source_location = format!("[from: {}]", std::panic::Location::caller());
-            println!("{}", source_location.dimmed());
+            text += &format!("{}\n", source_location.dimmed());
+        };
+        text
+    }
+
+    /// Format a line of the output of a test case, set apart from the messages of batrun
+    fn format_output_line(&self, line: &str) -> String {
+        let margin = if self.plain { "|" } else { "│" };
+        format!("{} {}\n", margin.dimmed(), line)
+    }
+
+    /// What the result of a test case follows: the running message if nothing was printed since,
+    /// or which test case it is about once its output was streamed
+    fn result_prefix(&self, test_case: &TestCase, target: &str) -> String {
+        match (self.show_output, self.verbosity) {
+            // Already printed when the test case started
+            (ShowOutput::Never | ShowOutput::Failed, Verbosity::Normal | Verbosity::Verbose) => {
+                String::new()
+            }
+            _ => fill(
+                self.messages.test_case_on_target,
+                &[&test_case.id(), &target],
+            ),
+        }
+    }
+
+    /// The message and details describing an error
+    pub(crate) fn error_message(error: &Error) -> (String, String) {
+        match &error {
+            Error::SuiteConfigIo(error) => (error.to_string(), error.source.to_string()),
+            Error::InvalidSuiteConfig(error) => (error.to_string(), error.source.to_string()),
+            Error::SuiteConfigValidation(error) => (
+                error.to_string(),
+                error
+                    .issues
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            Error::TestDriverIo(error) => (error.to_string(), error.source.to_string()),
+            Error::HistoryIo(error) => (error.to_string(), error.source.to_string()),
+            Error::InvalidHistory(error) => (error.to_string(), error.source.to_string()),
+            Error::TestFileExec(error) => (error.to_string(), error.details.clone()),
+            Error::InvalidTestMetadata(error) => (error.to_string(), error.details.clone()),
+            Error::TargetBackend(error) => (error.to_string(), error.details.clone()),
+            Error::ReportIo(error) => (error.to_string(), error.source.to_string()),
+            Error::InvalidExitCodes(error) => (error.to_string(), error.details.clone()),
+            Error::InvalidPreviousRun(error) => (error.to_string(), error.details.clone()),
+            Error::InvalidGlobalConfig(error) => (error.to_string(), error.details.clone()),
+            Error::Secrets(error) => (error.to_string(), error.details.clone()),
+            Error::ArtifactStorage(error) => (error.to_string(), error.details.clone()),
+            Error::Service(error) => (error.to_string(), error.details.clone()),
+            Error::PortAllocation(error) => (error.to_string(), error.source.to_string()),
+            Error::NetworkIsolation(error) => (error.to_string(), error.details.clone()),
+            _ => (error.to_string(), String::new()),
+        }
+    }
+
+    fn format_expected_duration(&self, expected_duration: &ExpectedDuration) -> String {
+        let message = match expected_duration.source {
+            ExpectedDurationSource::Declared => self.messages.expected_duration,
+            ExpectedDurationSource::HistoricalP95 => self.messages.historical_duration,
         };
+        fill(message, &[&time::format(expected_duration.duration)])
+    }
+
+    fn format_slow_test_case(&self, exec_info: &TestCaseExecInfo) -> String {
+        fill(
+            self.messages.took,
+            &[
+                &exec_info.duration().map(time::format).unwrap_or_default(),
+                &exec_info
+                    .expected_duration()
+                    .map(|expected| self.format_expected_duration(expected))
+                    .unwrap_or_default(),
+            ],
+        )
+    }
+
+    fn format_timeout(&self, exec_info: &TestCaseExecInfo, timeout: Duration) -> String {
+        let timed_out = fill(
+            self.messages.timed_out,
+            &[&time::format(timeout), &self.timeout_source(exec_info)],
+        );
+        match exec_info
+            .result()
+            .as_ref()
+            .ok()
+            .and_then(|output| output.killed_by)
+        {
+            Some(signal) => format!(
+                "{timed_out}, {}",
+                fill(self.messages.killed_by, &[&signal.to_string()])
+            ),
+            None => timed_out,
+        }
+    }
+
+    /// How long a test case took, how long it was expected to take and its timeout
+    fn format_timing(&self, exec_info: &TestCaseExecInfo) -> Option<String> {
+        let duration = exec_info.duration()?;
+        let mut timing = vec![fill(self.messages.ran_for, &[&time::format(duration)])];
+        if let Some(expected) = exec_info.expected_duration() {
+            timing.push(self.format_expected_duration(expected));
+        }
+        if let Some(timeout) = exec_info.timeout() {
+            timing.push(fill(
+                self.messages.timeout,
+                &[
+                    &time::format(timeout.duration),
+                    &self.timeout_source(exec_info),
+                ],
+            ));
+        }
+        Some(timing.join(", "))
+    }
+
+    fn timeout_source(&self, exec_info: &TestCaseExecInfo) -> &'static str {
+        match exec_info.timeout().map(|timeout| timeout.source) {
+            Some(TimeoutSource::FixtureCommandLine) => self.messages.command_line_fixture_timeout,
+            Some(TimeoutSource::TestSuiteFixture) => self.messages.test_suite_fixture_timeout,
+            Some(TimeoutSource::CommandLine) => self.messages.command_line_timeout,
+            Some(TimeoutSource::TestCase) => self.messages.test_case_timeout,
+            Some(TimeoutSource::TestFile) => self.messages.test_file_timeout,
+            Some(TimeoutSource::TestSuite) | None => self.messages.test_suite_timeout,
+        }
+    }
+
+    /// The counts of expected failures and unexpected passes to append to the statistics, if any
+    fn format_xfail_statistics(statistics: &Statistics, messages: &Catalog) -> String {
+        if statistics.xfailed + statistics.xpassed == 0 {
+            return String::new();
+        }
+        fill(
+            messages.statistics_xfail,
+            &[
+                &statistics.xfailed.to_string().yellow(),
+                &statistics.xpassed.to_string().yellow(),
+            ],
+        )
+    }
+
+    /// The count of test cases deselected by the filters of the run to append to the statistics,
+    /// if any
+    fn format_filtered_out_statistics(statistics: &Statistics, messages: &Catalog) -> String {
+        if statistics.filtered_out == 0 {
+            return String::new();
+        }
+        fill(
+            messages.statistics_filtered_out,
+            &[&statistics.filtered_out.to_string().dimmed()],
+        )
+    }
+
+    fn format_flaky_test_case(&self, exec_info: &TestCaseExecInfo) -> String {
+        fill(
+            self.messages.passed_on_attempt,
+            &[&exec_info.attempt(), &exec_info.max_attempts()],
+        )
+    }
+
+    /// Print the time spent running test cases on a target, including the failed attempts
+    fn print_target_duration(&self, exec_context: &ExecutionContext) {
+        writeln!(
+            self.console,
+            "  {}",
+            fill(
+                self.messages.target_duration,
+                &[
+                    &exec_context.target(),
+                    &time::format(exec_context.total_duration())
+                ]
+            )
+        );
+    }
+
+    /// List the slow test cases of a target, if any, in test suite order
+    fn print_slow_test_cases(&self, exec_context: &ExecutionContext) {
+        self.print_test_cases_where(
+            exec_context,
+            self.messages.slow_test_cases,
+            TestCaseExecInfo::is_slow,
+            |exec_info| self.format_slow_test_case(exec_info),
+        );
+    }
+
+    /// List the test cases of a target which passed on a retry, if any, in test suite order
+    fn print_flaky_test_cases(&self, exec_context: &ExecutionContext) {
+        self.print_test_cases_where(
+            exec_context,
+            self.messages.flaky_test_cases,
+            TestCaseExecInfo::is_flaky,
+            |exec_info| self.format_flaky_test_case(exec_info),
+        );
+    }
+
+    /// List the test cases of a target matching a predicate under a header, if any
+    fn print_test_cases_where(
+        &self,
+        exec_context: &ExecutionContext,
+        header: &str,
+        predicate: impl Fn(&TestCaseExecInfo) -> bool,
+        describe: impl Fn(&TestCaseExecInfo) -> String,
+    ) {
+        let mut header_printed = false;
+        for (tc, exec_info) in exec_context.iter_exec_info() {
+            if !predicate(exec_info) {
+                continue;
+            }
+            if !header_printed {
+                writeln!(
+                    self.console,
+                    "  {}",
+                    fill(
+                        self.messages.on_target,
+                        &[&header.yellow(), &exec_context.target().white()]
+                    )
+                );
+                header_printed = true;
+            }
+            writeln!(
+                self.console,
+                "    {} {}",
+                tc.id().to_string().yellow(),
+                describe(exec_info)
+            );
+        }
     }
 
     fn print_summary_header(&self, test_suite: &TestSuite) {
-        println!();
-        println!(
+        writeln!(self.console);
+        writeln!(
+            self.console,
             "{}",
-            format!(
-                "Test suite `{}` execution summary",
-                test_suite.path().display()
+            fill(
+                self.messages.summary_header,
+                &[&test_suite.path().display()]
             )
             .bright_white()
         );
+        if !self.run_metadata.is_empty() {
+            let run_metadata = self
+                .run_metadata
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>();
+            writeln!(
+                self.console,
+                "  {}",
+                fill(self.messages.run_metadata, &[&run_metadata.join(", ")])
+            );
+        }
     }
 }
 
 impl Reporter for HumanFriendlyReporter {
     fn report_target_list(&self, test_suite: &TestSuite) {
-        println!(
+        writeln!(
+            self.console,
             "{}",
-            format!(
-                "Targets supported by test suite `{}`",
-                test_suite.path().display()
+            fill(
+                self.messages.targets_supported,
+                &[&test_suite.path().display()]
             )
             .bright_white()
         );
         for target in &test_suite.config().targets {
-            println!("  {}", target.white());
+            writeln!(self.console, "  {}", target.name.white());
+            for (property, value) in &target.properties {
+                writeln!(
+                    self.console,
+                    "    {}",
+                    format!("{property}: {value}").dimmed()
+                );
+            }
+            for (name, value) in &target.env {
+                writeln!(self.console, "    {}", format!("{name}={value}").dimmed());
+            }
         }
-        println!("");
+        writeln!(self.console);
     }
 
-    fn report_test_list(&self, test_suite: &TestSuite) {
-        println!(
+    fn report_test_list(&self, test_suite: &TestSuite, include_fixtures: bool) {
+        writeln!(
+            self.console,
             "{}",
-            format!(
-                "Tests defined in test suite `{}`",
-                test_suite.path().display()
+            fill(self.messages.tests_defined, &[&test_suite.path().display()]).bright_white()
+        );
+        let list_fixture = |tc: &Option<TestCase>, kind: &str, covered: &str| {
+            if let (true, Some(tc)) = (include_fixtures, tc) {
+                writeln!(
+                    self.console,
+                    "  {} {}",
+                    tc.id().to_string().white(),
+                    fill(self.messages.fixture_covers, &[&kind, &covered]).dimmed()
+                );
+            }
+        };
+        let all_files = fill(
+            self.messages.all_test_files,
+            &[&test_suite.test_files().len()],
+        );
+        list_fixture(
+            &test_suite.fixture().setup_test_case,
+            self.messages.suite_setup,
+            &all_files,
+        );
+        for test_file in test_suite.test_files() {
+            let file = format!("`{}`", test_file.path.display());
+            list_fixture(&test_file.setup_test_case, self.messages.file_setup, &file);
+            for tc in &test_file.test_cases {
+                let tags = &tc.metadata().tags;
+                if tags.is_empty() {
+                    writeln!(self.console, "  {}", tc.id().to_string().white());
+                } else {
+                    writeln!(
+                        self.console,
+                        "  {} {}",
+                        tc.id().to_string().white(),
+                        format!("[{}]", tags.join(", ")).dimmed()
+                    );
+                }
+            }
+            list_fixture(
+                &test_file.teardown_test_case,
+                self.messages.file_teardown,
+                &file,
+            );
+        }
+        list_fixture(
+            &test_suite.fixture().teardown_test_case,
+            self.messages.suite_teardown,
+            &all_files,
+        );
+        writeln!(self.console);
+    }
+
+    fn report_test_suite_out_dir(&self, _test_suite: &TestSuite, out_dir: &Path) {
+        self.info(&fill(
+            self.messages.test_suite_out_dir,
+            &[&out_dir.display()],
+        ));
+    }
+
+    fn report_dry_run_estimate(
+        &self,
+        test_suite: &TestSuite,
+        estimates: &[TargetEstimate],
+        total: Duration,
+    ) {
+        writeln!(self.console);
+        for estimate in estimates {
+            writeln!(
+                self.console,
+                "{}",
+                fill(
+                    self.messages.target_estimate,
+                    &[
+                        &estimate.target,
+                        &time::format(estimate.duration),
+                        &estimate.test_cases
+                    ]
+                )
+            );
+            if !estimate.unknown.is_empty() {
+                let unknown = estimate
+                    .unknown
+                    .iter()
+                    .map(|test_case_id| format!("`{test_case_id}`"))
+                    .collect::<Vec<_>>();
+                self.warning_detailed(
+                    &fill(
+                        self.messages.unknown_durations,
+                        &[&estimate.unknown.len(), &estimate.target],
+                    ),
+                    &unknown.join("\n"),
+                );
+            }
+        }
+        writeln!(
+            self.console,
+            "{}",
+            fill(
+                self.messages.test_suite_estimate,
+                &[&test_suite.path().display(), &time::format(total)]
             )
-            .bright_white()
         );
-        Visitor::new(&test_suite).visit_all_ok(|tc, _| {
-            println!("  {}", tc.id().white());
-        });
-        println!("");
     }
 
-    fn report_test_suite_time(&self) {}
+    fn report_test_suite_time(&self, test_suite: &TestSuite, duration: Duration) {
+        writeln!(self.console);
+        writeln!(
+            self.console,
+            "{}",
+            fill(
+                self.messages.test_suite_duration,
+                &[&test_suite.path().display(), &time::format(duration)]
+            )
+        );
+    }
+
+    fn report_targets_execution_started(
+        &self,
+        test_suite: &TestSuite,
+        exec_contexts: &[ExecutionContext],
+    ) {
+        self.update_progress(|progress| {
+            for exec_context in exec_contexts {
+                for (test_case, _) in exec_context.iter_exec_info() {
+                    if !test_suite.is_fixture(test_case) {
+                        progress.tests.insert(test_case.id());
+                        progress.total += 1;
+                    }
+                }
+            }
+        });
+    }
 
     fn report_test_suite_execution_summary(
         &self,
         test_suite: &TestSuite,
         exec_contexts: &[ExecutionContext],
     ) {
+        // UNWRAP: the lock is never held while panicking
+        *self.progress.lock().unwrap() = Progress::default();
+        self.console.clear_status();
         if self.matrix_summary {
             self.print_summary_header(test_suite);
-            TestSuiteSummaryPrettyPrinter::new(test_suite, exec_contexts).print_matrix_summary();
+            let mut printer = TestSuiteSummaryPrettyPrinter::new(
+                &self.console,
+                test_suite,
+                exec_contexts,
+                self.messages,
+            );
+            if self.plain {
+                printer.print_plain_matrix_summary();
+            } else {
+                printer.print_matrix_summary();
+            }
+            for exec_context in exec_contexts {
+                self.print_target_duration(exec_context);
+                self.print_slow_test_cases(exec_context);
+                self.print_flaky_test_cases(exec_context);
+            }
         } else {
             for exec_context in exec_contexts {
                 self.print_summary_header(test_suite);
-                println!("  Target: {}", exec_context.target().white());
+                writeln!(
+                    self.console,
+                    "  {}",
+                    fill(
+                        self.messages.target,
+                        &[
+                            &exec_context.target().white(),
+                            &if exec_context.is_late_joined() {
+                                self.messages.joined_late
+                            } else {
+                                ""
+                            }
+                        ]
+                    )
+                );
                 let statistics = exec_context.get_statistics();
-                println!(
-                    "  Statistics: {} passed, {} failed, {} runner failed, {} skipped",
-                    statistics.passed.to_string().green(),
-                    statistics.failed.to_string().red(),
-                    statistics.runner_failed.to_string().red(),
-                    statistics.skipped.to_string().dimmed(),
+                writeln!(
+                    self.console,
+                    "  {}{}{}",
+                    fill(
+                        self.messages.statistics,
+                        &[
+                            &statistics.passed.to_string().green(),
+                            &statistics.failed.to_string().red(),
+                            &statistics.runner_failed.to_string().red(),
+                            &statistics.skipped.to_string().dimmed(),
+                        ]
+                    ),
+                    Self::format_xfail_statistics(&statistics, self.messages),
+                    Self::format_filtered_out_statistics(&statistics, self.messages)
                 );
+                self.print_target_duration(exec_context);
+                self.print_slow_test_cases(exec_context);
+                self.print_flaky_test_cases(exec_context);
             }
         }
     }
 
-    fn report_total_time(&self) {}
+    fn report_comparison(&self, _test_suite: &TestSuite, comparison: &Comparison) {
+        let count = |kind| comparison.changes_of_kind(kind).count();
+        writeln!(self.console);
+        writeln!(
+            self.console,
+            "{}",
+            fill(
+                self.messages.comparison,
+                &[
+                    &comparison.previous_run.display(),
+                    &count(ChangeKind::NewFailure),
+                    &count(ChangeKind::Fixed),
+                    &count(ChangeKind::NewlySkipped),
+                ]
+            )
+            .bright_white()
+        );
+        for kind in [
+            ChangeKind::NewFailure,
+            ChangeKind::Fixed,
+            ChangeKind::NewlySkipped,
+        ] {
+            let label = match kind {
+                ChangeKind::NewFailure => self.messages.new_failure.red(),
+                ChangeKind::Fixed => self.messages.fixed.green(),
+                ChangeKind::NewlySkipped => self.messages.newly_skipped.dimmed(),
+            };
+            for change in comparison.changes_of_kind(kind) {
+                let previously = match &change.previous_status {
+                    Some(status) => fill(self.messages.previously, &[status]),
+                    None => self.messages.not_run_previously.to_string(),
+                };
+                writeln!(
+                    self.console,
+                    "  {}",
+                    fill(
+                        self.messages.change,
+                        &[
+                            &label,
+                            &change.test_case_id.to_string().yellow(),
+                            &change.target.white(),
+                            &previously
+                        ]
+                    )
+                );
+            }
+        }
+    }
+
+    fn report_total_time(&self, duration: Duration) {
+        writeln!(self.console);
+        writeln!(
+            self.console,
+            "{}",
+            fill(self.messages.total_duration, &[&time::format(duration)])
+        );
+    }
+
+    fn report_runner_health(&self, health: &RunnerHealth) {
+        let line = fill(
+            self.messages.runner_health,
+            &[&health.warnings, &health.errors],
+        );
+        let line = if health.errors > 0 {
+            line.red()
+        } else if health.warnings > 0 {
+            line.yellow()
+        } else {
+            line.green()
+        };
+        writeln!(self.console, "{line}");
+    }
 
     fn notice_detailed(&self, message: &str, details: &str) {
+        if self.verbosity == Verbosity::Quiet {
+            return;
+        }
         self.print_with_details("".normal(), message, details)
     }
 
     fn info_detailed(&self, message: &str, details: &str) {
-        self.print_with_details("Info: ".cyan(), message, details)
+        if self.verbosity == Verbosity::Quiet {
+            return;
+        }
+        self.print_with_details(self.messages.info.cyan(), message, details)
     }
 
     fn warning_detailed(&self, message: &str, details: &str) {
-        self.print_with_details("Warning: ".yellow(), message, details)
+        self.print_with_details(self.messages.warning.yellow(), message, details)
     }
 
     fn error_detailed(&self, message: &str, details: &str) {
-        self.print_with_details("Error: ".red(), message, details)
+        self.print_with_details(self.messages.error.red(), message, details)
     }
 
     fn error_from(&self, error: &Error) {
-        let (message, details): (&str, &str) = match &error {
-            Error::SuiteConfigIo(error) => (&error.to_string(), &error.source.to_string()),
-            Error::InvalidSuiteConfig(error) => (&error.to_string(), &error.source.to_string()),
-            Error::TestDriverIo(error) => (&error.to_string(), &error.source.to_string()),
-            Error::TestFileExec(error) => (&error.to_string(), &error.details),
-            _ => (&error.to_string(), ""),
-        };
+        let (message, details) = Self::error_message(error);
         self.error_detailed(&message, &details);
     }
 
@@ -142,70 +751,352 @@ impl Reporter for HumanFriendlyReporter {
         target: &str,
         _exec_info: &TestCaseExecInfo,
     ) {
-        print!(
-            "Running test case `{}` for target `{}`",
-            test_case.id(),
-            &target
+        self.update_progress(|progress| {
+            *progress.running.entry(target.to_string()).or_default() += 1;
+        });
+        // Only the failures are printed, once known
+        if self.verbosity == Verbosity::Quiet {
+            return;
+        }
+        write!(
+            self.console.group(target),
+            "{}",
+            fill(self.messages.running_test_case, &[&test_case.id(), &target])
+        );
+        // The result is printed on its own line after the streamed output
+        if self.show_output == ShowOutput::All {
+            writeln!(self.console.group(target));
+        }
+    }
+
+    fn report_test_case_output(&self, _test_case: &TestCase, target: &str, line: &str) {
+        // The output of targets run concurrently would be mixed up without their names
+        write!(
+            self.console.group(target),
+            "  {}{}",
+            format!("{target} ").dimmed(),
+            self.format_output_line(line)
         );
     }
     fn report_test_case_execution_result(
         &self,
-        _test_case: &TestCase,
-        _target: &str,
+        test_case: &TestCase,
+        target: &str,
         exec_info: &TestCaseExecInfo,
     ) {
-        println!(
-            " {}",
+        self.update_progress(|progress| {
+            if let Some(running) = progress.running.get_mut(target) {
+                *running -= 1;
+                if *running == 0 {
+                    progress.running.remove(target);
+                }
+            }
+            if progress.tests.contains(&test_case.id()) {
+                progress.done += 1;
+                if matches!(
+                    exec_info.result(),
+                    Err(_)
+                        | Ok(RunTestOutput {
+                            test_case_status: TestCaseStatus::Failed(_),
+                            ..
+                        })
+                ) {
+                    progress.failed += 1;
+                }
+            }
+        });
+        let failed = matches!(
+            exec_info
+                .result()
+                .as_ref()
+                .map(|output| &output.test_case_status),
+            Err(_) | Ok(TestCaseStatus::Failed(_))
+        );
+        if self.verbosity == Verbosity::Quiet && !failed {
+            return;
+        }
+        let console = self.console.group(target);
+        let messages = self.messages;
+        write!(console, "{}", self.result_prefix(test_case, target));
+        // Only the test cases which actually ran took some time worth showing
+        let duration = match exec_info
+            .result()
+            .as_ref()
+            .map(|output| &output.test_case_status)
+        {
+            Err(_)
+            | Ok(
+                TestCaseStatus::Passed
+                | TestCaseStatus::Failed(_)
+                | TestCaseStatus::XFailed(_)
+                | TestCaseStatus::XPassed(_),
+            ) => exec_info
+                .duration()
+                .map(|duration| {
+                    format!(" [{}]", time::format(duration))
+                        .dimmed()
+                        .to_string()
+                })
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+        writeln!(
+            console,
+            " {}{}",
             match exec_info
                 .result()
                 .as_ref()
                 .map(|output| &output.test_case_status)
             {
-                Err(_) => "RUNNER_FAILED".red().to_string(),
-                Ok(TestCaseStatus::Failed) => "FAILED".red().to_string(),
-                Ok(TestCaseStatus::Passed) => "PASSED".green().to_string(),
-                Ok(TestCaseStatus::Skipped(reason)) =>
-                    format!("{} (reason: {:?})", "SKIPPED".dimmed(), reason),
-                Ok(TestCaseStatus::DryRun) => "DRYRUN".dimmed().to_string(),
-                Ok(TestCaseStatus::NotRun) => "NOTRUN".dimmed().to_string(),
-                Ok(TestCaseStatus::Running) => "RUNNING".dimmed().to_string(),
-            }
+                Err(_) => messages.runner_failed.red().to_string(),
+                Ok(TestCaseStatus::Failed(FailReason::TestCaseFailure)) =>
+                    messages.failed.red().to_string(),
+                Ok(TestCaseStatus::Failed(FailReason::Timeout(timeout))) => format!(
+                    "{} ({})",
+                    messages.failed.red(),
+                    self.format_timeout(exec_info, *timeout)
+                ),
+                Ok(TestCaseStatus::Failed(reason)) => format!(
+                    "{} ({}: {:?})",
+                    messages.failed.red(),
+                    messages.reason,
+                    reason
+                ),
+                Ok(TestCaseStatus::Passed) if exec_info.is_flaky() => format!(
+                    "{} ({}: {})",
+                    messages.passed.green(),
+                    messages.flaky.yellow(),
+                    self.format_flaky_test_case(exec_info)
+                ),
+                Ok(TestCaseStatus::Passed) => messages.passed.green().to_string(),
+                Ok(TestCaseStatus::Skipped(reason)) => format!(
+                    "{} ({}: {:?})",
+                    messages.skipped.dimmed(),
+                    messages.reason,
+                    reason
+                ),
+                Ok(TestCaseStatus::XFailed(reason)) => format!(
+                    "{} ({}: {})",
+                    messages.xfailed.yellow(),
+                    messages.reason,
+                    reason
+                ),
+                Ok(TestCaseStatus::XPassed(reason)) => format!(
+                    "{} ({}: {})",
+                    messages.xpassed.yellow(),
+                    messages.reason,
+                    reason
+                ),
+                Ok(TestCaseStatus::DryRun) => messages.dry_run.dimmed().to_string(),
+                Ok(TestCaseStatus::NotRun) => messages.not_run.dimmed().to_string(),
+                Ok(TestCaseStatus::Running) => messages.running.dimmed().to_string(),
+            },
+            duration
         );
-        match exec_info
+        if let Ok(Some(driver_output)) = exec_info
             .result()
             .as_ref()
             .map(|output| &output.driver_output)
         {
-            Ok(Some(driver_output)) => {
-                let driver_output_str = format!("{driver_output}");
-                if !driver_output_str.is_empty() {
-                    self.warning(&format!("{driver_output}"))
-                }
+            let driver_output_str = format!("{driver_output}");
+            if !driver_output_str.is_empty() {
+                write!(
+                    console,
+                    "{}",
+                    self.format_with_details(messages.warning.yellow(), &driver_output_str, "")
+                );
             }
-            _ => (),
+        }
+        if let Ok(Some(message)) = exec_info.result().as_ref().map(|output| &output.message) {
+            writeln!(console, "  {} {}", messages.message.dimmed(), message);
+        }
+        if let Err(error) = exec_info.result() {
+            let (message, details) = Self::error_message(error);
+            write!(
+                console,
+                "{}",
+                self.format_with_details(messages.error.red(), &message, &details)
+            );
+        }
+        let verbose = self.verbosity == Verbosity::Verbose;
+        if let Some(timing) = self.format_timing(exec_info).filter(|_| verbose) {
+            writeln!(console, "  {} {}", messages.timing.dimmed(), timing);
+        }
+        if let Some(log_file) = exec_info.main_log_file().filter(|_| failed || verbose) {
+            writeln!(
+                console,
+                "  {} {}",
+                messages.log.dimmed(),
+                log_file.location()
+            );
+        }
+        let artifacts = exec_info
+            .log_files()
+            .iter()
+            .filter(|log_file| (failed || verbose) && log_file.kind == "artifact");
+        for artifact in artifacts {
+            writeln!(
+                console,
+                "  {} {}",
+                messages.artifact.dimmed(),
+                artifact.location()
+            );
+        }
+        if !exec_info.telemetry().is_empty() {
+            let values = exec_info
+                .telemetry()
+                .iter()
+                .map(|(name, summary)| {
+                    fill(
+                        messages.telemetry_value,
+                        &[
+                            &name,
+                            &format_value(summary.max),
+                            &format_value(summary.avg),
+                        ],
+                    )
+                })
+                .collect::<Vec<_>>();
+            writeln!(
+                console,
+                "  {} {}",
+                messages.telemetry.dimmed(),
+                values.join("; ")
+            );
+        }
+        for artifact in exec_info.failure_artifacts() {
+            writeln!(
+                console,
+                "  {} {}",
+                messages.post_mortem.dimmed(),
+                artifact.location()
+            );
+        }
+        if exec_info.is_slow() {
+            let message = fill(
+                messages.slow_test_case,
+                &[
+                    &test_case.id(),
+                    &target,
+                    &self.format_slow_test_case(exec_info),
+                ],
+            );
+            write!(
+                console,
+                "{}",
+                self.format_with_details(messages.warning.yellow(), &message, "")
+            );
         }
     }
+
+    fn report_test_case_failure_output(
+        &self,
+        _test_case: &TestCase,
+        target: &str,
+        output: &CapturedOutput,
+    ) {
+        // Written at once so that the output of targets run concurrently does not get in between
+        let mut text = String::new();
+        if output.omitted > 0 {
+            let header = fill(
+                self.messages.output_tail,
+                &[&output.lines.len(), &output.omitted],
+            );
+            text += &format!("  {}\n", header.dimmed());
+        }
+        for line in &output.lines {
+            text += &format!("  {}", self.format_output_line(line));
+        }
+        write!(self.console.group(target), "{text}");
+    }
+
+    fn report_test_case_execution_retried(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        exec_info: &TestCaseExecInfo,
+    ) {
+        let console = self.console.group(target);
+        let messages = self.messages;
+        writeln!(
+            console,
+            "{} {} ({})",
+            self.result_prefix(test_case, target),
+            messages.failed.red(),
+            fill(
+                messages.retrying,
+                &[&exec_info.attempt(), &exec_info.max_attempts()]
+            )
+            .yellow()
+        );
+        if let Some(log_file) = exec_info
+            .failed_attempts()
+            .last()
+            .and_then(|attempt| attempt.log_files.first())
+        {
+            writeln!(
+                console,
+                "  {} {}",
+                messages.log.dimmed(),
+                log_file.path.display()
+            );
+        }
+    }
+
+    fn report_target_progress(&self, target: &str, progress: &TargetProgress) {
+        self.update_progress(|tracked| {
+            if progress.done < progress.total {
+                tracked.targets.insert(target.to_string(), *progress);
+            } else {
+                tracked.targets.remove(target);
+            }
+        });
+    }
+
+    fn report_target_execution_finished(&self, target: &str) {
+        self.console.end_group(target);
+    }
+}
+
+/// A sampled value with at most two decimals, e.g. `61.5` or `0.83`
+fn format_value(value: f64) -> String {
+    let value = format!("{value:.2}");
+    value
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
 }
 
 const CHAR_PASS: &str = "V";
 const CHAR_FAIL: &str = "X";
 const CHAR_RFAIL: &str = "O";
 const CHAR_SKIP: &str = "-";
+const CHAR_XFAIL: &str = "x";
+const CHAR_XPASS: &str = "!";
 
 struct TestSuiteSummaryPrettyPrinter<'a> {
+    console: &'a Console,
     test_suite: &'a TestSuite,
     exec_contexts: &'a [ExecutionContext],
+    messages: &'static Catalog,
     max_row_width: usize,
     max_column_width: usize,
 }
 
 impl<'a> TestSuiteSummaryPrettyPrinter<'a> {
-    fn new(test_suite: &'a TestSuite, exec_contexts: &'a [ExecutionContext]) -> Self {
+    fn new(
+        console: &'a Console,
+        test_suite: &'a TestSuite,
+        exec_contexts: &'a [ExecutionContext],
+        messages: &'static Catalog,
+    ) -> Self {
         Self {
+            console,
             test_suite,
             exec_contexts,
+            messages,
             max_row_width: Self::max_row_width(test_suite, exec_contexts),
-            max_column_width: Self::max_column_width(test_suite, exec_contexts),
+            max_column_width: Self::max_column_width(exec_contexts, messages),
         }
     }
 
@@ -225,42 +1116,91 @@ impl<'a> TestSuiteSummaryPrettyPrinter<'a> {
         CHAR_SKIP.bright_black()
     }
 
+    fn char_xfail() -> ColoredString {
+        CHAR_XFAIL.yellow()
+    }
+
+    fn char_xpass() -> ColoredString {
+        CHAR_XPASS.yellow()
+    }
+
+    /// Whether some test cases were expected to fail, in which case the summary has columns for
+    /// them
+    fn has_xfail(&self) -> bool {
+        self.exec_contexts.iter().any(|exec_context| {
+            let stats = exec_context.get_statistics();
+            stats.xfailed + stats.xpassed > 0
+        })
+    }
+
     fn max_row_width(test_suite: &TestSuite, _exec_contexts: &[ExecutionContext]) -> usize {
         let mut row_width = 0;
         Visitor::new(test_suite)
-            .visit_all_ok(|tc, _| row_width = std::cmp::max(row_width, tc.id().len()));
+            .visit_all_ok(|tc, _| row_width = std::cmp::max(row_width, tc.id().to_string().len()));
         row_width
     }
 
-    fn max_column_width(_test_suite: &TestSuite, exec_contexts: &[ExecutionContext]) -> usize {
+    fn max_column_width(exec_contexts: &[ExecutionContext], messages: &Catalog) -> usize {
         let mut column_width = 0;
         for exec_context in exec_contexts {
-            column_width = std::cmp::max(column_width, exec_context.target().len());
+            column_width = std::cmp::max(
+                column_width,
+                Self::target_label(exec_context, messages).chars().count(),
+            );
         }
         column_width
     }
 
-    fn pad(width: usize) {
-        print!("{:width$}", "");
+    fn target_label(exec_context: &ExecutionContext, messages: &Catalog) -> String {
+        if exec_context.is_late_joined() {
+            fill(messages.late, &[&exec_context.target()])
+        } else {
+            exec_context.target().to_string()
+        }
+    }
+
+    fn pad(&self, width: usize) {
+        write!(self.console, "{:width$}", "");
     }
 
     fn print_legend(&mut self) {
-        Self::pad(self.max_row_width + 1);
-        println!(
-            "{}: passed    {}: skipped",
+        self.pad(self.max_row_width + 1);
+        writeln!(
+            self.console,
+            "{}: {}    {}: {}",
             Self::char_pass(),
+            self.messages.legend_passed,
             Self::char_skip(),
+            self.messages.legend_skipped,
         );
-        Self::pad(self.max_row_width + 1);
-        println!(
-            "{}: failed    {}: runner failed",
+        self.pad(self.max_row_width + 1);
+        writeln!(
+            self.console,
+            "{}: {}    {}: {}",
             Self::char_fail(),
+            self.messages.legend_failed,
             Self::char_rfail(),
+            self.messages.legend_runner_failed,
         );
+        if self.has_xfail() {
+            self.pad(self.max_row_width + 1);
+            writeln!(
+                self.console,
+                "{}: {}    {}: {}",
+                Self::char_xfail(),
+                self.messages.legend_xfailed,
+                Self::char_xpass(),
+                self.messages.legend_xpassed,
+            );
+        }
     }
 
     fn print_single_statistic(&self, header: &ColoredString, stat: usize, max_stat_len: usize) {
-        print!("{header}: {stat:>width$}  ", width = max_stat_len)
+        write!(
+            self.console,
+            "{header}: {stat:>width$}  ",
+            width = max_stat_len
+        )
     }
 
     fn print_statistics(&self, exec_context: &ExecutionContext) {
@@ -270,60 +1210,165 @@ impl<'a> TestSuiteSummaryPrettyPrinter<'a> {
         self.print_single_statistic(&Self::char_fail(), stats.failed, max_stat_len);
         self.print_single_statistic(&Self::char_rfail(), stats.runner_failed, max_stat_len);
         self.print_single_statistic(&Self::char_skip(), stats.skipped, max_stat_len);
-        println!("/ {}", stats.total());
+        if self.has_xfail() {
+            self.print_single_statistic(&Self::char_xfail(), stats.xfailed, max_stat_len);
+            self.print_single_statistic(&Self::char_xpass(), stats.xpassed, max_stat_len);
+        }
+        writeln!(self.console, "/ {}", stats.total());
     }
 
     fn print_target_summary(&self) {
         let mut depth = 0;
         for exec_context in self.exec_contexts {
-            Self::pad(self.max_row_width + 1);
+            self.pad(self.max_row_width + 1);
             for _ in 0..depth {
-                print!("│ ")
+                write!(self.console, "│ ")
             }
-            print!("┌─ {}", exec_context.target());
-            Self::pad(
-                self.max_column_width - exec_context.target().len()
+            let target_label = Self::target_label(exec_context, self.messages);
+            write!(self.console, "┌─ {}", target_label);
+            self.pad(
+                self.max_column_width - target_label.chars().count()
                     + (self.exec_contexts.len() * 2)
                     - (depth * 2),
             );
             self.print_statistics(exec_context);
             depth += 1
         }
-        Self::pad(self.max_row_width + 1);
+        self.pad(self.max_row_width + 1);
         for _ in 0..depth {
-            print!("╵ ");
+            write!(self.console, "╵ ");
         }
-        println!();
+        writeln!(self.console);
     }
 
     fn print_test_cases_result(&self) {
         Visitor::new(self.test_suite).visit_all_ok(|tc, _| {
-            print!("{} ", tc.id());
-            Self::pad(self.max_row_width - tc.id().len());
+            let id = tc.id().to_string();
+            write!(self.console, "{} ", id);
+            self.pad(self.max_row_width - id.len());
             for exec_context in self.exec_contexts {
-                let exec_info = exec_context.exec_info().get(tc).unwrap();
+                let exec_info = exec_context.exec_info(tc).unwrap();
                 let c = match exec_info
                     .result()
                     .as_ref()
                     .map(|output| &output.test_case_status)
                 {
                     Err(_) => Self::char_rfail().to_string(),
-                    Ok(TestCaseStatus::Failed) => Self::char_fail().to_string(),
+                    Ok(TestCaseStatus::Failed(_)) => Self::char_fail().to_string(),
                     Ok(TestCaseStatus::Passed) => Self::char_pass().to_string(),
                     Ok(TestCaseStatus::Skipped(_)) => Self::char_skip().to_string(),
                     Ok(TestCaseStatus::DryRun) => Self::char_skip().to_string(),
+                    Ok(TestCaseStatus::XFailed(_)) => Self::char_xfail().to_string(),
+                    Ok(TestCaseStatus::XPassed(_)) => Self::char_xpass().to_string(),
                     _ => panic!("aie"), // TODO
                 };
-                print!("{} ", c);
+                write!(self.console, "{} ", c);
             }
-            println!();
+            writeln!(self.console);
+        });
+    }
+
+    fn status_word(&self, exec_info: &TestCaseExecInfo) -> &'static str {
+        match exec_info
+            .result()
+            .as_ref()
+            .map(|output| &output.test_case_status)
+        {
+            Err(_) => self.messages.runner_failed,
+            Ok(TestCaseStatus::Failed(_)) => self.messages.failed,
+            Ok(TestCaseStatus::Passed) => self.messages.passed,
+            Ok(TestCaseStatus::Skipped(_)) => self.messages.skipped,
+            Ok(TestCaseStatus::XFailed(_)) => self.messages.xfailed,
+            Ok(TestCaseStatus::XPassed(_)) => self.messages.xpassed,
+            Ok(TestCaseStatus::DryRun) => self.messages.dry_run,
+            Ok(TestCaseStatus::NotRun) => self.messages.not_run,
+            Ok(TestCaseStatus::Running) => self.messages.running,
+        }
+    }
+
+    /// Print the matrix summary as a table of status words, with one column per target
+    fn print_plain_matrix_summary(&self) {
+        let messages = self.messages;
+        let status_width = [
+            messages.passed,
+            messages.failed,
+            messages.runner_failed,
+            messages.skipped,
+            messages.xfailed,
+            messages.xpassed,
+            messages.dry_run,
+            messages.not_run,
+            messages.running,
+        ]
+        .iter()
+        .map(|word| word.chars().count())
+        .max()
+        .unwrap_or(0);
+        let column_width = std::cmp::max(self.max_column_width, status_width);
+        let row_width = self.max_row_width;
+
+        // Cells are padded with spaces only, and lines do not end with spaces
+        let print_row = |header: &str, cells: &mut dyn Iterator<Item = String>| {
+            let mut row = format!("{header:row_width$}");
+            for cell in cells {
+                row += &format!("  {cell:column_width$}");
+            }
+            writeln!(self.console, "{}", row.trim_end());
+        };
+
+        writeln!(self.console);
+        for exec_context in self.exec_contexts {
+            let stats = exec_context.get_statistics();
+            writeln!(
+                self.console,
+                "{}",
+                fill(
+                    messages.target,
+                    &[&Self::target_label(exec_context, messages), &""]
+                )
+            );
+            writeln!(
+                self.console,
+                "  {}{}{}",
+                fill(
+                    messages.statistics,
+                    &[
+                        &stats.passed,
+                        &stats.failed,
+                        &stats.runner_failed,
+                        &stats.skipped
+                    ]
+                ),
+                HumanFriendlyReporter::format_xfail_statistics(&stats, messages),
+                HumanFriendlyReporter::format_filtered_out_statistics(&stats, messages)
+            );
+        }
+        writeln!(self.console);
+        print_row(
+            "",
+            &mut self
+                .exec_contexts
+                .iter()
+                .map(|exec_context| Self::target_label(exec_context, messages)),
+        );
+        Visitor::new(self.test_suite).visit_all_ok(|tc, _| {
+            print_row(
+                &tc.id().to_string(),
+                &mut self.exec_contexts.iter().map(|exec_context| {
+                    exec_context
+                        .exec_info(tc)
+                        .map(|exec_info| self.status_word(exec_info))
+                        .unwrap_or(messages.not_run)
+                        .to_string()
+                }),
+            );
         });
     }
 
     fn print_matrix_summary(&mut self) {
-        println!();
+        writeln!(self.console);
         self.print_legend();
-        println!();
+        writeln!(self.console);
         self.print_target_summary();
         self.print_test_cases_result();
     }
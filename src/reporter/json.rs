@@ -1,3 +1,177 @@
+use crate::error::Error;
 use crate::reporter::Reporter;
+use crate::reporter::human_friendly::HumanFriendlyReporter;
+use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
+use crate::test_suite::config::TargetConfig;
+use crate::test_suite::id::TestCaseId;
+use crate::test_suite::{TestCase, TestSuite};
 
-pub(crate) struct JsonReporter;
+use serde::Serialize;
+
+use std::path::Path;
+use std::time::Duration;
+
+/// Print the lists of targets and test cases as JSON on the standard output, one object per line
+/// and per test suite, for tools and IDE integrations
+/// Warnings and errors are printed on the standard error so that the output stays parsable, the
+/// other messages and the run events are ignored.
+#[derive(Default)]
+pub struct JsonReporter;
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TestSuiteJson<'a> {
+    name: &'a str,
+    path: &'a Path,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TargetListJson<'a> {
+    test_suite: TestSuiteJson<'a>,
+    targets: Vec<&'a str>,
+    /// The targets with their environment variables and properties
+    target_configs: &'a [TargetConfig],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TestListJson<'a> {
+    test_suite: TestSuiteJson<'a>,
+    test_cases: Vec<TestCaseJson<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct TestCaseJson<'a> {
+    id: TestCaseId,
+    file: &'a Path,
+    name: &'a str,
+    tags: &'a [String],
+    /// Options of the shell running the test case, for the test drivers using one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell_options: Option<&'a [String]>,
+    /// `test` for test cases, or the kind of fixture (e.g. `file-setup`)
+    kind: &'static str,
+}
+
+impl JsonReporter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn print(value: &impl Serialize) {
+        // UNWRAP: the lists only contain strings and paths
+        println!("{}", serde_json::to_string(value).unwrap());
+    }
+}
+
+impl<'a> TestSuiteJson<'a> {
+    fn new(test_suite: &'a TestSuite) -> Self {
+        Self {
+            name: &test_suite.config().name,
+            path: test_suite.path(),
+        }
+    }
+}
+
+impl<'a> TestCaseJson<'a> {
+    fn new(test_case: &'a TestCase, kind: &'static str) -> Self {
+        Self {
+            id: test_case.id(),
+            file: test_case.path(),
+            name: test_case.name(),
+            tags: &test_case.metadata().tags,
+            shell_options: test_case.metadata().shell_options.as_deref(),
+            kind,
+        }
+    }
+
+    /// The fixture test case of the given kind, if any and if fixtures are `listed`
+    fn fixture(fixture: &'a Option<TestCase>, kind: &'static str, listed: bool) -> Option<Self> {
+        fixture
+            .as_ref()
+            .filter(|_| listed)
+            .map(|tc| Self::new(tc, kind))
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn notice_detailed(&self, _message: &str, _details: &str) {}
+    fn info_detailed(&self, _message: &str, _details: &str) {}
+
+    fn warning_detailed(&self, message: &str, details: &str) {
+        eprintln!("Warning: {message}");
+        if !details.is_empty() {
+            eprintln!("  {details}");
+        }
+    }
+
+    fn error_detailed(&self, message: &str, details: &str) {
+        eprintln!("Error: {message}");
+        if !details.is_empty() {
+            eprintln!("  {details}");
+        }
+    }
+
+    fn error_from(&self, error: &Error) {
+        let (message, details) = HumanFriendlyReporter::error_message(error);
+        self.error_detailed(&message, &details);
+    }
+
+    fn report_target_list(&self, test_suite: &TestSuite) {
+        Self::print(&TargetListJson {
+            test_suite: TestSuiteJson::new(test_suite),
+            targets: test_suite
+                .config()
+                .targets
+                .iter()
+                .map(|target| target.name.as_str())
+                .collect(),
+            target_configs: &test_suite.config().targets,
+        });
+    }
+
+    fn report_test_list(&self, test_suite: &TestSuite, include_fixtures: bool) {
+        let fixture = |tc, kind| TestCaseJson::fixture(tc, kind, include_fixtures);
+        let mut test_cases = Vec::new();
+        test_cases.extend(fixture(
+            &test_suite.fixture().setup_test_case,
+            "suite-setup",
+        ));
+        for test_file in test_suite.test_files() {
+            test_cases.extend(fixture(&test_file.setup_test_case, "file-setup"));
+            for tc in &test_file.test_cases {
+                test_cases.push(TestCaseJson::new(tc, "test"));
+            }
+            test_cases.extend(fixture(&test_file.teardown_test_case, "file-teardown"));
+        }
+        test_cases.extend(fixture(
+            &test_suite.fixture().teardown_test_case,
+            "suite-teardown",
+        ));
+        Self::print(&TestListJson {
+            test_suite: TestSuiteJson::new(test_suite),
+            test_cases,
+        });
+    }
+
+    fn report_test_suite_time(&self, _test_suite: &TestSuite, _duration: Duration) {}
+
+    fn report_test_suite_execution_summary(
+        &self,
+        _test_suite: &TestSuite,
+        _exec_context: &[ExecutionContext],
+    ) {
+    }
+
+    fn report_total_time(&self, _duration: Duration) {}
+
+    fn report_test_case_execution_result(
+        &self,
+        _test_case: &TestCase,
+        _target: &str,
+        _exec_info: &TestCaseExecInfo,
+    ) {
+    }
+}
@@ -0,0 +1,362 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Language of the messages printed for humans
+/// Machine readable outputs (e.g. the history file) are never localized, nor are the messages of
+/// errors which only exist in English.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+impl Locale {
+    /// Environment variables giving the locale of the messages, by order of precedence
+    const ENV_VARS: &[&str] = &["LC_ALL", "LC_MESSAGES", "LANG"];
+
+    /// The locale of the user environment, English if not set or not supported
+    pub fn from_env() -> Self {
+        Self::ENV_VARS
+            .iter()
+            .filter_map(|env_var| std::env::var(env_var).ok())
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| locale.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn catalog(self) -> &'static Catalog {
+        match self {
+            Locale::English => &ENGLISH,
+            Locale::French => &FRENCH,
+        }
+    }
+}
+
+/// Accept language codes (`fr`) as well as POSIX locale names (`fr_FR.UTF-8`)
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(locale: &str) -> Result<Self, Self::Err> {
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Ok(Locale::English),
+            "fr" => Ok(Locale::French),
+            _ => Err(format!("unsupported locale `{locale}`")),
+        }
+    }
+}
+
+/// The messages printed by the human friendly reporter in a given language
+/// Each `{}` in a message is replaced by an argument, in order, using [`fill`].
+pub(crate) struct Catalog {
+    pub(crate) info: &'static str,
+    pub(crate) warning: &'static str,
+    pub(crate) error: &'static str,
+    pub(crate) log: &'static str,
+    pub(crate) message: &'static str,
+    pub(crate) artifact: &'static str,
+    pub(crate) telemetry: &'static str,
+    pub(crate) telemetry_value: &'static str,
+    pub(crate) output_tail: &'static str,
+    pub(crate) post_mortem: &'static str,
+    pub(crate) reason: &'static str,
+    pub(crate) passed: &'static str,
+    pub(crate) failed: &'static str,
+    pub(crate) runner_failed: &'static str,
+    pub(crate) skipped: &'static str,
+    pub(crate) xfailed: &'static str,
+    pub(crate) xpassed: &'static str,
+    pub(crate) dry_run: &'static str,
+    pub(crate) not_run: &'static str,
+    pub(crate) running: &'static str,
+    pub(crate) running_test_case: &'static str,
+    pub(crate) test_case_on_target: &'static str,
+    pub(crate) slow_test_case: &'static str,
+    pub(crate) slow_test_cases: &'static str,
+    pub(crate) flaky: &'static str,
+    pub(crate) retrying: &'static str,
+    pub(crate) passed_on_attempt: &'static str,
+    pub(crate) flaky_test_cases: &'static str,
+    pub(crate) timed_out: &'static str,
+    pub(crate) killed_by: &'static str,
+    pub(crate) command_line_timeout: &'static str,
+    pub(crate) test_case_timeout: &'static str,
+    pub(crate) test_file_timeout: &'static str,
+    pub(crate) test_suite_timeout: &'static str,
+    pub(crate) command_line_fixture_timeout: &'static str,
+    pub(crate) test_suite_fixture_timeout: &'static str,
+    pub(crate) on_target: &'static str,
+    pub(crate) took: &'static str,
+    pub(crate) expected_duration: &'static str,
+    pub(crate) historical_duration: &'static str,
+    pub(crate) timing: &'static str,
+    pub(crate) ran_for: &'static str,
+    pub(crate) timeout: &'static str,
+    pub(crate) summary_header: &'static str,
+    pub(crate) run_metadata: &'static str,
+    pub(crate) comparison: &'static str,
+    pub(crate) new_failure: &'static str,
+    pub(crate) fixed: &'static str,
+    pub(crate) newly_skipped: &'static str,
+    pub(crate) change: &'static str,
+    pub(crate) previously: &'static str,
+    pub(crate) not_run_previously: &'static str,
+    pub(crate) target: &'static str,
+    pub(crate) joined_late: &'static str,
+    pub(crate) late: &'static str,
+    pub(crate) statistics: &'static str,
+    pub(crate) statistics_xfail: &'static str,
+    pub(crate) statistics_filtered_out: &'static str,
+    pub(crate) target_duration: &'static str,
+    pub(crate) test_suite_out_dir: &'static str,
+    pub(crate) test_suite_duration: &'static str,
+    pub(crate) target_estimate: &'static str,
+    pub(crate) progress: &'static str,
+    pub(crate) progress_targets: &'static str,
+    pub(crate) target_progress: &'static str,
+    pub(crate) target_eta: &'static str,
+    pub(crate) unknown_durations: &'static str,
+    pub(crate) test_suite_estimate: &'static str,
+    pub(crate) speed_filtered_out: &'static str,
+    pub(crate) speed_quick: &'static str,
+    pub(crate) speed_slow: &'static str,
+    pub(crate) total_duration: &'static str,
+    pub(crate) runner_health: &'static str,
+    pub(crate) targets_supported: &'static str,
+    pub(crate) tests_defined: &'static str,
+    pub(crate) fixture_covers: &'static str,
+    pub(crate) all_test_files: &'static str,
+    pub(crate) suite_setup: &'static str,
+    pub(crate) suite_teardown: &'static str,
+    pub(crate) file_setup: &'static str,
+    pub(crate) file_teardown: &'static str,
+    pub(crate) legend_passed: &'static str,
+    pub(crate) legend_failed: &'static str,
+    pub(crate) legend_runner_failed: &'static str,
+    pub(crate) legend_skipped: &'static str,
+    pub(crate) legend_xfailed: &'static str,
+    pub(crate) legend_xpassed: &'static str,
+}
+
+static ENGLISH: Catalog = Catalog {
+    info: "Info: ",
+    warning: "Warning: ",
+    error: "Error: ",
+    log: "Log:",
+    message: "Message:",
+    artifact: "Artifact:",
+    telemetry: "Telemetry:",
+    telemetry_value: "{} max {}, avg {}",
+    output_tail: "Last {} lines of the log, {} before them left out:",
+    post_mortem: "Post-mortem:",
+    reason: "reason",
+    passed: "PASSED",
+    failed: "FAILED",
+    runner_failed: "RUNNER_FAILED",
+    skipped: "SKIPPED",
+    xfailed: "XFAILED",
+    xpassed: "XPASSED",
+    dry_run: "DRYRUN",
+    not_run: "NOTRUN",
+    running: "RUNNING",
+    running_test_case: "Running test case `{}` for target `{}`",
+    test_case_on_target: "Test case `{}` for target `{}`",
+    slow_test_case: "Test case `{}` is slow on target `{}`: {}",
+    slow_test_cases: "Slow test cases",
+    flaky: "FLAKY",
+    retrying: "retrying, attempt {} of {}",
+    passed_on_attempt: "passed on attempt {} of {}",
+    flaky_test_cases: "Flaky test cases",
+    timed_out: "timed out after {}, {}",
+    killed_by: "killed by {}",
+    command_line_timeout: "command line timeout",
+    test_case_timeout: "test case timeout",
+    test_file_timeout: "test file timeout",
+    test_suite_timeout: "test suite timeout",
+    command_line_fixture_timeout: "command line fixture timeout",
+    test_suite_fixture_timeout: "test suite fixture timeout",
+    on_target: "{} on target {}:",
+    took: "took {} ({})",
+    expected_duration: "expected {}",
+    historical_duration: "historical p95 {}",
+    timing: "Timing:",
+    ran_for: "ran for {}",
+    timeout: "timeout {} ({})",
+    summary_header: "Test suite `{}` execution summary",
+    run_metadata: "Metadata: {}",
+    comparison: "Compared with `{}`: {} new failure(s), {} fixed, {} newly skipped",
+    new_failure: "NEW FAILURE",
+    fixed: "FIXED",
+    newly_skipped: "NEWLY SKIPPED",
+    change: "{} `{}` on target `{}` ({})",
+    previously: "previously {}",
+    not_run_previously: "did not run previously",
+    target: "Target: {}{}",
+    joined_late: " (joined late)",
+    late: "{} (late)",
+    statistics: "Statistics: {} passed, {} failed, {} runner failed, {} skipped",
+    statistics_xfail: ", {} expected failures, {} unexpected passes",
+    statistics_filtered_out: " ({} filtered out)",
+    target_duration: "Test time on target {}: {}",
+    test_suite_out_dir: "Output of the test suite written to `{}`.",
+    test_suite_duration: "Test suite `{}` ran in {}",
+    target_estimate: "Estimated test time on target {}: {} for {} test cases",
+    progress: "{}/{} tests, {} failed",
+    progress_targets: ", running on {}",
+    target_progress: "{} {}%",
+    target_eta: "{} {}% ({} left)",
+    unknown_durations: "No expected duration for {} test cases on target {}, left out of the estimate",
+    test_suite_estimate: "Test suite `{}` estimated to run in {}",
+    speed_filtered_out: "{} {} test cases of test suite `{}` filtered out (slow meaning usually \
+                         taking more than {}).",
+    speed_quick: "quick",
+    speed_slow: "slow",
+    total_duration: "Time elapsed: {}",
+    runner_health: "Runner health: {} warning(s), {} error(s)",
+    targets_supported: "Targets supported by test suite `{}`",
+    tests_defined: "Tests defined in test suite `{}`",
+    fixture_covers: "[{}, covers {}]",
+    all_test_files: "all {} test files",
+    suite_setup: "suite setup",
+    suite_teardown: "suite teardown",
+    file_setup: "file setup",
+    file_teardown: "file teardown",
+    legend_passed: "passed",
+    legend_failed: "failed",
+    legend_runner_failed: "runner failed",
+    legend_skipped: "skipped",
+    legend_xfailed: "expected failure",
+    legend_xpassed: "unexpected pass",
+};
+
+static FRENCH: Catalog = Catalog {
+    info: "Info : ",
+    warning: "Avertissement : ",
+    error: "Erreur : ",
+    log: "Journal :",
+    message: "Message :",
+    artifact: "Artefact :",
+    telemetry: "Télémétrie :",
+    telemetry_value: "{} max {}, moy. {}",
+    output_tail: "{} dernières lignes du journal, {} lignes précédentes omises :",
+    post_mortem: "Post-mortem :",
+    reason: "raison",
+    passed: "RÉUSSI",
+    failed: "ÉCHOUÉ",
+    runner_failed: "ÉCHEC_RUNNER",
+    skipped: "IGNORÉ",
+    xfailed: "ÉCHEC_ATTENDU",
+    xpassed: "RÉUSSITE_INATTENDUE",
+    dry_run: "SIMULÉ",
+    not_run: "NON_EXÉCUTÉ",
+    running: "EN_COURS",
+    running_test_case: "Exécution du cas de test `{}` sur la cible `{}`",
+    test_case_on_target: "Cas de test `{}` sur la cible `{}`",
+    slow_test_case: "Le cas de test `{}` est lent sur la cible `{}` : {}",
+    slow_test_cases: "Cas de test lents",
+    flaky: "INSTABLE",
+    retrying: "nouvelle tentative, {} sur {}",
+    passed_on_attempt: "réussi à la tentative {} sur {}",
+    flaky_test_cases: "Cas de test instables",
+    timed_out: "interrompu après {}, {}",
+    killed_by: "tué par {}",
+    command_line_timeout: "délai de la ligne de commande",
+    test_case_timeout: "délai du cas de test",
+    test_file_timeout: "délai du fichier de test",
+    test_suite_timeout: "délai de la suite de tests",
+    command_line_fixture_timeout: "délai des initialisations et finalisations de la ligne de commande",
+    test_suite_fixture_timeout: "délai des initialisations et finalisations de la suite de tests",
+    on_target: "{} sur la cible {} :",
+    took: "a duré {} ({})",
+    expected_duration: "attendu {}",
+    historical_duration: "p95 historique {}",
+    timing: "Durées :",
+    ran_for: "exécuté en {}",
+    timeout: "délai maximal {} ({})",
+    summary_header: "Résumé de l'exécution de la suite de tests `{}`",
+    run_metadata: "Métadonnées : {}",
+    comparison: "Comparé à `{}` : {} nouveau(x) échec(s), {} corrigé(s), {} nouvellement ignoré(s)",
+    new_failure: "NOUVEL ÉCHEC",
+    fixed: "CORRIGÉ",
+    newly_skipped: "NOUVELLEMENT IGNORÉ",
+    change: "{} `{}` sur la cible `{}` ({})",
+    previously: "précédemment {}",
+    not_run_previously: "non exécuté précédemment",
+    target: "Cible : {}{}",
+    joined_late: " (arrivée en cours)",
+    late: "{} (tardive)",
+    statistics: "Statistiques : {} réussis, {} échoués, {} échecs du runner, {} ignorés",
+    statistics_xfail: ", {} échecs attendus, {} réussites inattendues",
+    statistics_filtered_out: " ({} filtrés)",
+    target_duration: "Durée des tests sur la cible {} : {}",
+    test_suite_out_dir: "Sortie de la suite de tests écrite dans `{}`.",
+    test_suite_duration: "Suite de tests `{}` exécutée en {}",
+    target_estimate: "Durée estimée des tests sur la cible {} : {} pour {} cas de test",
+    progress: "{}/{} tests, {} échoués",
+    progress_targets: ", en cours sur {}",
+    target_progress: "{} {} %",
+    target_eta: "{} {} % (reste {})",
+    unknown_durations: "Aucune durée attendue pour {} cas de test sur la cible {}, non comptés dans l'estimation",
+    test_suite_estimate: "Durée estimée de la suite de tests `{}` : {}",
+    speed_filtered_out: "{} cas de test {} de la suite de tests `{}` filtrés (lent signifiant \
+                         durant habituellement plus de {}).",
+    speed_quick: "rapides",
+    speed_slow: "lents",
+    total_duration: "Temps écoulé : {}",
+    runner_health: "Santé de l'exécuteur : {} avertissement(s), {} erreur(s)",
+    targets_supported: "Cibles supportées par la suite de tests `{}`",
+    tests_defined: "Tests définis dans la suite de tests `{}`",
+    fixture_covers: "[{}, couvre {}]",
+    all_test_files: "les {} fichiers de test",
+    suite_setup: "initialisation de la suite",
+    suite_teardown: "finalisation de la suite",
+    file_setup: "initialisation du fichier",
+    file_teardown: "finalisation du fichier",
+    legend_passed: "réussi",
+    legend_failed: "échoué",
+    legend_runner_failed: "échec du runner",
+    legend_skipped: "ignoré",
+    legend_xfailed: "échec attendu",
+    legend_xpassed: "réussite inattendue",
+};
+
+/// Replace each `{}` in a catalog message by the next argument
+pub(crate) fn fill(message: &str, args: &[&dyn Display]) -> String {
+    let mut filled = String::new();
+    let mut args = args.iter();
+    let mut parts = message.split("{}");
+    // UNWRAP: splitting always yields at least one part
+    filled += parts.next().unwrap();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            filled += &arg.to_string();
+        }
+        filled += part;
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!("fr".parse(), Ok(Locale::French));
+        assert_eq!("fr_FR.UTF-8".parse(), Ok(Locale::French));
+        assert_eq!("en-US".parse(), Ok(Locale::English));
+        assert_eq!("C".parse(), Ok(Locale::English));
+        assert!("de_DE".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(fill("`{}` on `{}`", &[&"a", &2]), "`a` on `2`");
+        assert_eq!(fill("no argument", &[]), "no argument");
+    }
+}
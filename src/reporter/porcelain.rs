@@ -0,0 +1,289 @@
+use crate::comparison::Comparison;
+use crate::error::Error;
+use crate::reporter::Reporter;
+use crate::reporter::composite::RunnerHealth;
+use crate::reporter::human_friendly::HumanFriendlyReporter;
+use crate::test_executor::{ExecutionContext, TargetEstimate, TestCaseExecInfo};
+use crate::test_suite::status::TestCaseStatus;
+use crate::test_suite::{TestCase, TestSuite};
+
+use std::path::Path;
+use std::sync::Once;
+use std::time::Duration;
+
+/// Print strictly formatted lines on the standard output, for scripts and the integration tests
+/// of batrun itself
+/// Each line is a record made of tab-separated fields, the first one being the kind of record.
+/// The first line is always `version` followed by [`PorcelainReporter::VERSION`], which changes
+/// whenever an existing record changes; new kinds of records may be added without changing it.
+/// Backslashes, tabs and line breaks in the fields are escaped as `\\`, `\t`, `\n` and `\r`.
+/// Nothing depending on time or scheduling is printed: durations are left out, and the results
+/// are printed once a test suite ran on all its targets, in target then test suite order.
+///
+/// - `warning <message> <details>` and `error <message> <details>`
+/// - `target <test suite path> <target>`, listing the targets of a test suite
+/// - `test <test suite path> <kind> <test case id>`, listing the test cases of a test suite, the
+///   kind being `test` or the kind of fixture (e.g. `file-setup`)
+/// - `out-dir <test suite path> <output directory>`, before a test suite runs
+/// - `suite <test suite path> <name>`, before the results of a test suite
+/// - `filtered-out <test suite path> <test case id>`, right after the `suite` record for each
+///   test case deselected by the filters of the run
+/// - `result <target> <test case id> <status> <attempts> <reason>`, the status being the one of
+///   the run manifest (e.g. `passed` or `runner-failed`) and the reason empty if there is none
+/// - `message <target> <test case id> <message>`, right after the result of a test case which
+///   attached a message to it
+/// - `stats <target> passed=N failed=N runner-failed=N skipped=N xfailed=N xpassed=N`
+/// - `estimate <target> <test cases> <unknown>`, in dry-run mode after the results of a test
+///   suite, with the number of test cases which would run on the target and of the ones among
+///   them without expected duration
+/// - `no-estimate <target> <test case id>`, right after the estimate of a target for each test
+///   case which would run without expected duration
+/// - `change <target> <test case id> <kind> <previous status>`, after the results of a test suite
+///   compared with a previous run for each test case whose result changed, the kind being
+///   `new-failure`, `fixed` or `newly-skipped` and the previous status empty if it did not run
+/// - `runner-health warnings=N errors=N`, last, with how many warnings and errors were reported
+///   during the whole run
+pub struct PorcelainReporter {
+    version_printed: Once,
+}
+
+impl Default for PorcelainReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PorcelainReporter {
+    pub const VERSION: u32 = 1;
+
+    pub fn new() -> Self {
+        Self {
+            version_printed: Once::new(),
+        }
+    }
+
+    fn print(&self, fields: &[&str]) {
+        self.version_printed
+            .call_once(|| println!("version\t{}", Self::VERSION));
+        println!("{}", format_record(fields));
+    }
+
+    fn print_fixture(
+        &self,
+        test_suite: &TestSuite,
+        fixture: &Option<TestCase>,
+        kind: &str,
+        listed: bool,
+    ) {
+        if let Some(test_case) = fixture.as_ref().filter(|_| listed) {
+            self.print_test(test_suite, test_case, kind);
+        }
+    }
+
+    fn print_test(&self, test_suite: &TestSuite, test_case: &TestCase, kind: &str) {
+        self.print(&[
+            "test",
+            &test_suite.path().display().to_string(),
+            kind,
+            &test_case.id().to_string(),
+        ]);
+    }
+
+    fn print_result(&self, target: &str, test_case: &TestCase, exec_info: &TestCaseExecInfo) {
+        let (status, reason) = match exec_info.result() {
+            Ok(output) => {
+                let reason = match &output.test_case_status {
+                    TestCaseStatus::Failed(reason) => reason.to_string(),
+                    TestCaseStatus::Skipped(reason) => reason.to_string(),
+                    TestCaseStatus::XFailed(reason) | TestCaseStatus::XPassed(reason) => {
+                        reason.clone()
+                    }
+                    _ => String::new(),
+                };
+                (output.test_case_status.name(), reason)
+            }
+            Err(error) => ("runner-failed", error.to_string()),
+        };
+        self.print(&[
+            "result",
+            target,
+            &test_case.id().to_string(),
+            status,
+            &exec_info.attempt().to_string(),
+            &reason,
+        ]);
+        if let Ok(Some(message)) = exec_info.result().as_ref().map(|output| &output.message) {
+            self.print(&["message", target, &test_case.id().to_string(), message]);
+        }
+    }
+}
+
+/// Escape the fields of a record and join them with tabs
+fn format_record(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            field
+                .replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r")
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+impl Reporter for PorcelainReporter {
+    fn notice_detailed(&self, _message: &str, _details: &str) {}
+    fn info_detailed(&self, _message: &str, _details: &str) {}
+
+    fn warning_detailed(&self, message: &str, details: &str) {
+        self.print(&["warning", message, details]);
+    }
+
+    fn error_detailed(&self, message: &str, details: &str) {
+        self.print(&["error", message, details]);
+    }
+
+    fn error_from(&self, error: &Error) {
+        let (message, details) = HumanFriendlyReporter::error_message(error);
+        self.error_detailed(&message, &details);
+    }
+
+    fn report_target_list(&self, test_suite: &TestSuite) {
+        let path = test_suite.path().display().to_string();
+        for target in &test_suite.config().targets {
+            self.print(&["target", &path, &target.name]);
+        }
+    }
+
+    fn report_test_list(&self, test_suite: &TestSuite, include_fixtures: bool) {
+        let fixture = |tc, kind| self.print_fixture(test_suite, tc, kind, include_fixtures);
+        fixture(&test_suite.fixture().setup_test_case, "suite-setup");
+        for test_file in test_suite.test_files() {
+            fixture(&test_file.setup_test_case, "file-setup");
+            for tc in &test_file.test_cases {
+                self.print_test(test_suite, tc, "test");
+            }
+            fixture(&test_file.teardown_test_case, "file-teardown");
+        }
+        fixture(&test_suite.fixture().teardown_test_case, "suite-teardown");
+    }
+
+    fn report_test_suite_time(&self, _test_suite: &TestSuite, _duration: Duration) {}
+
+    fn report_test_suite_out_dir(&self, test_suite: &TestSuite, out_dir: &Path) {
+        self.print(&[
+            "out-dir",
+            &test_suite.path().display().to_string(),
+            &out_dir.display().to_string(),
+        ]);
+    }
+
+    fn report_test_suite_execution_summary(
+        &self,
+        test_suite: &TestSuite,
+        exec_contexts: &[ExecutionContext],
+    ) {
+        self.print(&[
+            "suite",
+            &test_suite.path().display().to_string(),
+            &test_suite.config().name,
+        ]);
+        for test_case_id in test_suite.filtered_out() {
+            self.print(&[
+                "filtered-out",
+                &test_suite.path().display().to_string(),
+                &test_case_id.to_string(),
+            ]);
+        }
+        for exec_context in exec_contexts {
+            let target = exec_context.target();
+            for (test_case, exec_info) in exec_context.iter_exec_info() {
+                self.print_result(target, test_case, exec_info);
+            }
+            let stats = exec_context.get_statistics();
+            self.print(&[
+                "stats",
+                target,
+                &format!("passed={}", stats.passed),
+                &format!("failed={}", stats.failed),
+                &format!("runner-failed={}", stats.runner_failed),
+                &format!("skipped={}", stats.skipped),
+                &format!("xfailed={}", stats.xfailed),
+                &format!("xpassed={}", stats.xpassed),
+            ]);
+        }
+    }
+
+    fn report_dry_run_estimate(
+        &self,
+        _test_suite: &TestSuite,
+        estimates: &[TargetEstimate],
+        _total: Duration,
+    ) {
+        for estimate in estimates {
+            self.print(&[
+                "estimate",
+                &estimate.target,
+                &estimate.test_cases.to_string(),
+                &estimate.unknown.len().to_string(),
+            ]);
+            for test_case_id in &estimate.unknown {
+                self.print(&["no-estimate", &estimate.target, &test_case_id.to_string()]);
+            }
+        }
+    }
+
+    fn report_comparison(&self, _test_suite: &TestSuite, comparison: &Comparison) {
+        for change in &comparison.changes {
+            self.print(&[
+                "change",
+                &change.target,
+                &change.test_case_id.to_string(),
+                change.kind.name(),
+                change.previous_status.as_deref().unwrap_or_default(),
+            ]);
+        }
+    }
+
+    fn report_total_time(&self, _duration: Duration) {}
+
+    fn report_runner_health(&self, health: &RunnerHealth) {
+        self.print(&[
+            "runner-health",
+            &format!("warnings={}", health.warnings),
+            &format!("errors={}", health.errors),
+        ]);
+    }
+
+    fn report_test_case_execution_result(
+        &self,
+        _test_case: &TestCase,
+        _target: &str,
+        _exec_info: &TestCaseExecInfo,
+    ) {
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_record_joins_fields_with_tabs() {
+        assert_eq!(
+            format_record(&["result", "foo", "a.sh::t"]),
+            "result\tfoo\ta.sh::t"
+        );
+        assert_eq!(format_record(&["error", "", ""]), "error\t\t");
+    }
+
+    #[test]
+    fn format_record_escapes_fields() {
+        assert_eq!(
+            format_record(&["error", "a\tb\nc\r", "C:\\dir"]),
+            "error\ta\\tb\\nc\\r\tC:\\\\dir"
+        );
+    }
+}
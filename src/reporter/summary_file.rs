@@ -0,0 +1,55 @@
+use crate::error::Error;
+use crate::reporter::Reporter;
+use crate::reporter::human_friendly::HumanFriendlyReporter;
+use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
+use crate::test_suite::{TestCase, TestSuite};
+
+use std::time::Duration;
+
+/// Write only the execution summaries of the test suites, the way the human friendly reporter
+/// prints them, e.g. to a file read by a wrapper script
+pub(crate) struct SummaryFileReporter {
+    summary_printer: HumanFriendlyReporter,
+}
+
+impl SummaryFileReporter {
+    pub(crate) fn new(summary_printer: HumanFriendlyReporter) -> Self {
+        Self { summary_printer }
+    }
+}
+
+impl Reporter for SummaryFileReporter {
+    fn notice_detailed(&self, _message: &str, _details: &str) {}
+    fn info_detailed(&self, _message: &str, _details: &str) {}
+    fn warning_detailed(&self, _message: &str, _details: &str) {}
+    fn error_detailed(&self, _message: &str, _details: &str) {}
+    fn error_from(&self, _error: &Error) {}
+
+    fn report_target_list(&self, _test_suite: &TestSuite) {}
+    fn report_test_list(&self, _test_suite: &TestSuite, _include_fixtures: bool) {}
+    fn report_test_suite_time(&self, test_suite: &TestSuite, duration: Duration) {
+        self.summary_printer
+            .report_test_suite_time(test_suite, duration);
+    }
+
+    fn report_test_suite_execution_summary(
+        &self,
+        test_suite: &TestSuite,
+        exec_context: &[ExecutionContext],
+    ) {
+        self.summary_printer
+            .report_test_suite_execution_summary(test_suite, exec_context);
+    }
+
+    fn report_total_time(&self, duration: Duration) {
+        self.summary_printer.report_total_time(duration);
+    }
+
+    fn report_test_case_execution_result(
+        &self,
+        _test_case: &TestCase,
+        _target: &str,
+        _exec_info: &TestCaseExecInfo,
+    ) {
+    }
+}
@@ -0,0 +1,233 @@
+use crate::error::Error;
+use crate::reporter::Reporter;
+use crate::reporter::composite::RunnerHealth;
+use crate::reporter::human_friendly::HumanFriendlyReporter;
+use crate::test_driver::Env;
+use crate::test_executor::{ExecutionContext, TestCaseExecInfo};
+use crate::test_suite::status::TestCaseStatus;
+use crate::test_suite::{TestCase, TestSuite};
+
+use std::fmt::Write as _;
+use std::sync::{Mutex, Once};
+use std::time::Duration;
+
+/// Print the results of the test cases in the Test Anything Protocol (TAP) version 13 on the
+/// standard output as they complete, for TAP consumers such as `prove`
+/// Each test case run on a target, fixtures included, is a test point described as
+/// `<test case id> on <target>` and followed by a YAML block with its target, duration, main log
+/// file and why it failed. Skipped test cases have the `SKIP` directive and the ones expected to
+/// fail the `TODO` one. The metadata of the run, warnings and errors are printed as diagnostics,
+/// and the plan once all the test suites ran, followed by the health of the runner.
+pub struct TapReporter {
+    version_printed: Once,
+    /// Number of the last test point printed, locked while printing so that the test points are
+    /// printed in order
+    last_number: Mutex<usize>,
+}
+
+impl Default for TapReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TapReporter {
+    pub fn new() -> Self {
+        Self {
+            version_printed: Once::new(),
+            last_number: Mutex::new(0),
+        }
+    }
+
+    fn print(&self, text: &str) {
+        self.version_printed
+            .call_once(|| println!("TAP version 13"));
+        println!("{text}");
+    }
+
+    fn print_diagnostic(&self, label: &str, message: &str, details: &str) {
+        let mut text = format!("# {label}: {}", single_line(message));
+        for line in details.lines() {
+            let _ = write!(text, "\n#   {line}");
+        }
+        self.print(&text);
+    }
+}
+
+/// The line of a test point, `status` being `None` if batrun failed to run the test case
+fn test_point(number: usize, description: &str, status: Option<&TestCaseStatus>) -> String {
+    let (ok, directive) = match status {
+        None
+        | Some(TestCaseStatus::Failed(_) | TestCaseStatus::NotRun | TestCaseStatus::Running) => {
+            (false, None)
+        }
+        Some(TestCaseStatus::Passed) => (true, None),
+        Some(TestCaseStatus::Skipped(reason)) => (true, Some(("SKIP", reason.to_string()))),
+        Some(TestCaseStatus::DryRun) => (true, Some(("SKIP", "dry run".to_string()))),
+        Some(TestCaseStatus::XFailed(reason)) => (false, Some(("TODO", reason.clone()))),
+        Some(TestCaseStatus::XPassed(reason)) => (true, Some(("TODO", reason.clone()))),
+    };
+    let mut line = format!(
+        "{}ok {number} - {}",
+        if ok { "" } else { "not " },
+        single_line(description).replace('#', "\\#")
+    );
+    if let Some((directive, reason)) = directive {
+        let _ = write!(line, " # {directive} {}", single_line(&reason));
+    }
+    line
+}
+
+/// The YAML block following a test point, the strings being quoted as JSON ones, which YAML
+/// reads as is
+fn yaml_block(target: &str, exec_info: &TestCaseExecInfo) -> String {
+    let quote = |value: &str| serde_json::Value::from(value).to_string();
+    let mut block = format!("  ---\n  target: {}\n", quote(target));
+    if let Some(duration) = exec_info.duration() {
+        let _ = writeln!(block, "  duration_ms: {}", duration.as_millis());
+    }
+    if let Some(log_file) = exec_info.main_log_file() {
+        let _ = writeln!(block, "  log: {}", quote(&log_file.location()));
+    }
+    match exec_info.result() {
+        Ok(output) => {
+            if let TestCaseStatus::Failed(reason) = &output.test_case_status {
+                let _ = writeln!(block, "  reason: {}", quote(&reason.to_string()));
+            }
+            if let Some(message) = &output.message {
+                let _ = writeln!(block, "  message: {}", quote(message));
+            }
+        }
+        Err(error) => {
+            let _ = writeln!(block, "  error: {}", quote(&error.to_string()));
+        }
+    }
+    block.push_str("  ...");
+    block
+}
+
+/// Descriptions and directives end at the end of their line
+fn single_line(text: &str) -> String {
+    text.replace(['\n', '\r'], " ")
+}
+
+impl Reporter for TapReporter {
+    fn notice_detailed(&self, _message: &str, _details: &str) {}
+    fn info_detailed(&self, _message: &str, _details: &str) {}
+
+    fn warning_detailed(&self, message: &str, details: &str) {
+        self.print_diagnostic("Warning", message, details);
+    }
+
+    fn error_detailed(&self, message: &str, details: &str) {
+        self.print_diagnostic("Error", message, details);
+    }
+
+    fn error_from(&self, error: &Error) {
+        let (message, details) = HumanFriendlyReporter::error_message(error);
+        self.error_detailed(&message, &details);
+    }
+
+    fn report_run_started(&self, run_metadata: &Env) {
+        for (key, value) in run_metadata {
+            self.print_diagnostic("meta", &format!("{key}={value}"), "");
+        }
+    }
+
+    fn report_target_list(&self, _test_suite: &TestSuite) {}
+
+    fn report_test_list(&self, _test_suite: &TestSuite, _include_fixtures: bool) {}
+
+    fn report_test_suite_time(&self, _test_suite: &TestSuite, _duration: Duration) {}
+
+    fn report_test_suite_execution_summary(
+        &self,
+        _test_suite: &TestSuite,
+        _exec_contexts: &[ExecutionContext],
+    ) {
+    }
+
+    fn report_total_time(&self, _duration: Duration) {
+        // UNWRAP: the lock is never held while panicking
+        let last_number = self.last_number.lock().unwrap();
+        self.print(&format!("1..{last_number}"));
+    }
+
+    fn report_runner_health(&self, health: &RunnerHealth) {
+        let message = format!("warnings={} errors={}", health.warnings, health.errors);
+        self.print_diagnostic("runner-health", &message, "");
+    }
+
+    fn report_test_case_execution_result(
+        &self,
+        test_case: &TestCase,
+        target: &str,
+        exec_info: &TestCaseExecInfo,
+    ) {
+        let status = exec_info
+            .result()
+            .as_ref()
+            .ok()
+            .map(|output| &output.test_case_status);
+        // UNWRAP: the lock is never held while panicking
+        let mut last_number = self.last_number.lock().unwrap();
+        *last_number += 1;
+        let description = format!("{} on {target}", test_case.id());
+        self.print(&test_point(*last_number, &description, status));
+        self.print(&yaml_block(target, exec_info));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_suite::status::{FailReason, SkipReason};
+
+    #[test]
+    fn test_points() {
+        assert_eq!(
+            test_point(1, "a.sh::t on foo", Some(&TestCaseStatus::Passed)),
+            "ok 1 - a.sh::t on foo"
+        );
+        assert_eq!(
+            test_point(
+                2,
+                "a.sh::t on foo",
+                Some(&TestCaseStatus::Failed(FailReason::TestCaseFailure))
+            ),
+            "not ok 2 - a.sh::t on foo"
+        );
+        assert_eq!(
+            test_point(3, "a.sh::t on foo", None),
+            "not ok 3 - a.sh::t on foo"
+        );
+    }
+
+    #[test]
+    fn test_points_with_directive() {
+        assert_eq!(
+            test_point(
+                1,
+                "a.sh::t on foo",
+                Some(&TestCaseStatus::Skipped(SkipReason::TestCaseSetupError))
+            ),
+            "ok 1 - a.sh::t on foo # SKIP test-case-setup-error"
+        );
+        assert_eq!(
+            test_point(
+                2,
+                "a.sh::t on foo",
+                Some(&TestCaseStatus::XFailed("bug #12\nopen".to_string()))
+            ),
+            "not ok 2 - a.sh::t on foo # TODO bug #12 open"
+        );
+    }
+
+    #[test]
+    fn hash_escaped_in_description() {
+        assert_eq!(
+            test_point(1, "a.sh::t[#1] on foo", Some(&TestCaseStatus::Passed)),
+            "ok 1 - a.sh::t[\\#1] on foo"
+        );
+    }
+}
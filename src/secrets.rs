@@ -0,0 +1,114 @@
+use crate::error::{self, Result};
+use crate::test_driver::Env;
+use crate::test_suite::config::SecretsConfig;
+
+use std::path::Path;
+use std::process::Command;
+
+/// Secrets passed to the test processes as environment variables, whose values are redacted from
+/// the logs of the test cases (see [`Redactor`](crate::redaction::Redactor))
+#[derive(Debug, Default)]
+pub struct Secrets {
+    env: Env,
+}
+
+impl Secrets {
+    /// Load the secrets of a test suite from the provider declared in its config
+    pub fn load(config: &SecretsConfig, test_suite_dir: &Path) -> Result<Self> {
+        let error = |details: String| error::kind::Secrets {
+            test_suite: test_suite_dir.to_path_buf(),
+            details,
+        };
+        let contents = match config {
+            SecretsConfig::EnvFile { path } => {
+                let path = test_suite_dir.join(path);
+                Self::check_permissions(&path).map_err(error)?;
+                std::fs::read_to_string(&path)
+                    .map_err(|io_err| error(format!("`{}`: {io_err}", path.display())))?
+            }
+            SecretsConfig::Command { command } => {
+                // UNWRAP: the command is checked not to be empty when loading the test suite
+                // config
+                let (program, args) = command.split_first().unwrap();
+                let output = Command::new(program)
+                    .args(args)
+                    .current_dir(test_suite_dir)
+                    .output()
+                    .map_err(|io_err| error(format!("cannot execute `{program}`: {io_err}")))?;
+                if !output.status.success() {
+                    return Err(error(format!(
+                        "`{program}` exited with {}: {}",
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ))
+                    .into());
+                }
+                String::from_utf8(output.stdout)
+                    .map_err(|_| error(format!("`{program}` printed invalid UTF-8")))?
+            }
+        };
+        Self::parse(&contents).map_err(|details| error(details).into())
+    }
+
+    /// Parse `NAME=VALUE` lines, ignoring empty lines and `#` comments
+    pub(crate) fn parse(contents: &str) -> std::result::Result<Self, String> {
+        let mut env = Env::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim_start();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once('=') {
+                Some((name, value)) if !name.trim().is_empty() => {
+                    env.insert(name.trim().to_string(), value.to_string());
+                }
+                _ => return Err(format!("line {}: expected NAME=VALUE", index + 1)),
+            }
+        }
+        Ok(Self { env })
+    }
+
+    #[cfg(unix)]
+    fn check_permissions(path: &Path) -> std::result::Result<(), String> {
+        use std::os::unix::fs::PermissionsExt;
+        let metadata =
+            std::fs::metadata(path).map_err(|io_err| format!("`{}`: {io_err}", path.display()))?;
+        if metadata.permissions().mode() & 0o077 != 0 {
+            return Err(format!(
+                "`{}` is accessible to other users, restrict its permissions to its owner (e.g. \
+                 `chmod 600`)",
+                path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn check_permissions(_path: &Path) -> std::result::Result<(), String> {
+        Ok(())
+    }
+
+    /// The secrets as environment variables
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    /// Whether an environment variable holds a secret
+    pub fn contains(&self, name: &str) -> bool {
+        self.env.contains_key(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let secrets = Secrets::parse("# comment\n\nTOKEN=abc=def\n  PASSWORD=hunter2\n").unwrap();
+        assert_eq!(secrets.env()["TOKEN"], "abc=def");
+        assert_eq!(secrets.env()["PASSWORD"], "hunter2");
+        assert!(Secrets::parse("NO_VALUE\n").is_err());
+        assert!(Secrets::parse("=value\n").is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::error::{self, Result};
+use crate::test_driver::{Env, KillSignal, process};
+use crate::test_suite::config::ServiceConfig;
+use crate::time;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// The services of a test suite (e.g. a database, a mock server), started before it runs on any
+/// target and stopped once it ran on all of them
+/// The services still running when dropped are killed, e.g. when one of them fails to start.
+#[derive(Default)]
+pub(crate) struct Services {
+    running: Vec<RunningService>,
+}
+
+struct RunningService {
+    config: ServiceConfig,
+    child: Child,
+    /// Environment of the service, along with its pid and log file for the ready and stop
+    /// commands
+    env: Env,
+    working_dir: PathBuf,
+    log: PathBuf,
+}
+
+impl Services {
+    /// Passed to the ready and stop commands so that they can check on the service
+    const PID_ENV_VAR: &str = "BATRUN_SERVICE_PID";
+    const LOG_ENV_VAR: &str = "BATRUN_SERVICE_LOG";
+    const READY_POLL_PERIOD: Duration = Duration::from_millis(100);
+
+    /// Start the services in order from the test suite directory, each one being ready before
+    /// the next one starts, their output being captured in the given output directory
+    pub(crate) fn start(
+        configs: &[ServiceConfig],
+        test_suite_dir: &Path,
+        env: &Env,
+        out_dir: &Path,
+    ) -> Result<Self> {
+        let mut services = Self::default();
+        for config in configs {
+            let service = RunningService::start(config, test_suite_dir, env, out_dir)?;
+            // Pushed before waiting so that the service is stopped if it is not ready in time
+            services.running.push(service);
+            // UNWRAP: the service was just pushed
+            services.running.last_mut().unwrap().wait_ready()?;
+        }
+        Ok(services)
+    }
+
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.running
+            .iter()
+            .map(|service| service.config.name.as_str())
+    }
+
+    /// Stop the services in reverse order, returning the errors of the ones which did not stop
+    /// gracefully
+    pub(crate) fn stop(mut self) -> Vec<error::Error> {
+        let mut errors = Vec::new();
+        while let Some(service) = self.running.pop() {
+            errors.extend(service.stop().err());
+        }
+        errors
+    }
+}
+
+impl RunningService {
+    fn start(
+        config: &ServiceConfig,
+        test_suite_dir: &Path,
+        env: &Env,
+        out_dir: &Path,
+    ) -> Result<Self> {
+        let io_error = |io_err: std::io::Error| error(config, io_err.to_string());
+
+        std::fs::create_dir_all(out_dir).map_err(io_error)?;
+        let log = out_dir.join(format!("{}.service.log", config.name));
+        let log_file = File::create(&log).map_err(io_error)?;
+
+        // UNWRAP: the command is checked not to be empty when loading the test suite config
+        let (program, args) = config.command.split_first().unwrap();
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(test_suite_dir)
+            .env_clear()
+            .envs(env)
+            .stdin(Stdio::null())
+            .stdout(log_file.try_clone().map_err(io_error)?)
+            .stderr(log_file);
+        // In its own process group, so that it is stopped with the processes it started, and
+        // that it keeps running for the teardowns if the run is interrupted from the terminal
+        let child = process::spawn(&mut command, None)
+            .map_err(|io_err| error(config, format!("cannot start `{program}`: {io_err}")))?;
+
+        let mut env = env.clone();
+        env.insert(Services::PID_ENV_VAR.to_string(), child.id().to_string());
+        env.insert(Services::LOG_ENV_VAR.to_string(), log.display().to_string());
+        Ok(Self {
+            config: config.clone(),
+            child,
+            env,
+            working_dir: test_suite_dir.to_path_buf(),
+            log,
+        })
+    }
+
+    /// Run the ready command until it succeeds, if any
+    fn wait_ready(&mut self) -> Result<()> {
+        let Some(ready_command) = &self.config.ready_command else {
+            return Ok(());
+        };
+        let deadline = Instant::now() + self.config.ready_timeout;
+        loop {
+            let exit_status = self
+                .child
+                .try_wait()
+                .map_err(|io_err| error(&self.config, io_err.to_string()))?;
+            if let Some(exit_status) = exit_status {
+                return Err(error(
+                    &self.config,
+                    format!(
+                        "the service exited ({exit_status}) before being ready, see `{}`",
+                        self.log.display()
+                    ),
+                ));
+            }
+            let time_left = deadline.saturating_duration_since(Instant::now());
+            let ready = self
+                .run(ready_command, time_left)
+                .is_ok_and(|exit_status| exit_status.is_ok_and(|status| status.success()));
+            if ready {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(error(
+                    &self.config,
+                    format!(
+                        "the service was not ready after {}, see `{}`",
+                        time::format(self.config.ready_timeout),
+                        self.log.display()
+                    ),
+                ));
+            }
+            std::thread::sleep(Services::READY_POLL_PERIOD);
+        }
+    }
+
+    /// Run the stop command if any, then wait for the service to exit, the service being killed
+    /// if it does not exit in time or if there is no stop command
+    fn stop(mut self) -> Result<()> {
+        let Some(stop_command) = &self.config.stop_command else {
+            // Killed when dropped
+            return Ok(());
+        };
+        let details = match self.run(stop_command, self.config.stop_timeout) {
+            Ok(Ok(status)) if status.success() => {
+                match process::wait(&mut self.child, Some(self.config.stop_timeout)) {
+                    Ok(Ok(_)) => return Ok(()),
+                    _ => format!(
+                        "the service was still running {} after the stop command",
+                        time::format(self.config.stop_timeout)
+                    ),
+                }
+            }
+            Ok(Ok(status)) => format!("the stop command exited with {status}"),
+            Ok(Err(_)) => format!(
+                "the stop command was still running after {}",
+                time::format(self.config.stop_timeout)
+            ),
+            Err(io_err) => format!("cannot run the stop command: {io_err}"),
+        };
+        Err(error(
+            &self.config,
+            format!("{details}, the service was killed"),
+        ))
+    }
+
+    /// Run a ready or stop command, its output being appended to the service log
+    fn run(
+        &self,
+        command_line: &[String],
+        timeout: Duration,
+    ) -> std::io::Result<std::result::Result<std::process::ExitStatus, KillSignal>> {
+        // UNWRAP: the command is checked not to be empty when loading the test suite config
+        let (program, args) = command_line.split_first().unwrap();
+        let log_file = File::options().append(true).open(&self.log)?;
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(&self.working_dir)
+            .env_clear()
+            .envs(&self.env)
+            .stdin(Stdio::null())
+            .stdout(log_file.try_clone()?)
+            .stderr(log_file);
+        process::status(&mut command, Some(timeout), None)
+    }
+}
+
+impl Drop for RunningService {
+    fn drop(&mut self) {
+        // The service may have exited already, nothing to do then
+        let _ = process::wait(&mut self.child, Some(Duration::ZERO));
+    }
+}
+
+fn error(config: &ServiceConfig, details: impl Into<String>) -> error::Error {
+    error::kind::Service {
+        service: config.name.clone(),
+        details: details.into(),
+    }
+    .into()
+}
@@ -1,15 +1,341 @@
+pub mod global_config;
+
+use crate::comparison::PreviousRun;
 use crate::execution_strategy::ExecutionStrategy;
+use crate::fault_injection::FaultSpec;
+use crate::paused_run::PausedRun;
+use crate::reporter::locale::Locale;
+use crate::test_driver::Env;
+use crate::test_suite::filter::{PathFilter, SpeedFilter, TagFilter, TestFilter};
+
+use global_config::GlobalConfig;
 
+use regex::Regex;
+use serde::Deserialize;
+
+use std::io::IsTerminal;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// Which outputs of the test cases are printed on the console
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ShowOutput {
+    /// Only write the outputs to the log files
+    Never,
+    /// Print the last lines of the output of the failed test cases once they are done
+    Failed,
+    /// Stream the output of all test cases as they run
+    All,
+}
+
+/// How much the console output tells about the run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Only the failures, warnings, errors and summaries
+    Quiet,
+    Normal,
+    /// Also the log files, artifacts and timings of all test cases
+    Verbose,
+}
+
+/// When the messages printed on the console are colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ColorChoice {
+    /// Only when printing to a terminal and `NO_COLOR` is not set
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether the messages printed on stdout are colored
+    pub fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// When the failures, warnings and errors are also printed as GitHub Actions annotations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum GithubAnnotations {
+    /// Only when running in a GitHub Actions workflow (`GITHUB_ACTIONS` set to `true`) and
+    /// reporting on the console, so that machine-readable outputs are left untouched
+    Auto,
+    Always,
+    Never,
+}
 
+impl GithubAnnotations {
+    /// Whether the annotations are printed, `console_output` telling whether the run is reported
+    /// on the console rather than by another main reporter
+    pub fn enabled(self, console_output: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                console_output
+                    && std::env::var_os("GITHUB_ACTIONS").is_some_and(|value| value == "true")
+            }
+        }
+    }
+}
+
+/// Settings of a run, built with [`Settings::new`] and the `with_*` methods when embedding batrun,
+/// all the other settings being public
 #[derive(Debug)]
 pub struct Settings {
     pub test_suite_dirs: Vec<PathBuf>,
     pub out_dir: PathBuf,
+    /// Write the output of each run into its own directory, so that successive runs do not
+    /// overwrite each other
+    pub keep_history: bool,
+    /// Metadata of the run given by the user (e.g. the build number or the firmware version
+    /// under test), recorded in the run manifest and the reports
+    pub run_metadata: Env,
     pub targets: Vec<String>,
     pub exec_strategy: ExecutionStrategy,
+    /// Maximum number of targets run at the same time by the parallel execution strategy, all
+    /// targets at once if not set
+    pub jobs: Option<NonZeroUsize>,
+    /// Maximum number of test files declared independent run at the same time on each target,
+    /// by all execution strategies but round-robin
+    pub file_jobs: NonZeroUsize,
     pub dry_run: bool,
-    pub test_filter: Option<String>,
+    /// Stop running test cases at the first failure, skipping the remaining ones except the
+    /// teardowns of what was already set up
+    pub fail_fast: bool,
+    /// Count the test cases expected to fail which pass as failures
+    pub strict_xfail: bool,
+    /// Count the test cases skipped by themselves or because of missing files as failures, e.g.
+    /// for release runs where all test cases must actually run
+    pub strict_skips: bool,
+    /// Tags of the test cases still allowed to skip themselves with `strict_skips`
+    pub allowed_skip_tags: Vec<String>,
+    /// Count the test cases leaving processes running once done as failures, setups excepted
+    pub strict_orphans: bool,
+    /// Kill the processes left running by the test cases once done, setups excepted
+    pub kill_orphans: bool,
+    /// Previous run of a test suite which its results are compared with, reporting the new
+    /// failures, the fixed test cases and the newly skipped ones
+    pub compare_with: Option<PreviousRun>,
+    /// Only fail the run for the test cases failing which did not in the previous run compared
+    /// with, the test suites not compared failing as usual
+    pub fail_only_on_regressions: bool,
+    /// Selection of the test cases to run or list, applied once test cases are discovered
+    pub test_filter: TestFilter,
+    /// Selection of the test files to run or list, applied while discovering test cases
+    pub path_filter: PathFilter,
+    /// Selection of the test cases to run or list by their tags
+    pub tag_filter: TagFilter,
+    /// Selection of the test cases to run or list by how long they usually take
+    pub speed_filter: SpeedFilter,
+    /// Test cases usually taking longer than this on previous runs are considered slow by the
+    /// speed filter
+    pub slow_threshold: Duration,
     pub debug: bool,
     pub matrix_summary: bool,
+    /// Print the output related to a target as a single block once the target is done instead
+    /// of as it comes
+    pub group_output: bool,
+    /// Print messages without colors, box-drawing characters nor progress status line, for screen
+    /// readers and dumb terminals
+    pub plain: bool,
+    /// When the messages are colored, `--plain` disabling colors anyway
+    pub color: ColorChoice,
+    /// When the failures, warnings and errors are also printed as GitHub Actions annotations
+    pub github_annotations: GithubAnnotations,
+    pub verbosity: Verbosity,
+    /// Which outputs of the test cases are printed on the console, besides their log files
+    pub show_output: ShowOutput,
+    /// Number of lines of the log of a failed test case printed when showing the output of the
+    /// failed test cases, all of them if 0
+    pub output_lines: usize,
+    /// File the execution summaries are also written to, e.g. for wrapper scripts
+    pub summary_file: Option<PathBuf>,
+    /// File the events of the run are written to as JSON lines as they happen, e.g. for
+    /// dashboards following the run live
+    pub events_file: Option<PathBuf>,
+    /// Also write the results of the test cases in the format of Allure, to the `allure-results`
+    /// directory of the output directory, except for dry runs
+    pub allure_results: bool,
+    /// Language of the messages printed for humans
+    pub locale: Locale,
+    /// Test cases taking longer than their expected duration multiplied by this factor are
+    /// reported as slow
+    pub slow_factor: f64,
+    /// Number of times a failed test case is run again, overriding the test suite config if set
+    pub retries: Option<u32>,
+    /// Timeout of all test cases, overriding the ones declared in test suites if set
+    pub timeout: Option<Duration>,
+    /// Timeout of all setups and teardowns, overriding the ones declared in test suites if set
+    pub fixture_timeout: Option<Duration>,
+    /// How long the test processes killed after their timeout (or because the run was
+    /// interrupted) are given to exit once sent SIGTERM, before being sent SIGKILL
+    pub kill_grace_period: Duration,
+    /// Run the test processes of all test suites in a clean environment, see
+    /// [`TestSuiteConfig::clean_env`](crate::test_suite::config::TestSuiteConfig::clean_env)
+    pub clean_env: bool,
+    /// Environment variables forwarded to the test processes in clean environment mode, in
+    /// addition to the ones listed by test suites
+    pub env_pass: Vec<String>,
+    /// Environment variables set in all the test processes, overriding the ones forwarded from
+    /// batrun and the ones declared in the test suite configs
+    pub env: Env,
+    /// Regular expressions matching sensitive data to redact from the logs of the test cases, in
+    /// addition to the ones listed by test suites
+    pub redact: Vec<Regex>,
+    /// File listing additional targets, polled during the run so that targets attached later on
+    /// can join it
+    pub targets_file: Option<PathBuf>,
+    /// How long to wait for new targets in the targets file once all known targets are done
+    pub targets_wait: Duration,
+    /// When set, the order in which targets are serviced is shuffled using this seed
+    pub target_shuffle_seed: Option<u64>,
+    /// The run is paused as soon as this file exists, as when batrun receives SIGUSR1: the test
+    /// cases running finish but no new one starts, and the progress is saved in the output
+    /// directory
+    pub pause_file: Option<PathBuf>,
+    /// Arguments of the command line of the run, saved along with its progress when it is paused
+    /// so that it is resumed with the same options
+    pub command_line: Vec<String>,
+    /// The paused run this run resumes, the test cases which ran before the pause not running
+    /// again
+    pub resumed_run: Option<PausedRun>,
+    /// Faults injected into the run to exercise the error paths of batrun, for its developers
+    pub inject_faults: Vec<FaultSpec>,
+}
+
+impl Settings {
+    pub const DEFAULT_OUT_DIR: &str = "out";
+    pub const DEFAULT_SLOW_FACTOR: f64 = 2.0;
+    pub const DEFAULT_SLOW_THRESHOLD: Duration = Duration::from_secs(60);
+    pub const DEFAULT_OUTPUT_LINES: usize = 20;
+    pub const DEFAULT_KILL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+    /// Settings of a run of the given test suites, with the same defaults as the command line
+    /// interface
+    /// No target is set: the test suites can be listed, but running them needs targets.
+    pub fn new(test_suite_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            test_suite_dirs,
+            out_dir: PathBuf::from(Self::DEFAULT_OUT_DIR),
+            keep_history: false,
+            run_metadata: Env::new(),
+            targets: Vec::new(),
+            exec_strategy: ExecutionStrategy::RoundRobin,
+            jobs: None,
+            file_jobs: NonZeroUsize::MIN,
+            dry_run: false,
+            fail_fast: false,
+            strict_xfail: false,
+            strict_skips: false,
+            allowed_skip_tags: Vec::new(),
+            strict_orphans: false,
+            kill_orphans: false,
+            compare_with: None,
+            fail_only_on_regressions: false,
+            test_filter: TestFilter::default(),
+            path_filter: PathFilter::default(),
+            tag_filter: TagFilter::default(),
+            speed_filter: SpeedFilter::All,
+            slow_threshold: Self::DEFAULT_SLOW_THRESHOLD,
+            debug: false,
+            matrix_summary: false,
+            group_output: false,
+            plain: false,
+            color: ColorChoice::Auto,
+            github_annotations: GithubAnnotations::Auto,
+            verbosity: Verbosity::Normal,
+            show_output: ShowOutput::Failed,
+            output_lines: Self::DEFAULT_OUTPUT_LINES,
+            summary_file: None,
+            events_file: None,
+            allure_results: false,
+            locale: Locale::from_env(),
+            slow_factor: Self::DEFAULT_SLOW_FACTOR,
+            retries: None,
+            timeout: None,
+            fixture_timeout: None,
+            kill_grace_period: Self::DEFAULT_KILL_GRACE_PERIOD,
+            clean_env: false,
+            env_pass: Vec::new(),
+            env: Env::new(),
+            redact: Vec::new(),
+            targets_file: None,
+            targets_wait: Duration::ZERO,
+            target_shuffle_seed: None,
+            pause_file: None,
+            command_line: Vec::new(),
+            resumed_run: None,
+            inject_faults: Vec::new(),
+        }
+    }
+
+    /// Apply the settings set by a global config file, over the defaults
+    pub fn with_global_config(mut self, global_config: &GlobalConfig) -> Self {
+        let global_config = global_config.clone();
+        self.out_dir = global_config.out_dir.unwrap_or(self.out_dir);
+        self.keep_history = global_config.keep_history.unwrap_or(self.keep_history);
+        self.targets = global_config.targets.unwrap_or(self.targets);
+        self.exec_strategy = global_config.exec_strategy.unwrap_or(self.exec_strategy);
+        self.jobs = global_config.jobs.or(self.jobs);
+        self.file_jobs = global_config.file_jobs.unwrap_or(self.file_jobs);
+        self.retries = global_config.retries.or(self.retries);
+        self.timeout = global_config.timeout.or(self.timeout);
+        self.fixture_timeout = global_config.fixture_timeout.or(self.fixture_timeout);
+        self.kill_grace_period = global_config
+            .kill_grace_period
+            .unwrap_or(self.kill_grace_period);
+        self.show_output = global_config.show_output.unwrap_or(self.show_output);
+        self.output_lines = global_config.output_lines.unwrap_or(self.output_lines);
+        self.matrix_summary = global_config.matrix_summary.unwrap_or(self.matrix_summary);
+        self.group_output = global_config.group_output.unwrap_or(self.group_output);
+        self.plain = global_config.plain.unwrap_or(self.plain);
+        self.color = global_config.color.unwrap_or(self.color);
+        self.locale = global_config.locale.unwrap_or(self.locale);
+        self
+    }
+
+    pub fn with_out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = out_dir.into();
+        self
+    }
+
+    pub fn with_targets(mut self, targets: Vec<String>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    pub fn with_exec_strategy(mut self, exec_strategy: ExecutionStrategy) -> Self {
+        self.exec_strategy = exec_strategy;
+        self
+    }
+
+    pub fn with_test_filter(mut self, test_filter: TestFilter) -> Self {
+        self.test_filter = test_filter;
+        self
+    }
+
+    pub fn with_path_filter(mut self, path_filter: PathFilter) -> Self {
+        self.path_filter = path_filter;
+        self
+    }
+
+    pub fn with_tag_filter(mut self, tag_filter: TagFilter) -> Self {
+        self.tag_filter = tag_filter;
+        self
+    }
 }
@@ -0,0 +1,83 @@
+use crate::error::{self, Result};
+use crate::execution_strategy::ExecutionStrategy;
+use crate::reporter::locale::Locale;
+use crate::settings::{ColorChoice, ShowOutput};
+use crate::test_suite::config::deserialize_optional_duration;
+
+use serde::{Deserialize, Deserializer};
+
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Settings of the runs set by a config file of the project or of the user, such as
+/// `{ "out-dir": "build/batrun", "exec-strategy": "parallel" }`, overridden by the command line
+/// The settings not set keep their default value.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct GlobalConfig {
+    /// Output directory, relative to the directory of the config file
+    pub out_dir: Option<PathBuf>,
+    pub keep_history: Option<bool>,
+    pub targets: Option<Vec<String>>,
+    pub exec_strategy: Option<ExecutionStrategy>,
+    pub jobs: Option<NonZeroUsize>,
+    pub file_jobs: Option<NonZeroUsize>,
+    pub retries: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub timeout: Option<Duration>,
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub fixture_timeout: Option<Duration>,
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub kill_grace_period: Option<Duration>,
+    pub show_output: Option<ShowOutput>,
+    pub output_lines: Option<usize>,
+    pub matrix_summary: Option<bool>,
+    pub group_output: Option<bool>,
+    pub plain: Option<bool>,
+    pub color: Option<ColorChoice>,
+    #[serde(default, deserialize_with = "deserialize_locale")]
+    pub locale: Option<Locale>,
+}
+
+fn deserialize_locale<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Locale>, D::Error> {
+    let locale = String::deserialize(deserializer)?;
+    locale.parse().map(Some).map_err(serde::de::Error::custom)
+}
+
+impl GlobalConfig {
+    pub const FILE_NAME: &str = "batrun.json";
+
+    /// Find the config file applying to batrun run from the given directory: the closest one in
+    /// the directory and its ancestors, for project settings, or else the one in the config
+    /// directory of the user (`$XDG_CONFIG_HOME/batrun` or `~/.config/batrun`)
+    pub fn discover(dir: &Path) -> Option<PathBuf> {
+        let user_config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+            .map(|dir| dir.join("batrun"));
+        dir.ancestors()
+            .map(Path::to_path_buf)
+            .chain(user_config_dir)
+            .map(|dir| dir.join(Self::FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |details: String| error::kind::InvalidGlobalConfig {
+            filename: path.to_path_buf(),
+            details,
+        };
+        let contents =
+            std::fs::read_to_string(path).map_err(|io_err| invalid(io_err.to_string()))?;
+        let mut global_config: Self =
+            serde_json::from_str(&contents).map_err(|serde_err| invalid(serde_err.to_string()))?;
+        if let (Some(out_dir), Some(dir)) = (&global_config.out_dir, path.parent()) {
+            global_config.out_dir = Some(dir.join(out_dir));
+        }
+        Ok(global_config)
+    }
+}
@@ -0,0 +1,149 @@
+//! A minimal executor of futures, polling its tasks in turn on the current thread
+//!
+//! The tasks are interleaved cooperatively: each one runs until it awaits a future which is not
+//! ready (e.g. [`yield_now`]), the next task being polled then. The thread is parked while no task
+//! can make progress, until one of them is woken.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::Thread;
+
+type Task<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+#[derive(Default)]
+pub(crate) struct SimpleExecutor<'a> {
+    tasks: VecDeque<Task<'a>>,
+}
+
+impl<'a> SimpleExecutor<'a> {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a task, polled for the first time by [`Self::run`]
+    pub(crate) fn spawn(&mut self, future: impl Future<Output = ()> + 'a) {
+        self.tasks.push_back(Box::pin(future));
+    }
+
+    /// Run the tasks until all of them are complete, polling them in the order they were spawned
+    pub(crate) fn run(&mut self) {
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut context = Context::from_waker(&waker);
+        while !self.tasks.is_empty() {
+            for _ in 0..self.tasks.len() {
+                // UNWRAP: there are as many tasks as iterations, pending ones being pushed back
+                let mut task = self.tasks.pop_front().unwrap();
+                if task.as_mut().poll(&mut context).is_pending() {
+                    self.tasks.push_back(task);
+                }
+            }
+            if !self.tasks.is_empty() {
+                // Returns immediately if a task was woken since the last park
+                std::thread::park();
+            }
+        }
+    }
+}
+
+/// Wake the executor by unparking the thread running it
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Let the other tasks of the executor run before resuming the current one
+pub(crate) fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+pub(crate) struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        context.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::sync::Mutex;
+
+    #[test]
+    fn tasks_interleaved_at_yields() {
+        let events = RefCell::new(Vec::new());
+        let mut executor = SimpleExecutor::new();
+        for task in ["a", "b"] {
+            let events = &events;
+            executor.spawn(async move {
+                for step in 0..task.len() + 1 {
+                    events.borrow_mut().push(format!("{task}{step}"));
+                    yield_now().await;
+                }
+            });
+        }
+        executor.spawn(async { events.borrow_mut().push("c".to_string()) });
+        executor.run();
+        drop(executor);
+        assert_eq!(events.into_inner(), ["a0", "b0", "c", "a1", "b1"]);
+    }
+
+    #[test]
+    fn task_woken_from_another_thread() {
+        /// Ready once set by another thread
+        struct Flag(Arc<Mutex<(bool, Option<Waker>)>>);
+        impl Future for Flag {
+            type Output = ();
+            fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+                let mut flag = self.0.lock().unwrap();
+                if flag.0 {
+                    return Poll::Ready(());
+                }
+                flag.1 = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+
+        let flag = Arc::new(Mutex::new((false, None::<Waker>)));
+        let setter = {
+            let flag = flag.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                let mut flag = flag.lock().unwrap();
+                flag.0 = true;
+                if let Some(waker) = flag.1.take() {
+                    waker.wake();
+                }
+            })
+        };
+        let done = RefCell::new(false);
+        let mut executor = SimpleExecutor::new();
+        executor.spawn(async {
+            Flag(flag.clone()).await;
+            *done.borrow_mut() = true;
+        });
+        executor.run();
+        setter.join().unwrap();
+        assert!(*done.borrow());
+    }
+}
@@ -0,0 +1,120 @@
+pub(crate) mod device_farm;
+pub(crate) mod kubernetes;
+pub(crate) mod lab_locks;
+pub(crate) mod simulator;
+
+use crate::error::{self, Result};
+use crate::test_driver::{Env, LogFile};
+use crate::test_suite::config::TargetBackendConfig;
+
+use device_farm::DeviceFarm;
+use kubernetes::KubernetesJob;
+use simulator::Simulator;
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A target backend makes a target available before its test cases run (e.g. by starting a
+/// simulator), and releases it once they are done
+/// It must be `Send` and `Sync` as it is shared between threads by parallel executors
+pub trait TargetBackend: Send + Sync {
+    /// Make the target available, `target_out_dir` being where to store the backend logs
+    fn acquire(&self, target: &str, target_out_dir: &Path) -> Result<Box<dyn TargetLease>>;
+}
+
+/// A target made available by a backend, released when dropped if not released explicitly
+pub trait TargetLease: Send {
+    /// Environment variables passed to the test cases, e.g. to tell them how to reach the target
+    fn env(&self) -> &Env;
+
+    /// Information about the lease worth recording with the run results (e.g. a reservation id)
+    fn metadata(&self) -> Env {
+        Env::new()
+    }
+
+    /// Capture the state of the target right after a test case failed (e.g. a screenshot, the
+    /// console buffer), returning the artifacts written
+    /// The artifact file names start with `artifact_prefix` (the test case output directory
+    /// joined with the test case name). Capturing is best effort: failing to capture something
+    /// must not prevent capturing the rest.
+    fn collect_failure_artifacts(&self, _artifact_prefix: &Path) -> Vec<LogFile> {
+        Vec::new()
+    }
+
+    /// Release the target once all its test cases ran
+    fn release(self: Box<Self>) -> Result<()>;
+}
+
+/// Create the backend described in the config of the test suite located in `test_suite_dir`
+pub(crate) fn from_config(
+    config: &TargetBackendConfig,
+    test_suite_dir: &Path,
+) -> Box<dyn TargetBackend> {
+    match config {
+        TargetBackendConfig::Simulator(config) => {
+            Box::new(Simulator::new(config.clone(), test_suite_dir))
+        }
+        TargetBackendConfig::Kubernetes(config) => {
+            Box::new(KubernetesJob::new(config.clone(), test_suite_dir))
+        }
+        TargetBackendConfig::DeviceFarm(config) => {
+            Box::new(DeviceFarm::new(config.clone(), test_suite_dir))
+        }
+    }
+}
+
+fn error(target: &str, details: impl Into<String>) -> error::Error {
+    error::kind::TargetBackend {
+        target: target.to_string(),
+        details: details.into(),
+    }
+    .into()
+}
+
+/// Path of a failure artifact, e.g. `<artifact prefix>.console.log` for the `.console.log` suffix
+fn artifact_path(artifact_prefix: &Path, suffix: &str) -> PathBuf {
+    let mut path = artifact_prefix.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Copy the end of a log file growing during the run (e.g. a console log) as a failure artifact,
+/// returning the artifact if the log could be copied
+fn copy_log_tail(log: &Path, artifact: &Path, kind: &'static str) -> Option<LogFile> {
+    /// Enough to hold what a target printed around a failure, without copying hours of logs
+    const MAX_TAIL_LEN: u64 = 64 * 1024;
+    let mut log = File::open(log).ok()?;
+    let len = log.metadata().ok()?.len();
+    log.seek(SeekFrom::Start(len.saturating_sub(MAX_TAIL_LEN)))
+        .ok()?;
+    let mut tail = Vec::new();
+    log.take(MAX_TAIL_LEN).read_to_end(&mut tail).ok()?;
+    std::fs::write(artifact, tail).ok()?;
+    Some(LogFile {
+        kind,
+        path: artifact.to_path_buf(),
+        location: None,
+    })
+}
+
+/// Run a command to completion, returning its standard output
+fn run_command(target: &str, command: &mut Command) -> Result<String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command
+        .output()
+        .map_err(|io_err| error(target, format!("cannot execute `{program}`: {io_err}")))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(error(
+            target,
+            format!(
+                "`{program}` failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ))
+    }
+}
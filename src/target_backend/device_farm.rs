@@ -0,0 +1,151 @@
+use crate::error::Result;
+use crate::target_backend::{self, TargetBackend, TargetLease};
+use crate::test_driver::{Env, LogFile};
+use crate::test_suite::config::DeviceFarmConfig;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Reserve a device from a device farm before the test cases of the target run, and release it
+/// once they are done
+/// The device farm API is reached through user-provided lease and release commands, so that any
+/// device farm can be integrated without changing batrun. The lease metadata is recorded in the
+/// target output directory. An optional capture command collects the state of the device when a
+/// test case fails.
+pub(crate) struct DeviceFarm {
+    config: DeviceFarmConfig,
+    /// Directory the lease and release commands are run from
+    working_dir: PathBuf,
+}
+
+struct DeviceLease {
+    target: String,
+    release_command: Command,
+    /// Command line capturing the state of the device, run from `working_dir`
+    capture_command: Option<Vec<String>>,
+    working_dir: PathBuf,
+    metadata: Env,
+    released: bool,
+}
+
+impl DeviceFarm {
+    const LEASE_FILE_NAME: &str = "device-farm.lease.json";
+    const TARGET_ENV_VAR: &str = "BATRUN_TARGET";
+    const ARTIFACT_PREFIX_ENV_VAR: &str = "BATRUN_ARTIFACT_PREFIX";
+
+    pub(crate) fn new(config: DeviceFarmConfig, working_dir: &Path) -> Self {
+        Self {
+            config,
+            working_dir: working_dir.to_path_buf(),
+        }
+    }
+
+    fn command(&self, target: &str, command_line: &[String]) -> Command {
+        Self::command_in(&self.working_dir, target, command_line)
+    }
+
+    fn command_in(working_dir: &Path, target: &str, command_line: &[String]) -> Command {
+        // UNWRAP: the commands are checked not to be empty when loading the test suite config
+        let (program, args) = command_line.split_first().unwrap();
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(working_dir)
+            .env(Self::TARGET_ENV_VAR, target)
+            .stdin(Stdio::null());
+        command
+    }
+
+    /// Parse the `KEY=VALUE` lines printed by the lease command, ignoring the other lines
+    fn parse_metadata(output: &str) -> Env {
+        output
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .filter(|(key, _)| !key.is_empty())
+            .collect()
+    }
+}
+
+impl TargetBackend for DeviceFarm {
+    fn acquire(&self, target: &str, target_out_dir: &Path) -> Result<Box<dyn TargetLease>> {
+        let io_error = |io_err: std::io::Error| target_backend::error(target, io_err.to_string());
+
+        let output = target_backend::run_command(
+            target,
+            &mut self.command(target, &self.config.lease_command),
+        )?;
+        let metadata = Self::parse_metadata(&output);
+        let mut release_command = self.command(target, &self.config.release_command);
+        release_command.envs(&metadata);
+        let lease = DeviceLease {
+            target: target.to_string(),
+            release_command,
+            capture_command: self.config.capture_command.clone(),
+            working_dir: self.working_dir.clone(),
+            metadata,
+            released: false,
+        };
+
+        std::fs::create_dir_all(target_out_dir).map_err(io_error)?;
+        // UNWRAP: a map of strings can always be serialized
+        let lease_json = serde_json::to_string_pretty(&lease.metadata).unwrap();
+        std::fs::write(target_out_dir.join(Self::LEASE_FILE_NAME), lease_json).map_err(io_error)?;
+        Ok(Box::new(lease))
+    }
+}
+
+impl DeviceLease {
+    fn release_device(&mut self) -> Result<()> {
+        self.released = true;
+        target_backend::run_command(&self.target, &mut self.release_command).map(|_| ())
+    }
+}
+
+impl TargetLease for DeviceLease {
+    fn env(&self) -> &Env {
+        &self.metadata
+    }
+
+    fn metadata(&self) -> Env {
+        self.metadata.clone()
+    }
+
+    fn collect_failure_artifacts(&self, artifact_prefix: &Path) -> Vec<LogFile> {
+        let Some(capture_command) = &self.capture_command else {
+            return Vec::new();
+        };
+        let path = target_backend::artifact_path(artifact_prefix, ".device.log");
+        let capture = || -> std::io::Result<()> {
+            let output = File::create(&path)?;
+            DeviceFarm::command_in(&self.working_dir, &self.target, capture_command)
+                .envs(&self.metadata)
+                .env(DeviceFarm::ARTIFACT_PREFIX_ENV_VAR, artifact_prefix)
+                .stdout(output.try_clone()?)
+                .stderr(output)
+                .status()
+                .map(|_| ())
+        };
+        // Best effort, the output tells what went wrong if the command itself failed
+        let _ = capture();
+        vec![LogFile {
+            kind: "device",
+            path,
+            location: None,
+        }]
+    }
+
+    fn release(mut self: Box<Self>) -> Result<()> {
+        self.release_device()
+    }
+}
+
+impl Drop for DeviceLease {
+    fn drop(&mut self) {
+        if !self.released {
+            // Best effort, there is no way to report the error from here
+            let _ = self.release_device();
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::error::Result;
+use crate::target_backend::{self, TargetBackend, TargetLease};
+use crate::test_driver::{Env, LogFile};
+use crate::test_suite::config::KubernetesConfig;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+/// Run the target as a Kubernetes job (e.g. a device simulator), created before the test cases
+/// of the target run and deleted once they are done
+/// The test cases are told the job and pod names so that they can reach the pod (e.g. with
+/// `kubectl exec`), and the pod logs are streamed into the target output directory, their end
+/// being copied next to the log of the test cases which fail.
+pub(crate) struct KubernetesJob {
+    config: KubernetesConfig,
+    /// Directory kubectl is run from
+    working_dir: PathBuf,
+}
+
+struct RunningJob {
+    target: String,
+    kubectl: Kubectl,
+    job_name: String,
+    log_streamer: Option<Child>,
+    pod_log: PathBuf,
+    env: Env,
+    deleted: bool,
+}
+
+/// Builds the kubectl commands
+#[derive(Clone)]
+struct Kubectl {
+    program: String,
+    namespace: Option<String>,
+    working_dir: PathBuf,
+}
+
+impl Kubectl {
+    fn command<'a>(&self, args: impl IntoIterator<Item = &'a str>) -> Command {
+        let mut command = Command::new(&self.program);
+        command.current_dir(&self.working_dir);
+        if let Some(namespace) = &self.namespace {
+            command.args(["--namespace", namespace]);
+        }
+        command.args(args).stdin(Stdio::null());
+        command
+    }
+}
+
+impl KubernetesJob {
+    const POD_LOG_FILE_NAME: &str = "kubernetes.pod.log";
+    const NAMESPACE_ENV_VAR: &str = "BATRUN_K8S_NAMESPACE";
+    const JOB_ENV_VAR: &str = "BATRUN_K8S_JOB";
+    const POD_ENV_VAR: &str = "BATRUN_K8S_POD";
+    /// Kubernetes object names are limited to 63 characters
+    const MAX_JOB_NAME_LEN: usize = 63;
+
+    pub(crate) fn new(config: KubernetesConfig, working_dir: &Path) -> Self {
+        Self {
+            config,
+            working_dir: working_dir.to_path_buf(),
+        }
+    }
+
+    /// A job name unique to this run, valid for Kubernetes whatever the target name
+    fn job_name(target: &str) -> String {
+        let suffix = format!("-{}", std::process::id());
+        let target = target
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect::<String>();
+        let mut name = format!("batrun-{target}");
+        name.truncate(Self::MAX_JOB_NAME_LEN - suffix.len());
+        name.trim_end_matches('-').to_string() + &suffix
+    }
+}
+
+impl TargetBackend for KubernetesJob {
+    fn acquire(&self, target: &str, target_out_dir: &Path) -> Result<Box<dyn TargetLease>> {
+        let io_error = |io_err: std::io::Error| target_backend::error(target, io_err.to_string());
+        std::fs::create_dir_all(target_out_dir).map_err(io_error)?;
+
+        let kubectl = Kubectl {
+            program: self.config.kubectl.clone(),
+            namespace: self.config.namespace.clone(),
+            working_dir: self.working_dir.clone(),
+        };
+        let job_name = Self::job_name(target);
+        let selector = format!("--selector=job-name={job_name}");
+
+        let image = format!("--image={}", self.config.image);
+        let mut create_job = kubectl.command(["create", "job", &job_name, &image]);
+        if !self.config.command.is_empty() {
+            create_job.arg("--").args(&self.config.command);
+        }
+        target_backend::run_command(target, &mut create_job)?;
+
+        // Built right after creation so that the job is deleted if anything goes wrong
+        let mut job = RunningJob {
+            target: target.to_string(),
+            kubectl: kubectl.clone(),
+            job_name: job_name.clone(),
+            log_streamer: None,
+            pod_log: target_out_dir.join(Self::POD_LOG_FILE_NAME),
+            env: self.config.env.clone(),
+            deleted: false,
+        };
+
+        let timeout = format!("--timeout={}s", self.config.ready_timeout.as_secs());
+        target_backend::run_command(
+            target,
+            &mut kubectl.command(["wait", "--for=condition=Ready", "pod", &selector, &timeout]),
+        )?;
+        let pod_name = target_backend::run_command(
+            target,
+            &mut kubectl.command([
+                "get",
+                "pods",
+                &selector,
+                "--output=jsonpath={.items[0].metadata.name}",
+            ]),
+        )?;
+
+        let pod_log = File::create(&job.pod_log).map_err(io_error)?;
+        let pod = format!("pod/{pod_name}");
+        job.log_streamer = Some(
+            kubectl
+                .command(["logs", "--follow", &pod])
+                .stdout(pod_log.try_clone().map_err(io_error)?)
+                .stderr(pod_log)
+                .spawn()
+                .map_err(io_error)?,
+        );
+
+        if let Some(namespace) = &self.config.namespace {
+            job.env
+                .insert(Self::NAMESPACE_ENV_VAR.to_string(), namespace.clone());
+        }
+        job.env.insert(Self::JOB_ENV_VAR.to_string(), job_name);
+        job.env.insert(Self::POD_ENV_VAR.to_string(), pod_name);
+        Ok(Box::new(job))
+    }
+}
+
+impl RunningJob {
+    fn delete(&mut self) -> Result<()> {
+        if let Some(mut log_streamer) = self.log_streamer.take() {
+            // The log streamer may have exited already with the pod, nothing to do then
+            let _ = log_streamer.kill();
+            let _ = log_streamer.wait();
+        }
+        self.deleted = true;
+        target_backend::run_command(
+            &self.target,
+            &mut self
+                .kubectl
+                .command(["delete", "job", &self.job_name, "--wait=false"]),
+        )
+        .map(|_| ())
+    }
+}
+
+impl TargetLease for RunningJob {
+    fn env(&self) -> &Env {
+        &self.env
+    }
+
+    fn collect_failure_artifacts(&self, artifact_prefix: &Path) -> Vec<LogFile> {
+        let artifact = target_backend::artifact_path(artifact_prefix, ".pod.log");
+        target_backend::copy_log_tail(&self.pod_log, &artifact, "pod")
+            .into_iter()
+            .collect()
+    }
+
+    fn release(mut self: Box<Self>) -> Result<()> {
+        self.delete()
+    }
+}
+
+impl Drop for RunningJob {
+    fn drop(&mut self) {
+        if !self.deleted {
+            // Best effort, there is no way to report the error from here
+            let _ = self.delete();
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::error::Result;
+use crate::target_backend::{self, TargetBackend, TargetLease};
+use crate::test_driver::{Env, LogFile};
+use crate::test_suite::config::{LabLocksConfig, LockBackendConfig};
+use crate::time;
+
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Lock the lab resources used by a target before making it available with its own backend, if
+/// any, and unlock them once the target is released
+/// The resources are locked one at a time in name order, so that batrun instances locking the
+/// same resources cannot deadlock.
+pub(crate) struct LabLocks {
+    locks: Arc<LockBackend>,
+    resources: Vec<String>,
+    timeout: Duration,
+    inner: Option<Arc<dyn TargetBackend>>,
+}
+
+/// Where the locks are stored, shared by all the targets of a test suite
+pub(crate) struct LockBackend {
+    config: LockBackendConfig,
+    /// Directory the lock commands are run from
+    working_dir: PathBuf,
+    /// Directory a relative lock directory is relative to
+    out_dir: PathBuf,
+}
+
+struct LockedTarget {
+    locks: Arc<LockBackend>,
+    target: String,
+    /// The resources locked so far, in locking order
+    locked: Vec<String>,
+    inner: Option<Box<dyn TargetLease>>,
+    env: Env,
+}
+
+impl LockBackend {
+    const RETRY_PERIOD: Duration = Duration::from_secs(1);
+    const LOCK_ENV_VAR: &str = "BATRUN_LOCK";
+    const TARGET_ENV_VAR: &str = "BATRUN_TARGET";
+
+    pub(crate) fn new(config: LockBackendConfig, working_dir: &Path, out_dir: &Path) -> Self {
+        Self {
+            config,
+            working_dir: working_dir.to_path_buf(),
+            out_dir: out_dir.to_path_buf(),
+        }
+    }
+
+    fn lock_file(&self, directory: &Path, resource: &str) -> PathBuf {
+        self.out_dir
+            .join(directory)
+            .join(format!("{resource}.lock"))
+    }
+
+    fn command(&self, command_line: &[String], target: &str, resource: &str) -> Command {
+        // UNWRAP: the command is checked not to be empty when loading the test suite config
+        let (program, args) = command_line.split_first().unwrap();
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(&self.working_dir)
+            .env(Self::TARGET_ENV_VAR, target)
+            .env(Self::LOCK_ENV_VAR, resource)
+            .stdin(Stdio::null());
+        command
+    }
+
+    /// Try to lock a resource once, returning who holds it if it is locked already
+    fn try_lock(&self, target: &str, resource: &str) -> Result<Option<String>> {
+        match &self.config {
+            LockBackendConfig::File { directory } => {
+                let lock_file = self.lock_file(directory, resource);
+                std::fs::create_dir_all(self.out_dir.join(directory))
+                    .map_err(|io_err| target_backend::error(target, io_err.to_string()))?;
+                // Creating a file that must not exist is atomic, even on NFS
+                let created = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&lock_file);
+                match created {
+                    Ok(mut file) => {
+                        let owner =
+                            format!("{target} on {} (pid {})", hostname(), std::process::id());
+                        file.write_all(owner.as_bytes())
+                            .map_err(|io_err| target_backend::error(target, io_err.to_string()))?;
+                        Ok(None)
+                    }
+                    Err(io_err) if io_err.kind() == ErrorKind::AlreadyExists => {
+                        let owner = std::fs::read_to_string(&lock_file).unwrap_or_default();
+                        let owner = owner.trim();
+                        Ok(Some(format!("{owner} (`{}`)", lock_file.display())))
+                    }
+                    Err(io_err) => Err(target_backend::error(
+                        target,
+                        format!("cannot create `{}`: {io_err}", lock_file.display()),
+                    )),
+                }
+            }
+            LockBackendConfig::Command { lock_command, .. } => {
+                let mut command = self.command(lock_command, target, resource);
+                match target_backend::run_command(target, &mut command) {
+                    Ok(_) => Ok(None),
+                    Err(crate::error::Error::TargetBackend(error)) => Ok(Some(error.details)),
+                    Err(error) => Err(error),
+                }
+            }
+        }
+    }
+
+    /// Lock a resource, waiting for its holder to unlock it for at most `timeout`
+    fn lock(&self, target: &str, resource: &str, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let Some(holder) = self.try_lock(target, resource)? else {
+                return Ok(());
+            };
+            if Instant::now() >= deadline {
+                return Err(target_backend::error(
+                    target,
+                    format!(
+                        "lab resource `{resource}` still locked after {}, held by: {holder}",
+                        time::format(timeout)
+                    ),
+                ));
+            }
+            std::thread::sleep(Self::RETRY_PERIOD);
+        }
+    }
+
+    fn unlock(&self, target: &str, resource: &str) -> Result<()> {
+        match &self.config {
+            LockBackendConfig::File { directory } => {
+                let lock_file = self.lock_file(directory, resource);
+                std::fs::remove_file(&lock_file).map_err(|io_err| {
+                    target_backend::error(
+                        target,
+                        format!("cannot remove `{}`: {io_err}", lock_file.display()),
+                    )
+                })
+            }
+            LockBackendConfig::Command { unlock_command, .. } => {
+                let mut command = self.command(unlock_command, target, resource);
+                target_backend::run_command(target, &mut command).map(|_| ())
+            }
+        }
+    }
+}
+
+/// The name of the host, only used to tell who holds a lock
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .map(|hostname| hostname.trim().to_string())
+        .unwrap_or_else(|_| "unknown host".to_string())
+}
+
+impl LabLocks {
+    /// Lock the lab resources used by `target` around the given backend, if it uses any
+    pub(crate) fn wrap(
+        config: &LabLocksConfig,
+        locks: &Arc<LockBackend>,
+        target: &str,
+        inner: Option<Arc<dyn TargetBackend>>,
+    ) -> Option<Arc<dyn TargetBackend>> {
+        let resources = config.resources_of(target);
+        if resources.is_empty() {
+            return inner;
+        }
+        Some(Arc::new(Self {
+            locks: locks.clone(),
+            resources,
+            timeout: config.timeout,
+            inner,
+        }))
+    }
+}
+
+impl TargetBackend for LabLocks {
+    fn acquire(&self, target: &str, target_out_dir: &Path) -> Result<Box<dyn TargetLease>> {
+        // Built before locking so that the resources locked are unlocked if anything goes wrong
+        let mut locked_target = LockedTarget {
+            locks: self.locks.clone(),
+            target: target.to_string(),
+            locked: Vec::new(),
+            inner: None,
+            env: Env::new(),
+        };
+        for resource in &self.resources {
+            self.locks.lock(target, resource, self.timeout)?;
+            locked_target.locked.push(resource.clone());
+        }
+        if let Some(inner) = &self.inner {
+            let lease = inner.acquire(target, target_out_dir)?;
+            locked_target.env = lease.env().clone();
+            locked_target.inner = Some(lease);
+        }
+        Ok(Box::new(locked_target))
+    }
+}
+
+impl LockedTarget {
+    /// Unlock the resources in reverse locking order, even if unlocking one of them fails
+    fn unlock_all(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        while let Some(resource) = self.locked.pop() {
+            let unlocked = self.locks.unlock(&self.target, &resource);
+            result = result.and(unlocked);
+        }
+        result
+    }
+}
+
+impl TargetLease for LockedTarget {
+    fn env(&self) -> &Env {
+        &self.env
+    }
+
+    fn metadata(&self) -> Env {
+        self.inner
+            .as_ref()
+            .map(|lease| lease.metadata())
+            .unwrap_or_default()
+    }
+
+    fn collect_failure_artifacts(&self, artifact_prefix: &Path) -> Vec<LogFile> {
+        self.inner
+            .as_ref()
+            .map(|lease| lease.collect_failure_artifacts(artifact_prefix))
+            .unwrap_or_default()
+    }
+
+    fn release(mut self: Box<Self>) -> Result<()> {
+        let released = match self.inner.take() {
+            Some(lease) => lease.release(),
+            None => Ok(()),
+        };
+        let unlocked = self.unlock_all();
+        released.and(unlocked)
+    }
+}
+
+impl Drop for LockedTarget {
+    fn drop(&mut self) {
+        // The target is released before unlocking the resources it uses
+        self.inner.take();
+        // Best effort, there is no way to report the error from here
+        let _ = self.unlock_all();
+    }
+}
@@ -0,0 +1,139 @@
+use crate::error::Result;
+use crate::target_backend::{self, TargetBackend, TargetLease};
+use crate::test_driver::{Env, LogFile, process};
+use crate::test_suite::config::SimulatorConfig;
+use crate::time;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Start a simulator (e.g. QEMU) for the target, wait for it to be ready, and stop it once the
+/// test cases of the target are done
+/// The simulator console (stdout and stderr) is captured in the target output directory, its end
+/// being copied next to the log of the test cases which fail.
+pub(crate) struct Simulator {
+    config: SimulatorConfig,
+    /// Directory the simulator command is run from
+    working_dir: PathBuf,
+}
+
+struct RunningSimulator {
+    child: Child,
+    env: Env,
+    console_log: PathBuf,
+}
+
+impl Simulator {
+    const CONSOLE_LOG_FILE_NAME: &str = "simulator.console.log";
+    /// Passed to the test cases so that they can inspect the simulator console
+    const CONSOLE_LOG_ENV_VAR: &str = "BATRUN_SIMULATOR_CONSOLE_LOG";
+    const READY_POLL_PERIOD: Duration = Duration::from_millis(100);
+
+    pub(crate) fn new(config: SimulatorConfig, working_dir: &Path) -> Self {
+        Self {
+            config,
+            working_dir: working_dir.to_path_buf(),
+        }
+    }
+
+    fn wait_ready(&self, target: &str, child: &mut Child, console_log: &Path) -> Result<()> {
+        let Some(ready_pattern) = &self.config.ready_pattern else {
+            return Ok(());
+        };
+        let deadline = Instant::now() + self.config.ready_timeout;
+        loop {
+            let exit_status = child
+                .try_wait()
+                .map_err(|io_err| target_backend::error(target, io_err.to_string()))?;
+            if let Some(exit_status) = exit_status {
+                return Err(target_backend::error(
+                    target,
+                    format!(
+                        "the simulator exited ({exit_status}) before being ready, see `{}`",
+                        console_log.display()
+                    ),
+                ));
+            }
+            let console = std::fs::read_to_string(console_log).unwrap_or_default();
+            if console.contains(ready_pattern.as_str()) {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(target_backend::error(
+                    target,
+                    format!(
+                        "the simulator was not ready after {}, see `{}`",
+                        time::format(self.config.ready_timeout),
+                        console_log.display()
+                    ),
+                ));
+            }
+            std::thread::sleep(Self::READY_POLL_PERIOD);
+        }
+    }
+}
+
+impl TargetBackend for Simulator {
+    fn acquire(&self, target: &str, target_out_dir: &Path) -> Result<Box<dyn TargetLease>> {
+        let io_error = |io_err: std::io::Error| target_backend::error(target, io_err.to_string());
+
+        std::fs::create_dir_all(target_out_dir).map_err(io_error)?;
+        let console_log = target_out_dir.join(Self::CONSOLE_LOG_FILE_NAME);
+        let console = File::create(&console_log).map_err(io_error)?;
+
+        // UNWRAP: the command is checked not to be empty when loading the test suite config
+        let (program, args) = self.config.command.split_first().unwrap();
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::null())
+            .stdout(console.try_clone().map_err(io_error)?)
+            .stderr(console);
+        // In its own process group, so that it is stopped with the processes it started
+        let child = process::spawn(&mut command, None).map_err(|io_err| {
+            target_backend::error(target, format!("cannot start `{program}`: {io_err}"))
+        })?;
+
+        let mut env = self.config.env.clone();
+        env.insert(
+            Self::CONSOLE_LOG_ENV_VAR.to_string(),
+            console_log.display().to_string(),
+        );
+        // Built before waiting so that the simulator is stopped if it is not ready in time
+        let mut simulator = RunningSimulator {
+            child,
+            env,
+            console_log: console_log.clone(),
+        };
+        self.wait_ready(target, &mut simulator.child, &console_log)?;
+        Ok(Box::new(simulator))
+    }
+}
+
+impl TargetLease for RunningSimulator {
+    fn env(&self) -> &Env {
+        &self.env
+    }
+
+    fn collect_failure_artifacts(&self, artifact_prefix: &Path) -> Vec<LogFile> {
+        let artifact = target_backend::artifact_path(artifact_prefix, ".console.log");
+        target_backend::copy_log_tail(&self.console_log, &artifact, "console")
+            .into_iter()
+            .collect()
+    }
+
+    fn release(self: Box<Self>) -> Result<()> {
+        // Stopped when dropped
+        Ok(())
+    }
+}
+
+impl Drop for RunningSimulator {
+    fn drop(&mut self) {
+        // The simulator may have exited already, the processes it started being killed anyway
+        let _ = process::kill(&mut self.child);
+    }
+}
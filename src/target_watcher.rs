@@ -0,0 +1,53 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Watch a file listing targets, one per line, to let targets attached during a run (e.g.
+/// hotplugged boards) join it
+/// Empty lines and lines starting with `#` are ignored.
+pub struct TargetWatcher {
+    path: PathBuf,
+    wait: Duration,
+}
+
+impl TargetWatcher {
+    /// How often the file is read for new targets
+    pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    /// Create a watcher on the given file, new targets being waited for up to `wait` once all
+    /// known targets are done
+    pub fn new(path: &Path, wait: Duration) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            wait,
+        }
+    }
+
+    /// The targets currently listed in the file
+    /// A missing or unreadable file lists no target, as it may be created later on.
+    pub fn targets(&self) -> Vec<String> {
+        std::fs::read_to_string(&self.path)
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect()
+    }
+
+    /// How long new targets are waited for once all known targets are done
+    pub fn wait(&self) -> Duration {
+        self.wait
+    }
+
+    /// The targets listed in the file which are not part of the known ones, each one once, in
+    /// the order they are listed
+    pub fn new_targets(&self, known_targets: &[&str]) -> Vec<String> {
+        let mut seen = BTreeSet::new();
+        self.targets()
+            .into_iter()
+            .filter(|target| !known_targets.contains(&target.as_str()))
+            .filter(|target| seen.insert(target.clone()))
+            .collect()
+    }
+}
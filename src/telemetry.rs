@@ -0,0 +1,263 @@
+use crate::test_driver::process;
+use crate::test_driver::{Env, LogFile, Metrics};
+use crate::test_suite::config::TelemetryConfig;
+use crate::time;
+
+use serde::Serialize;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Summary of the samples of a telemetry value (e.g. the board temperature) taken while a test
+/// case ran
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TelemetrySummary {
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+    pub samples: usize,
+}
+
+/// The summaries of the telemetry values sampled while a test case ran, by name
+pub type Telemetry = BTreeMap<String, TelemetrySummary>;
+
+/// Values printed by a run of the telemetry command, with the time it started at in seconds
+/// since the test case started
+struct Sample {
+    time: f64,
+    values: Metrics,
+}
+
+/// What a sampling thread gathered once stopped
+#[derive(Default)]
+struct Samples {
+    samples: Vec<Sample>,
+    /// Why the samples which could not be taken failed
+    errors: Vec<String>,
+}
+
+/// Runs the telemetry command of a test suite on its own thread while a test case runs
+pub(crate) struct TelemetrySampler {
+    stop: mpsc::Sender<()>,
+    thread: JoinHandle<Samples>,
+    path: PathBuf,
+}
+
+impl TelemetrySampler {
+    /// Start sampling right away, then every interval until stopped, the samples being written to
+    /// the given file once stopped
+    pub(crate) fn start(
+        config: &TelemetryConfig,
+        test_suite_dir: &Path,
+        env: Env,
+        path: PathBuf,
+    ) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let command = config.command.clone();
+        let interval = config.interval;
+        let test_suite_dir = test_suite_dir.to_path_buf();
+        let thread = thread::spawn(move || {
+            let start = Instant::now();
+            let mut next_sample = start;
+            let mut samples = Samples::default();
+            loop {
+                let time = start.elapsed().as_secs_f64();
+                match Self::sample(&command, &test_suite_dir, &env, interval) {
+                    Ok(values) => samples.samples.push(Sample { time, values }),
+                    Err(error) => samples.errors.push(error),
+                }
+                next_sample += interval;
+                let wait = next_sample.saturating_duration_since(Instant::now());
+                match stopped.recv_timeout(wait) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            samples
+        });
+        Self { stop, thread, path }
+    }
+
+    /// Stop sampling and write the samples as CSV, one column per value, returning the file along
+    /// with the summary of the values and the errors of the samples which could not be taken
+    /// Writing the file is best effort, as the summary is what the reports show.
+    pub(crate) fn stop(self) -> (LogFile, Telemetry, Vec<String>) {
+        let _ = self.stop.send(());
+        let samples = self.thread.join().unwrap_or_else(|_| Samples {
+            samples: Vec::new(),
+            errors: vec!["the sampling thread panicked".to_string()],
+        });
+        let _ = std::fs::write(&self.path, to_csv(&samples.samples));
+        let log_file = LogFile {
+            kind: "telemetry",
+            path: self.path,
+            location: None,
+        };
+        (log_file, summarize(&samples.samples), samples.errors)
+    }
+
+    fn sample(
+        command: &[String],
+        test_suite_dir: &Path,
+        env: &Env,
+        interval: Duration,
+    ) -> Result<Metrics, String> {
+        // UNWRAP: the command is checked not to be empty when loading the test suite config
+        let (program, args) = command.split_first().unwrap();
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(test_suite_dir)
+            .env_clear()
+            .envs(env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        let output = process::spawn(&mut command, None)
+            .and_then(|child| process::wait_with_output(child, Some(interval)))
+            .map_err(|io_err| format!("cannot execute `{program}`: {io_err}"))?
+            .map_err(|_| format!("`{program}` still running after {}", time::format(interval)))?;
+        if !output.status.success() {
+            return Err(format!("`{program}` exited with {}", output.status));
+        }
+        parse_values(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+/// Parse the `name=value` pairs printed by the telemetry command
+fn parse_values(output: &str) -> Result<Metrics, String> {
+    let mut values = Metrics::new();
+    for pair in output.split([' ', ',', '\n', '\r', '\t']) {
+        if pair.is_empty() {
+            continue;
+        }
+        match pair
+            .split_once('=')
+            .and_then(|(name, value)| Some((name, value.parse::<f64>().ok()?)))
+        {
+            Some((name, value)) if !name.is_empty() => {
+                values.insert(name.to_string(), value);
+            }
+            _ => return Err(format!("invalid value `{pair}`, expected `name=number`")),
+        }
+    }
+    if values.is_empty() {
+        return Err("no value printed".to_string());
+    }
+    Ok(values)
+}
+
+fn summarize(samples: &[Sample]) -> Telemetry {
+    let mut telemetry = Telemetry::new();
+    for (name, &value) in samples.iter().flat_map(|sample| &sample.values) {
+        let summary = telemetry.entry(name.clone()).or_insert(TelemetrySummary {
+            min: value,
+            max: value,
+            avg: 0.0,
+            samples: 0,
+        });
+        summary.min = summary.min.min(value);
+        summary.max = summary.max.max(value);
+        summary.samples += 1;
+        summary.avg += (value - summary.avg) / summary.samples as f64;
+    }
+    telemetry
+}
+
+/// The samples as CSV, the values missing from a sample being left empty
+fn to_csv(samples: &[Sample]) -> String {
+    let names = samples
+        .iter()
+        .flat_map(|sample| sample.values.keys())
+        .collect::<BTreeSet<_>>();
+    let mut csv = String::from("time");
+    for name in &names {
+        let _ = write!(csv, ",{name}");
+    }
+    csv.push('\n');
+    for sample in samples {
+        let _ = write!(csv, "{:.3}", sample.time);
+        for name in &names {
+            csv.push(',');
+            if let Some(value) = sample.values.get(*name) {
+                let _ = write!(csv, "{value}");
+            }
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: f64, values: &[(&str, f64)]) -> Sample {
+        Sample {
+            time,
+            values: values
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parse_pairs() {
+        let values = parse_values("temperature=61.5, load=0.8\nfan=1200\n").unwrap();
+        assert_eq!(
+            values,
+            Metrics::from([
+                ("fan".to_string(), 1200.0),
+                ("load".to_string(), 0.8),
+                ("temperature".to_string(), 61.5),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_invalid_pairs() {
+        assert!(parse_values("temperature=hot").is_err());
+        assert!(parse_values("=1").is_err());
+        assert!(parse_values("temperature").is_err());
+        assert!(parse_values(" \n").is_err());
+    }
+
+    #[test]
+    fn summary_of_samples() {
+        let samples = [
+            sample(0.0, &[("temperature", 60.0), ("load", 0.5)]),
+            sample(1.0, &[("temperature", 70.0)]),
+            sample(2.0, &[("temperature", 65.0), ("load", 1.0)]),
+        ];
+        let telemetry = summarize(&samples);
+        assert_eq!(
+            telemetry["temperature"],
+            TelemetrySummary {
+                min: 60.0,
+                max: 70.0,
+                avg: 65.0,
+                samples: 3,
+            }
+        );
+        assert_eq!(telemetry["load"].avg, 0.75);
+        assert_eq!(telemetry["load"].samples, 2);
+    }
+
+    #[test]
+    fn samples_as_csv() {
+        let samples = [
+            sample(0.0, &[("temperature", 60.0), ("load", 0.5)]),
+            sample(1.25, &[("temperature", 70.5)]),
+        ];
+        assert_eq!(
+            to_csv(&samples),
+            "time,load,temperature\n0.000,0.5,60\n1.250,,70.5\n"
+        );
+    }
+}
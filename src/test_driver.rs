@@ -1,31 +1,161 @@
 use crate::error::{Error, Result};
 use crate::test_suite::config::TestSuiteConfig;
-use crate::test_suite::status::TestCaseStatus;
+use crate::test_suite::filter::PathFilter;
+use crate::test_suite::status::{FailReason, TestCaseStatus};
 use crate::test_suite::{TestCase, TestSuite};
 
-use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Driver specific output attached to a test case result
+/// It must be `Send` as test case results are moved between threads by parallel executors
+pub trait DriverOutput: Display + Send {
+    /// Warnings of the test driver about the output of the test case (e.g. unknown output env
+    /// vars), one per issue
+    fn warnings(&self) -> Vec<String>;
+}
+
+/// Performance metrics emitted by a test case, by name
+pub type Metrics = BTreeMap<String, f64>;
+
+/// Environment variables passed to a test case, by name
+pub type Env = BTreeMap<String, String>;
 
-pub trait DriverOutput: Display {}
+/// Options of the test driver of a test suite, from the `driver-options` object of its config,
+/// each test driver parsing the ones it supports
+pub type DriverOptions = serde_json::Map<String, serde_json::Value>;
+
+/// Parse driver options into the options of a test driver, which should deny unknown fields
+pub(crate) fn parse_driver_options<T: DeserializeOwned>(
+    options: &DriverOptions,
+) -> serde_json::Result<T> {
+    serde_json::from_value(serde_json::Value::Object(options.clone()))
+}
+
+/// Signal which ended a test process killed by batrun, after its timeout or because the run was
+/// interrupted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum KillSignal {
+    /// The test process and the processes it started exited within the grace period once asked
+    /// to
+    #[serde(rename = "SIGTERM")]
+    Sigterm,
+    /// Some were still running at the end of the grace period
+    #[serde(rename = "SIGKILL")]
+    Sigkill,
+}
+
+impl Display for KillSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            KillSignal::Sigterm => "SIGTERM",
+            KillSignal::Sigkill => "SIGKILL",
+        })
+    }
+}
 
 pub struct RunTestOutput {
     pub test_case_status: TestCaseStatus,
     pub driver_output: Option<Box<dyn DriverOutput>>,
+    pub metrics: Metrics,
+    /// Why the test case is expected to fail, if it declared so itself
+    pub expected_failure: Option<String>,
+    /// Message of the test case for the user, attached to its result
+    pub message: Option<String>,
+    /// Files the test case asked to collect along with its logs, relative paths being relative
+    /// to its output directory
+    pub artifacts: Vec<PathBuf>,
+    /// Values exported by a setup (e.g. an allocated port), passed as environment variables to
+    /// the test cases of its test file, or of the whole test suite for the test suite setup
+    pub exports: Env,
+    /// Signal which ended the test case, if it was killed
+    pub killed_by: Option<KillSignal>,
+}
+
+impl RunTestOutput {
+    pub fn from_status(test_case_status: TestCaseStatus) -> Self {
+        Self {
+            test_case_status,
+            driver_output: None,
+            metrics: Metrics::new(),
+            expected_failure: None,
+            message: None,
+            artifacts: Vec::new(),
+            exports: Env::new(),
+            killed_by: None,
+        }
+    }
+
+    /// The output of a test case killed after running for longer than its timeout, with the
+    /// signal which ended it if known
+    pub(crate) fn timed_out(timeout: Option<Duration>, killed_by: Option<KillSignal>) -> Self {
+        Self {
+            killed_by,
+            // Only a test case with a timeout can time out
+            ..Self::from_status(TestCaseStatus::Failed(FailReason::Timeout(
+                timeout.unwrap_or_default(),
+            )))
+        }
+    }
 }
 
-pub trait TestDriver {
+/// A log file written by a driver when running a test case
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogFile {
+    /// Short name identifying the kind of log (e.g. `test`, `debug`)
+    pub kind: &'static str,
+    pub path: PathBuf,
+    /// Where the artifact storage stored the file, if not only in the output directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+impl LogFile {
+    /// Where to find the file: where the artifact storage stored it, or its path
+    pub fn location(&self) -> String {
+        self.location
+            .clone()
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+/// A test driver discovers and runs the test cases of a test suite
+/// It must be `Send` and `Sync` as it is shared between threads by parallel executors
+pub trait TestDriver: Send + Sync {
     fn test_file_patterns_default(&self) -> Vec<String>;
 
-    /// Walk through all files in the test suite and return a list of test cases found
+    /// Check the driver options of a test suite, before discovering its tests
+    /// Test drivers supporting no options reject any.
+    fn check_options(&self, options: &DriverOptions) -> serde_json::Result<()> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct NoOptions {}
+        parse_driver_options::<NoOptions>(options).map(|_| ())
+    }
+
+    /// Walk through all files in the test suite selected by the path filter and return a list of
+    /// test cases found
     /// The list must be sorted by files and by execution order within a file (setup,
     /// then test functions, and finally teardown)
     fn discover_tests(
         &self,
         test_suite_dir: &Path,
         test_suite_config: &TestSuiteConfig,
+        path_filter: &PathFilter,
     ) -> Result<TestSuite>;
 
+    /// Run a test case, passing it the given environment variables only: the environment of
+    /// batrun is not inherited, the variables to forward from it being already part of `env`
+    /// The test case must be killed and reported as failed with [`FailReason::Timeout`] if it is
+    /// still running after `timeout`. Its processes must be started in the network namespace
+    /// `network_namespace` (e.g. `/proc/1234/ns/net`) if given, the test case being isolated from
+    /// the network of the host.
+    #[allow(clippy::too_many_arguments)]
     fn run_test(
         &self,
         test_suite_dir: &Path,
@@ -33,8 +163,16 @@ pub trait TestDriver {
         target: &str,
         test_case: &TestCase,
         test_case_out_dir: &Path,
+        env: &Env,
+        timeout: Option<Duration>,
+        network_namespace: Option<&Path>,
     ) -> Result<RunTestOutput>;
 
+    /// The log files written when running the given test case, the main log being first
+    fn log_files(&self, _test_case: &TestCase, _test_case_out_dir: &Path) -> Vec<LogFile> {
+        Vec::new()
+    }
+
     fn test_file_pattern_or_default(&self, test_suite_config: &TestSuiteConfig) -> Vec<String> {
         if test_suite_config.test_file_patterns.is_empty() {
             self.test_file_patterns_default()
@@ -46,15 +184,13 @@ pub trait TestDriver {
     fn matches_file_pattern(&self, filename: &Path, test_suite_config: &TestSuiteConfig) -> bool {
         filename.is_file()
             && self
-                .test_file_pattern_or_default(&test_suite_config)
+                .test_file_pattern_or_default(test_suite_config)
                 .iter()
                 .map(AsRef::as_ref)
                 .map(glob::Pattern::new)
-                .any(|pattern| {
-                    pattern
-                        .expect("provided string should be a valid glob pattern")
-                        .matches_path(filename)
-                })
+                // UNWRAP: the patterns of the test suite config are checked when loading it, and
+                // the default ones of the test drivers are valid
+                .any(|pattern| pattern.unwrap().matches_path(filename))
     }
 
     fn matches_global_fixture_file(
@@ -71,29 +207,35 @@ pub trait TestDriver {
         }
     }
 
-    /// Returns the list of all test files paths in the test suite.
+    /// Returns the list of all test files paths in the test suite selected by the path filter.
     /// The paths returned are relative to the test suite root directory (where the json config file
     /// is located).
     fn discover_test_files(
         &self,
         test_suite_dir: &Path,
         test_suite_config: &TestSuiteConfig,
+        path_filter: &PathFilter,
     ) -> Vec<PathBuf> {
+        // As we are retrieving only subdirs of the test suite dir, making the subdirs absolute
+        // paths relative to the parent test suite dir should never fail
+        let local_path = |path: &Path| {
+            path.strip_prefix(test_suite_dir)
+                .expect("This should not happen")
+                .to_path_buf()
+        };
         let mut test_files = Vec::new();
-        for entry in walkdir::WalkDir::new(test_suite_dir) {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if self.matches_file_pattern(path, test_suite_config) {
-                    if !self.matches_global_fixture_file(path, test_suite_dir, test_suite_config) {
-                        match path.strip_prefix(test_suite_dir) {
-                            Ok(local_path) => test_files.push(local_path.to_path_buf()),
-                            // As we are retrieving only subdirs of the test suite dir, making the
-                            // subdirs absolute paths relative to the parent test suite dir should
-                            // never fail
-                            Err(_) => panic!("This should not happen"),
-                        }
-                    }
-                }
+        let entries = walkdir::WalkDir::new(test_suite_dir)
+            .into_iter()
+            // Do not even walk through excluded directories
+            .filter_entry(|entry| !path_filter.excludes(&local_path(entry.path())))
+            .flatten();
+        for entry in entries {
+            let path = entry.path();
+            if self.matches_file_pattern(path, test_suite_config)
+                && !self.matches_global_fixture_file(path, test_suite_dir, test_suite_config)
+                && path_filter.matches(&local_path(path))
+            {
+                test_files.push(local_path(path));
             }
         }
         test_files.sort();
@@ -101,11 +243,17 @@ pub trait TestDriver {
     }
 }
 
+pub(crate) mod annotations;
 mod bash;
+mod executable;
+mod external;
+pub(crate) mod process;
 
 use bash::BashTestDriver;
+use executable::ExecutableTestDriver;
+use external::ExternalTestDriver;
 
-type TestDriverMap = HashMap<&'static str, Box<dyn TestDriver>>;
+type TestDriverMap = HashMap<String, Box<dyn TestDriver>>;
 
 pub(crate) struct TestDriverRegistry {
     test_drivers: TestDriverMap,
@@ -113,14 +261,37 @@ pub(crate) struct TestDriverRegistry {
 impl TestDriverRegistry {
     pub(crate) fn new() -> Self {
         let mut test_drivers = TestDriverMap::new();
-        test_drivers.insert("bash", Box::new(BashTestDriver::new()));
+        test_drivers.insert("bash".to_string(), Box::new(BashTestDriver::new()));
+        test_drivers.insert(
+            "executable".to_string(),
+            Box::new(ExecutableTestDriver::new()),
+        );
         Self { test_drivers }
     }
 
-    pub(crate) fn get(&self, driver_name: &str) -> Result<&Box<dyn TestDriver>> {
+    /// Register a driver under the given name, replacing the driver registered under that name
+    /// if any
+    pub(crate) fn register(&mut self, driver_name: &str, test_driver: Box<dyn TestDriver>) {
+        self.test_drivers
+            .insert(driver_name.to_string(), test_driver);
+    }
+
+    /// Register the external driver designated by a driver name (`exec:<program>`) if not done
+    /// yet, so that it can be retrieved with [`Self::get`]; other names are left to `get`
+    pub(crate) fn load(&mut self, driver_name: &str) {
+        if self.test_drivers.contains_key(driver_name) {
+            return;
+        }
+        if let Some(test_driver) = ExternalTestDriver::from_driver_name(driver_name) {
+            self.test_drivers
+                .insert(driver_name.to_string(), Box::new(test_driver));
+        }
+    }
+
+    pub(crate) fn get(&self, driver_name: &str) -> Result<&dyn TestDriver> {
         let test_driver = self.test_drivers.get(driver_name);
         match test_driver {
-            Some(test_driver) => Ok(test_driver),
+            Some(test_driver) => Ok(test_driver.as_ref()),
             None => Err(Error::UnknownTestDriver(driver_name.to_string())),
         }
     }
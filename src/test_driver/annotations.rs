@@ -0,0 +1,299 @@
+use crate::error::{self, Error, Result};
+use crate::test_suite::TestCase;
+use crate::test_suite::metadata::TestCaseMetadata;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A list of `key: value` pairs declared in comments using the `# @key: value` syntax
+pub type AnnotationList = Vec<(String, String)>;
+
+/// Annotations found in a test file
+///
+/// An annotation block (consecutive annotation or comment lines) placed right before a function
+/// definition applies to that function. Any other annotation applies to the whole file.
+#[derive(Debug, Default)]
+pub(crate) struct Annotations {
+    file: AnnotationList,
+    functions: HashMap<String, AnnotationList>,
+}
+
+impl Annotations {
+    const PREFIX: &str = "# @";
+
+    /// Load the annotations of a file, which may not be a text file (e.g. a compiled test program)
+    pub(crate) fn load(file_path: &Path) -> Result<Self> {
+        let contents = std::fs::read(file_path).map_err(|io_err| {
+            Error::from(error::kind::TestFileExec {
+                filename: file_path.to_path_buf(),
+                details: io_err.to_string(),
+            })
+        })?;
+        Ok(Self::parse(&String::from_utf8_lossy(&contents)))
+    }
+
+    pub(crate) fn parse(contents: &str) -> Self {
+        let mut annotations = Self::default();
+        let mut pending = AnnotationList::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if let Some(annotation) = Self::parse_annotation(line) {
+                pending.push(annotation);
+            } else if line.starts_with('#') {
+                // Regular comments do not break an annotation block
+            } else if let Some(fn_name) = Self::parse_function_definition(line) {
+                annotations
+                    .functions
+                    .entry(fn_name.to_string())
+                    .or_default()
+                    .append(&mut pending);
+            } else {
+                annotations.file.append(&mut pending);
+            }
+        }
+        annotations.file.append(&mut pending);
+        annotations
+    }
+
+    pub(crate) fn file(&self) -> &AnnotationList {
+        &self.file
+    }
+
+    pub(crate) fn function(&self, fn_name: &str) -> &[(String, String)] {
+        self.functions
+            .get(fn_name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Create a test case for the given function, with metadata built from the file-level
+    /// annotations overridden by the function-level ones
+    pub(crate) fn test_case(
+        &self,
+        file_path: &Path,
+        local_path: &Path,
+        fn_name: &str,
+    ) -> Result<TestCase> {
+        let metadata = TestCaseMetadata::from_annotations(self.file(), self.function(fn_name))
+            .map_err(|details| error::kind::InvalidTestMetadata {
+                filename: file_path.to_path_buf(),
+                details,
+            })?;
+        Ok(TestCase::new(local_path, fn_name).with_metadata(metadata))
+    }
+
+    fn parse_annotation(line: &str) -> Option<(String, String)> {
+        let annotation = line.strip_prefix(Self::PREFIX)?;
+        let (key, value) = annotation.split_once(':')?;
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return None;
+        }
+        Some((key.to_string(), value.trim().to_string()))
+    }
+
+    /// Recognize both `function name {` and `name() {` definition styles
+    fn parse_function_definition(line: &str) -> Option<&str> {
+        let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-' || c == ':';
+        if let Some(rest) = line.strip_prefix("function ") {
+            let rest = rest.trim_start();
+            let end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+            return (end > 0).then(|| &rest[..end]);
+        }
+        let (name, rest) = line.split_once('(')?;
+        let name = name.trim_end();
+        (!name.is_empty() && name.chars().all(is_name_char) && rest.trim_start().starts_with(')'))
+            .then_some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    const CONTENTS: &str = r#"#!/bin/bash
+
+# @expected-duration: 5m
+
+# @expected-duration: 30s
+# A regular comment
+function test_flash {
+    return 0
+}
+
+test_boot() {
+    return 0
+}
+"#;
+
+    #[test]
+    fn test_file_and_function_annotations() {
+        let annotations = Annotations::parse(CONTENTS);
+        let expected = |value: &str| vec![("expected-duration".to_string(), value.to_string())];
+        assert_eq!(annotations.file(), &expected("5m"));
+        assert_eq!(annotations.function("test_flash"), expected("30s"));
+        assert!(annotations.function("test_boot").is_empty());
+    }
+
+    #[test]
+    fn test_timeout_levels() {
+        let annotations = Annotations::parse(
+            "# @timeout: 5m\n\n# @timeout: 30s\ntest_flash() {\n}\ntest_boot() {\n}\n",
+        );
+        let metadata = |fn_name: &str| {
+            annotations
+                .test_case(Path::new("a.sh"), Path::new("a.sh"), fn_name)
+                .unwrap()
+                .metadata()
+                .clone()
+        };
+        let flash = metadata("test_flash");
+        assert_eq!(flash.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(flash.file_timeout, Some(Duration::from_secs(300)));
+        let boot = metadata("test_boot");
+        assert_eq!(boot.timeout, None);
+        assert_eq!(boot.file_timeout, Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn test_needed_files_add_up() {
+        let annotations = Annotations::parse(
+            "# @needs-file: data/common.bin\n\n# @needs-file: data/input.bin\ntest_read() {\n}\n",
+        );
+        let test_case = annotations
+            .test_case(Path::new("a.sh"), Path::new("a.sh"), "test_read")
+            .unwrap();
+        assert_eq!(
+            test_case.metadata().needed_files,
+            [
+                PathBuf::from("data/common.bin"),
+                PathBuf::from("data/input.bin")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tags_add_up() {
+        let annotations = Annotations::parse(
+            "# @tags: network, slow
+
+# @tags: slow wifi
+test_scan() {
+}
+",
+        );
+        let test_case = annotations
+            .test_case(Path::new("a.sh"), Path::new("a.sh"), "test_scan")
+            .unwrap();
+        assert_eq!(test_case.metadata().tags, ["network", "slow", "wifi"]);
+    }
+
+    #[test]
+    fn test_locks_add_up() {
+        let annotations = Annotations::parse(
+            "# @locks: i2c
+
+# @locks: power, i2c
+test_sensor() {
+}
+# @locks:
+test_none() {
+}
+",
+        );
+        let test_case = annotations
+            .test_case(Path::new("a.sh"), Path::new("a.sh"), "test_sensor")
+            .unwrap();
+        assert_eq!(test_case.metadata().locks, ["i2c", "power"]);
+        assert!(
+            annotations
+                .test_case(Path::new("a.sh"), Path::new("a.sh"), "test_none")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_ports_override() {
+        let annotations = Annotations::parse(
+            "# @ports: 1
+
+# @ports: 3
+test_cluster() {
+}
+test_server() {
+}
+# @ports: many
+test_invalid() {
+}
+",
+        );
+        let ports = |fn_name: &str| {
+            annotations
+                .test_case(Path::new("a.sh"), Path::new("a.sh"), fn_name)
+                .map(|test_case| test_case.metadata().ports)
+        };
+        assert_eq!(ports("test_cluster").unwrap(), Some(3));
+        assert_eq!(ports("test_server").unwrap(), Some(1));
+        assert!(ports("test_invalid").is_err());
+    }
+
+    #[test]
+    fn test_shell_options_override() {
+        let annotations = Annotations::parse(
+            "# @shell-options: errexit, pipefail
+
+# @shell-options: none
+test_legacy() {
+}
+test_strict() {
+}
+",
+        );
+        let shell_options = |fn_name: &str| {
+            annotations
+                .test_case(Path::new("a.sh"), Path::new("a.sh"), fn_name)
+                .unwrap()
+                .metadata()
+                .shell_options
+                .clone()
+        };
+        assert_eq!(shell_options("test_legacy"), Some(Vec::new()));
+        assert_eq!(
+            shell_options("test_strict"),
+            Some(vec!["errexit".to_string(), "pipefail".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_independent() {
+        let annotations = Annotations::parse(
+            "# @independent: true
+
+test_a() {
+}
+# @independent: false
+test_b() {
+}
+# @independent: yes
+test_c() {
+}
+",
+        );
+        let independent = |fn_name: &str| {
+            annotations
+                .test_case(Path::new("a.sh"), Path::new("a.sh"), fn_name)
+                .map(|test_case| test_case.metadata().independent)
+                .is_ok_and(|independent| independent)
+        };
+        assert!(independent("test_a"));
+        assert!(!independent("test_b"));
+        assert!(
+            annotations
+                .test_case(Path::new("a.sh"), Path::new("a.sh"), "test_c")
+                .is_err()
+        );
+    }
+}
@@ -1,31 +1,142 @@
 use crate::error::{self, Error, Result};
-use crate::test_driver::{DriverOutput, RunTestOutput, TestDriver};
+use crate::test_driver::annotations::Annotations;
+use crate::test_driver::{
+    DriverOptions, DriverOutput, Env, KillSignal, LogFile, Metrics, RunTestOutput, TestDriver,
+    parse_driver_options, process,
+};
 use crate::test_suite::config::TestSuiteConfig;
-use crate::test_suite::status::{SkipReason, TestCaseStatus};
+use crate::test_suite::filter::PathFilter;
+use crate::test_suite::status::{FailReason, SkipReason, TestCaseStatus};
 use crate::test_suite::{TestCase, TestFile, TestSuite, TestSuiteFixture};
 
+use serde::Deserialize;
+
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::time::Duration;
 
 pub(crate) struct BashTestDriver;
 
+/// Options of the bash driver, from the `driver-options` of the test suite config
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BashOptions {
+    /// Shell options enabled with `shopt -s` before sourcing the test files (e.g. `extglob`, which
+    /// changes how they are parsed)
+    #[serde(default)]
+    shopt: Vec<String>,
+    /// Options the test cases run with, enabled with `set -o`, unless overridden by their
+    /// `# @shell-options:` annotations; strict mode by default
+    #[serde(default = "BashOptions::default_set")]
+    set: Vec<String>,
+}
+
+impl Default for BashOptions {
+    fn default() -> Self {
+        Self {
+            shopt: Vec::new(),
+            set: Self::default_set(),
+        }
+    }
+}
+
+impl BashOptions {
+    fn default_set() -> Vec<String> {
+        ["errexit", "nounset", "pipefail"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn is_valid_name(name: &str) -> bool {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    /// Check the options the test cases run with, from the test suite config or the annotations
+    fn check_set(set: &[String]) -> std::result::Result<(), String> {
+        if let Some(name) = set.iter().find(|name| !Self::is_valid_name(name)) {
+            return Err(format!("invalid shell option `{name}`"));
+        }
+        if set.iter().any(|name| name == "xtrace") {
+            return Err(
+                "`xtrace` is not a shell option of its own, it is enabled by `xtrace` in the test \
+                 suite config"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    fn parse(options: &DriverOptions) -> serde_json::Result<Self> {
+        let options: Self = parse_driver_options(options)?;
+        if let Some(name) = options.shopt.iter().find(|name| !Self::is_valid_name(name)) {
+            return Err(serde::de::Error::custom(format!(
+                "invalid shell option `{name}` in `shopt`"
+            )));
+        }
+        Self::check_set(&options.set)
+            .map_err(|details| serde::de::Error::custom(format!("{details} in `set`")))?;
+        Ok(options)
+    }
+
+    /// Record the options a test case runs with in its metadata: the ones of its annotations,
+    /// or else the ones of the test suite
+    fn resolve_shell_options(&self, file_path: &Path, test_case: TestCase) -> Result<TestCase> {
+        let mut metadata = test_case.metadata().clone();
+        let shell_options = metadata
+            .shell_options
+            .get_or_insert_with(|| self.set.clone());
+        Self::check_set(shell_options).map_err(|details| error::kind::InvalidTestMetadata {
+            filename: file_path.to_path_buf(),
+            details,
+        })?;
+        Ok(test_case.with_metadata(metadata))
+    }
+
+    /// The options of a test suite, checked by [`BashTestDriver::check_options`] before its tests
+    /// are discovered
+    fn of(test_suite_config: &TestSuiteConfig) -> Self {
+        Self::parse(&test_suite_config.driver_options).unwrap_or_default()
+    }
+
+    /// The commands setting the options up, run before sourcing any file
+    fn prelude(&self) -> String {
+        if self.shopt.is_empty() {
+            String::new()
+        } else {
+            format!("shopt -s {}; ", self.shopt.join(" "))
+        }
+    }
+}
+
 impl BashTestDriver {
     const SETUP_FN_NAME: &str = "setup";
     const TEARDOWN_FN_NAME: &str = "teardown";
     const TEST_FN_PREFIX: &str = "test_";
+    const PARAMS_FN_SUFFIX: &str = "_params";
+    /// Prefix of the commands traced into the debug logs, telling where they come from as
+    /// `<file>:<line> (<function>)`, the `+` being repeated by bash for each level of
+    /// indirection (e.g. command substitutions)
+    const XTRACE_PS4: &str =
+        "+ ${BASH_SOURCE[0]-batrun}:${LINENO}${FUNCNAME[0]:+ (${FUNCNAME[0]})}: ";
 
     pub(crate) fn new() -> Self {
         Self
     }
 
-    fn get_functions_in_file(&self, file_path: &Path, fn_regex: &str) -> Result<Vec<String>> {
+    fn get_functions_in_file(
+        &self,
+        options: &BashOptions,
+        file_path: &Path,
+        fn_regex: &str,
+    ) -> Result<Vec<String>> {
         let mut list_functions_command = Command::new("bash");
         let output = list_functions_command
             .arg("-c")
             .arg(format!(
-                "source '{}'; compgen -A function | grep '{}'",
+                "{}source '{}'; compgen -A function | grep '{}'",
+                options.prelude(),
                 file_path.display(),
                 fn_regex,
             ))
@@ -53,11 +164,12 @@ impl BashTestDriver {
 
     fn get_named_function_in_file(
         &self,
+        options: &BashOptions,
         file_path: &Path,
         fn_name: &str,
     ) -> Result<Option<String>> {
         let fn_regex = format!("^{}$", fn_name);
-        let functions = self.get_functions_in_file(file_path, &fn_regex)?;
+        let functions = self.get_functions_in_file(options, file_path, &fn_regex)?;
         match functions.len() {
             0 => Ok(None),
             1 => Ok(Some(functions[0].clone())),
@@ -67,8 +179,98 @@ impl BashTestDriver {
         }
     }
 
+    /// Print the parameters of a parameterized test function, one per line, by calling its
+    /// `<test function>_params` function
+    fn get_params_of_function(
+        &self,
+        options: &BashOptions,
+        file_path: &Path,
+        params_fn_name: &str,
+    ) -> Result<Vec<String>> {
+        let mut params_command = Command::new("bash");
+        let output = params_command
+            .arg("-c")
+            .arg(format!(
+                "{}source '{}'; {}",
+                options.prelude(),
+                file_path.display(),
+                params_fn_name
+            ))
+            .output()
+            .map_err(|io_err| error::kind::TestDriverIo {
+                filename: PathBuf::from(params_command.get_program()),
+                source: io_err,
+            })?;
+        let invalid = |details: String| {
+            Error::from(error::kind::TestFileExec {
+                filename: file_path.to_path_buf(),
+                details,
+            })
+        };
+        if !output.status.success() {
+            return Err(invalid(format!(
+                "`{params_fn_name}` exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let params = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|params| !params.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        if let Some(params) = params
+            .iter()
+            .find(|params| !TestCase::is_valid_params(params))
+        {
+            return Err(invalid(format!(
+                "`{params_fn_name}` printed invalid parameters `{params}`, they must not contain \
+                 brackets"
+            )));
+        }
+        if params.is_empty() {
+            return Err(invalid(format!("`{params_fn_name}` printed no parameters")));
+        }
+        Ok(params)
+    }
+
+    /// Discover the test functions of a file, a test function `test_foo` being expanded into one
+    /// test case per parameter printed by its `test_foo_params` function if any
+    fn get_test_cases_in_file(
+        &self,
+        options: &BashOptions,
+        file_path: &Path,
+        test_case: impl Fn(String) -> Result<TestCase>,
+    ) -> Result<Vec<TestCase>> {
+        let functions = self.get_functions_in_file(
+            options,
+            file_path,
+            &format!("^{}", BashTestDriver::TEST_FN_PREFIX),
+        )?;
+        let is_params_fn = |fn_name: &String| {
+            fn_name
+                .strip_suffix(BashTestDriver::PARAMS_FN_SUFFIX)
+                .is_some_and(|test_fn_name| functions.iter().any(|f| f == test_fn_name))
+        };
+        let mut test_cases = Vec::new();
+        for fn_name in functions.iter().filter(|fn_name| !is_params_fn(fn_name)) {
+            let params_fn_name = format!("{fn_name}{}", BashTestDriver::PARAMS_FN_SUFFIX);
+            let test_case = test_case(fn_name.clone())?;
+            if functions.contains(&params_fn_name) {
+                for params in self.get_params_of_function(options, file_path, &params_fn_name)? {
+                    test_cases.push(test_case.clone().with_params(&params));
+                }
+            } else {
+                test_cases.push(test_case);
+            }
+        }
+        Ok(test_cases)
+    }
+
     fn get_test_suite_fixture(
         &self,
+        options: &BashOptions,
         test_suite_dir: &Path,
         test_suite_config: &TestSuiteConfig,
     ) -> Result<TestSuiteFixture> {
@@ -78,20 +280,37 @@ impl BashTestDriver {
             .map(PathBuf::from)
             .map_or(Ok(TestSuiteFixture::default()), |local_fixture_path| {
                 let fixture_path = test_suite_dir.join(&local_fixture_path);
+                let annotations = Annotations::load(&fixture_path)?;
                 Ok(TestSuiteFixture {
                     setup_test_case: self
-                        .get_named_function_in_file(&fixture_path, BashTestDriver::SETUP_FN_NAME)?
-                        .map(|setup_fn| TestCase::new(&local_fixture_path, &setup_fn)),
+                        .get_named_function_in_file(
+                            options,
+                            &fixture_path,
+                            BashTestDriver::SETUP_FN_NAME,
+                        )?
+                        .map(|setup_fn| {
+                            annotations
+                                .test_case(&fixture_path, &local_fixture_path, &setup_fn)
+                                .and_then(|tc| options.resolve_shell_options(&fixture_path, tc))
+                        })
+                        .transpose()?,
                     teardown_test_case: self
                         .get_named_function_in_file(
+                            options,
                             &fixture_path,
                             BashTestDriver::TEARDOWN_FN_NAME,
                         )?
-                        .map(|teardown_fn| TestCase::new(&local_fixture_path, &teardown_fn)),
+                        .map(|teardown_fn| {
+                            annotations
+                                .test_case(&fixture_path, &local_fixture_path, &teardown_fn)
+                                .and_then(|tc| options.resolve_shell_options(&fixture_path, tc))
+                        })
+                        .transpose()?,
                 })
             })
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn run_test_function_from_file(
         &self,
         test_suite_dir: &Path,
@@ -101,49 +320,91 @@ impl BashTestDriver {
         target: &str,
         out_dir: &Path,
         log_files: LogFiles,
-    ) -> Result<(TestCaseStatus, TestCaseOutput)> {
+        shell_options: Option<&[String]>,
+        env: &Env,
+        timeout: Option<Duration>,
+        network_namespace: Option<&Path>,
+    ) -> Result<(TestCaseStatus, TestCaseOutput, Option<KillSignal>)> {
         let run_fn_command = RunFnCommandBuilder::new()
+            .set_options(&BashOptions::of(test_suite_config))
             .source_fixture_if_necessary(
                 test_suite_config.global_fixture.clone(),
                 file_path,
                 test_suite_dir,
             )
-            .source_test_file(&file_path)
+            .source_test_file(file_path)
             .execute_fn(fn_name, target, out_dir)
             .build();
 
         let mut bash_command = Command::new("bash");
+        bash_command.env_clear().envs(env);
+        let xtrace = if test_suite_config.xtrace {
+            bash_command.arg("-x");
+            // The commands are traced into the debug log rather than mixed with what the test case
+            // prints, after the environment of the test process written there by the executor
+            // PS4 is set by the command rather than in the environment, which bash ignores when
+            // run as root, its own trace going to the discarded stderr
+            format!(
+                "PS4='{ps4}'; exec {{batrun_xtrace_fd}}>>\"{debug_file}\"; echo 'Trace of the commands run:' >&$batrun_xtrace_fd; BASH_XTRACEFD=$batrun_xtrace_fd; ",
+                ps4 = Self::XTRACE_PS4,
+                debug_file = log_files.debug.display()
+            )
+        } else {
+            String::new()
+        };
+        // Recorded in the metadata of the test cases when discovering them
+        let shell_options = shell_options
+            .map(<[String]>::to_vec)
+            .unwrap_or_else(|| BashOptions::of(test_suite_config).set);
+        for shell_option in shell_options {
+            bash_command.arg("-o").arg(shell_option);
+        }
         bash_command
-            .args(["-x", "-e", "-u", "-o", "pipefail"])
             .arg("-c")
-            .arg(&format!(
-                "{{ {run_fn_command} }} &> \"{log_file}\"; {{ env | grep -E '^BATRUN_' || true; }} > \"{envout_file}\";",
+            // The output env vars are collected on exit so that a failing test case can still set
+            // them (e.g. `BATRUN_XFAIL`), `-e` exiting the shell at the first failing command
+            .arg(format!(
+                "trap '{{ env | grep -E \"^BATRUN_\" || true; }} > \"{envout_file}\"' EXIT; {xtrace}{{ {run_fn_command} }} &> \"{log_file}\";",
                 log_file = log_files.test_case.display(),
-                // debug_file = log_files.debug.display(),
                 envout_file = log_files.envout.display()
             ));
 
-        let output = bash_command
-            .output()
-            .map_err(|io_err| error::kind::TestDriverIo {
-                filename: PathBuf::from(bash_command.get_program()),
-                source: io_err,
+        // The outputs of the test case are redirected to the log files by the command itself
+        bash_command.stdout(Stdio::null()).stderr(Stdio::null());
+        let exit_status =
+            process::status(&mut bash_command, timeout, network_namespace).map_err(|io_err| {
+                error::kind::TestDriverIo {
+                    filename: PathBuf::from(bash_command.get_program()),
+                    source: io_err,
+                }
             })?;
 
-        let tc_output = TestCaseOutput::new(&log_files.envout);
+        let tc_output = TestCaseOutput::new(&log_files.envout, env);
 
-        if output.status.success() {
+        let exit_status = match exit_status {
+            Ok(exit_status) => exit_status,
+            Err(signal) => {
+                let timed_out = RunTestOutput::timed_out(timeout, Some(signal));
+                return Ok((timed_out.test_case_status, tc_output, timed_out.killed_by));
+            }
+        };
+        if exit_status.success() {
             if let Some(ref skipped_reason) = tc_output.skipped {
                 return Ok((
                     TestCaseStatus::Skipped(SkipReason::TestCaseSpecificReason(
                         skipped_reason.clone(),
                     )),
                     tc_output,
+                    None,
                 ));
             }
-            Ok((TestCaseStatus::Passed, tc_output))
+            Ok((TestCaseStatus::Passed, tc_output, None))
         } else {
-            Ok((TestCaseStatus::Failed, tc_output))
+            Ok((
+                TestCaseStatus::Failed(FailReason::TestCaseFailure),
+                tc_output,
+                None,
+            ))
         }
     }
 }
@@ -153,35 +414,52 @@ impl TestDriver for BashTestDriver {
         vec!["*.sh".to_string(), "*.bash".to_string()]
     }
 
+    fn check_options(&self, options: &DriverOptions) -> serde_json::Result<()> {
+        BashOptions::parse(options).map(|_| ())
+    }
+
     fn discover_tests(
         &self,
         test_suite_dir: &Path,
         test_suite_config: &TestSuiteConfig,
+        path_filter: &PathFilter,
     ) -> Result<TestSuite> {
         let mut test_files = Vec::new();
+        let options = BashOptions::of(test_suite_config);
 
         let test_suite_fixture =
-            self.get_test_suite_fixture(&test_suite_dir, &test_suite_config)?;
+            self.get_test_suite_fixture(&options, test_suite_dir, test_suite_config)?;
 
-        let test_files_path = self.discover_test_files(test_suite_dir, test_suite_config);
+        let test_files_path =
+            self.discover_test_files(test_suite_dir, test_suite_config, path_filter);
 
         for test_file_local_path in &test_files_path {
-            let test_file_path = test_suite_dir.join(&test_file_local_path);
+            let test_file_path = test_suite_dir.join(test_file_local_path);
+            let annotations = Annotations::load(&test_file_path)?;
+            let test_case = |fn_name: String| {
+                annotations
+                    .test_case(&test_file_path, test_file_local_path, &fn_name)
+                    .and_then(|tc| options.resolve_shell_options(&test_file_path, tc))
+            };
             test_files.push(TestFile {
+                path: test_file_local_path.clone(),
                 setup_test_case: self
-                    .get_named_function_in_file(&test_file_path, BashTestDriver::SETUP_FN_NAME)?
-                    .map(|setup_fn| TestCase::new(&test_file_local_path, &setup_fn)),
+                    .get_named_function_in_file(
+                        &options,
+                        &test_file_path,
+                        BashTestDriver::SETUP_FN_NAME,
+                    )?
+                    .map(test_case)
+                    .transpose()?,
                 teardown_test_case: self
-                    .get_named_function_in_file(&test_file_path, BashTestDriver::TEARDOWN_FN_NAME)?
-                    .map(|teardown_fn| TestCase::new(&test_file_local_path, &teardown_fn)),
-                test_cases: self
-                    .get_functions_in_file(
+                    .get_named_function_in_file(
+                        &options,
                         &test_file_path,
-                        &format!("^{}", BashTestDriver::TEST_FN_PREFIX),
+                        BashTestDriver::TEARDOWN_FN_NAME,
                     )?
-                    .into_iter()
-                    .map(|test_fn| TestCase::new(&test_file_local_path, &test_fn))
-                    .collect(),
+                    .map(test_case)
+                    .transpose()?,
+                test_cases: self.get_test_cases_in_file(&options, &test_file_path, test_case)?,
             });
         }
 
@@ -200,6 +478,9 @@ impl TestDriver for BashTestDriver {
         target: &str,
         test_case: &TestCase,
         test_case_out_dir: &Path,
+        env: &Env,
+        timeout: Option<Duration>,
+        network_namespace: Option<&Path>,
     ) -> Result<RunTestOutput> {
         self.run_test_function_from_file(
             test_suite_dir,
@@ -208,17 +489,46 @@ impl TestDriver for BashTestDriver {
             test_case.name(),
             target,
             test_case_out_dir,
-            LogFiles::new(test_case_out_dir, test_case.name()),
+            LogFiles::new(test_case_out_dir, &test_case.instance_name()),
+            test_case.metadata().shell_options.as_deref(),
+            env,
+            timeout,
+            network_namespace,
+        )
+        .map(
+            |(test_case_status, mut test_case_output, killed_by)| RunTestOutput {
+                test_case_status,
+                metrics: std::mem::take(&mut test_case_output.metrics),
+                expected_failure: test_case_output.xfail.take(),
+                message: test_case_output.message.take(),
+                artifacts: std::mem::take(&mut test_case_output.artifacts),
+                exports: std::mem::take(&mut test_case_output.exports),
+                driver_output: Some(Box::new(BashDriverOutput { test_case_output })),
+                killed_by,
+            },
         )
-        .map(|(test_case_status, test_case_output)| RunTestOutput {
-            test_case_status,
-            driver_output: Some(Box::new(BashDriverOutput { test_case_output })),
-        })
+    }
+
+    fn log_files(&self, test_case: &TestCase, test_case_out_dir: &Path) -> Vec<LogFile> {
+        let log_files = LogFiles::new(test_case_out_dir, &test_case.instance_name());
+        vec![
+            LogFile {
+                kind: "test",
+                path: log_files.test_case,
+                location: None,
+            },
+            LogFile {
+                kind: "envout",
+                path: log_files.envout,
+                location: None,
+            },
+        ]
     }
 }
 
 struct LogFiles {
     test_case: PathBuf,
+    /// Trace of the commands run, also holding the environment of the test process
     debug: PathBuf,
     envout: PathBuf,
 }
@@ -236,18 +546,27 @@ impl LogFiles {
 struct BashDriverOutput {
     test_case_output: TestCaseOutput,
 }
-impl DriverOutput for BashDriverOutput {}
-impl Display for BashDriverOutput {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.test_case_output.unknown_env_vars.len() != 0 {
-            write!(
-                f,
+impl DriverOutput for BashDriverOutput {
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.test_case_output.unknown_env_vars.is_empty() {
+            warnings.push(format!(
                 "Unknown output env vars: {:?}, ignoring.",
                 self.test_case_output.unknown_env_vars
-            )
-        } else {
-            Ok(())
+            ));
         }
+        if !self.test_case_output.invalid_metrics.is_empty() {
+            warnings.push(format!(
+                "Non-numeric metric values: {:?}, ignoring.",
+                self.test_case_output.invalid_metrics
+            ));
+        }
+        warnings
+    }
+}
+impl Display for BashDriverOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.warnings().join(" "))
     }
 }
 
@@ -262,6 +581,11 @@ impl RunFnCommandBuilder {
         }
     }
 
+    fn set_options(mut self, options: &BashOptions) -> RunFnCommandBuilder {
+        self.bash_command += &options.prelude();
+        self
+    }
+
     fn source_fixture(mut self, fixture: &Path) -> RunFnCommandBuilder {
         self.bash_command += &format!(
             "echo Sourcing global fixture '{0}'; source '{0}'; ",
@@ -309,24 +633,59 @@ impl RunFnCommandBuilder {
 
 struct TestCaseOutput {
     unknown_env_vars: Vec<String>,
+    invalid_metrics: Vec<String>,
     skipped: Option<String>,
+    xfail: Option<String>,
+    message: Option<String>,
+    metrics: Metrics,
+    artifacts: Vec<PathBuf>,
+    exports: Env,
 }
 
 impl TestCaseOutput {
-    const KNOWN_OUTPUT_ENV_VARS: &'static [&'static str] = &["BATRUN_SKIPPED"];
+    const KNOWN_OUTPUT_ENV_VARS: &'static [&'static str] = &[
+        "BATRUN_SKIPPED",
+        "BATRUN_XFAIL",
+        "BATRUN_MESSAGE",
+        Self::METRICS_ENV_VAR,
+        "BATRUN_ARTIFACTS",
+    ];
+    /// Metrics are emitted as `BATRUN_METRIC_<name>=<value>`
+    const METRIC_ENV_VAR_PREFIX: &'static str = "BATRUN_METRIC_";
+    /// Metrics can also be emitted at once as `BATRUN_METRICS="<name>=<value> <name>=<value>"`,
+    /// separated by spaces or commas
+    const METRICS_ENV_VAR: &'static str = "BATRUN_METRICS";
+    /// Files to collect are emitted as `BATRUN_ARTIFACTS=<path>:<path>`, like `PATH`
+    const ARTIFACTS_SEPARATOR: char = ':';
+    /// Values exported by setups are emitted as `BATRUN_EXPORT_<name>=<value>`, the test cases
+    /// getting them as `<name>=<value>`
+    const EXPORT_ENV_VAR_PREFIX: &'static str = "BATRUN_EXPORT_";
+
+    fn is_known_output_env_var(envvar: &str) -> bool {
+        Self::KNOWN_OUTPUT_ENV_VARS.contains(&envvar)
+            || [Self::METRIC_ENV_VAR_PREFIX, Self::EXPORT_ENV_VAR_PREFIX]
+                .iter()
+                .any(|prefix| {
+                    envvar
+                        .strip_prefix(prefix)
+                        .is_some_and(|name| !name.is_empty())
+                })
+    }
 
-    fn parse_output_env_vars(envout_file: &Path) -> (HashMap<String, String>, Vec<String>) {
+    /// Parse the output env vars, ignoring the ones passed to the test case and left unchanged
+    fn parse_output_env_vars(
+        envout_file: &Path,
+        input_env: &Env,
+    ) -> (HashMap<String, String>, Vec<String>) {
         let mut unknown_env_vars = Vec::new();
         let env_vars = std::fs::read_to_string(envout_file)
-            .unwrap_or(String::new())
+            .unwrap_or_default()
             .lines()
             .filter_map(|line| {
                 if let Some((envvar, value)) = line.split_once('=') {
-                    if Self::KNOWN_OUTPUT_ENV_VARS
-                        .iter()
-                        .find(|known_env_var| envvar == **known_env_var)
-                        .is_some()
-                    {
+                    if input_env.get(envvar).is_some_and(|input| input == value) {
+                        None
+                    } else if Self::is_known_output_env_var(envvar) {
                         Some((envvar.to_string(), value.to_string()))
                     } else {
                         unknown_env_vars.push(envvar.to_string());
@@ -340,11 +699,60 @@ impl TestCaseOutput {
         (env_vars, unknown_env_vars)
     }
 
-    fn new(envout_file: &Path) -> Self {
-        let (env_vars, unknown_env_vars) = Self::parse_output_env_vars(&envout_file);
+    fn new(envout_file: &Path, input_env: &Env) -> Self {
+        let (env_vars, unknown_env_vars) = Self::parse_output_env_vars(envout_file, input_env);
+        let mut metrics = Metrics::new();
+        let mut invalid_metrics = Vec::new();
+        if let Some(values) = env_vars.get(Self::METRICS_ENV_VAR) {
+            let values = values.split([' ', ',']).filter(|value| !value.is_empty());
+            for value in values {
+                match value
+                    .split_once('=')
+                    .and_then(|(metric, value)| Some((metric, value.parse::<f64>().ok()?)))
+                {
+                    Some((metric, value)) if !metric.is_empty() => {
+                        metrics.insert(metric.to_string(), value);
+                    }
+                    _ => invalid_metrics.push(format!("{}: {value}", Self::METRICS_ENV_VAR)),
+                }
+            }
+        }
+        // The metrics emitted on their own take precedence
+        for (envvar, value) in &env_vars {
+            if let Some(metric) = envvar.strip_prefix(Self::METRIC_ENV_VAR_PREFIX) {
+                match value.trim().parse::<f64>() {
+                    Ok(value) => {
+                        metrics.insert(metric.to_string(), value);
+                    }
+                    Err(_) => invalid_metrics.push(envvar.clone()),
+                }
+            }
+        }
+        invalid_metrics.sort();
         Self {
             unknown_env_vars,
+            invalid_metrics,
             skipped: env_vars.get("BATRUN_SKIPPED").cloned(),
+            xfail: env_vars.get("BATRUN_XFAIL").cloned(),
+            message: env_vars.get("BATRUN_MESSAGE").cloned(),
+            metrics,
+            artifacts: env_vars
+                .get("BATRUN_ARTIFACTS")
+                .map(|paths| {
+                    paths
+                        .split(Self::ARTIFACTS_SEPARATOR)
+                        .filter(|path| !path.is_empty())
+                        .map(PathBuf::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            exports: env_vars
+                .iter()
+                .filter_map(|(envvar, value)| {
+                    let name = envvar.strip_prefix(Self::EXPORT_ENV_VAR_PREFIX)?;
+                    Some((name.to_string(), value.clone()))
+                })
+                .collect(),
         }
     }
 }
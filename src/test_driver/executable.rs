@@ -0,0 +1,145 @@
+use crate::error::{self, Result};
+use crate::test_driver::annotations::Annotations;
+use crate::test_driver::{Env, LogFile, RunTestOutput, TestDriver, process};
+use crate::test_suite::config::TestSuiteConfig;
+use crate::test_suite::filter::PathFilter;
+use crate::test_suite::status::{FailReason, SkipReason, TestCaseStatus};
+use crate::test_suite::{TestCase, TestFile, TestSuite, TestSuiteFixture};
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Run each test file as a single test case, executing it directly with the target and the test
+/// case output directory as arguments, so that test suites can be written in any language
+/// The exit code gives the test case status: 0 means passed, 77 means skipped (as with Automake
+/// and Meson), anything else means failed. Test files may be annotated with `# @key: value`
+/// comments, but test suite and test file fixtures are not supported.
+pub(crate) struct ExecutableTestDriver;
+
+impl ExecutableTestDriver {
+    const SKIP_EXIT_CODE: i32 = 77;
+
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    fn log_file(test_case: &TestCase, test_case_out_dir: &Path) -> PathBuf {
+        test_case_out_dir.join(format!("{}.test.log", test_case.instance_name()))
+    }
+}
+
+impl TestDriver for ExecutableTestDriver {
+    fn test_file_patterns_default(&self) -> Vec<String> {
+        vec!["*.test".to_string(), "*.test.*".to_string()]
+    }
+
+    fn discover_tests(
+        &self,
+        test_suite_dir: &Path,
+        test_suite_config: &TestSuiteConfig,
+        path_filter: &PathFilter,
+    ) -> Result<TestSuite> {
+        let mut test_files = Vec::new();
+        for test_file_local_path in
+            self.discover_test_files(test_suite_dir, test_suite_config, path_filter)
+        {
+            let test_file_path = test_suite_dir.join(&test_file_local_path);
+            // UNWRAP: discovered test files are files, so they have a file name
+            let name = test_file_local_path.file_name().unwrap().to_string_lossy();
+            let test_case = Annotations::load(&test_file_path)?.test_case(
+                &test_file_path,
+                &test_file_local_path,
+                &name,
+            )?;
+            test_files.push(TestFile {
+                path: test_file_local_path.clone(),
+                setup_test_case: None,
+                teardown_test_case: None,
+                test_cases: vec![test_case],
+            });
+        }
+
+        Ok(TestSuite::new(
+            test_suite_dir,
+            test_suite_config.clone(),
+            test_files,
+            TestSuiteFixture::default(),
+        ))
+    }
+
+    fn run_test(
+        &self,
+        test_suite_dir: &Path,
+        _test_suite_config: &TestSuiteConfig,
+        target: &str,
+        test_case: &TestCase,
+        test_case_out_dir: &Path,
+        env: &Env,
+        timeout: Option<Duration>,
+        network_namespace: Option<&Path>,
+    ) -> Result<RunTestOutput> {
+        let log_file_path = Self::log_file(test_case, test_case_out_dir);
+        let log_file =
+            File::create(&log_file_path).map_err(|io_err| error::kind::TestDriverIo {
+                filename: log_file_path.clone(),
+                source: io_err,
+            })?;
+        // Canonicalized so that the test file is not looked up in `PATH`
+        let test_file_path = test_suite_dir
+            .join(test_case.path())
+            .canonicalize()
+            .map_err(|io_err| error::kind::TestDriverIo {
+                filename: test_suite_dir.join(test_case.path()),
+                source: io_err,
+            })?;
+        let mut test_command = Command::new(&test_file_path);
+        test_command
+            .arg(target)
+            .arg(test_case_out_dir)
+            .env_clear()
+            .envs(env)
+            .stdin(Stdio::null())
+            .stdout(
+                log_file
+                    .try_clone()
+                    .map_err(|io_err| error::kind::TestDriverIo {
+                        filename: log_file_path.clone(),
+                        source: io_err,
+                    })?,
+            )
+            .stderr(log_file);
+        let exit_status =
+            process::status(&mut test_command, timeout, network_namespace).map_err(|io_err| {
+                error::kind::TestFileExec {
+                    filename: test_file_path.clone(),
+                    details: io_err.to_string(),
+                }
+            })?;
+        let exit_status = match exit_status {
+            Ok(exit_status) => exit_status,
+            Err(signal) => return Ok(RunTestOutput::timed_out(timeout, Some(signal))),
+        };
+
+        let test_case_status = match exit_status.code() {
+            Some(0) => TestCaseStatus::Passed,
+            Some(Self::SKIP_EXIT_CODE) => {
+                TestCaseStatus::Skipped(SkipReason::TestCaseSpecificReason(format!(
+                    "exited with code {}",
+                    Self::SKIP_EXIT_CODE
+                )))
+            }
+            _ => TestCaseStatus::Failed(FailReason::TestCaseFailure),
+        };
+        Ok(RunTestOutput::from_status(test_case_status))
+    }
+
+    fn log_files(&self, test_case: &TestCase, test_case_out_dir: &Path) -> Vec<LogFile> {
+        vec![LogFile {
+            kind: "test",
+            path: Self::log_file(test_case, test_case_out_dir),
+            location: None,
+        }]
+    }
+}
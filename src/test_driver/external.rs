@@ -0,0 +1,355 @@
+use crate::error::{self, Result};
+use crate::test_driver::annotations::Annotations;
+use crate::test_driver::{
+    DriverOptions, Env, KillSignal, LogFile, Metrics, RunTestOutput, TestDriver, process,
+};
+use crate::test_suite::config::TestSuiteConfig;
+use crate::test_suite::filter::PathFilter;
+use crate::test_suite::status::{FailReason, SkipReason, TestCaseStatus};
+use crate::test_suite::{TestCase, TestFile, TestSuite, TestSuiteFixture};
+
+use serde::{Deserialize, Serialize};
+
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Delegate the discovery and execution of test cases to an external driver program, declared in
+/// the test suite config as `exec:<program>`, so that drivers can be added without rebuilding
+/// batrun
+///
+/// The program is run with the phase as single argument (`discover` or `run`), receives a JSON
+/// request on its standard input and answers with a JSON response on its standard output:
+/// - `discover` gets the test files matching the test file patterns and returns the setup,
+///   teardown and test cases of each file, and of the global fixture if any;
+/// - `run` gets a test case, with its parameters if it is parameterized in the test suite config,
+///   and returns its status (`passed`, `failed` or `skipped`), with an optional reason, metrics
+///   and, for setups, `exports` passed as environment variables to the test cases of their scope.
+///   The standard error of the driver is the test case log.
+///
+/// Both requests hold the `driver-options` of the test suite config as they are, for the program
+/// to check them.
+///
+/// A program containing a `/` is relative to the test suite directory, otherwise it is looked up
+/// in `PATH`.
+pub(crate) struct ExternalTestDriver {
+    program: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct DiscoverRequest<'a> {
+    test_suite_dir: &'a Path,
+    driver_options: &'a DriverOptions,
+    global_fixture: Option<&'a str>,
+    test_files: &'a [PathBuf],
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DiscoverResponse {
+    #[serde(default)]
+    fixture: FixtureFunctions,
+    #[serde(default)]
+    test_files: Vec<DiscoveredTestFile>,
+}
+
+#[derive(Deserialize, Default)]
+struct FixtureFunctions {
+    setup: Option<String>,
+    teardown: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DiscoveredTestFile {
+    path: PathBuf,
+    #[serde(flatten)]
+    fixture: FixtureFunctions,
+    #[serde(default)]
+    test_cases: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct RunRequest<'a> {
+    test_suite_dir: &'a Path,
+    driver_options: &'a DriverOptions,
+    global_fixture: Option<&'a str>,
+    test_file: &'a Path,
+    test_case: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<&'a str>,
+    target: &'a str,
+    out_dir: &'a Path,
+}
+
+#[derive(Deserialize)]
+struct RunResponse {
+    status: RunStatus,
+    reason: Option<String>,
+    #[serde(default)]
+    metrics: Metrics,
+    #[serde(default)]
+    exports: Env,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum RunStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+impl ExternalTestDriver {
+    /// Prefix of the driver names designating external drivers in the test suite config
+    pub(crate) const PREFIX: &str = "exec:";
+
+    /// Create the external driver designated by a driver name, if it is one
+    pub(crate) fn from_driver_name(driver_name: &str) -> Option<Self> {
+        driver_name
+            .strip_prefix(Self::PREFIX)
+            .filter(|program| !program.is_empty())
+            .map(|program| Self {
+                program: program.to_string(),
+            })
+    }
+
+    fn program_path(&self, test_suite_dir: &Path) -> PathBuf {
+        if self.program.contains('/') {
+            test_suite_dir.join(&self.program)
+        } else {
+            PathBuf::from(&self.program)
+        }
+    }
+
+    fn log_file(test_case: &TestCase, test_case_out_dir: &Path) -> PathBuf {
+        test_case_out_dir.join(format!("{}.test.log", test_case.instance_name()))
+    }
+
+    /// Run a phase of the driver, sending it the request and parsing its response, `Err` holding
+    /// the signal which ended the driver if it was killed after `timeout`
+    /// `subject` is the file the request is about, used in error messages.
+    #[allow(clippy::too_many_arguments)]
+    fn call<Response: for<'de> Deserialize<'de>>(
+        &self,
+        test_suite_dir: &Path,
+        phase: &str,
+        request: &impl Serialize,
+        mut command: impl FnMut(&mut Command) -> Result<()>,
+        subject: &Path,
+        timeout: Option<Duration>,
+        network_namespace: Option<&Path>,
+    ) -> Result<std::result::Result<Response, KillSignal>> {
+        let program = self.program_path(test_suite_dir);
+        let io_error = |io_err: std::io::Error| error::kind::TestDriverIo {
+            filename: program.clone(),
+            source: io_err,
+        };
+        let mut driver_command = Command::new(&program);
+        driver_command
+            .arg(phase)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped());
+        command(&mut driver_command)?;
+        let mut driver =
+            process::spawn(&mut driver_command, network_namespace).map_err(io_error)?;
+        // UNWRAP: a map of strings and paths can always be serialized
+        let request = serde_json::to_vec(request).unwrap();
+        if let Some(mut stdin) = driver.stdin.take() {
+            match stdin.write_all(&request) {
+                // The driver does not need the whole request, which is fine
+                Err(io_err) if io_err.kind() == ErrorKind::BrokenPipe => {}
+                written => written.map_err(io_error)?,
+            }
+        }
+        let output = match process::wait_with_output(driver, timeout).map_err(io_error)? {
+            Ok(output) => output,
+            Err(signal) => return Ok(Err(signal)),
+        };
+
+        let exec_error = |details: String| error::kind::TestFileExec {
+            filename: subject.to_path_buf(),
+            details: format!("driver `{}` {phase}: {details}", self.program),
+        };
+        if !output.status.success() {
+            return Err(exec_error(format!("exited with {}", output.status)).into());
+        }
+        serde_json::from_slice(&output.stdout)
+            .map(Ok)
+            .map_err(|serde_err| exec_error(format!("invalid response: {serde_err}")).into())
+    }
+}
+
+impl TestDriver for ExternalTestDriver {
+    fn test_file_patterns_default(&self) -> Vec<String> {
+        // The driver ignores the files it does not handle
+        vec!["*".to_string()]
+    }
+
+    fn check_options(&self, _options: &DriverOptions) -> serde_json::Result<()> {
+        // Checked by the program itself
+        Ok(())
+    }
+
+    fn discover_tests(
+        &self,
+        test_suite_dir: &Path,
+        test_suite_config: &TestSuiteConfig,
+        path_filter: &PathFilter,
+    ) -> Result<TestSuite> {
+        let test_files_path =
+            self.discover_test_files(test_suite_dir, test_suite_config, path_filter);
+        let request = DiscoverRequest {
+            test_suite_dir,
+            driver_options: &test_suite_config.driver_options,
+            global_fixture: test_suite_config.global_fixture.as_deref(),
+            test_files: &test_files_path,
+        };
+        let response: DiscoverResponse = self
+            .call(
+                test_suite_dir,
+                "discover",
+                &request,
+                |command| {
+                    command.stderr(Stdio::inherit());
+                    Ok(())
+                },
+                test_suite_dir,
+                None,
+                None,
+            )?
+            // UNWRAP: the driver cannot time out without a timeout
+            .unwrap();
+
+        let fixture = match &test_suite_config.global_fixture {
+            Some(local_fixture_path) => {
+                let local_fixture_path = PathBuf::from(local_fixture_path);
+                let fixture_path = test_suite_dir.join(&local_fixture_path);
+                let annotations = Annotations::load(&fixture_path)?;
+                let test_case = |fn_name: &String| {
+                    annotations.test_case(&fixture_path, &local_fixture_path, fn_name)
+                };
+                TestSuiteFixture {
+                    setup_test_case: response.fixture.setup.as_ref().map(test_case).transpose()?,
+                    teardown_test_case: response
+                        .fixture
+                        .teardown
+                        .as_ref()
+                        .map(test_case)
+                        .transpose()?,
+                }
+            }
+            None => TestSuiteFixture::default(),
+        };
+
+        let mut test_files = Vec::new();
+        for discovered in response.test_files {
+            if discovered.test_cases.is_empty() {
+                continue;
+            }
+            let test_file_path = test_suite_dir.join(&discovered.path);
+            let annotations = Annotations::load(&test_file_path)?;
+            let test_case = |fn_name: &String| {
+                annotations.test_case(&test_file_path, &discovered.path, fn_name)
+            };
+            test_files.push(TestFile {
+                setup_test_case: discovered
+                    .fixture
+                    .setup
+                    .as_ref()
+                    .map(test_case)
+                    .transpose()?,
+                teardown_test_case: discovered
+                    .fixture
+                    .teardown
+                    .as_ref()
+                    .map(test_case)
+                    .transpose()?,
+                test_cases: discovered
+                    .test_cases
+                    .iter()
+                    .map(test_case)
+                    .collect::<Result<_>>()?,
+                path: discovered.path,
+            });
+        }
+        test_files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(TestSuite::new(
+            test_suite_dir,
+            test_suite_config.clone(),
+            test_files,
+            fixture,
+        ))
+    }
+
+    fn run_test(
+        &self,
+        test_suite_dir: &Path,
+        test_suite_config: &TestSuiteConfig,
+        target: &str,
+        test_case: &TestCase,
+        test_case_out_dir: &Path,
+        env: &Env,
+        timeout: Option<Duration>,
+        network_namespace: Option<&Path>,
+    ) -> Result<RunTestOutput> {
+        let request = RunRequest {
+            test_suite_dir,
+            driver_options: &test_suite_config.driver_options,
+            global_fixture: test_suite_config.global_fixture.as_deref(),
+            test_file: test_case.path(),
+            test_case: test_case.name(),
+            params: test_case.params(),
+            target,
+            out_dir: test_case_out_dir,
+        };
+        let log_file_path = Self::log_file(test_case, test_case_out_dir);
+        let response: std::result::Result<RunResponse, KillSignal> = self.call(
+            test_suite_dir,
+            "run",
+            &request,
+            |command| {
+                let log_file =
+                    File::create(&log_file_path).map_err(|io_err| error::kind::TestDriverIo {
+                        filename: log_file_path.clone(),
+                        source: io_err,
+                    })?;
+                command.env_clear().envs(env).stderr(log_file);
+                Ok(())
+            },
+            &test_suite_dir.join(test_case.path()),
+            timeout,
+            network_namespace,
+        )?;
+        let response = match response {
+            Ok(response) => response,
+            Err(signal) => return Ok(RunTestOutput::timed_out(timeout, Some(signal))),
+        };
+
+        let test_case_status = match response.status {
+            RunStatus::Passed => TestCaseStatus::Passed,
+            RunStatus::Failed => TestCaseStatus::Failed(FailReason::TestCaseFailure),
+            RunStatus::Skipped => TestCaseStatus::Skipped(SkipReason::TestCaseSpecificReason(
+                response.reason.unwrap_or_default(),
+            )),
+        };
+        Ok(RunTestOutput {
+            metrics: response.metrics,
+            exports: response.exports,
+            ..RunTestOutput::from_status(test_case_status)
+        })
+    }
+
+    fn log_files(&self, test_case: &TestCase, test_case_out_dir: &Path) -> Vec<LogFile> {
+        vec![LogFile {
+            kind: "test",
+            path: Self::log_file(test_case, test_case_out_dir),
+            location: None,
+        }]
+    }
+}
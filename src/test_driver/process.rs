@@ -0,0 +1,187 @@
+use crate::interrupt;
+#[cfg(target_os = "linux")]
+use crate::network_isolation;
+use crate::settings::Settings;
+use crate::test_driver::KillSignal;
+
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often a running test process is checked for completion, and for its timeout or the run
+/// being interrupted
+const POLL_PERIOD: Duration = Duration::from_millis(10);
+
+/// How long the processes sent SIGKILL are waited for, as they do not exit right away (and may
+/// be left as zombies by a parent not reaping them soon)
+#[cfg(unix)]
+const EXIT_AFTER_SIGKILL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long the test processes being killed are given to exit once sent SIGTERM, in milliseconds
+static KILL_GRACE_PERIOD_MS: AtomicU64 =
+    AtomicU64::new(Settings::DEFAULT_KILL_GRACE_PERIOD.as_millis() as u64);
+
+/// Set how long the test processes killed after their timeout (or because the run was
+/// interrupted) are given to exit once sent SIGTERM, before being sent SIGKILL, zero sending
+/// SIGKILL right away
+pub(crate) fn set_kill_grace_period(grace_period: Duration) {
+    KILL_GRACE_PERIOD_MS.store(grace_period.as_millis() as u64, Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+fn kill_grace_period() -> Duration {
+    Duration::from_millis(KILL_GRACE_PERIOD_MS.load(Ordering::SeqCst))
+}
+
+/// Start a test process, in its own process group so that it can be killed with all the processes
+/// it started, and in the given network namespace if its test case is isolated from the network
+/// of the host
+pub(crate) fn spawn(command: &mut Command, network_namespace: Option<&Path>) -> io::Result<Child> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(target_os = "linux")]
+    if let Some(network_namespace) = network_namespace {
+        use std::os::unix::process::CommandExt;
+        let netns = std::fs::File::open(network_namespace)?;
+        // SAFETY: entering the namespace is a single async-signal-safe system call
+        unsafe {
+            command.pre_exec(move || network_isolation::enter(&netns));
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    if network_namespace.is_some() {
+        return Err(io::ErrorKind::Unsupported.into());
+    }
+    command.spawn()
+}
+
+/// Run a test process to completion, `Err` holding the signal which ended it if it was killed
+/// after `timeout` (or because the run was interrupted)
+pub(crate) fn status(
+    command: &mut Command,
+    timeout: Option<Duration>,
+    network_namespace: Option<&Path>,
+) -> io::Result<Result<ExitStatus, KillSignal>> {
+    let mut child = spawn(command, network_namespace)?;
+    wait(&mut child, timeout)
+}
+
+/// Wait for a test process started with [`spawn`] and collect its piped outputs, `Err` holding
+/// the signal which ended it if it was killed after `timeout` (or because the run was
+/// interrupted)
+pub(crate) fn wait_with_output(
+    mut child: Child,
+    timeout: Option<Duration>,
+) -> io::Result<Result<Output, KillSignal>> {
+    // Read concurrently so that the process cannot block on a full pipe
+    let stdout = child.stdout.take().map(read_to_end);
+    let stderr = child.stderr.take().map(read_to_end);
+    let status = wait(&mut child, timeout)?;
+    let join = |reader: Option<JoinHandle<Vec<u8>>>| {
+        reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default()
+    };
+    let (stdout, stderr) = (join(stdout), join(stderr));
+    Ok(status.map(|status| Output {
+        status,
+        stdout,
+        stderr,
+    }))
+}
+
+fn read_to_end(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut contents = Vec::new();
+        // The output read so far is kept if the process is killed
+        let _ = pipe.read_to_end(&mut contents);
+        contents
+    })
+}
+
+/// Wait for a test process started with [`spawn`], `Err` holding the signal which ended it if it
+/// was killed after `timeout` (or because the run was interrupted)
+pub(crate) fn wait(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> io::Result<Result<ExitStatus, KillSignal>> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    // Teardowns run after an interrupt must not be killed
+    let interruptible = !interrupt::is_interrupted();
+    if deadline.is_none() && !interruptible {
+        return child.wait().map(Ok);
+    }
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Ok(status));
+        }
+        // Test processes are in their own process group, so they do not get the interrupt from
+        // the terminal
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline)
+            || (interruptible && interrupt::is_interrupted())
+        {
+            return kill(child).map(Err);
+        }
+        thread::sleep(POLL_PERIOD);
+    }
+}
+
+/// Kill a process started with [`spawn`] and the processes it started, which would otherwise keep
+/// running (and keep its pipes open), returning the last signal sent
+/// They are first sent SIGTERM so that they can clean up, then SIGKILL if any of them is still
+/// running after the grace period.
+pub(crate) fn kill(child: &mut Child) -> io::Result<KillSignal> {
+    #[cfg(unix)]
+    {
+        use libc::{SIGKILL, SIGTERM};
+
+        let grace_period = kill_grace_period();
+        if !grace_period.is_zero() && unix::signal_group(child, SIGTERM) {
+            let deadline = Instant::now() + grace_period;
+            loop {
+                // The processes it started may outlive it
+                if child.try_wait()?.is_some() && !unix::signal_group(child, 0) {
+                    return Ok(KillSignal::Sigterm);
+                }
+                if Instant::now() >= deadline {
+                    break;
+                }
+                thread::sleep(POLL_PERIOD);
+            }
+        }
+        // Best effort, the process itself is killed below anyway
+        unix::signal_group(child, SIGKILL);
+    }
+    let _ = child.kill();
+    child.wait()?;
+    // Until the processes it started are gone too, so that they are not found running afterwards
+    // (e.g. as orphans)
+    #[cfg(unix)]
+    {
+        let deadline = Instant::now() + EXIT_AFTER_SIGKILL_TIMEOUT;
+        while unix::signal_group(child, 0) && Instant::now() < deadline {
+            thread::sleep(POLL_PERIOD);
+        }
+    }
+    Ok(KillSignal::Sigkill)
+}
+
+#[cfg(unix)]
+mod unix {
+    use libc::{c_int, kill, pid_t};
+
+    use std::process::Child;
+
+    /// Send a signal to the process group of a test process, returning whether any process of
+    /// the group got it (the signal 0 only checking that the group still has processes)
+    pub(super) fn signal_group(child: &Child, signal: c_int) -> bool {
+        // SAFETY: kill only sends a signal, to the process group led by the test process
+        unsafe { kill(-(child.id() as pid_t), signal) == 0 }
+    }
+}
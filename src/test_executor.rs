@@ -1,55 +1,323 @@
 pub(crate) mod parallel;
+pub(crate) mod parallel_targets;
 pub(crate) mod round_robin;
+mod scheduler;
 pub(crate) mod sequential;
 
-use crate::error::{self, Result};
+use crate::artifact_storage::{ArtifactStorage, LocalStorage};
+use crate::error::{self, Error, Result};
+use crate::history::History;
+use crate::interrupt;
+use crate::network_isolation::{self, NetworkNamespace};
+use crate::orphans;
+use crate::paused_run::{SavedResult, SavedResults};
+use crate::ports::PortAllocator;
+use crate::progress::ProgressTracker;
+use crate::redaction::Redactor;
 use crate::reporter::Reporter;
-use crate::test_driver::{RunTestOutput, TestDriver};
-use crate::test_suite::status::{Statistics, TestCaseStatus};
+use crate::secrets::Secrets;
+use crate::target_backend::{TargetBackend, TargetLease};
+use crate::telemetry::{Telemetry, TelemetrySampler};
+use crate::test_driver::{Env, LogFile, RunTestOutput, TestDriver, process};
+use crate::test_suite::config::TestSuiteConfig;
+use crate::test_suite::id::TestCaseId;
+use crate::test_suite::status::{FailReason, SkipReason, Statistics, TestCaseStatus};
 use crate::test_suite::visitor::{ShouldSkip, Visitor};
 use crate::test_suite::{TestCase, TestSuite};
 use crate::time::TimeInterval;
 
-use std::collections::HashMap;
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Stops all the execution contexts of a run sharing it, e.g. at the first failure in fail-fast
+/// mode
+#[derive(Debug, Default)]
+pub struct AbortSignal {
+    reason: Mutex<Option<SkipReason>>,
+    /// File whose existence pauses the run
+    pause_file: Option<PathBuf>,
+}
+
+impl AbortSignal {
+    /// An abort signal also pausing the run as soon as the given file exists
+    pub fn with_pause_file(pause_file: PathBuf) -> Self {
+        Self {
+            reason: Mutex::default(),
+            pause_file: Some(pause_file),
+        }
+    }
+
+    /// Skip all the test cases not run yet with the given reason, except the teardowns of what
+    /// was already set up; only the first reason is kept
+    pub fn abort(&self, reason: SkipReason) {
+        // UNWRAP: the lock is never held while panicking
+        let mut self_reason = self.reason.lock().unwrap();
+        if self_reason.is_none() {
+            *self_reason = Some(reason);
+        }
+    }
+
+    /// Why the run was aborted, if it was, an interrupt aborting all runs
+    /// A pause request (SIGUSR1 or the pause file) aborts the run too, the test cases not run yet
+    /// being skipped until the run is resumed.
+    pub fn reason(&self) -> Option<SkipReason> {
+        if interrupt::is_interrupted() {
+            return Some(SkipReason::Interrupted);
+        }
+        if interrupt::is_pause_requested()
+            || self.pause_file.as_ref().is_some_and(|path| path.exists())
+        {
+            self.abort(SkipReason::Paused);
+        }
+        // UNWRAP: the lock is never held while panicking
+        self.reason.lock().unwrap().clone()
+    }
+}
+
+/// Locks of the shared resources used by the test cases (e.g. a bus or an instrument), shared by
+/// all the execution contexts of a run so that test cases holding the same lock never run at the
+/// same time, whatever the execution strategy
+#[derive(Debug, Default)]
+pub struct TestLocks {
+    held: Mutex<BTreeSet<String>>,
+    released: Condvar,
+}
+
+impl TestLocks {
+    /// Take all the given locks at once, waiting for the ones held by other test cases to be
+    /// released, so that test cases waiting for each other's locks cannot deadlock
+    fn acquire<'a>(&'a self, locks: &'a [String]) -> HeldLocks<'a> {
+        // UNWRAP: the lock is never held while panicking
+        let mut held = self.held.lock().unwrap();
+        while locks.iter().any(|lock| held.contains(lock)) {
+            // UNWRAP: the lock is never held while panicking
+            held = self.released.wait(held).unwrap();
+        }
+        held.extend(locks.iter().cloned());
+        HeldLocks {
+            test_locks: self,
+            locks,
+        }
+    }
+}
+
+/// Locks taken by a test case, released once dropped
+struct HeldLocks<'a> {
+    test_locks: &'a TestLocks,
+    locks: &'a [String],
+}
+
+impl Drop for HeldLocks<'_> {
+    fn drop(&mut self) {
+        if self.locks.is_empty() {
+            return;
+        }
+        // UNWRAP: the lock is never held while panicking
+        let mut held = self.test_locks.held.lock().unwrap();
+        for lock in self.locks {
+            held.remove(lock);
+        }
+        self.test_locks.released.notify_all();
+    }
+}
 
 pub trait Executor<'tr> {
     fn execute(
         &self,
-        reporter: &'tr Box<dyn Reporter>,
-        test_driver: &'tr Box<dyn TestDriver>,
+        reporter: &'tr dyn Reporter,
+        test_driver: &'tr dyn TestDriver,
         test_suite: &'tr TestSuite,
         exec_contexts: &'tr mut [ExecutionContext],
     );
 }
 
+/// Where the expected duration of a test case comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedDurationSource {
+    /// Declared by the test author in the test case metadata
+    Declared,
+    /// 95th percentile of the durations recorded during previous runs
+    HistoricalP95,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExpectedDuration {
+    pub duration: Duration,
+    pub source: ExpectedDurationSource,
+}
+
+impl ExpectedDuration {
+    fn resolve(test_case: &TestCase, target: &str, history: &History) -> Option<Self> {
+        if let Some(duration) = test_case.metadata().expected_duration {
+            return Some(Self {
+                duration,
+                source: ExpectedDurationSource::Declared,
+            });
+        }
+        history
+            .get(&test_case.id(), target)
+            .and_then(|tc_history| tc_history.duration_p95())
+            .map(|duration| Self {
+                duration,
+                source: ExpectedDurationSource::HistoricalP95,
+            })
+    }
+}
+
+/// How long the test cases which would run on a target are expected to take, estimated in
+/// dry-run mode from their expected durations
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TargetEstimate {
+    pub target: String,
+    /// Sum of the expected durations of the test cases which would run
+    pub duration: Duration,
+    /// Number of test cases which would run, fixtures included
+    pub test_cases: usize,
+    /// Test cases which would run without expected duration, neither declared nor recorded by
+    /// previous runs, left out of the estimated duration
+    pub unknown: Vec<TestCaseId>,
+}
+
+/// A failed attempt at running a test case, which was then run again
+#[derive(Debug, Clone)]
+pub struct FailedAttempt {
+    pub reason: FailReason,
+    pub duration: Option<Duration>,
+    /// The log files of the attempt, renamed so that the next attempts do not overwrite them
+    pub log_files: Vec<LogFile>,
+}
+
+/// The last lines a failed test case wrote to its main log file, once redacted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapturedOutput {
+    pub lines: Vec<String>,
+    /// Number of lines written before them, left out
+    pub omitted: usize,
+}
+
+impl CapturedOutput {
+    /// The last `count` lines of the given output, all of them if `count` is 0
+    pub fn tail(output: &str, count: usize) -> Self {
+        let lines = output.lines().collect::<Vec<_>>();
+        let omitted = match count {
+            0 => 0,
+            count => lines.len().saturating_sub(count),
+        };
+        Self {
+            lines: lines[omitted..]
+                .iter()
+                .map(|line| line.to_string())
+                .collect(),
+            omitted,
+        }
+    }
+}
+
+/// Where the timeout of a test case comes from, by order of precedence
+/// The fixture sources only apply to setups and teardowns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimeoutSource {
+    /// Given on the command line for all fixtures
+    FixtureCommandLine,
+    /// Declared in the test suite config for all its fixtures
+    TestSuiteFixture,
+    /// Given on the command line for all test cases
+    CommandLine,
+    /// Declared by the test author for the test case
+    TestCase,
+    /// Declared by the test author for all the test cases of the test file
+    TestFile,
+    /// Declared in the test suite config for all its test cases
+    TestSuite,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EffectiveTimeout {
+    pub duration: Duration,
+    pub source: TimeoutSource,
+}
+
+impl EffectiveTimeout {
+    /// Resolve the timeout of a test case, the one of a fixture being resolved from the fixture
+    /// timeouts first, the timeout declared by the fixture itself included
+    fn resolve(
+        command_line: Option<Duration>,
+        fixture_command_line: Option<Duration>,
+        test_case: &TestCase,
+        test_suite: &TestSuite,
+    ) -> Option<Self> {
+        let metadata = test_case.metadata();
+        let config = test_suite.config();
+        let fixture_timeouts = [
+            (fixture_command_line, TimeoutSource::FixtureCommandLine),
+            (metadata.timeout, TimeoutSource::TestCase),
+            (config.fixture_timeout, TimeoutSource::TestSuiteFixture),
+        ];
+        let timeouts = [
+            (command_line, TimeoutSource::CommandLine),
+            (metadata.timeout, TimeoutSource::TestCase),
+            (metadata.file_timeout, TimeoutSource::TestFile),
+            (config.timeout, TimeoutSource::TestSuite),
+        ];
+        let fixture_timeouts = fixture_timeouts
+            .into_iter()
+            .filter(|_| test_suite.is_fixture(test_case));
+        fixture_timeouts
+            .chain(timeouts)
+            .find_map(|(duration, source)| duration.map(|duration| Self { duration, source }))
+    }
+}
+
 pub struct TestCaseExecInfo {
     result: Result<RunTestOutput>,
     duration: TimeInterval,
     out_dir: PathBuf,
+    log_files: Vec<LogFile>,
+    failure_artifacts: Vec<LogFile>,
+    expected_duration: Option<ExpectedDuration>,
+    slow: bool,
+    failed_attempts: Vec<FailedAttempt>,
+    max_attempts: u32,
+    timeout: Option<EffectiveTimeout>,
+    telemetry: Telemetry,
 }
 impl TestCaseExecInfo {
-    fn new(out_dir: PathBuf) -> Self {
+    fn new(out_dir: PathBuf, expected_duration: Option<ExpectedDuration>) -> Self {
         Self {
-            result: Ok(RunTestOutput {
-                test_case_status: TestCaseStatus::NotRun,
-                driver_output: None,
-            }),
+            result: Ok(RunTestOutput::from_status(TestCaseStatus::NotRun)),
             duration: TimeInterval::new(),
             out_dir,
+            log_files: Vec::new(),
+            failure_artifacts: Vec::new(),
+            expected_duration,
+            slow: false,
+            failed_attempts: Vec::new(),
+            max_attempts: 1,
+            timeout: None,
+            telemetry: Telemetry::new(),
         }
     }
     pub fn set_result(&mut self, result: Result<RunTestOutput>) {
         match result {
             Ok(RunTestOutput {
                 test_case_status: TestCaseStatus::NotRun,
-                driver_output: _,
+                ..
             }) => {
                 panic!("Test case status cannot be reset")
             }
             Ok(RunTestOutput {
                 test_case_status: TestCaseStatus::Running,
-                driver_output: _,
+                ..
             }) => {
                 self.result = result;
                 self.duration = TimeInterval::new();
@@ -63,32 +331,626 @@ impl TestCaseExecInfo {
     pub fn result(&self) -> &Result<RunTestOutput> {
         &self.result
     }
+    /// The directory where the test case output and artifacts are written
+    pub fn out_dir(&self) -> &Path {
+        &self.out_dir
+    }
+    /// The log files written by the driver, the main log being first, then the artifacts of the
+    /// test case
+    /// Empty if the test case was not executed by the driver (e.g. skipped).
+    pub fn log_files(&self) -> &[LogFile] {
+        &self.log_files
+    }
+    /// The main log file written by the driver, if any
+    pub fn main_log_file(&self) -> Option<&LogFile> {
+        self.log_files.first()
+    }
+    /// The artifacts collected right after the test case failed: the state of the target
+    /// captured by its backend, then the output of the on-failure command of the test suite
+    pub fn failure_artifacts(&self) -> &[LogFile] {
+        &self.failure_artifacts
+    }
+    /// The summary of the telemetry sampled during the last attempt, empty if none was
+    pub fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+    /// When the last attempt at running the test case started
+    pub fn started_at(&self) -> SystemTime {
+        self.duration.started_at()
+    }
+    /// The time taken by the test case, once finished
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration.elapsed()
+    }
+    pub fn expected_duration(&self) -> Option<&ExpectedDuration> {
+        self.expected_duration.as_ref()
+    }
+    /// The timeout the test case ran with, and where it comes from, if it had one
+    pub fn timeout(&self) -> Option<&EffectiveTimeout> {
+        self.timeout.as_ref()
+    }
+    /// Whether the test case took longer than its expected duration by more than the configured
+    /// slow factor
+    pub fn is_slow(&self) -> bool {
+        self.slow
+    }
+    fn check_slow(&mut self, slow_factor: f64) {
+        let executed = matches!(
+            self.result.as_ref().map(|output| &output.test_case_status),
+            Ok(TestCaseStatus::Passed
+                | TestCaseStatus::Failed(_)
+                | TestCaseStatus::XFailed(_)
+                | TestCaseStatus::XPassed(_))
+        );
+        self.slow = match (executed, self.duration(), self.expected_duration) {
+            (true, Some(duration), Some(expected)) => {
+                duration > expected.duration.mul_f64(slow_factor)
+            }
+            _ => false,
+        };
+    }
+    /// The attempts at running the test case which failed before the current one, in order
+    pub fn failed_attempts(&self) -> &[FailedAttempt] {
+        &self.failed_attempts
+    }
+    /// The number of the current attempt at running the test case, starting from 1
+    pub fn attempt(&self) -> u32 {
+        self.failed_attempts.len() as u32 + 1
+    }
+    /// The number of attempts the test case is given to pass
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+    /// Whether the test case passed after failing at least once
+    pub fn is_flaky(&self) -> bool {
+        !self.failed_attempts.is_empty()
+            && matches!(
+                self.result.as_ref().map(|output| &output.test_case_status),
+                Ok(TestCaseStatus::Passed)
+            )
+    }
+    /// Whether the test case failed and may be run again
+    fn should_retry(&self) -> bool {
+        self.attempt() < self.max_attempts
+            && matches!(
+                self.result.as_ref().map(|output| &output.test_case_status),
+                Ok(TestCaseStatus::Failed(_))
+            )
+    }
+    /// Keep the failed attempt before running the test case again, moving its log files and
+    /// failure artifacts aside
+    fn record_failed_attempt(&mut self) {
+        let Ok(RunTestOutput {
+            test_case_status: TestCaseStatus::Failed(reason),
+            ..
+        }) = &self.result
+        else {
+            return;
+        };
+        let attempt = self.attempt();
+        let log_files = self
+            .log_files
+            .iter()
+            .chain(&self.failure_artifacts)
+            .filter_map(|log_file| {
+                let stem = log_file.path.file_stem()?.to_string_lossy();
+                // The artifacts dropped by the test cases may have no extension
+                let file_name = match log_file.path.extension() {
+                    Some(extension) => {
+                        format!("{stem}.attempt{attempt}.{}", extension.to_string_lossy())
+                    }
+                    None => format!("{stem}.attempt{attempt}"),
+                };
+                let attempt_path = log_file.path.with_file_name(file_name);
+                std::fs::rename(&log_file.path, &attempt_path).ok()?;
+                Some(LogFile {
+                    kind: log_file.kind,
+                    path: attempt_path,
+                    location: None,
+                })
+            })
+            .collect();
+        self.failed_attempts.push(FailedAttempt {
+            reason: reason.clone(),
+            duration: self.duration(),
+            log_files,
+        });
+    }
 }
 
 pub struct ExecutionContext {
     target: String,
-    exec_info: HashMap<TestCase, TestCaseExecInfo>,
+    /// Execution information of each test case, in test suite order
+    exec_info: Vec<(TestCase, TestCaseExecInfo)>,
+    /// Position of each test case in `exec_info`
+    exec_info_index: HashMap<TestCaseId, usize>,
+    slow_factor: f64,
+    /// When set, all test cases run in this context are skipped with this reason
+    skip_reason: Option<SkipReason>,
+    /// Whether the target joined the run after it started
+    late_joined: bool,
+    /// Where the logs not specific to a test case are stored
+    target_out_dir: PathBuf,
+    /// Makes the target available before running its first test case, if set
+    backend: Option<Arc<dyn TargetBackend>>,
+    /// The target made available by the backend, until released
+    lease: Option<Box<dyn TargetLease>>,
+    /// Why the backend could not make the target available, if it failed
+    acquire_error: Option<String>,
+    /// Information about the last lease of the target, kept once released
+    lease_metadata: Env,
+    /// Environment variables of batrun forwarded to the test processes
+    host_env: Env,
+    /// Environment variables declared in the test suite config, for all targets then for this
+    /// target
+    config_env: Env,
+    /// Environment variables set for the whole run (e.g. with `--env`)
+    run_env: Env,
+    /// Values exported by the test suite setup on this target, passed to all the test cases
+    suite_exports: Env,
+    /// Values exported by the setups of the test files on this target, keyed by test file path
+    file_exports: HashMap<PathBuf, Env>,
+    /// Secrets passed to the test processes
+    secrets: Arc<Secrets>,
+    /// Removes the secrets and whatever matches the redaction rules from the logs
+    redactor: Arc<Redactor>,
+    /// Keeps the log files and artifacts of the test cases once they ran
+    artifact_storage: Arc<dyn ArtifactStorage>,
+    /// Number of times a failed test case is run again
+    retries: u32,
+    /// Timeout of all test cases, overriding the ones they declare
+    timeout: Option<Duration>,
+    /// Timeout of all fixtures, overriding the ones they declare
+    fixture_timeout: Option<Duration>,
+    /// Whether to abort the run at the first failure
+    fail_fast: bool,
+    /// Whether a test case expected to fail which passes counts as a failure
+    strict_xfail: bool,
+    /// Whether a test case skipped by itself or because of missing files fails, unless it has
+    /// one of the `allowed_skip_tags`
+    strict_skips: bool,
+    allowed_skip_tags: Vec<String>,
+    /// Whether a test case leaving processes running once done fails, and whether they are killed
+    strict_orphans: bool,
+    kill_orphans: bool,
+    /// Whether to report the lines written to the main log files of the test cases as they run
+    stream_output: bool,
+    /// Number of lines of the main log file of the failed test cases to report, all of them if
+    /// 0, none if not set
+    failure_output_lines: Option<usize>,
+    /// Aborts the run, shared with the other execution contexts
+    abort_signal: Arc<AbortSignal>,
+    /// Locks of the shared resources used by the test cases, shared with the other execution
+    /// contexts
+    test_locks: Arc<TestLocks>,
+    /// Allocates the ports of the test cases, shared with the other execution contexts
+    port_allocator: Arc<PortAllocator>,
+    /// Results of the test cases which ran before the run was paused, reported instead of
+    /// running them again now that it is resumed
+    restored: SavedResults,
+    /// Whether the test cases which would run are only reported as such
+    dry_run: bool,
+    /// Number of test cases of the test suite deselected by the filters of the run
+    filtered_out: usize,
+    /// Follows how far the targets went through the test suite, shared with the other execution
+    /// contexts
+    progress: Arc<ProgressTracker>,
 }
 
 impl<'tr> ExecutionContext {
-    pub fn new(test_suite: &'tr TestSuite, target: String, out_dir: &Path) -> Self {
-        let mut exec_info = HashMap::<TestCase, TestCaseExecInfo>::new();
-        Visitor::new(&test_suite).visit_all_ok(|tc, _| {
+    const TARGET_ENV_VAR: &'static str = "BATRUN_TARGET";
+    const TEST_CASE_ENV_VAR: &'static str = "BATRUN_TEST_CASE";
+    const OUT_DIR_ENV_VAR: &'static str = "BATRUN_OUT_DIR";
+    const PARAM_ENV_VAR: &'static str = "BATRUN_PARAM";
+    /// The ports allocated to the test case, separated by spaces
+    const PORTS_ENV_VAR: &'static str = "BATRUN_PORTS";
+    /// Directory where the test cases drop the files to collect as artifacts
+    const ARTIFACTS_DIR_ENV_VAR: &'static str = "BATRUN_ARTIFACTS_DIR";
+    /// Timeout of the on-failure command when there is no fixture timeout
+    const ON_FAILURE_DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+    pub fn new(
+        test_suite: &'tr TestSuite,
+        target: String,
+        out_dir: &Path,
+        history: &History,
+        slow_factor: f64,
+    ) -> Self {
+        let mut exec_info = Vec::new();
+        let mut exec_info_index = HashMap::new();
+        Visitor::new(test_suite).visit_all_ok(|tc, _| {
             let out_dir_result = Self::prepare_test_case_out_dir(out_dir, &target, tc);
+            let expected_duration = ExpectedDuration::resolve(tc, &target, history);
             match out_dir_result {
-                Ok(out_dir) => exec_info.insert(tc.clone(), TestCaseExecInfo::new(out_dir)),
+                Ok(out_dir) => {
+                    exec_info_index.insert(tc.id(), exec_info.len());
+                    exec_info.push((
+                        tc.clone(),
+                        TestCaseExecInfo::new(out_dir, expected_duration),
+                    ));
+                }
                 Err(err) => panic!("{:?}", err),
             };
         });
-        Self { target, exec_info }
+        Self {
+            target_out_dir: out_dir.join(&target),
+            target,
+            exec_info,
+            exec_info_index,
+            slow_factor,
+            skip_reason: None,
+            late_joined: false,
+            backend: None,
+            lease: None,
+            acquire_error: None,
+            lease_metadata: Env::new(),
+            host_env: Env::new(),
+            config_env: Env::new(),
+            run_env: Env::new(),
+            suite_exports: Env::new(),
+            file_exports: HashMap::new(),
+            secrets: Arc::default(),
+            redactor: Arc::default(),
+            artifact_storage: Arc::new(LocalStorage),
+            retries: 0,
+            timeout: None,
+            fixture_timeout: None,
+            fail_fast: false,
+            strict_xfail: false,
+            strict_skips: false,
+            allowed_skip_tags: Vec::new(),
+            strict_orphans: false,
+            kill_orphans: false,
+            stream_output: false,
+            failure_output_lines: None,
+            abort_signal: Arc::default(),
+            test_locks: Arc::default(),
+            port_allocator: Arc::default(),
+            restored: SavedResults::new(),
+            dry_run: false,
+            filtered_out: test_suite.filtered_out().len(),
+            progress: Arc::default(),
+        }
+    }
+
+    /// Forward these environment variables of batrun to the test processes, which get no other
+    /// variables than these and the ones of the target lease
+    pub fn with_host_env(mut self, host_env: Env) -> Self {
+        self.host_env = host_env;
+        self
+    }
+
+    /// Pass these environment variables declared in the test suite config to the test processes,
+    /// overriding the forwarded ones
+    pub fn with_config_env(mut self, config_env: Env) -> Self {
+        self.config_env = config_env;
+        self
+    }
+
+    /// Pass these environment variables set for the whole run to the test processes, overriding
+    /// the forwarded ones, the ones of the test suite config and the secrets
+    pub fn with_run_env(mut self, run_env: Env) -> Self {
+        self.run_env = run_env;
+        self
+    }
+
+    /// Pass these secrets to the test processes
+    /// Their values are only redacted from the logs if the redactor knows them too.
+    pub fn with_secrets(mut self, secrets: Arc<Secrets>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Redact sensitive data from the logs of the test cases and the reasons they give for being
+    /// skipped
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Store the log files and artifacts of the test cases with this storage once they ran
+    pub fn with_artifact_storage(mut self, artifact_storage: Arc<dyn ArtifactStorage>) -> Self {
+        self.artifact_storage = artifact_storage;
+        self
+    }
+
+    /// Run failed test cases again up to `retries` times before reporting them as failed
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Use a backend to make the target available before running its first test case
+    pub fn with_backend(mut self, backend: Arc<dyn TargetBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Kill the test cases running for longer than `timeout`, whatever timeout they declare
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Kill the setups and teardowns running for longer than `timeout`, whatever timeout they
+    /// declare
+    pub fn with_fixture_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.fixture_timeout = timeout;
+        self
+    }
+
+    /// Count the test cases expected to fail which pass as failures if `strict_xfail` is set
+    pub fn with_strict_xfail(mut self, strict_xfail: bool) -> Self {
+        self.strict_xfail = strict_xfail;
+        self
+    }
+
+    /// Fail the test cases skipped by themselves or because of missing files if `strict_skips` is
+    /// set, the ones with one of the allowed tags excepted; the skips declared in the test suite
+    /// config are always allowed
+    pub fn with_strict_skips(mut self, strict_skips: bool, allowed_tags: Vec<String>) -> Self {
+        self.strict_skips = strict_skips;
+        self.allowed_skip_tags = allowed_tags;
+        self
+    }
+
+    /// Fail the test cases leaving processes running once done (setups excepted, as they may start
+    /// services for the next test cases) if `strict_orphans` is set, and kill these processes if
+    /// `kill_orphans` is set; they are reported in any case
+    pub fn with_orphans(mut self, strict_orphans: bool, kill_orphans: bool) -> Self {
+        self.strict_orphans = strict_orphans;
+        self.kill_orphans = kill_orphans;
+        self
+    }
+
+    /// Report the lines written to the main log files of the test cases as they run if
+    /// `stream_output` is set
+    pub fn with_stream_output(mut self, stream_output: bool) -> Self {
+        self.stream_output = stream_output;
+        self
+    }
+
+    /// Report the last `lines` lines of the main log file of the failed test cases once they are
+    /// done if set, all of them if 0
+    pub fn with_failure_output(mut self, lines: Option<usize>) -> Self {
+        self.failure_output_lines = lines;
+        self
+    }
+
+    /// Share the abort signal of the run, aborting it at the first failure if `fail_fast` is set
+    pub fn with_abort_signal(mut self, abort_signal: Arc<AbortSignal>, fail_fast: bool) -> Self {
+        self.abort_signal = abort_signal;
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Share the locks of the shared resources used by the test cases with the other execution
+    /// contexts of the run
+    pub fn with_test_locks(mut self, test_locks: Arc<TestLocks>) -> Self {
+        self.test_locks = test_locks;
+        self
+    }
+
+    /// Share the allocator of the ports used by the test cases with the other execution contexts
+    /// of the run
+    pub fn with_port_allocator(mut self, port_allocator: Arc<PortAllocator>) -> Self {
+        self.port_allocator = port_allocator;
+        self
+    }
+
+    /// Report the saved results of the test cases which ran before the run was paused instead of
+    /// running them again
+    /// A setup or teardown is only restored if all the test cases of its test file, or of the
+    /// test suite for the test suite fixture, are: it runs again otherwise, for the test cases
+    /// left to run.
+    pub fn with_restored_results(mut self, restored: SavedResults) -> Self {
+        self.restored = restored;
+        self
+    }
+
+    /// Go through the test cases without running them, the ones which would run being reported
+    /// as such
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Report the progress of the target through the test suite, along with the other execution
+    /// contexts sharing the tracker
+    pub fn with_progress_tracker(mut self, progress: Arc<ProgressTracker>) -> Self {
+        progress.add_target(
+            &self.target,
+            self.exec_info.iter().map(|(test_case, exec_info)| {
+                (
+                    test_case.id(),
+                    exec_info
+                        .expected_duration()
+                        .map(|expected| expected.duration),
+                )
+            }),
+        );
+        self.progress = progress;
+        self
+    }
+
+    /// Why the run was aborted, if it was, in which case the test cases not run yet are skipped
+    pub fn abort_reason(&self) -> Option<SkipReason> {
+        self.abort_signal.reason()
+    }
+
+    /// Make the target available through its backend if not done yet
+    fn acquire_target(&mut self) -> Result<()> {
+        if let Some(details) = &self.acquire_error {
+            return Err(error::kind::TargetBackend {
+                target: self.target.clone(),
+                details: details.clone(),
+            }
+            .into());
+        }
+        let (Some(backend), None) = (&self.backend, &self.lease) else {
+            return Ok(());
+        };
+        match backend.acquire(&self.target, &self.target_out_dir) {
+            Ok(lease) => {
+                self.lease_metadata = lease.metadata();
+                self.lease = Some(lease);
+                Ok(())
+            }
+            Err(error) => {
+                self.acquire_error = Some(match &error {
+                    Error::TargetBackend(error) => error.details.clone(),
+                    error => error.to_string(),
+                });
+                Err(error)
+            }
+        }
+    }
+
+    /// Information about the last lease of the target by its backend (e.g. a reservation id)
+    pub fn lease_metadata(&self) -> &Env {
+        &self.lease_metadata
+    }
+
+    /// Release the target made available by its backend, if any
+    /// The target is made available again if more test cases are run afterwards.
+    pub fn release_target(&mut self) -> Result<()> {
+        match self.lease.take() {
+            Some(lease) => lease.release(),
+            None => Ok(()),
+        }
+    }
+
+    /// Mark the target as having joined the run after it started
+    pub fn with_late_joined(mut self) -> Self {
+        self.late_joined = true;
+        self
+    }
+
+    pub fn is_late_joined(&self) -> bool {
+        self.late_joined
     }
 
     pub fn target(&self) -> &str {
         &self.target
     }
 
-    pub fn exec_info(&self) -> &HashMap<TestCase, TestCaseExecInfo> {
-        &self.exec_info
+    /// The execution information of a test case, if it is part of this context
+    pub fn exec_info(&self, test_case: &TestCase) -> Option<&TestCaseExecInfo> {
+        let index = *self.exec_info_index.get(&test_case.id())?;
+        Some(&self.exec_info[index].1)
+    }
+
+    /// Iterate over the test cases and their execution information, in test suite order
+    pub fn iter_exec_info(&self) -> impl Iterator<Item = (&TestCase, &TestCaseExecInfo)> {
+        self.exec_info
+            .iter()
+            .map(|(test_case, exec_info)| (test_case, exec_info))
+    }
+
+    /// Whether the test cases of this context may run on several threads, each running a part of
+    /// them in a context forked with [`Self::fork`]
+    /// The lease of the target by its backend cannot be shared between threads.
+    pub(crate) fn can_fork(&self) -> bool {
+        self.backend.is_none()
+    }
+
+    /// A context running the given test cases of this one on another thread, with the same
+    /// settings, their results being merged back into this one by [`Self::join`]
+    pub(crate) fn fork<'a>(&mut self, test_cases: impl IntoIterator<Item = &'a TestCase>) -> Self {
+        let mut exec_info = Vec::new();
+        let mut exec_info_index = HashMap::new();
+        for test_case in test_cases {
+            let Some(&index) = self.exec_info_index.get(&test_case.id()) else {
+                continue;
+            };
+            let placeholder = TestCaseExecInfo::new(self.exec_info[index].1.out_dir.clone(), None);
+            exec_info_index.insert(test_case.id(), exec_info.len());
+            exec_info.push((
+                test_case.clone(),
+                std::mem::replace(&mut self.exec_info[index].1, placeholder),
+            ));
+        }
+        Self {
+            target: self.target.clone(),
+            exec_info,
+            exec_info_index,
+            slow_factor: self.slow_factor,
+            skip_reason: self.skip_reason.clone(),
+            late_joined: self.late_joined,
+            target_out_dir: self.target_out_dir.clone(),
+            backend: None,
+            lease: None,
+            acquire_error: self.acquire_error.clone(),
+            lease_metadata: Env::new(),
+            host_env: self.host_env.clone(),
+            config_env: self.config_env.clone(),
+            run_env: self.run_env.clone(),
+            suite_exports: self.suite_exports.clone(),
+            file_exports: HashMap::new(),
+            secrets: self.secrets.clone(),
+            redactor: self.redactor.clone(),
+            artifact_storage: self.artifact_storage.clone(),
+            retries: self.retries,
+            timeout: self.timeout,
+            fixture_timeout: self.fixture_timeout,
+            fail_fast: self.fail_fast,
+            strict_xfail: self.strict_xfail,
+            strict_skips: self.strict_skips,
+            allowed_skip_tags: self.allowed_skip_tags.clone(),
+            strict_orphans: self.strict_orphans,
+            kill_orphans: self.kill_orphans,
+            stream_output: self.stream_output,
+            failure_output_lines: self.failure_output_lines,
+            abort_signal: self.abort_signal.clone(),
+            test_locks: self.test_locks.clone(),
+            port_allocator: self.port_allocator.clone(),
+            restored: self.restored.clone(),
+            dry_run: self.dry_run,
+            filtered_out: self.filtered_out,
+            progress: self.progress.clone(),
+        }
+    }
+
+    /// Merge back the results of a context created by [`Self::fork`]
+    pub(crate) fn join(&mut self, forked: Self) {
+        for (test_case, exec_info) in forked.exec_info {
+            if let Some(&index) = self.exec_info_index.get(&test_case.id()) {
+                self.exec_info[index].1 = exec_info;
+            }
+        }
+        self.file_exports.extend(forked.file_exports);
+    }
+
+    /// Skip all test cases run from now on with the given reason
+    pub fn skip_remaining(&mut self, reason: SkipReason) {
+        self.skip_reason = Some(reason);
+    }
+
+    /// Stop skipping the test cases run from now on
+    pub fn resume(&mut self) {
+        self.skip_reason = None;
+    }
+
+    pub fn skip_reason(&self) -> Option<&SkipReason> {
+        self.skip_reason.as_ref()
+    }
+
+    /// Whether any test case of the given test suite (usually a part of the test suite this
+    /// context was created for) failed
+    pub fn has_failures(&self, test_suite: &TestSuite) -> bool {
+        let mut has_failures = false;
+        Visitor::new(test_suite).visit_all_ok(|tc, _| {
+            has_failures |= self.exec_info(tc).is_some_and(|exec_info| {
+                matches!(
+                    exec_info
+                        .result
+                        .as_ref()
+                        .map(|output| &output.test_case_status),
+                    Err(_) | Ok(TestCaseStatus::Failed(_))
+                )
+            });
+        });
+        has_failures
     }
 
     pub fn prepare_test_case_out_dir(
@@ -112,66 +974,962 @@ impl<'tr> ExecutionContext {
         Ok(test_case_out_dir)
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn run(
         &mut self,
-        reporter: &'tr Box<dyn Reporter>,
-        test_driver: &'tr Box<dyn TestDriver>,
+        reporter: &'tr dyn Reporter,
+        test_driver: &'tr dyn TestDriver,
         test_suite: &'tr TestSuite,
         test_case: &TestCase,
         should_skip: ShouldSkip,
     ) -> std::result::Result<(), ()> {
+        let result = self.run_test_case(reporter, test_driver, test_suite, test_case, should_skip);
+        self.report_progress(reporter, test_case);
+        result
+    }
+
+    fn run_test_case(
+        &mut self,
+        reporter: &'tr dyn Reporter,
+        test_driver: &'tr dyn TestDriver,
+        test_suite: &'tr TestSuite,
+        test_case: &TestCase,
+        should_skip: ShouldSkip,
+    ) -> std::result::Result<(), ()> {
+        if let Some(saved) = self.restored_result(test_suite, test_case) {
+            return self.restore(reporter, test_case, saved);
+        }
         let test_suite_dir = test_suite.path();
-        // UNWRAP: exec_info is initialized with all test cases so the key is guaranteed to exist
-        let tc_exec_info = self.exec_info.get_mut(test_case).unwrap();
 
-        tc_exec_info.set_result(Ok(RunTestOutput {
-            test_case_status: TestCaseStatus::Running,
-            driver_output: None,
-        }));
-        reporter.report_test_case_execution_started(&test_case, &self.target, &tc_exec_info);
+        let should_skip = match &self.skip_reason {
+            Some(reason) => ShouldSkip::Yes(reason.clone()),
+            None if should_skip != ShouldSkip::No => should_skip,
+            None => {
+                let missing_files = test_case.missing_files();
+                if let Some(reason) = test_suite
+                    .config()
+                    .check_only_targets(test_case, &self.target)
+                {
+                    ShouldSkip::Yes(SkipReason::NotSelectedForTarget(reason))
+                } else if let Some(reason) = test_suite.config().check_skip(test_case, &self.target)
+                {
+                    ShouldSkip::Yes(SkipReason::IncompatibleWithTarget(reason.to_string()))
+                } else if !missing_files.is_empty() {
+                    let missing_files = missing_files
+                        .iter()
+                        .map(|file| file.display().to_string())
+                        .collect::<Vec<_>>();
+                    ShouldSkip::Yes(SkipReason::MissingFiles(format!(
+                        "needed files missing from the test suite directory: `{}`",
+                        missing_files.join("`, `")
+                    )))
+                } else {
+                    ShouldSkip::No
+                }
+            }
+        };
+        if self.dry_run && should_skip == ShouldSkip::No {
+            self.simulate(reporter, test_case);
+            return Ok(());
+        }
+        let mut acquire_error = match should_skip {
+            ShouldSkip::No => self.acquire_target().err(),
+            ShouldSkip::Yes(_) => None,
+        };
+        // Held until the test case is done, retries included
+        let locks = match (&should_skip, &acquire_error) {
+            (ShouldSkip::No, None) => test_suite.config().locks_of(test_case),
+            _ => Vec::new(),
+        };
+        let test_locks = self.test_locks.clone();
+        let _held_locks = test_locks.acquire(&locks);
+        // Also held until the test case is done, retries included
+        let ports = match (&should_skip, &acquire_error) {
+            (ShouldSkip::No, None) => test_suite.config().ports_of(test_case),
+            _ => 0,
+        };
+        let port_allocator = self.port_allocator.clone();
+        let allocated_ports = match port_allocator.allocate(ports) {
+            Ok(allocated_ports) => Some(allocated_ports),
+            Err(io_err) => {
+                acquire_error = Some(
+                    error::kind::PortAllocation {
+                        count: ports,
+                        source: io_err,
+                    }
+                    .into(),
+                );
+                None
+            }
+        };
+        // Also kept until the test case is done, retries included
+        let network_namespace = match (&should_skip, &acquire_error) {
+            (ShouldSkip::No, None) if !test_suite.is_fixture(test_case) => {
+                match test_suite.config().network_isolation.as_ref() {
+                    Some(config) => match NetworkNamespace::create(config) {
+                        Ok(network_namespace) => Some(network_namespace),
+                        Err(details) => {
+                            acquire_error = Some(error::kind::NetworkIsolation { details }.into());
+                            None
+                        }
+                    },
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        let mut env = self.test_env();
+        if let Some(network_namespace) = &network_namespace {
+            env.insert(
+                network_isolation::NETNS_ENV_VAR.to_string(),
+                network_namespace.path().display().to_string(),
+            );
+        }
+        if let Some(allocated_ports) = &allocated_ports
+            && !allocated_ports.ports().is_empty()
+        {
+            let ports = allocated_ports
+                .ports()
+                .iter()
+                .map(u16::to_string)
+                .collect::<Vec<_>>();
+            env.insert(Self::PORTS_ENV_VAR.to_string(), ports.join(" "));
+        }
+        env.extend(self.suite_exports.clone());
+        if let Some(exports) = self.file_exports.get(test_case.path()) {
+            env.extend(exports.clone());
+        }
+        if let Some(params) = test_case.params() {
+            env.insert(Self::PARAM_ENV_VAR.to_string(), params.to_string());
+        }
+        let marker = orphans::new_marker();
+        env.insert(orphans::MARKER_ENV_VAR.to_string(), marker.clone());
+        let secrets = self.secrets.clone();
+        let redactor = self.redactor.clone();
 
-        let result = {
-            if let ShouldSkip::Yes(reason) = should_skip {
-                Ok(RunTestOutput {
-                    test_case_status: TestCaseStatus::Skipped(reason),
-                    driver_output: None, // TODO
-                })
-            } else {
-                test_driver.run_test(
-                    test_suite_dir,
+        // PANIC: exec_info is initialized with all test cases so the key is guaranteed to exist
+        let index = self.exec_info_index[&test_case.id()];
+        let tc_exec_info = &mut self.exec_info[index].1;
+        let artifacts_dir = tc_exec_info
+            .out_dir
+            .join(format!("{}.artifacts", test_case.instance_name()));
+        env.insert(
+            Self::ARTIFACTS_DIR_ENV_VAR.to_string(),
+            artifacts_dir.display().to_string(),
+        );
+        // Left by a previous run
+        let _ = std::fs::remove_dir_all(&artifacts_dir);
+
+        tc_exec_info.failed_attempts.clear();
+        tc_exec_info.failure_artifacts.clear();
+        tc_exec_info.timeout =
+            EffectiveTimeout::resolve(self.timeout, self.fixture_timeout, test_case, test_suite);
+        tc_exec_info.max_attempts = match should_skip {
+            ShouldSkip::No if acquire_error.is_none() => self.retries.saturating_add(1),
+            _ => 1,
+        };
+
+        let mut warnings = Vec::new();
+        loop {
+            let interrupted_before = interrupt::is_interrupted();
+            tc_exec_info.set_result(Ok(RunTestOutput::from_status(TestCaseStatus::Running)));
+            reporter.report_test_case_execution_started(test_case, &self.target, tc_exec_info);
+
+            let result = {
+                if let ShouldSkip::Yes(reason) = &should_skip {
+                    Ok(RunTestOutput::from_status(TestCaseStatus::Skipped(
+                        reason.clone(),
+                    )))
+                } else if let Some(error) = acquire_error.take() {
+                    Err(error)
+                } else {
+                    tc_exec_info.log_files =
+                        test_driver.log_files(test_case, &tc_exec_info.out_dir);
+                    tc_exec_info.log_files.push(Self::write_debug_log(
+                        &secrets,
+                        test_case,
+                        &tc_exec_info.out_dir,
+                        &env,
+                    ));
+                    tc_exec_info.failure_artifacts.clear();
+                    tc_exec_info.telemetry.clear();
+                    let _ = std::fs::create_dir_all(&artifacts_dir);
+                    let sampler = test_suite.config().telemetry.as_ref().map(|config| {
+                        let mut sampler_env = env.clone();
+                        // The telemetry of the target is sampled from the network of the host
+                        sampler_env.remove(network_isolation::NETNS_ENV_VAR);
+                        sampler_env.insert(Self::TARGET_ENV_VAR.to_string(), self.target.clone());
+                        sampler_env.insert(
+                            Self::TEST_CASE_ENV_VAR.to_string(),
+                            test_case.id().to_string(),
+                        );
+                        sampler_env.insert(
+                            Self::OUT_DIR_ENV_VAR.to_string(),
+                            tc_exec_info.out_dir.display().to_string(),
+                        );
+                        let path = tc_exec_info
+                            .out_dir
+                            .join(format!("{}.telemetry.csv", test_case.instance_name()));
+                        TelemetrySampler::start(config, test_suite_dir, sampler_env, path)
+                    });
+                    let run_test = || {
+                        test_driver.run_test(
+                            test_suite_dir,
+                            test_suite.config(),
+                            &self.target,
+                            test_case,
+                            &tc_exec_info.out_dir,
+                            &env,
+                            tc_exec_info.timeout.map(|timeout| timeout.duration),
+                            network_namespace.as_ref().map(NetworkNamespace::path),
+                        )
+                    };
+                    let main_log_file = tc_exec_info.main_log_file().filter(|_| self.stream_output);
+                    let result = match main_log_file {
+                        Some(log_file) => Self::stream_output(
+                            reporter,
+                            &redactor,
+                            test_case,
+                            &self.target,
+                            &log_file.path,
+                            run_test,
+                        ),
+                        None => run_test(),
+                    }
+                    .map(|output| Self::check_metric_thresholds(test_suite.config(), output));
+                    if let Some(sampler) = sampler {
+                        let (log_file, telemetry, errors) = sampler.stop();
+                        tc_exec_info.log_files.push(log_file);
+                        tc_exec_info.telemetry = telemetry;
+                        if let Some(error) = errors.first() {
+                            warnings.push(format!(
+                                "Telemetry of test case `{}` on target `{}` incomplete, {} \
+                                 failed samples: {error}",
+                                test_case.id(),
+                                self.target,
+                                errors.len()
+                            ));
+                        }
+                    }
+                    // Setups may start processes for the next test cases on purpose
+                    let orphans = if test_suite.is_setup(test_case) {
+                        Vec::new()
+                    } else {
+                        orphans::find(&marker)
+                    };
+                    if orphans.is_empty() {
+                        result
+                    } else {
+                        let orphans_list = orphans
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join("`, `");
+                        if self.kill_orphans {
+                            orphans::kill(&orphans);
+                        }
+                        warnings.push(format!(
+                            "Test case `{}` left process(es) `{orphans_list}` running on target \
+                             `{}`{}",
+                            test_case.id(),
+                            self.target,
+                            if self.kill_orphans { ", killed" } else { "" }
+                        ));
+                        result.map(|mut output| {
+                            if self.strict_orphans
+                                && output.test_case_status == TestCaseStatus::Passed
+                            {
+                                output.test_case_status = TestCaseStatus::Failed(
+                                    FailReason::OrphanedProcesses(orphans_list),
+                                );
+                            }
+                            output
+                        })
+                    }
+                }
+            };
+
+            let killed_by = result.as_ref().ok().and_then(|output| output.killed_by);
+            let result = match result {
+                // The test case was most likely killed by the interrupt rather than failing
+                Err(_)
+                | Ok(RunTestOutput {
+                    test_case_status: TestCaseStatus::Failed(_),
+                    ..
+                }) if !interrupted_before && interrupt::is_interrupted() => Ok(RunTestOutput {
+                    killed_by,
+                    ..RunTestOutput::from_status(TestCaseStatus::Skipped(SkipReason::Interrupted))
+                }),
+                result => result,
+            };
+            let result = result.map(|output| {
+                let output = Self::check_expected_failure(
                     test_suite.config(),
                     &self.target,
                     test_case,
-                    &tc_exec_info.out_dir,
+                    output,
+                );
+                Self::check_unexpected_skip(
+                    self.strict_skips,
+                    &self.allowed_skip_tags,
+                    test_case,
+                    output,
                 )
+            });
+            tc_exec_info.set_result(result);
+            tc_exec_info.check_slow(self.slow_factor);
+            warnings.extend(Self::collect_artifacts(
+                test_case,
+                &artifacts_dir,
+                tc_exec_info,
+            ));
+            if let Ok(TestCaseStatus::Failed(_)) = tc_exec_info
+                .result
+                .as_ref()
+                .map(|output| &output.test_case_status)
+            {
+                if let Some(lease) = &self.lease {
+                    let artifact_prefix = tc_exec_info.out_dir.join(test_case.instance_name());
+                    let artifacts = lease.collect_failure_artifacts(&artifact_prefix);
+                    tc_exec_info.failure_artifacts.extend(artifacts);
+                }
+                if let Some(on_failure) = &test_suite.config().on_failure {
+                    let timeout = self
+                        .fixture_timeout
+                        .or(test_suite.config().fixture_timeout)
+                        .unwrap_or(Self::ON_FAILURE_DEFAULT_TIMEOUT);
+                    let (log_file, warning) = Self::run_on_failure_command(
+                        on_failure,
+                        timeout,
+                        test_suite_dir,
+                        &self.target,
+                        test_case,
+                        &tc_exec_info.out_dir,
+                        &env,
+                    );
+                    tc_exec_info.failure_artifacts.push(log_file);
+                    warnings.extend(warning);
+                }
+            }
+            Self::redact(&redactor, tc_exec_info);
+            if !tc_exec_info.should_retry() || self.abort_signal.reason().is_some() {
+                break;
+            }
+            tc_exec_info.record_failed_attempt();
+            reporter.report_test_case_execution_retried(test_case, &self.target, tc_exec_info);
+        }
+        // Only kept if the test case dropped files into it, removing it failing otherwise
+        let _ = std::fs::remove_dir(&artifacts_dir);
+        // Captured before the artifact storage gets the log file, as it may not keep it locally
+        let failure_output = self
+            .failure_output_lines
+            .and_then(|lines| Self::capture_failure_output(tc_exec_info, lines));
+        let storage_errors = Self::store_artifacts(self.artifact_storage.as_ref(), tc_exec_info);
+        reporter.report_test_case_execution_result(test_case, &self.target, tc_exec_info);
+        if let Some(output) = &failure_output {
+            reporter.report_test_case_failure_output(test_case, &self.target, output);
+        }
+        if let Ok(output) = &tc_exec_info.result
+            && matches!(output.test_case_status, TestCaseStatus::Passed)
+            && !output.exports.is_empty()
+        {
+            let is_setup = |setup: &Option<TestCase>| setup.as_ref() == Some(test_case);
+            if is_setup(&test_suite.fixture().setup_test_case) {
+                self.suite_exports = output.exports.clone();
+            } else if test_suite
+                .test_files()
+                .iter()
+                .any(|test_file| is_setup(&test_file.setup_test_case))
+            {
+                self.file_exports
+                    .insert(test_case.path().to_path_buf(), output.exports.clone());
+            } else {
+                warnings.push(format!(
+                    "Values exported by test case `{}` ignored, only setups can export values",
+                    test_case.id()
+                ));
+            }
+        }
+        for warning in &warnings {
+            reporter.warning(warning);
+        }
+        for error in &storage_errors {
+            reporter.error_from(error);
+        }
+
+        match tc_exec_info
+            .result
+            .as_ref()
+            .map(|output| &output.test_case_status)
+        {
+            Err(_) | Ok(TestCaseStatus::Failed(_)) => {
+                if self.fail_fast {
+                    self.abort_signal.abort(SkipReason::AbortedByFailFast);
+                }
+                Err(())
             }
+            Ok(TestCaseStatus::XPassed(_)) if self.strict_xfail => {
+                if self.fail_fast {
+                    self.abort_signal.abort(SkipReason::AbortedByFailFast);
+                }
+                Err(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The saved result of a test case to report instead of running it, see
+    /// [`Self::with_restored_results`]
+    fn restored_result(&self, test_suite: &TestSuite, test_case: &TestCase) -> Option<SavedResult> {
+        let saved = self.restored.get(&test_case.id())?;
+        let is_fixture = |setup: &Option<TestCase>, teardown: &Option<TestCase>| {
+            setup.as_ref() == Some(test_case) || teardown.as_ref() == Some(test_case)
         };
+        let fixture = test_suite.fixture();
+        let scope =
+            if is_fixture(&fixture.setup_test_case, &fixture.teardown_test_case) {
+                test_suite
+                    .test_files()
+                    .iter()
+                    .flat_map(|test_file| &test_file.test_cases)
+                    .collect()
+            } else if let Some(test_file) = test_suite.test_files().iter().find(|test_file| {
+                is_fixture(&test_file.setup_test_case, &test_file.teardown_test_case)
+            }) {
+                test_file.test_cases.iter().collect()
+            } else {
+                Vec::new()
+            };
+        scope
+            .iter()
+            .all(|tc| self.restored.contains_key(&tc.id()))
+            .then(|| saved.clone())
+    }
 
-        tc_exec_info.set_result(result);
-        reporter.report_test_case_execution_result(&test_case, &self.target, &tc_exec_info);
+    /// Report how far the target went through the test suite once a test case is done, the time
+    /// it took helping estimate the time left if it actually ran
+    fn report_progress(&self, reporter: &'tr dyn Reporter, test_case: &TestCase) {
+        // PANIC: exec_info is initialized with all test cases so the key is guaranteed to exist
+        let exec_info = &self.exec_info[self.exec_info_index[&test_case.id()]].1;
+        let ran = matches!(
+            exec_info
+                .result()
+                .as_ref()
+                .map(|output| &output.test_case_status),
+            Ok(TestCaseStatus::Passed
+                | TestCaseStatus::Failed(_)
+                | TestCaseStatus::XFailed(_)
+                | TestCaseStatus::XPassed(_))
+        );
+        let progress = self.progress.finish(
+            &self.target,
+            &test_case.id(),
+            exec_info.duration().filter(|_| ran),
+        );
+        reporter.report_target_progress(&self.target, &progress);
+    }
 
+    /// Report a test case which would run, without acquiring the target nor running it
+    fn simulate(&mut self, reporter: &'tr dyn Reporter, test_case: &TestCase) {
+        // PANIC: exec_info is initialized with all test cases so the key is guaranteed to exist
+        let index = self.exec_info_index[&test_case.id()];
+        let tc_exec_info = &mut self.exec_info[index].1;
+        tc_exec_info.set_result(Ok(RunTestOutput::from_status(TestCaseStatus::Running)));
+        reporter.report_test_case_execution_started(test_case, &self.target, tc_exec_info);
+        tc_exec_info.set_result(Ok(RunTestOutput::from_status(TestCaseStatus::DryRun)));
+        reporter.report_test_case_execution_result(test_case, &self.target, tc_exec_info);
+    }
+
+    /// Report the saved result of a test case as if it just ran
+    fn restore(
+        &mut self,
+        reporter: &'tr dyn Reporter,
+        test_case: &TestCase,
+        saved: SavedResult,
+    ) -> std::result::Result<(), ()> {
+        // PANIC: exec_info is initialized with all test cases so the key is guaranteed to exist
+        let index = self.exec_info_index[&test_case.id()];
+        let tc_exec_info = &mut self.exec_info[index].1;
+        tc_exec_info.set_result(Ok(RunTestOutput::from_status(TestCaseStatus::Running)));
+        reporter.report_test_case_execution_started(test_case, &self.target, tc_exec_info);
+        tc_exec_info.set_result(Ok(RunTestOutput {
+            message: saved.message,
+            ..RunTestOutput::from_status(saved.status)
+        }));
+        if let Some(duration) = saved.duration {
+            tc_exec_info.duration = TimeInterval::ending_now(duration);
+        }
+        reporter.report_test_case_execution_result(test_case, &self.target, tc_exec_info);
         match tc_exec_info
             .result
             .as_ref()
             .map(|output| &output.test_case_status)
         {
-            Err(_) | Ok(TestCaseStatus::Failed) => Err(()),
+            Ok(TestCaseStatus::Failed(_)) => Err(()),
+            Ok(TestCaseStatus::XPassed(_)) if self.strict_xfail => Err(()),
             _ => Ok(()),
         }
     }
 
+    /// Run the on-failure command of the test suite for a failed test case, from the test suite
+    /// directory, storing its output next to the test case log
+    /// The command failing is noted in its output only, as it does not change the test case
+    /// status. It is killed after `timeout` (the fixture timeout if any), returning a warning.
+    #[allow(clippy::too_many_arguments)]
+    fn run_on_failure_command(
+        on_failure: &[String],
+        timeout: Duration,
+        test_suite_dir: &Path,
+        target: &str,
+        test_case: &TestCase,
+        test_case_out_dir: &Path,
+        env: &Env,
+    ) -> (LogFile, Option<String>) {
+        let path = test_case_out_dir.join(format!("{}.on-failure.log", test_case.instance_name()));
+        // UNWRAP: the command is checked not to be empty when loading the test suite config
+        let (program, args) = on_failure.split_first().unwrap();
+        let mut warning = None;
+        let mut run = || -> std::io::Result<()> {
+            let mut log = File::create(&path)?;
+            let mut command = Command::new(program);
+            command
+                .args(args)
+                .current_dir(test_suite_dir)
+                .env_clear()
+                .envs(env)
+                .env(Self::TARGET_ENV_VAR, target)
+                .env(Self::TEST_CASE_ENV_VAR, test_case.id().to_string())
+                .env(Self::OUT_DIR_ENV_VAR, test_case_out_dir)
+                .stdin(Stdio::null())
+                .stdout(log.try_clone()?)
+                .stderr(log.try_clone()?);
+            match process::status(&mut command, Some(timeout), None) {
+                Ok(Ok(status)) if status.success() => Ok(()),
+                Ok(Ok(status)) => writeln!(log, "batrun: `{program}` exited with {status}"),
+                Ok(Err(signal)) => {
+                    let message = format!(
+                        "On-failure command `{program}` of test case `{}` killed with {signal} \
+                         after {}",
+                        test_case.id(),
+                        crate::time::format(timeout)
+                    );
+                    writeln!(log, "batrun: {message}")?;
+                    warning = Some(message);
+                    Ok(())
+                }
+                Err(io_err) => writeln!(log, "batrun: cannot execute `{program}`: {io_err}"),
+            }
+        };
+        // Best effort, the test case already failed anyway
+        let _ = run();
+        let log_file = LogFile {
+            kind: "on-failure",
+            path,
+            location: None,
+        };
+        (log_file, warning)
+    }
+
+    /// The environment variables of the test processes: the ones forwarded from batrun, the ones
+    /// of the test suite config, the secrets, the ones set for the run, then the ones of the target
+    /// lease
+    fn test_env(&self) -> Env {
+        let mut env = self.host_env.clone();
+        env.extend(self.config_env.clone());
+        env.extend(self.secrets.env().clone());
+        env.extend(self.run_env.clone());
+        if let Some(lease) = &self.lease {
+            env.extend(lease.env().clone());
+        }
+        env
+    }
+
+    /// Record the environment a test case runs with in its debug log
+    /// Writing the debug log is best effort, as it only helps investigating the test case.
+    /// The values of the secrets are not recorded.
+    fn write_debug_log(
+        secrets: &Secrets,
+        test_case: &TestCase,
+        test_case_out_dir: &Path,
+        env: &Env,
+    ) -> LogFile {
+        let path = test_case_out_dir.join(format!("{}.debug.log", test_case.instance_name()));
+        let mut contents = String::from("Environment of the test process:\n");
+        for (name, value) in env {
+            let value = if secrets.contains(name) {
+                Redactor::REDACTED
+            } else {
+                value
+            };
+            contents += &format!("{name}={value}\n");
+        }
+        let _ = std::fs::write(&path, contents);
+        LogFile {
+            kind: "debug",
+            path,
+            location: None,
+        }
+    }
+
+    /// Add the artifacts of the test case to its log files, so that they are stored and listed
+    /// in the run manifest like them: the ones it asked to collect, then the files it dropped
+    /// into its artifacts directory
+    /// Relative paths are resolved from the output directory of the test case. The artifacts
+    /// written elsewhere are copied into it, prefixed with the name of the test case instance.
+    /// The artifacts which cannot be collected are returned as warnings, to be reported with the
+    /// result of the test case.
+    fn collect_artifacts(
+        test_case: &TestCase,
+        artifacts_dir: &Path,
+        tc_exec_info: &mut TestCaseExecInfo,
+    ) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let listed = match &mut tc_exec_info.result {
+            Ok(output) => std::mem::take(&mut output.artifacts),
+            Err(_) => Vec::new(),
+        };
+        for artifact in listed {
+            let path = tc_exec_info.out_dir.join(&artifact);
+            let Some(file_name) = path.file_name().filter(|_| path.is_file()) else {
+                warnings.push(format!(
+                    "Artifact `{}` of test case `{}` not found, ignoring",
+                    artifact.display(),
+                    test_case.id()
+                ));
+                continue;
+            };
+            let in_out_dir = path.parent() == Some(tc_exec_info.out_dir.as_path())
+                || path.starts_with(artifacts_dir);
+            let path = if in_out_dir {
+                path
+            } else {
+                let copy = tc_exec_info.out_dir.join(format!(
+                    "{}.{}",
+                    test_case.instance_name(),
+                    file_name.to_string_lossy()
+                ));
+                if let Err(io_err) = std::fs::copy(&path, &copy) {
+                    warnings.push(format!(
+                        "Cannot copy artifact `{}` of test case `{}`: {io_err}",
+                        artifact.display(),
+                        test_case.id()
+                    ));
+                    continue;
+                }
+                copy
+            };
+            tc_exec_info.log_files.push(LogFile {
+                kind: "artifact",
+                path,
+                location: None,
+            });
+        }
+        let dropped = WalkDir::new(artifacts_dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .collect::<Vec<_>>();
+        for path in dropped {
+            // The files of the failed attempts were renamed and collected with them
+            let collected = tc_exec_info
+                .log_files
+                .iter()
+                .chain(
+                    tc_exec_info
+                        .failed_attempts
+                        .iter()
+                        .flat_map(|attempt| &attempt.log_files),
+                )
+                .any(|log_file| log_file.path == path);
+            if !collected {
+                tc_exec_info.log_files.push(LogFile {
+                    kind: "artifact",
+                    path,
+                    location: None,
+                });
+            }
+        }
+        warnings
+    }
+
+    /// The last lines of the main log file of a failed test case, already redacted
+    fn capture_failure_output(
+        tc_exec_info: &TestCaseExecInfo,
+        lines: usize,
+    ) -> Option<CapturedOutput> {
+        let failed = matches!(
+            tc_exec_info
+                .result
+                .as_ref()
+                .map(|output| &output.test_case_status),
+            Err(_) | Ok(TestCaseStatus::Failed(_))
+        );
+        if !failed {
+            return None;
+        }
+        let log = std::fs::read(&tc_exec_info.main_log_file()?.path).ok()?;
+        Some(CapturedOutput::tail(&String::from_utf8_lossy(&log), lines))
+    }
+
+    /// Run a test case while reporting the lines written to its main log file as they come
+    fn stream_output<T>(
+        reporter: &dyn Reporter,
+        redactor: &Redactor,
+        test_case: &TestCase,
+        target: &str,
+        log_file: &Path,
+        run_test: impl FnOnce() -> T,
+    ) -> T {
+        // The log of a previous run would be reported before the test case overwrites it
+        let _ = std::fs::remove_file(log_file);
+        let done = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                Self::follow_log(reporter, redactor, test_case, target, log_file, &done);
+            });
+            let output = run_test();
+            done.store(true, Ordering::Release);
+            output
+        })
+    }
+
+    /// Report the lines written to a log file, redacted, until `done` is set
+    fn follow_log(
+        reporter: &dyn Reporter,
+        redactor: &Redactor,
+        test_case: &TestCase,
+        target: &str,
+        log_file: &Path,
+        done: &AtomicBool,
+    ) {
+        const POLL_PERIOD: Duration = Duration::from_millis(50);
+        let report = |line: &[u8]| {
+            let line = String::from_utf8_lossy(line);
+            let line = redactor.redact(line.trim_end_matches('\r'));
+            reporter.report_test_case_output(test_case, target, &line);
+        };
+        let mut offset = 0;
+        let mut pending = Vec::new();
+        loop {
+            // Whatever is written once the test case is done is read below before returning
+            let finished = done.load(Ordering::Acquire);
+            let read = File::open(log_file).and_then(|mut file| {
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_to_end(&mut pending)
+            });
+            // The test case may not have created its log file yet
+            if let Ok(read) = read {
+                offset += read as u64;
+            }
+            while let Some(end_of_line) = pending.iter().position(|&byte| byte == b'\n') {
+                let line = pending.drain(..=end_of_line).collect::<Vec<_>>();
+                report(&line[..end_of_line]);
+            }
+            if finished {
+                if !pending.is_empty() {
+                    report(&pending);
+                }
+                return;
+            }
+            std::thread::sleep(POLL_PERIOD);
+        }
+    }
+
+    /// Remove the sensitive data from what the last attempt at running a test case left
+    /// Redacting the log files is best effort, some of them may not have been written.
+    fn redact(redactor: &Redactor, tc_exec_info: &mut TestCaseExecInfo) {
+        for log_file in tc_exec_info
+            .log_files
+            .iter()
+            .chain(&tc_exec_info.failure_artifacts)
+        {
+            let _ = redactor.redact_file(&log_file.path);
+        }
+        if let Ok(RunTestOutput {
+            test_case_status:
+                TestCaseStatus::Skipped(SkipReason::TestCaseSpecificReason(reason))
+                | TestCaseStatus::XFailed(reason)
+                | TestCaseStatus::XPassed(reason),
+            ..
+        }) = &mut tc_exec_info.result
+        {
+            *reason = redactor.redact(reason);
+        }
+        if let Ok(RunTestOutput {
+            message: Some(message),
+            ..
+        }) = &mut tc_exec_info.result
+        {
+            *message = redactor.redact(message);
+        }
+    }
+
+    /// Turn the result of a test case expected to fail, according to the test suite config or to
+    /// itself, into an expected failure or an unexpected pass
+    /// Timeouts are still reported as failures, as hanging is not the expected failure.
+    fn check_expected_failure(
+        test_suite_config: &TestSuiteConfig,
+        target: &str,
+        test_case: &TestCase,
+        mut output: RunTestOutput,
+    ) -> RunTestOutput {
+        let Some(reason) = test_suite_config
+            .check_xfail(test_case, target)
+            .map(str::to_string)
+            .or_else(|| output.expected_failure.clone())
+        else {
+            return output;
+        };
+        output.test_case_status = match output.test_case_status {
+            TestCaseStatus::Failed(FailReason::Timeout(timeout)) => {
+                TestCaseStatus::Failed(FailReason::Timeout(timeout))
+            }
+            TestCaseStatus::Failed(_) => TestCaseStatus::XFailed(reason),
+            TestCaseStatus::Passed => TestCaseStatus::XPassed(reason),
+            status => status,
+        };
+        output
+    }
+
+    /// Turn the skip of a test case by itself or because of missing files into a failure if skips
+    /// are strict and the test case is not allowed to be skipped
+    fn check_unexpected_skip(
+        strict_skips: bool,
+        allowed_tags: &[String],
+        test_case: &TestCase,
+        mut output: RunTestOutput,
+    ) -> RunTestOutput {
+        if !strict_skips
+            || test_case
+                .metadata()
+                .tags
+                .iter()
+                .any(|tag| allowed_tags.contains(tag))
+        {
+            return output;
+        }
+        if let TestCaseStatus::Skipped(
+            SkipReason::TestCaseSpecificReason(reason) | SkipReason::MissingFiles(reason),
+        ) = &output.test_case_status
+        {
+            output.test_case_status =
+                TestCaseStatus::Failed(FailReason::UnexpectedSkip(reason.clone()));
+        }
+        output
+    }
+
+    /// Store the log files and artifacts of all the attempts at running a test case, recording
+    /// where they were stored
+    /// The files failing to be stored are left in the output directory, the errors being returned
+    /// to be reported without changing the test case status.
+    fn store_artifacts(
+        artifact_storage: &dyn ArtifactStorage,
+        tc_exec_info: &mut TestCaseExecInfo,
+    ) -> Vec<Error> {
+        let failed_attempts_log_files = tc_exec_info
+            .failed_attempts
+            .iter_mut()
+            .flat_map(|attempt| &mut attempt.log_files);
+        let log_files = tc_exec_info
+            .log_files
+            .iter_mut()
+            .chain(&mut tc_exec_info.failure_artifacts)
+            .chain(failed_attempts_log_files)
+            // Some log files may not have been written, e.g. if the test case could not start
+            .filter(|log_file| log_file.path.is_file());
+        let mut errors = Vec::new();
+        for log_file in log_files {
+            match artifact_storage.store(&log_file.path) {
+                Ok(location) => log_file.location = location,
+                Err(error) => errors.push(error),
+            }
+        }
+        errors
+    }
+
+    /// Mark a passed test case as failed if one of its metrics is out of the thresholds declared
+    /// in the test suite config
+    fn check_metric_thresholds(
+        test_suite_config: &TestSuiteConfig,
+        mut output: RunTestOutput,
+    ) -> RunTestOutput {
+        if let TestCaseStatus::Passed = output.test_case_status {
+            let violation = output.metrics.iter().find_map(|(metric, value)| {
+                test_suite_config
+                    .metric_thresholds
+                    .get(metric)
+                    .and_then(|threshold| threshold.check(metric, *value))
+            });
+            if let Some(violation) = violation {
+                output.test_case_status =
+                    TestCaseStatus::Failed(FailReason::MetricThresholdExceeded(violation));
+            }
+        }
+        output
+    }
+
+    /// How long the test cases gone through in dry-run mode are expected to take on the target
+    pub fn estimate(&self) -> TargetEstimate {
+        let mut estimate = TargetEstimate {
+            target: self.target.clone(),
+            ..TargetEstimate::default()
+        };
+        for (test_case, exec_info) in self.iter_exec_info() {
+            if !matches!(
+                exec_info.result(),
+                Ok(RunTestOutput {
+                    test_case_status: TestCaseStatus::DryRun,
+                    ..
+                })
+            ) {
+                continue;
+            }
+            estimate.test_cases += 1;
+            match exec_info.expected_duration() {
+                Some(expected) => estimate.duration += expected.duration,
+                None => estimate.unknown.push(test_case.id()),
+            }
+        }
+        estimate
+    }
+
+    /// Record the duration of all passed test cases and the metrics emitted by all test cases into
+    /// the history
+    pub fn record_history(&self, history: &mut History) {
+        for (test_case, exec_info) in self.iter_exec_info() {
+            let Ok(output) = &exec_info.result else {
+                continue;
+            };
+            if let (TestCaseStatus::Passed, Some(duration)) =
+                (&output.test_case_status, exec_info.duration())
+            {
+                history.record_duration(&test_case.id(), &self.target, duration);
+            }
+            for (metric, value) in &output.metrics {
+                history.record_metric(&test_case.id(), &self.target, metric, *value);
+            }
+        }
+    }
+
     pub fn get_statistics(&self) -> Statistics {
-        let mut stats = Statistics::default();
+        let mut stats = Statistics {
+            filtered_out: self.filtered_out,
+            ..Statistics::default()
+        };
 
-        for exec_info in self.exec_info.values() {
+        for (_, exec_info) in self.iter_exec_info() {
             match exec_info
                 .result
                 .as_ref()
                 .map(|output| &output.test_case_status)
             {
                 Ok(TestCaseStatus::Passed) => stats.passed += 1,
-                Ok(TestCaseStatus::Failed) => stats.failed += 1,
+                Ok(TestCaseStatus::Failed(_)) => stats.failed += 1,
                 Ok(TestCaseStatus::Skipped(_) | TestCaseStatus::DryRun) => stats.skipped += 1,
+                Ok(TestCaseStatus::XFailed(_)) => stats.xfailed += 1,
+                Ok(TestCaseStatus::XPassed(_)) => stats.xpassed += 1,
                 Err(_) => stats.runner_failed += 1,
                 _ => {}
             }
@@ -179,4 +1937,18 @@ impl<'tr> ExecutionContext {
 
         stats
     }
+
+    /// The time spent running the test cases on the target, failed attempts included
+    pub fn total_duration(&self) -> Duration {
+        self.iter_exec_info()
+            .flat_map(|(_, exec_info)| {
+                exec_info
+                    .failed_attempts()
+                    .iter()
+                    .map(|attempt| attempt.duration)
+                    .chain([exec_info.duration()])
+            })
+            .flatten()
+            .sum()
+    }
 }
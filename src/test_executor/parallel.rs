@@ -1,18 +1,54 @@
 use crate::reporter::Reporter;
 use crate::test_driver::TestDriver;
+use crate::test_executor::scheduler::TargetScheduler;
+use crate::test_executor::sequential::SequentialExecutor;
 use crate::test_executor::{ExecutionContext, Executor};
 use crate::test_suite::TestSuite;
 
-pub(crate) struct ParallelExecutor;
+use std::num::NonZeroUsize;
+
+/// Run the test cases of the targets in parallel on a pool of worker threads
+/// Each worker takes the next pending target and runs all its test cases sequentially, each
+/// target having its own visitor and execution context, so results never need to be merged.
+/// Targets remain queued until a worker frees up, or until the targets they are exclusive with
+/// are done.
+pub(crate) struct ParallelExecutor {
+    /// Maximum number of targets run at the same time, all targets at once if not set
+    pub(crate) max_workers: Option<NonZeroUsize>,
+    /// Maximum number of independent test files run at the same time on a target
+    pub(crate) file_jobs: NonZeroUsize,
+}
 
 impl<'tr> Executor<'tr> for ParallelExecutor {
     fn execute(
         &self,
-        _reporter: &'tr Box<dyn Reporter>,
-        _test_driver: &'tr Box<(dyn TestDriver + 'static)>,
-        _test_suite: &'tr TestSuite,
-        _exec_contexts: &mut [ExecutionContext],
+        reporter: &'tr dyn Reporter,
+        test_driver: &'tr dyn TestDriver,
+        test_suite: &'tr TestSuite,
+        exec_contexts: &'tr mut [ExecutionContext],
     ) {
-        todo!()
+        let worker_count = match self.max_workers {
+            Some(max_workers) => exec_contexts.len().min(max_workers.get()),
+            None => exec_contexts.len(),
+        };
+        let scheduler = TargetScheduler::new(test_suite.config(), exec_contexts);
+        std::thread::scope(|scope| {
+            for _ in 0..worker_count {
+                scope.spawn(|| {
+                    while let Some(exec_context) = scheduler.next() {
+                        SequentialExecutor {
+                            file_jobs: self.file_jobs,
+                        }
+                        .execute(
+                            reporter,
+                            test_driver,
+                            test_suite,
+                            std::slice::from_mut(exec_context),
+                        );
+                        scheduler.finished(exec_context.target());
+                    }
+                });
+            }
+        });
     }
 }
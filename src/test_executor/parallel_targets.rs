@@ -0,0 +1,48 @@
+use crate::reporter::Reporter;
+use crate::test_driver::TestDriver;
+use crate::test_executor::scheduler::TargetScheduler;
+use crate::test_executor::sequential::SequentialExecutor;
+use crate::test_executor::{ExecutionContext, Executor};
+use crate::test_suite::TestSuite;
+
+use std::num::NonZeroUsize;
+
+/// Run the test cases of each target sequentially in a dedicated thread, so all targets are
+/// serviced concurrently while the execution order within a target is preserved
+/// Targets declared mutually exclusive in the test suite config still run one after the other.
+pub(crate) struct ParallelTargetsExecutor {
+    /// Maximum number of independent test files run at the same time on a target
+    pub(crate) file_jobs: NonZeroUsize,
+}
+
+impl<'tr> Executor<'tr> for ParallelTargetsExecutor {
+    fn execute(
+        &self,
+        reporter: &'tr dyn Reporter,
+        test_driver: &'tr dyn TestDriver,
+        test_suite: &'tr TestSuite,
+        exec_contexts: &'tr mut [ExecutionContext],
+    ) {
+        let thread_count = exec_contexts.len();
+        let scheduler = TargetScheduler::new(test_suite.config(), exec_contexts);
+        std::thread::scope(|scope| {
+            for _ in 0..thread_count {
+                scope.spawn(|| {
+                    // Each thread runs a single target, the first one allowed to run
+                    if let Some(exec_context) = scheduler.next() {
+                        SequentialExecutor {
+                            file_jobs: self.file_jobs,
+                        }
+                        .execute(
+                            reporter,
+                            test_driver,
+                            test_suite,
+                            std::slice::from_mut(exec_context),
+                        );
+                        scheduler.finished(exec_context.target());
+                    }
+                });
+            }
+        });
+    }
+}
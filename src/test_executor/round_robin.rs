@@ -1,49 +1,49 @@
 use crate::reporter::Reporter;
+use crate::simple_executor::{self, SimpleExecutor};
 use crate::test_driver::TestDriver;
 use crate::test_executor::{ExecutionContext, Executor};
 use crate::test_suite::TestSuite;
 use crate::test_suite::visitor::Visitor;
 
-use std::collections::VecDeque;
-
+/// Interleave the targets cooperatively, each one being visited by a task of a
+/// [`SimpleExecutor`] yielding to the others after each step of its visit
 pub(crate) struct RoundRobinExecutor;
 
 impl<'tr> Executor<'tr> for RoundRobinExecutor {
     fn execute(
         &self,
-        reporter: &'tr Box<dyn Reporter>,
-        test_driver: &'tr Box<(dyn TestDriver + 'static)>,
+        reporter: &'tr dyn Reporter,
+        test_driver: &'tr dyn TestDriver,
         test_suite: &'tr TestSuite,
         exec_contexts: &'tr mut [ExecutionContext],
     ) {
-        struct VisitorContext<'a> {
-            execution_context: &'a mut ExecutionContext,
-            visitor: Visitor<'a>,
-        }
-
-        let mut visitor_contexts = exec_contexts
-            .iter_mut()
-            .map(|exec_ctx| VisitorContext::<'tr> {
-                execution_context: exec_ctx,
-                visitor: Visitor::new(&test_suite),
-            })
-            .collect::<VecDeque<_>>();
-        let mut finished_contexts = VecDeque::new();
-
-        while !visitor_contexts.is_empty() {
-            let visitor_context = &mut visitor_contexts[0];
-            let exec_context = &mut visitor_context.execution_context;
-            let visitor = &mut visitor_context.visitor;
-            let done = visitor.visit_next(|test_case, should_skip| {
-                exec_context.run(reporter, test_driver, test_suite, test_case, should_skip)
-            });
-            if done {
-                if let Some(context) = visitor_contexts.pop_front() {
-                    finished_contexts.push_back(context.execution_context);
+        let mut executor = SimpleExecutor::new();
+        for exec_context in exec_contexts {
+            executor.spawn(async move {
+                let mut visitor = Visitor::new(test_suite);
+                loop {
+                    if let Some(reason) = exec_context.abort_reason() {
+                        visitor.abort(reason);
+                    }
+                    let done = visitor
+                        .visit_next_async(async |test_case, should_skip| {
+                            exec_context.run(
+                                reporter,
+                                test_driver,
+                                test_suite,
+                                test_case,
+                                should_skip,
+                            )
+                        })
+                        .await;
+                    if done {
+                        break;
+                    }
+                    simple_executor::yield_now().await;
                 }
-            } else {
-                visitor_contexts.rotate_left(1);
-            }
+                reporter.report_target_execution_finished(exec_context.target());
+            });
         }
+        executor.run();
     }
 }
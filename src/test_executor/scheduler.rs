@@ -0,0 +1,69 @@
+use crate::test_executor::ExecutionContext;
+use crate::test_suite::config::TestSuiteConfig;
+
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+
+/// Hand out the targets to run to the threads of parallel executors, making sure targets
+/// declared mutually exclusive in the test suite config never run at the same time
+pub(crate) struct TargetScheduler<'a> {
+    config: &'a TestSuiteConfig,
+    state: Mutex<SchedulerState<'a>>,
+    target_finished: Condvar,
+}
+
+struct SchedulerState<'a> {
+    pending: VecDeque<&'a mut ExecutionContext>,
+    running: Vec<String>,
+}
+
+impl<'a> TargetScheduler<'a> {
+    pub(crate) fn new(
+        config: &'a TestSuiteConfig,
+        exec_contexts: impl IntoIterator<Item = &'a mut ExecutionContext>,
+    ) -> Self {
+        Self {
+            config,
+            state: Mutex::new(SchedulerState {
+                pending: exec_contexts.into_iter().collect(),
+                running: Vec::new(),
+            }),
+            target_finished: Condvar::new(),
+        }
+    }
+
+    /// Take the first pending target not exclusive with a running one, waiting for running
+    /// targets to finish if there is none, or return `None` once all targets were handed out
+    /// [`Self::finished`] must be called once the target ran.
+    pub(crate) fn next(&self) -> Option<&'a mut ExecutionContext> {
+        // UNWRAP: the lock is never held while panicking
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.pending.is_empty() {
+                return None;
+            }
+            let runnable = state.pending.iter().position(|exec_context| {
+                !state
+                    .running
+                    .iter()
+                    .any(|running| self.config.are_exclusive(running, exec_context.target()))
+            });
+            if let Some(index) = runnable {
+                // UNWRAP: the index was just found in the pending targets
+                let exec_context = state.pending.remove(index).unwrap();
+                state.running.push(exec_context.target().to_string());
+                return Some(exec_context);
+            }
+            // UNWRAP: the lock is never held while panicking
+            state = self.target_finished.wait(state).unwrap();
+        }
+    }
+
+    /// Let the targets exclusive with the given one run
+    pub(crate) fn finished(&self, target: &str) {
+        // UNWRAP: the lock is never held while panicking
+        let mut state = self.state.lock().unwrap();
+        state.running.retain(|running| running != target);
+        self.target_finished.notify_all();
+    }
+}
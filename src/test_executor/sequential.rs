@@ -1,29 +1,145 @@
 use crate::reporter::Reporter;
 use crate::test_driver::TestDriver;
 use crate::test_executor::{ExecutionContext, Executor};
-use crate::test_suite::TestSuite;
 use crate::test_suite::visitor::Visitor;
+use crate::test_suite::{TestCase, TestFile, TestSuite};
 
-pub(crate) struct SequentialExecutor;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+/// Run the test cases of each target in order, one target after the other
+/// Consecutive test files declared independent may run concurrently on the same target, up to
+/// `file_jobs` at a time, the test cases of a test file still running in order between its setup
+/// and teardown.
+pub(crate) struct SequentialExecutor {
+    /// Maximum number of independent test files run at the same time on a target
+    pub(crate) file_jobs: NonZeroUsize,
+}
 
 impl<'tr> Executor<'tr> for SequentialExecutor {
     fn execute(
         &self,
-        reporter: &'tr Box<dyn Reporter>,
-        test_driver: &'tr Box<(dyn TestDriver + 'static)>,
+        reporter: &'tr dyn Reporter,
+        test_driver: &'tr dyn TestDriver,
         test_suite: &'tr TestSuite,
         exec_contexts: &'tr mut [ExecutionContext],
     ) {
         for exec_context in exec_contexts {
-            let mut visitor = Visitor::new(&test_suite);
-            loop {
-                let done = visitor.visit_next(|test_case, should_skip| {
-                    exec_context.run(reporter, test_driver, test_suite, test_case, should_skip)
-                });
-                if done {
-                    break;
+            if self.file_jobs.get() > 1 && exec_context.can_fork() {
+                self.execute_test_files_concurrently(
+                    reporter,
+                    test_driver,
+                    test_suite,
+                    exec_context,
+                );
+            } else {
+                let mut visitor = Visitor::new(test_suite);
+                while !Self::visit_next(
+                    reporter,
+                    test_driver,
+                    test_suite,
+                    exec_context,
+                    &mut visitor,
+                ) {}
+            }
+            reporter.report_target_execution_finished(exec_context.target());
+        }
+    }
+}
+
+impl<'tr> SequentialExecutor {
+    /// Run the next test case of a visit, returning whether the visit is done
+    fn visit_next(
+        reporter: &'tr dyn Reporter,
+        test_driver: &'tr dyn TestDriver,
+        test_suite: &'tr TestSuite,
+        exec_context: &mut ExecutionContext,
+        visitor: &mut Visitor,
+    ) -> bool {
+        if let Some(reason) = exec_context.abort_reason() {
+            visitor.abort(reason);
+        }
+        visitor.visit_next(|test_case, should_skip| {
+            exec_context.run(reporter, test_driver, test_suite, test_case, should_skip)
+        })
+    }
+
+    /// Run the test suite on a target, the consecutive independent test files running
+    /// concurrently once the test suite is set up, each in its own forked execution context
+    fn execute_test_files_concurrently(
+        &self,
+        reporter: &'tr dyn Reporter,
+        test_driver: &'tr dyn TestDriver,
+        test_suite: &'tr TestSuite,
+        exec_context: &mut ExecutionContext,
+    ) {
+        let mut suite_visitor = Visitor::of_suite_fixture(test_suite);
+        // Only the test suite setup, the teardown being visited once all test files ran
+        Self::visit_next(
+            reporter,
+            test_driver,
+            test_suite,
+            exec_context,
+            &mut suite_visitor,
+        );
+        let batches = test_suite
+            .test_files()
+            .chunk_by(|test_file, next| test_file.is_independent() && next.is_independent());
+        for batch in batches {
+            if let Some(reason) = exec_context.abort_reason() {
+                suite_visitor.abort(reason);
+            }
+            let should_skip = suite_visitor.skip_advice();
+            let mut forked = batch
+                .iter()
+                .map(|test_file| (exec_context.fork(Self::test_cases_of(test_file)), test_file))
+                .collect::<Vec<_>>()
+                .into_iter();
+            let pending = Mutex::new(&mut forked);
+            let done = Mutex::new(Vec::new());
+            std::thread::scope(|scope| {
+                for _ in 0..batch.len().min(self.file_jobs.get()) {
+                    scope.spawn(|| {
+                        loop {
+                            // UNWRAP: the lock is never held while panicking
+                            let next = pending.lock().unwrap().next();
+                            let Some((mut forked_context, test_file)) = next else {
+                                break;
+                            };
+                            let mut visitor =
+                                Visitor::of_test_file(test_suite, test_file, should_skip.clone());
+                            while !Self::visit_next(
+                                reporter,
+                                test_driver,
+                                test_suite,
+                                &mut forked_context,
+                                &mut visitor,
+                            ) {}
+                            // UNWRAP: the lock is never held while panicking
+                            done.lock().unwrap().push(forked_context);
+                        }
+                    });
                 }
+            });
+            // UNWRAP: the lock is never held while panicking
+            for forked_context in done.into_inner().unwrap() {
+                exec_context.join(forked_context);
             }
         }
+        while !Self::visit_next(
+            reporter,
+            test_driver,
+            test_suite,
+            exec_context,
+            &mut suite_visitor,
+        ) {}
+    }
+
+    fn test_cases_of(test_file: &TestFile) -> impl Iterator<Item = &TestCase> {
+        test_file
+            .setup_test_case
+            .iter()
+            .chain(&test_file.test_cases)
+            .chain(&test_file.teardown_test_case)
     }
 }
@@ -1,110 +1,806 @@
+use crate::allure::AllureResults;
+use crate::artifact_storage::{self, ArtifactStorage};
+use crate::comparison::Comparison;
 use crate::error::{self, Result};
 use crate::execution_strategy::ExecutionStrategy;
+use crate::exit_code::RunOutcome;
+use crate::fault_injection::{FaultInjector, FaultyReporter, FaultyTestDriver};
+use crate::history::History;
+use crate::interrupt;
+use crate::manifest::RunManifest;
+use crate::paused_run::Checkpoint;
+use crate::ports::PortAllocator;
+use crate::progress::ProgressTracker;
+use crate::redaction::Redactor;
 use crate::reporter::Reporter;
+use crate::reporter::checkpoint::CheckpointReporter;
+use crate::reporter::composite::CompositeReporter;
+use crate::reporter::events::EventReporter;
+use crate::reporter::github::GithubReporter;
 use crate::reporter::human_friendly::HumanFriendlyReporter;
-use crate::settings::Settings;
-use crate::test_driver::{TestDriver, TestDriverRegistry};
+use crate::reporter::locale::fill;
+use crate::reporter::summary_file::SummaryFileReporter;
+use crate::rng::Rng;
+use crate::secrets::Secrets;
+use crate::services::Services;
+use crate::settings::{Settings, ShowOutput};
+use crate::target_backend::lab_locks::{LabLocks, LockBackend};
+use crate::target_backend::{self, TargetBackend};
+use crate::target_watcher::TargetWatcher;
+use crate::test_driver::{Env, TestDriver, TestDriverRegistry, process};
+use crate::test_executor::parallel::ParallelExecutor;
+use crate::test_executor::parallel_targets::ParallelTargetsExecutor;
 use crate::test_executor::round_robin::RoundRobinExecutor;
 use crate::test_executor::sequential::SequentialExecutor;
-use crate::test_executor::{ExecutionContext, Executor};
+use crate::test_executor::{AbortSignal, ExecutionContext, Executor, TestLocks};
 use crate::test_suite::TestSuite;
-use crate::test_suite::config::TestSuiteConfig;
+use crate::test_suite::config::{StageFailurePolicy, TestSuiteConfig};
+use crate::test_suite::filter::SpeedFilter;
 use crate::test_suite::registry::TestSuiteRegistry;
+use crate::test_suite::status::{SkipReason, Statistics};
+use crate::time;
 
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Builder of a [`TestRunner`], created with [`TestRunner::builder`]
+pub struct TestRunnerBuilder {
+    settings: Settings,
+    /// Reporter replacing the console one
+    main_reporter: Option<Box<dyn Reporter>>,
+    reporters: Vec<Box<dyn Reporter>>,
+    test_drivers: TestDriverRegistry,
+}
+
+impl TestRunnerBuilder {
+    /// Report the run with the given reporter instead of the console one
+    pub fn with_reporter(mut self, main_reporter: Box<dyn Reporter>) -> Self {
+        self.main_reporter = Some(main_reporter);
+        self
+    }
+
+    /// Add a reporter receiving the events of the run, after the main one and the summary file
+    pub fn add_reporter(mut self, reporter: Box<dyn Reporter>) -> Self {
+        self.reporters.push(reporter);
+        self
+    }
+
+    /// Register a test driver, used by the test suites declaring the given driver name in their
+    /// config, e.g. to run test cases written in a language batrun does not support
+    pub fn with_test_driver(mut self, driver_name: &str, test_driver: Box<dyn TestDriver>) -> Self {
+        self.test_drivers.register(driver_name, test_driver);
+        self
+    }
+
+    /// Create the test runner and load the test suites of the settings
+    pub fn build(self) -> Result<TestRunner> {
+        let settings = self.settings;
+        let console_output = self.main_reporter.is_none();
+        let main_reporter = self.main_reporter.unwrap_or_else(|| {
+            Box::new(
+                HumanFriendlyReporter::new(
+                    settings.debug,
+                    settings.matrix_summary,
+                    settings.group_output,
+                    settings.plain,
+                    settings.color,
+                    settings.locale,
+                )
+                .with_run_metadata(settings.run_metadata.clone())
+                .with_show_output(settings.show_output)
+                .with_verbosity(settings.verbosity),
+            )
+        });
+        process::set_kill_grace_period(settings.kill_grace_period);
+        let mut reporter = CompositeReporter::new();
+        reporter.add(main_reporter);
+        if settings.github_annotations.enabled(console_output) {
+            reporter.add(Box::new(GithubReporter::new()));
+        }
+        if let Some(summary_file) = &settings.summary_file {
+            let file = fs::File::create(summary_file).map_err(|io_err| error::kind::ReportIo {
+                filename: summary_file.clone(),
+                source: io_err,
+            });
+            match file {
+                Ok(file) => reporter.add(Box::new(SummaryFileReporter::new(
+                    HumanFriendlyReporter::new(
+                        false,
+                        settings.matrix_summary,
+                        false,
+                        settings.plain,
+                        settings.color,
+                        settings.locale,
+                    )
+                    .with_run_metadata(settings.run_metadata.clone())
+                    .with_file(file),
+                ))),
+                Err(error) => {
+                    let error = error.into();
+                    reporter.error_from(&error);
+                    return Err(error);
+                }
+            }
+        }
+        if let Some(events_file) = &settings.events_file {
+            match fs::File::create(events_file) {
+                Ok(file) => reporter.add(Box::new(EventReporter::json_lines(file))),
+                Err(io_err) => {
+                    let error = error::kind::ReportIo {
+                        filename: events_file.clone(),
+                        source: io_err,
+                    }
+                    .into();
+                    reporter.error_from(&error);
+                    return Err(error);
+                }
+            }
+        }
+        let checkpoint = Arc::new(Checkpoint::new(&settings));
+        reporter.add(Box::new(CheckpointReporter::new(checkpoint.clone())));
+        for added_reporter in self.reporters {
+            reporter.add(added_reporter);
+        }
+        let fault_injector = Arc::new(FaultInjector::new(settings.inject_faults.clone()));
+        if !fault_injector.is_empty() {
+            reporter.add(Box::new(FaultyReporter::new(fault_injector.clone())));
+        }
+        let abort_signal = match &settings.pause_file {
+            Some(pause_file) => AbortSignal::with_pause_file(pause_file.clone()),
+            None => AbortSignal::default(),
+        };
+        let mut test_runner = TestRunner {
+            settings,
+            test_drivers: self.test_drivers,
+            test_suites: TestSuiteRegistry::new(),
+            reporter,
+            abort_signal: Arc::new(abort_signal),
+            test_locks: Arc::default(),
+            port_allocator: Arc::default(),
+            fault_injector,
+            checkpoint,
+            allure_results: None,
+            last_regressions: None,
+        };
+        test_runner.load_test_suites()?;
+        Ok(test_runner)
+    }
+}
 
 pub struct TestRunner {
     settings: Settings,
     test_drivers: TestDriverRegistry,
     test_suites: TestSuiteRegistry,
-    console_reporter: Box<dyn Reporter>,
+    reporter: CompositeReporter,
+    /// Aborts the run of all test suites, e.g. at the first failure in fail-fast mode
+    abort_signal: Arc<AbortSignal>,
+    /// Serializes the test cases holding the same lock, across all test suites and targets
+    test_locks: Arc<TestLocks>,
+    /// Allocates distinct ports to the test cases running at the same time, across all test
+    /// suites and targets
+    port_allocator: Arc<PortAllocator>,
+    /// Injects the faults given in the settings, if any
+    fault_injector: Arc<FaultInjector>,
+    /// Progress of the run, saved as the test cases complete so that the run can be resumed
+    checkpoint: Arc<Checkpoint>,
+    /// Results of the test suites which ran in the format of Allure, if written, once the first
+    /// test suite ran
+    allure_results: Option<AllureResults>,
+    /// Whether test cases of the last test suite run fail while they did not in the previous run
+    /// compared with, if compared
+    last_regressions: Option<bool>,
 }
 
 impl TestRunner {
+    /// Directory of the output directory of a test suite holding the output of each run, when
+    /// kept
+    const RUNS_DIR_NAME: &str = "runs";
+    /// Symbolic link to the output of the last run, when the output of each run is kept
+    const LATEST_RUN_LINK_NAME: &str = "latest";
+    /// Environment variables forwarded to the test processes even in clean environment mode, so
+    /// that programs can still be found
+    const ALWAYS_PASSED_ENV_VARS: &[&str] = &["PATH"];
+
+    /// Create a test runner reporting the run on the console
     pub fn new(settings: Settings) -> Result<Self> {
-        let debug_enabled = settings.debug;
-        let matrix_summary = settings.matrix_summary;
-        let mut test_runner = Self {
+        Self::builder(settings).build()
+    }
+
+    /// Create a test runner reporting the run with the given reporter instead of the console
+    /// one, e.g. for applications embedding batrun with their own user interface
+    /// More reporters can be added with [`Self::add_reporter`].
+    pub fn with_reporter(settings: Settings, main_reporter: Box<dyn Reporter>) -> Result<Self> {
+        Self::builder(settings).with_reporter(main_reporter).build()
+    }
+
+    /// Start building a test runner, to customize its reporters and test drivers before the test
+    /// suites are loaded
+    pub fn builder(settings: Settings) -> TestRunnerBuilder {
+        TestRunnerBuilder {
             settings,
+            main_reporter: None,
+            reporters: Vec::new(),
             test_drivers: TestDriverRegistry::new(),
-            test_suites: TestSuiteRegistry::new(),
-            console_reporter: Box::new(HumanFriendlyReporter::new(debug_enabled, matrix_summary)),
-        };
-        test_runner.load_test_suites()?;
-        Ok(test_runner)
+        }
     }
 
-    pub fn list_tests(&self, test_suite_dir: &Path) -> Result<()> {
+    /// Add a reporter receiving the events of the run, after the ones already added
+    pub fn add_reporter(&mut self, reporter: Box<dyn Reporter>) {
+        self.reporter.add(reporter);
+    }
+
+    pub fn list_tests(&self, test_suite_dir: &Path, include_fixtures: bool) -> Result<()> {
         let test_suite = self.test_suites.get(test_suite_dir)?;
-        self.console_reporter.report_test_list(test_suite);
+        self.reporter.report_test_list(test_suite, include_fixtures);
         Ok(())
     }
 
     pub fn list_targets(&self, test_suite_dir: &Path) -> Result<()> {
         let test_suite = self.test_suites.get(test_suite_dir)?;
-        self.console_reporter.report_target_list(test_suite);
+        self.reporter.report_target_list(test_suite);
         Ok(())
     }
 
-    pub fn run_tests(&mut self, test_suite_dir: &Path) -> Result<()> {
-        self.console_reporter.notice("");
-        self.console_reporter.notice(&format!(
+    /// Run a test suite on all the targets, returning the statistics of all targets together
+    pub fn run_tests(&mut self, test_suite_dir: &Path) -> Result<Statistics> {
+        self.reporter.notice("");
+        self.reporter.notice(&format!(
             "Running test suite `{}`...",
             test_suite_dir.display()
         ));
 
+        let started_at = SystemTime::now();
+        let start = Instant::now();
+        let health_at_start = self.reporter.health();
         let test_suite = self.test_suites.get(test_suite_dir)?;
         let test_driver = self.test_drivers.get(&test_suite.config().driver)?;
+        let fault_injector = self.fault_injector.clone();
+        let faulty_test_driver;
+        let test_driver: &dyn TestDriver = if fault_injector.is_empty() {
+            test_driver
+        } else {
+            faulty_test_driver = FaultyTestDriver::new(test_driver, &fault_injector);
+            &faulty_test_driver
+        };
 
-        let out_dir = self.settings.out_dir.join(&test_suite.config().name);
+        let test_suite_out_dir = self
+            .settings
+            .out_dir
+            .join(self.test_suites.out_dir_name(test_suite_dir)?);
 
-        self.prepare_out_dir(&out_dir)?;
-        let mut exec_contexts = self
+        self.prepare_out_dir(&test_suite_out_dir)?;
+        self.reporter
+            .report_test_suite_out_dir(test_suite, &test_suite_out_dir);
+        // The history is shared by all runs
+        let history_path = test_suite_out_dir.join(History::FILE_NAME);
+        let out_dir = if self.settings.keep_history {
+            self.prepare_run_out_dir(&test_suite_out_dir, started_at)?
+        } else {
+            test_suite_out_dir.clone()
+        };
+        let mut history = History::load(&history_path).unwrap_or_else(|error| {
+            self.reporter.error_from(&error);
+            self.reporter
+                .warning("Expected durations from previous runs are unavailable.");
+            History::default()
+        });
+        let target_watcher = self
             .settings
-            .targets
+            .targets_file
+            .as_ref()
+            .map(|path| TargetWatcher::new(path, self.settings.targets_wait));
+        let mut targets = self.settings.targets.clone();
+        if let Some(target_watcher) = &target_watcher {
+            for target in target_watcher.targets() {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+        }
+        if let Some(seed) = self.settings.target_shuffle_seed {
+            Rng::new(seed).shuffle(&mut targets);
+            self.reporter.info(&format!(
+                "Targets shuffled with seed {seed}: `{}`.",
+                targets.join("`, `")
+            ));
+        }
+        let target_backends = test_suite
+            .config()
+            .target_backends
+            .iter()
+            .map(|(target, config)| {
+                (
+                    target.as_str(),
+                    Arc::from(target_backend::from_config(config, test_suite.path())),
+                )
+            })
+            .collect::<HashMap<_, Arc<dyn TargetBackend>>>();
+        let lab_locks = test_suite.config().lab_locks.as_ref().map(|config| {
+            (
+                config,
+                Arc::new(LockBackend::new(
+                    config.backend.clone(),
+                    test_suite.path(),
+                    &self.settings.out_dir,
+                )),
+            )
+        });
+        let host_env = self.host_env(test_suite.config());
+        let secrets = match &test_suite.config().secrets {
+            Some(config) => Arc::new(
+                Secrets::load(config, test_suite.path())
+                    .inspect_err(|error| self.reporter.error_from(error))?,
+            ),
+            None => Arc::default(),
+        };
+        let redaction_rules = test_suite
+            .config()
+            .redact
             .iter()
-            .map(|target| ExecutionContext::new(&test_suite, target.clone(), &out_dir))
+            .chain(&self.settings.redact)
+            .cloned()
+            .collect();
+        let redactor = Arc::new(Redactor::new(&secrets, redaction_rules));
+        let artifact_storage: Arc<dyn ArtifactStorage> = Arc::from(artifact_storage::from_config(
+            &test_suite.config().artifact_storage,
+            test_suite.path(),
+            &self.settings.out_dir,
+        ));
+        let progress = Arc::new(ProgressTracker::default());
+        let new_exec_context = |target: String| {
+            let restored = self
+                .settings
+                .resumed_run
+                .as_ref()
+                .and_then(|resumed_run| resumed_run.results(test_suite_dir, &target))
+                .cloned()
+                .unwrap_or_default();
+            let mut backend = target_backends.get(target.as_str()).cloned();
+            if let Some((config, locks)) = &lab_locks {
+                backend = LabLocks::wrap(config, locks, &target, backend);
+            }
+            let mut config_env = test_suite.config().env.clone();
+            if let Some(target_config) = test_suite.config().target(&target) {
+                config_env.extend(target_config.env());
+            }
+            let exec_context = ExecutionContext::new(
+                test_suite,
+                target,
+                &out_dir,
+                &history,
+                self.settings.slow_factor,
+            )
+            .with_host_env(host_env.clone())
+            .with_config_env(config_env)
+            .with_run_env(self.settings.env.clone())
+            .with_secrets(secrets.clone())
+            .with_redactor(redactor.clone())
+            .with_stream_output(self.settings.show_output == ShowOutput::All)
+            .with_failure_output(
+                (self.settings.show_output == ShowOutput::Failed)
+                    .then_some(self.settings.output_lines),
+            )
+            .with_artifact_storage(artifact_storage.clone())
+            .with_retries(self.settings.retries.unwrap_or(test_suite.config().retries))
+            .with_timeout(self.settings.timeout)
+            .with_fixture_timeout(self.settings.fixture_timeout)
+            .with_strict_xfail(self.settings.strict_xfail)
+            .with_orphans(self.settings.strict_orphans, self.settings.kill_orphans)
+            .with_strict_skips(
+                self.settings.strict_skips,
+                self.settings.allowed_skip_tags.clone(),
+            )
+            .with_abort_signal(self.abort_signal.clone(), self.settings.fail_fast)
+            .with_test_locks(self.test_locks.clone())
+            .with_port_allocator(self.port_allocator.clone())
+            .with_restored_results(restored)
+            .with_dry_run(self.settings.dry_run)
+            .with_progress_tracker(progress.clone());
+            match backend {
+                Some(backend) => exec_context.with_backend(backend),
+                None => exec_context,
+            }
+        };
+        let services = if self.settings.dry_run {
+            Services::default()
+        } else {
+            self.start_services(test_suite, &host_env, &out_dir)?
+        };
+        let mut exec_contexts = targets
+            .into_iter()
+            .map(new_exec_context)
             .collect::<Vec<_>>();
+        self.reporter
+            .report_targets_execution_started(test_suite, &exec_contexts);
+        let exec_contexts = match &target_watcher {
+            Some(target_watcher) => self.run_batches(
+                test_suite,
+                test_driver,
+                exec_contexts,
+                target_watcher,
+                |target| new_exec_context(target).with_late_joined(),
+            ),
+            None => {
+                self.run_batch(test_suite, test_driver, &mut exec_contexts);
+                self.release_targets(&mut exec_contexts);
+                exec_contexts
+            }
+        };
+        for error in services.stop() {
+            self.reporter.error_from(&error);
+        }
 
-        Self::run_executor(
+        self.reporter
+            .report_test_suite_execution_summary(test_suite, &exec_contexts);
+        if self.settings.dry_run {
+            self.report_estimate(test_suite, &exec_contexts);
+        }
+        self.reporter
+            .report_test_suite_time(test_suite, start.elapsed());
+        if interrupt::is_interrupted() {
+            self.reporter
+                .warning("The run was interrupted, the test cases not run yet were skipped.");
+        } else if self.abort_signal.reason() == Some(SkipReason::Paused) {
+            self.reporter
+                .warning("The run was paused, the test cases not run yet were skipped.");
+        }
+
+        for exec_context in &exec_contexts {
+            exec_context.record_history(&mut history);
+        }
+        if let Err(error) = history.save(&history_path) {
+            self.reporter.error_from(&error);
+        }
+        let comparison = self.compare(test_suite, &exec_contexts);
+        self.last_regressions = comparison.as_ref().map(Comparison::has_regressions);
+        let manifest = RunManifest::new(
+            &self.settings,
             test_suite,
-            &test_driver,
-            &self.console_reporter,
-            &mut exec_contexts,
-            self.settings.exec_strategy.clone(),
-        );
+            &test_suite_out_dir,
+            &exec_contexts,
+            started_at,
+        )
+        .with_comparison(comparison.as_ref())
+        .with_runner_health(self.reporter.health().since(&health_at_start));
+        if let Err(error) = manifest.save(&out_dir.join(RunManifest::FILE_NAME)) {
+            self.reporter.error_from(&error);
+        }
+        if self.settings.allure_results && !self.settings.dry_run {
+            if self.allure_results.is_none() {
+                let dir = self.settings.out_dir.join(AllureResults::DIR_NAME);
+                match AllureResults::new(dir, &self.settings.run_metadata) {
+                    Ok(allure_results) => self.allure_results = Some(allure_results),
+                    Err(error) => self.reporter.error_from(&error),
+                }
+            }
+            if let Some(allure_results) = &mut self.allure_results
+                && let Err(error) = allure_results.write(test_suite, &exec_contexts)
+            {
+                self.reporter.error_from(&error);
+            }
+        }
 
-        self.console_reporter
-            .report_test_suite_execution_summary(&test_suite, &exec_contexts);
+        let mut statistics = Statistics::default();
+        for exec_context in &exec_contexts {
+            statistics += &exec_context.get_statistics();
+        }
+        Ok(statistics)
+    }
 
-        Ok(())
+    /// Report how long the test cases gone through in dry-run mode are expected to take, on each
+    /// target and overall given the execution strategy
+    fn report_estimate(&self, test_suite: &TestSuite, exec_contexts: &[ExecutionContext]) {
+        let estimates = exec_contexts
+            .iter()
+            .map(ExecutionContext::estimate)
+            .collect::<Vec<_>>();
+        let durations = estimates
+            .iter()
+            .map(|estimate| estimate.duration)
+            .collect::<Vec<_>>();
+        let total = self
+            .settings
+            .exec_strategy
+            .estimate_duration(&durations, self.settings.jobs);
+        self.reporter
+            .report_dry_run_estimate(test_suite, &estimates, total);
+    }
+
+    /// Run the test suite for a batch of targets, stage by stage if stages are declared
+    fn run_batch(
+        &self,
+        test_suite: &TestSuite,
+        test_driver: &dyn TestDriver,
+        exec_contexts: &mut [ExecutionContext],
+    ) {
+        if test_suite.config().stages.is_empty() {
+            self.run_executor(test_suite, test_driver, exec_contexts);
+        } else {
+            self.run_stages(test_suite, test_driver, exec_contexts);
+        }
+    }
+
+    /// Run a batch of targets, the targets joining the run meanwhile (or up to the wait duration
+    /// of the watcher after all targets are done) being run in batches of their own at the same
+    /// time, and return the execution contexts of all of them
+    fn run_batches(
+        &self,
+        test_suite: &TestSuite,
+        test_driver: &dyn TestDriver,
+        exec_contexts: Vec<ExecutionContext>,
+        target_watcher: &TargetWatcher,
+        new_exec_context: impl Fn(String) -> ExecutionContext,
+    ) -> Vec<ExecutionContext> {
+        let mut known_targets = exec_contexts
+            .iter()
+            .map(|exec_context| exec_context.target().to_string())
+            .collect::<Vec<_>>();
+        std::thread::scope(|scope| {
+            let run_batch = |mut exec_contexts: Vec<ExecutionContext>| {
+                scope.spawn(move || {
+                    self.run_batch(test_suite, test_driver, &mut exec_contexts);
+                    self.release_targets(&mut exec_contexts);
+                    exec_contexts
+                })
+            };
+            let mut batches = vec![run_batch(exec_contexts)];
+            let mut idle_since = None;
+            while self.abort_signal.reason().is_none() {
+                let new_targets = target_watcher
+                    .new_targets(&known_targets.iter().map(String::as_str).collect::<Vec<_>>());
+                if !new_targets.is_empty() {
+                    self.reporter.notice(&format!(
+                        "Target(s) `{}` joined the run.",
+                        new_targets.join("`, `")
+                    ));
+                    known_targets.extend(new_targets.iter().cloned());
+                    let exec_contexts = new_targets
+                        .into_iter()
+                        .map(&new_exec_context)
+                        .collect::<Vec<_>>();
+                    self.reporter
+                        .report_targets_execution_started(test_suite, &exec_contexts);
+                    batches.push(run_batch(exec_contexts));
+                    idle_since = None;
+                    continue;
+                }
+                if batches.iter().all(|batch| batch.is_finished()) {
+                    let idle_since = *idle_since.get_or_insert_with(Instant::now);
+                    if idle_since.elapsed() >= target_watcher.wait() {
+                        break;
+                    }
+                }
+                std::thread::sleep(TargetWatcher::POLL_INTERVAL);
+            }
+            batches
+                .into_iter()
+                // UNWRAP: a batch panics only if the run would have panicked without threads
+                .flat_map(|batch| batch.join().unwrap())
+                .collect()
+        })
+    }
+
+    /// Start the services of a test suite, with the environment of the test suite
+    fn start_services(
+        &self,
+        test_suite: &TestSuite,
+        host_env: &Env,
+        out_dir: &Path,
+    ) -> Result<Services> {
+        let services = &test_suite.config().services;
+        if services.is_empty() {
+            return Ok(Services::default());
+        }
+        let mut env = host_env.clone();
+        env.extend(test_suite.config().env.clone());
+        env.extend(self.settings.env.clone());
+        let services = Services::start(services, test_suite.path(), &env, out_dir)
+            .inspect_err(|error| self.reporter.error_from(error))?;
+        self.reporter.info(&format!(
+            "Service(s) `{}` started.",
+            services.names().collect::<Vec<_>>().join("`, `")
+        ));
+        Ok(services)
+    }
+
+    /// Release the targets made available by their backend (e.g. stop simulators)
+    fn release_targets(&self, exec_contexts: &mut [ExecutionContext]) {
+        for exec_context in exec_contexts {
+            if let Err(error) = exec_context.release_target() {
+                self.reporter.error_from(&error);
+            }
+        }
     }
 
     fn run_executor(
+        &self,
         test_suite: &TestSuite,
-        test_driver: &Box<dyn TestDriver>,
-        reporter: &Box<dyn Reporter>,
+        test_driver: &dyn TestDriver,
         exec_contexts: &mut [ExecutionContext],
-        exec_strategy: ExecutionStrategy,
     ) {
-        let executor: Box<dyn Executor> = match exec_strategy {
+        let executor: Box<dyn Executor> = match self.settings.exec_strategy {
             ExecutionStrategy::RoundRobin => Box::new(RoundRobinExecutor {}),
-            ExecutionStrategy::Sequential => Box::new(SequentialExecutor {}),
-            _ => todo!("{:X?}", exec_strategy),
+            ExecutionStrategy::Sequential => Box::new(SequentialExecutor {
+                file_jobs: self.settings.file_jobs,
+            }),
+            ExecutionStrategy::Parallel => Box::new(ParallelExecutor {
+                max_workers: self.settings.jobs,
+                file_jobs: self.settings.file_jobs,
+            }),
+            ExecutionStrategy::ParallelTargets => Box::new(ParallelTargetsExecutor {
+                file_jobs: self.settings.file_jobs,
+            }),
         };
-        executor.execute(reporter, test_driver, test_suite, exec_contexts);
+        executor.execute(&self.reporter, test_driver, test_suite, exec_contexts);
+    }
+
+    /// Run the test suite setup, then each stage one after the other, and finally the test suite
+    /// teardown
+    /// A failure in a stage whose policy is to stop skips the next stages for the failing target,
+    /// but not the test suite teardown.
+    fn run_stages(
+        &self,
+        test_suite: &TestSuite,
+        test_driver: &dyn TestDriver,
+        exec_contexts: &mut [ExecutionContext],
+    ) {
+        let reporter = &self.reporter;
+
+        let setup = test_suite.setup_only();
+        self.run_executor(&setup, test_driver, exec_contexts);
+        for exec_context in exec_contexts.iter_mut() {
+            if exec_context.has_failures(&setup) {
+                exec_context.skip_remaining(SkipReason::TestSuiteSetupError);
+            }
+        }
+
+        for stage in test_suite.stages() {
+            reporter.notice(&format!("Running stage `{}`...", stage.name));
+            self.run_executor(&stage.test_suite, test_driver, exec_contexts);
+            if stage.on_failure == StageFailurePolicy::Stop {
+                for exec_context in exec_contexts.iter_mut() {
+                    if exec_context.skip_reason().is_none()
+                        && exec_context.has_failures(&stage.test_suite)
+                    {
+                        reporter.warning(&format!(
+                            "Stage `{}` failed for target `{}`, skipping the next stages.",
+                            stage.name,
+                            exec_context.target()
+                        ));
+                        exec_context
+                            .skip_remaining(SkipReason::PreviousStageFailed(stage.name.clone()));
+                    }
+                }
+            }
+        }
+
+        for exec_context in exec_contexts.iter_mut() {
+            if let Some(SkipReason::PreviousStageFailed(_)) = exec_context.skip_reason() {
+                exec_context.resume();
+            }
+        }
+        let teardown = test_suite.teardown_only();
+        self.run_executor(&teardown, test_driver, exec_contexts);
+    }
+
+    /// Run the test cases of all the test suites of the settings, in order, until interrupted or
+    /// paused
+    /// Return the outcome of the whole run, from which the exit code of batrun is computed. The
+    /// progress of the run is saved in the output directory as the test cases complete, and
+    /// removed once the run completes, so that a run paused, interrupted or stopped by a crash
+    /// can be resumed.
+    pub fn run_all(&mut self) -> Result<RunOutcome> {
+        self.reporter
+            .report_run_started(&self.settings.run_metadata);
+        let mut outcome = RunOutcome::Passed;
+        for test_suite_dir in self.settings.test_suite_dirs.clone() {
+            let statistics = self.run_tests(&test_suite_dir)?;
+            let mut test_suite_outcome =
+                RunOutcome::from_statistics(&statistics, self.settings.strict_xfail);
+            // The failures already there in the previous run do not fail the run
+            if self.settings.fail_only_on_regressions
+                && self.last_regressions == Some(false)
+                && matches!(
+                    test_suite_outcome,
+                    RunOutcome::Failed | RunOutcome::RunnerFailed
+                )
+            {
+                test_suite_outcome = RunOutcome::Passed;
+            }
+            outcome = outcome.max(test_suite_outcome);
+            if interrupt::is_interrupted() {
+                return Ok(self.save_progress(RunOutcome::Interrupted));
+            }
+            if self.abort_signal.reason() == Some(SkipReason::Paused) {
+                return Ok(self.save_progress(RunOutcome::Paused));
+            }
+        }
+        self.checkpoint.remove();
+        Ok(outcome)
+    }
+
+    /// Save the progress of the run stopped before all test cases ran, so that it can be resumed,
+    /// returning the given outcome if saved
+    fn save_progress(&self, outcome: RunOutcome) -> RunOutcome {
+        match self.checkpoint.save() {
+            Ok(path) => {
+                self.reporter.warning_detailed(
+                    &format!("Progress of the run saved to `{}`.", path.display()),
+                    &format!(
+                        "Run `batrun resume {}` to run the remaining test cases.",
+                        self.settings.out_dir.display()
+                    ),
+                );
+                outcome
+            }
+            Err(error) => {
+                self.reporter.error_from(&error);
+                RunOutcome::Error
+            }
+        }
     }
 
     pub fn settings(&self) -> &Settings {
         &self.settings
     }
 
+    /// The environment variables of batrun forwarded to the test processes of a test suite: all of
+    /// them, or only the allowed ones in clean environment mode
+    fn host_env(&self, test_suite_config: &TestSuiteConfig) -> Env {
+        let clean_env = self.settings.clean_env || test_suite_config.clean_env;
+        let is_passed = |name: &String| {
+            !clean_env
+                || Self::ALWAYS_PASSED_ENV_VARS.contains(&name.as_str())
+                || self.settings.env_pass.contains(name)
+                || test_suite_config.env_pass.contains(name)
+        };
+        std::env::vars_os()
+            // Variables which are not valid Unicode cannot be passed along as an `Env`
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .filter(|(name, _)| is_passed(name))
+            .collect()
+    }
+
+    /// Compare the results of a test suite with the previous run given in the settings, if any,
+    /// reporting how they changed
+    fn compare(
+        &self,
+        test_suite: &TestSuite,
+        exec_contexts: &[ExecutionContext],
+    ) -> Option<Comparison> {
+        let previous_run = self.settings.compare_with.as_ref()?;
+        if self.settings.dry_run {
+            return None;
+        }
+        let comparison = previous_run.compare(test_suite, exec_contexts);
+        match &comparison {
+            Some(comparison) => self.reporter.report_comparison(test_suite, comparison),
+            None => self.reporter.warning(&format!(
+                "The results of test suite `{}` are not compared, the run manifest `{}` being of \
+                 test suite `{}`.",
+                test_suite.config().name,
+                previous_run.path().display(),
+                previous_run.test_suite()
+            )),
+        }
+        comparison
+    }
+
+    /// Report the time the whole run took and how many warnings and errors were reported, once
+    /// all the test suites ran
+    pub fn report_total_time(&self, duration: Duration) {
+        self.reporter.report_total_time(duration);
+        self.reporter.report_runner_health(&self.reporter.health());
+    }
+
     fn load_test_suites(&mut self) -> Result<()> {
         let mut last_error = None;
         for test_suite_dir in self.settings.test_suite_dirs.clone() {
             if let Err(error) = self.load_test_suite(&test_suite_dir) {
-                self.console_reporter.error_from(&error);
+                self.reporter.error_from(&error);
                 last_error = Some(error);
             }
         }
@@ -116,29 +812,163 @@ impl TestRunner {
     }
 
     fn load_test_suite(&mut self, test_suite_dir: &Path) -> Result<()> {
-        let config = TestSuiteConfig::load(&test_suite_dir)?;
+        let config = TestSuiteConfig::load(test_suite_dir)?;
+        let out_dir_name = self
+            .test_suites
+            .new_out_dir_name(test_suite_dir, &config.name);
+        if out_dir_name != config.name {
+            self.reporter.warning(&format!(
+                "Another test suite is named `{}`, the output of test suite `{}` is written to \
+                 `{}`.",
+                config.name,
+                test_suite_dir.display(),
+                self.settings.out_dir.join(&out_dir_name).display()
+            ));
+        }
+        for deprecation in &config.deprecations {
+            self.reporter.warning_detailed(
+                &format!(
+                    "Test suite config `{}`: {deprecation}.",
+                    TestSuiteConfig::path_in(test_suite_dir).display()
+                ),
+                &format!(
+                    "Run `batrun migrate-config {}` to update it.",
+                    test_suite_dir.display()
+                ),
+            );
+        }
+        self.test_drivers.load(&config.driver);
         let test_driver = self.test_drivers.get(&config.driver)?;
-        let test_suite = test_driver.discover_tests(&test_suite_dir, &config)?;
-        self.test_suites.insert(test_suite_dir, test_suite);
+        test_driver
+            .check_options(&config.driver_options)
+            .map_err(|serde_err| error::kind::InvalidSuiteConfig {
+                filename: TestSuiteConfig::path_in(test_suite_dir),
+                source: serde::de::Error::custom(format!(
+                    "invalid `driver-options` for the `{}` driver: {serde_err}",
+                    config.driver
+                )),
+            })?;
+        let mut test_suite =
+            test_driver.discover_tests(test_suite_dir, &config, &self.settings.path_filter)?;
+        test_suite.expand_params();
+        if !self.settings.test_filter.is_empty() {
+            test_suite.retain_test_cases(|tc| self.settings.test_filter.matches(&tc.id()));
+        }
+        if !self.settings.tag_filter.is_empty() {
+            test_suite
+                .retain_test_cases(|tc| self.settings.tag_filter.matches(&tc.metadata().tags));
+        }
+        if self.settings.speed_filter != SpeedFilter::All {
+            self.filter_by_speed(&mut test_suite, &out_dir_name);
+        }
+        self.test_suites
+            .insert(test_suite_dir, test_suite, out_dir_name);
         Ok(())
     }
 
+    /// Select the test cases by how long they took on previous runs, so the selection follows
+    /// the durations as they change
+    fn filter_by_speed(&self, test_suite: &mut TestSuite, out_dir_name: &str) {
+        let history_path = self
+            .settings
+            .out_dir
+            .join(out_dir_name)
+            .join(History::FILE_NAME);
+        let history = History::load(&history_path).unwrap_or_else(|error| {
+            self.reporter.error_from(&error);
+            self.reporter.warning(
+                "All test cases are considered quick without the durations of previous runs.",
+            );
+            History::default()
+        });
+        let threshold = self.settings.slow_threshold;
+        let mut filtered_out = 0;
+        test_suite.retain_test_cases(|tc| {
+            let selected = self
+                .settings
+                .speed_filter
+                .matches(history.is_slow(&tc.id(), threshold));
+            filtered_out += usize::from(!selected);
+            selected
+        });
+        let messages = self.settings.locale.catalog();
+        let speed = match self.settings.speed_filter {
+            SpeedFilter::Slow => messages.speed_quick,
+            _ => messages.speed_slow,
+        };
+        self.reporter.info(&fill(
+            messages.speed_filtered_out,
+            &[
+                &filtered_out,
+                &speed,
+                &test_suite.config().name,
+                &time::format(threshold),
+            ],
+        ));
+    }
+
     fn prepare_out_dir(&self, out_dir: &Path) -> Result<()> {
         if out_dir.exists() {
-            self.console_reporter.warning(&format!(
-                "Output directory `{}` already exists. Contents may be overwritten.",
-                out_dir.display()
-            ));
+            // Each run gets its own directory when the output of previous runs is kept
+            if !self.settings.keep_history {
+                self.reporter.warning(&format!(
+                    "Output directory `{}` already exists. Contents may be overwritten.",
+                    out_dir.display()
+                ));
+            }
         } else {
-            fs::create_dir_all(&out_dir).map_err(|io_err| error::kind::SuiteConfigIo {
+            fs::create_dir_all(out_dir).map_err(|io_err| error::kind::SuiteConfigIo {
                 filename: out_dir.to_path_buf(),
                 source: io_err,
             })?;
-            self.console_reporter.info(&format!(
+            self.reporter.info(&format!(
                 "Output directory `{}` created.",
                 out_dir.display()
             ));
         }
         Ok(())
     }
+
+    /// Create the directory of this run in the output directory of the test suite, named after
+    /// the time it started, and point the `latest` symbolic link to it
+    fn prepare_run_out_dir(
+        &self,
+        test_suite_out_dir: &Path,
+        started_at: SystemTime,
+    ) -> Result<PathBuf> {
+        let runs_dir = test_suite_out_dir.join(Self::RUNS_DIR_NAME);
+        let timestamp = time::format_compact_timestamp(started_at);
+        // Runs started during the same second (e.g. one per test suite) get a numbered suffix
+        let mut run_dir_name = timestamp.clone();
+        let mut run_dir = runs_dir.join(&run_dir_name);
+        for suffix in 2.. {
+            if !run_dir.exists() {
+                break;
+            }
+            run_dir_name = format!("{timestamp}-{suffix}");
+            run_dir = runs_dir.join(&run_dir_name);
+        }
+        fs::create_dir_all(&run_dir).map_err(|io_err| error::kind::SuiteConfigIo {
+            filename: run_dir.clone(),
+            source: io_err,
+        })?;
+        self.reporter.info(&format!(
+            "Output of this run written to `{}`.",
+            run_dir.display()
+        ));
+
+        #[cfg(unix)]
+        {
+            let latest = test_suite_out_dir.join(Self::LATEST_RUN_LINK_NAME);
+            let _ = fs::remove_file(&latest);
+            let target = Path::new(Self::RUNS_DIR_NAME).join(&run_dir_name);
+            if let Err(io_err) = std::os::unix::fs::symlink(target, &latest) {
+                self.reporter.warning(&format!(
+                    "Cannot point `{}` to the output of this run: {io_err}.",
+                    latest.display()
+                ));
+            }
+        }
+        Ok(run_dir)
+    }
 }
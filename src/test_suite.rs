@@ -1,9 +1,15 @@
 pub mod config;
+pub mod filter;
+pub mod id;
+pub mod metadata;
 pub mod registry;
+pub mod stage;
 pub mod status;
 pub mod visitor;
 
 use self::config::TestSuiteConfig;
+use self::id::TestCaseId;
+use self::metadata::TestCaseMetadata;
 
 use std::path::{Path, PathBuf};
 
@@ -13,21 +19,40 @@ pub struct TestSuite {
     config: TestSuiteConfig,
     fixture: TestSuiteFixture,
     test_files: Vec<TestFile>,
+    /// Test cases deselected by the filters of the run (e.g. `--filter` or tags)
+    filtered_out: Vec<TestCaseId>,
 }
 
 impl TestSuite {
+    /// Create a test suite from the discovered test cases, checking that the files they need
+    /// exist so that they can be skipped if not
     pub fn new(
         path: &Path,
         config: TestSuiteConfig,
-        test_files: Vec<TestFile>,
-        fixture: TestSuiteFixture,
+        mut test_files: Vec<TestFile>,
+        mut fixture: TestSuiteFixture,
     ) -> Self {
         // TODO assert that test files and test cases are sorted
+        let test_cases = test_files
+            .iter_mut()
+            .flat_map(|test_file| {
+                test_file
+                    .setup_test_case
+                    .iter_mut()
+                    .chain(&mut test_file.test_cases)
+                    .chain(&mut test_file.teardown_test_case)
+            })
+            .chain(&mut fixture.setup_test_case)
+            .chain(&mut fixture.teardown_test_case);
+        for test_case in test_cases {
+            test_case.check_needed_files(path);
+        }
         Self {
             path: path.to_path_buf(),
             config,
             fixture,
             test_files,
+            filtered_out: Vec::new(),
         }
     }
 
@@ -51,48 +76,95 @@ impl TestSuite {
         &self.test_files
     }
 
+    /// The test cases deselected by the filters of the run, which are neither run nor reported
+    /// as skipped
+    pub fn filtered_out(&self) -> &[TestCaseId] {
+        &self.filtered_out
+    }
+
     pub fn test_files_mut(&mut self) -> &mut [TestFile] {
         &mut self.test_files
     }
 
-    // pub async fn visit_async(
-    //     &self,
-    //     mut f: impl AsyncFnMut(&TestCase, ShouldSkip) -> TestSuiteVisitResult,
-    // ) {
-    //     let mut should_skip_suite = ShouldSkip::No;
-    //     if let Some(tc) = &self.fixture.setup_test_case {
-    //         if let TestSuiteVisitResult::Err = f(tc, should_skip_suite).await {
-    //             should_skip_suite = ShouldSkip::Yes;
-    //         }
-    //     }
-    //     for test_file in &self.test_files {
-    //         let mut should_skip_file = ShouldSkip::No;
-    //         if let Some(tc) = &test_file.setup_test_case {
-    //             if let TestSuiteVisitResult::Err = f(tc, should_skip_suite).await {
-    //                 should_skip_file = ShouldSkip::Yes;
-    //             }
-    //         }
-    //         for tc in &test_file.test_cases {
-    //             let _ = f(tc, should_skip_suite.or(should_skip_file)).await;
-    //         }
-    //         if let Some(tc) = &test_file.teardown_test_case {
-    //             let _ = f(tc, should_skip_suite.or(should_skip_file)).await;
-    //         }
-    //     }
-    //     if let Some(tc) = &self.fixture.teardown_test_case {
-    //         f(tc, should_skip_suite).await;
-    //     }
-    // }
+    /// Whether a test case is the setup or teardown of the test suite or of one of its test files
+    pub fn is_fixture(&self, test_case: &TestCase) -> bool {
+        let is = |fixture: &Option<TestCase>| fixture.as_ref() == Some(test_case);
+        is(&self.fixture.setup_test_case)
+            || is(&self.fixture.teardown_test_case)
+            || self.test_files.iter().any(|test_file| {
+                test_file.path == test_case.path()
+                    && (is(&test_file.setup_test_case) || is(&test_file.teardown_test_case))
+            })
+    }
+
+    /// Whether a test case is the setup of the test suite or of one of its test files
+    pub fn is_setup(&self, test_case: &TestCase) -> bool {
+        let is = |fixture: &Option<TestCase>| fixture.as_ref() == Some(test_case);
+        is(&self.fixture.setup_test_case)
+            || self.test_files.iter().any(|test_file| {
+                test_file.path == test_case.path() && is(&test_file.setup_test_case)
+            })
+    }
+
+    /// Expand the test cases given parameters by the test suite config into one instance per
+    /// parameter, the test cases already parameterized by their driver being left as is
+    pub fn expand_params(&mut self) {
+        for test_file in &mut self.test_files {
+            test_file.test_cases = std::mem::take(&mut test_file.test_cases)
+                .into_iter()
+                .flat_map(|test_case| match self.config.params_of(&test_case) {
+                    Some(params) if test_case.params().is_none() => params
+                        .iter()
+                        .map(|params| test_case.clone().with_params(params))
+                        .collect(),
+                    _ => vec![test_case],
+                })
+                .collect();
+        }
+    }
+
+    /// Keep only the test cases (not the fixtures) for which `f` returns true, the others being
+    /// recorded as filtered out, and drop the test files left without test cases so that their
+    /// fixtures do not run for nothing
+    pub fn retain_test_cases(&mut self, mut f: impl FnMut(&TestCase) -> bool) {
+        for test_file in &mut self.test_files {
+            test_file.test_cases.retain(|test_case| {
+                let retained = f(test_case);
+                if !retained {
+                    self.filtered_out.push(test_case.id());
+                }
+                retained
+            });
+        }
+        self.test_files
+            .retain(|test_file| !test_file.test_cases.is_empty());
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TestFile {
+    /// Path of the test file relative to the test suite directory
+    pub path: PathBuf,
     pub setup_test_case: Option<TestCase>,
     pub teardown_test_case: Option<TestCase>,
     pub test_cases: Vec<TestCase>,
 }
 
-#[derive(Debug, Default)]
+impl TestFile {
+    /// Whether the test file may run concurrently with other test files, all its test cases and
+    /// fixtures being declared independent
+    pub fn is_independent(&self) -> bool {
+        !self.test_cases.is_empty()
+            && self
+                .setup_test_case
+                .iter()
+                .chain(&self.test_cases)
+                .chain(&self.teardown_test_case)
+                .all(|test_case| test_case.metadata().independent)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct TestSuiteFixture {
     pub setup_test_case: Option<TestCase>,
     pub teardown_test_case: Option<TestCase>,
@@ -102,6 +174,11 @@ pub struct TestSuiteFixture {
 pub struct TestCase {
     path: PathBuf,
     name: String,
+    metadata: TestCaseMetadata,
+    /// Parameters of this instance of a parameterized test case, passed to it as `BATRUN_PARAM`
+    params: Option<String>,
+    /// Files needed by the test case which did not exist when the test suite was discovered
+    missing_files: Vec<PathBuf>,
 }
 
 impl TestCase {
@@ -109,9 +186,23 @@ impl TestCase {
         Self {
             path: path.to_path_buf(),
             name: name.to_string(),
+            metadata: TestCaseMetadata::default(),
+            params: None,
+            missing_files: Vec::new(),
         }
     }
 
+    /// An instance of a parameterized test case, run with the given parameters
+    pub fn with_params(mut self, params: &str) -> Self {
+        self.params = Some(params.to_string());
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: TestCaseMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
     pub fn path(&self) -> &Path {
         &self.path
     }
@@ -120,7 +211,49 @@ impl TestCase {
         &self.name
     }
 
-    pub fn id(&self) -> String {
-        format!("{}::{}", self.path.display(), &self.name)
+    pub fn metadata(&self) -> &TestCaseMetadata {
+        &self.metadata
+    }
+
+    pub fn params(&self) -> Option<&str> {
+        self.params.as_deref()
+    }
+
+    pub fn id(&self) -> TestCaseId {
+        let id = TestCaseId::new(&self.path, &self.name);
+        match &self.params {
+            Some(params) => id.with_params(params),
+            None => id,
+        }
+    }
+
+    /// The name of the test case followed by its parameters if any (e.g. `test_ping[eth0]`),
+    /// distinguishing the log files of the instances of a parameterized test case
+    pub fn instance_name(&self) -> String {
+        match &self.params {
+            Some(params) => format!("{}[{params}]", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Files needed by the test case (see [`TestCaseMetadata::needed_files`]) which are missing,
+    /// relative to the test suite directory
+    pub fn missing_files(&self) -> &[PathBuf] {
+        &self.missing_files
+    }
+
+    /// Whether parameters can be part of a test case id
+    pub fn is_valid_params(params: &str) -> bool {
+        !params.is_empty() && !params.contains(['[', ']', '\n', '\r'])
+    }
+
+    fn check_needed_files(&mut self, test_suite_dir: &Path) {
+        self.missing_files = self
+            .metadata
+            .needed_files
+            .iter()
+            .filter(|file| !test_suite_dir.join(file).exists())
+            .cloned()
+            .collect();
     }
 }
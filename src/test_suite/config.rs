@@ -1,10 +1,24 @@
+mod format;
+pub mod migration;
+mod validation;
+
 use crate::error::{self, Result};
+use crate::test_driver::{DriverOptions, Env};
+use crate::test_suite::TestCase;
+use crate::time;
+
+use format::Format;
+use migration::Deprecation;
+use validation::Validator;
 
-use serde::Deserialize;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
 
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct TestSuiteConfig {
@@ -12,17 +26,710 @@ pub struct TestSuiteConfig {
     pub description: String,
     pub version: String,
     pub driver: String,
+    /// Options of the test driver, checked by the test driver itself
+    #[serde(rename = "driver-options", default)]
+    pub driver_options: DriverOptions,
     #[serde(rename = "test-file-patterns", default)]
     pub test_file_patterns: Vec<String>,
     #[serde(rename = "global-fixture")]
     pub global_fixture: Option<String>,
+    /// Targets supported by the test suite, given by their name or as objects with environment
+    /// variables and properties (e.g. connection info) passed to their test processes
+    pub targets: Vec<TargetConfig>,
+    #[serde(rename = "metric-thresholds", default)]
+    pub metric_thresholds: BTreeMap<String, MetricThreshold>,
+    #[serde(default)]
+    pub stages: Vec<StageConfig>,
+    /// Glob patterns of the targets the test cases may run on, keyed by glob patterns matched
+    /// against the test case ids; test cases are skipped on the other targets
+    #[serde(rename = "only-targets", default)]
+    pub only_targets: BTreeMap<String, Vec<String>>,
+    /// Test cases known not to work on some targets, skipped on them with the given reason
+    #[serde(default)]
+    pub skip: Vec<SkipConfig>,
+    /// Parameters of the test cases run once per parameter, keyed by glob patterns matched
+    /// against the test case ids
+    #[serde(default)]
+    pub params: BTreeMap<String, Vec<String>>,
+    /// Test cases expected to fail (e.g. because of a known bug), reported as expected failures
+    /// when they fail and as unexpected passes when they pass
+    #[serde(default)]
+    pub xfail: Vec<XFailConfig>,
+    /// How batrun makes targets available before running their test cases, keyed by target
+    /// name; targets without backend are expected to be available already
+    #[serde(rename = "target-backends", default)]
+    pub target_backends: BTreeMap<String, TargetBackendConfig>,
+    /// Groups of targets that must not run at the same time (e.g. because they share a power
+    /// supply), at most one target of each group running at a time in parallel executions
+    #[serde(rename = "exclusive-targets", default)]
+    pub exclusive_targets: Vec<Vec<String>>,
+    /// Names of the shared resources used by the test cases, keyed by glob patterns matched
+    /// against the test case ids, in addition to the ones of their `# @locks:` annotations; no
+    /// two test cases holding the same lock run at the same time, even on different targets
+    #[serde(default)]
+    pub locks: BTreeMap<String, Vec<String>>,
+    /// Number of TCP ports allocated by batrun to the test cases, keyed by glob patterns matched
+    /// against the test case ids, the `# @ports:` annotations of the test cases taking precedence;
+    /// no two test cases running at the same time get the same port
+    #[serde(default)]
+    pub ports: BTreeMap<String, usize>,
+    /// Named lab resources (e.g. instruments) locked by the targets using them, so that several
+    /// batrun instances, possibly on different hosts, do not use them at the same time
+    #[serde(rename = "lab-locks")]
+    pub lab_locks: Option<LabLocksConfig>,
+    /// Number of times a failed test case is run again before being reported as failed, a test
+    /// case passing on a retry being reported as flaky
+    #[serde(default)]
+    pub retries: u32,
+    /// Default timeout of the test cases, overridden by the `# @timeout:` annotations of test
+    /// files and test cases; test cases may run for as long as they want if not set
+    #[serde(default, deserialize_with = "deserialize_optional_duration")]
+    pub timeout: Option<Duration>,
+    /// Timeout of the setups and teardowns, typically longer than the one of the test cases (e.g.
+    /// to flash a board), overridden by the `# @timeout:` annotation of the fixture itself; the
+    /// timeouts of the test cases apply to fixtures if not set
+    #[serde(
+        rename = "fixture-timeout",
+        default,
+        deserialize_with = "deserialize_optional_duration"
+    )]
+    pub fixture_timeout: Option<Duration>,
+    /// Command run right after a test case fails to collect post-mortem data (e.g. `dmesg`, device
+    /// logs), its output being stored next to the test case log; it is killed after the fixture
+    /// timeout, or a minute if not set
+    #[serde(rename = "on-failure")]
+    pub on_failure: Option<Vec<String>>,
+    /// Command sampling the state of the target (e.g. board temperature, load) periodically while
+    /// each test case runs, the samples being stored next to the test case log and summarized in
+    /// the reports
+    pub telemetry: Option<TelemetryConfig>,
+    /// Services the test cases depend on (e.g. a database, a mock server), started in order
+    /// before the test suite runs on any target and stopped in reverse order once it ran on all
+    /// of them
+    #[serde(default)]
+    pub services: Vec<ServiceConfig>,
+    /// Run each test case in a network namespace of its own with only a loopback interface (on
+    /// Linux, batrun running as root), so that test cases binding fixed ports can run at the same
+    /// time; setups and teardowns run in the network of the host
+    #[serde(rename = "network-isolation")]
+    pub network_isolation: Option<NetworkIsolationConfig>,
+    /// Run the test processes with only the environment variables listed in `env-pass` (and
+    /// `PATH`) instead of the whole environment of batrun, so that the results do not depend on
+    /// the shell batrun is started from
+    #[serde(rename = "clean-env", default)]
+    pub clean_env: bool,
+    /// Trace the commands run by the test cases of the bash driver (`bash -x`) into their debug
+    /// logs, their test logs only holding what they print; each command is prefixed by the file,
+    /// line and function it comes from. Disable it for test suites running commands with secrets.
+    #[serde(default = "TestSuiteConfig::default_xtrace")]
+    pub xtrace: bool,
+    /// Environment variables of batrun forwarded to the test processes in clean environment mode
+    #[serde(rename = "env-pass", default)]
+    pub env_pass: Vec<String>,
+    /// Environment variables set in the test processes of all targets, overriding the ones
+    /// forwarded from batrun
+    #[serde(default)]
+    pub env: Env,
+    /// Where the secrets (e.g. credentials of a lab service) passed to the test processes as
+    /// environment variables come from; their values are redacted from the logs of the test
+    /// cases
+    pub secrets: Option<SecretsConfig>,
+    /// Regular expressions matching sensitive data (e.g. tokens, passwords, serial numbers) to
+    /// redact from the logs of the test cases, matched against each line
+    #[serde(default, deserialize_with = "deserialize_regexes")]
+    pub redact: Vec<Regex>,
+    /// Where the log files and artifacts of the test cases are stored once they ran, in addition
+    /// to the output directory
+    #[serde(rename = "artifact-storage", default)]
+    pub artifact_storage: ArtifactStorageConfig,
+    /// Deprecated keys found in the config file, migrated when loading it
+    #[serde(skip)]
+    pub deprecations: Vec<Deprecation>,
+}
+
+/// How batrun makes a target available before running its test cases
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum TargetBackendConfig {
+    /// A simulator or emulator started by batrun
+    Simulator(SimulatorConfig),
+    /// A Kubernetes job started by batrun, e.g. running a device simulator in a cluster
+    Kubernetes(KubernetesConfig),
+    /// A device reserved from a device farm by batrun
+    DeviceFarm(DeviceFarmConfig),
+}
+
+/// How the state of the targets is sampled while the test cases run
+#[derive(Debug, Deserialize, Clone)]
+pub struct TelemetryConfig {
+    /// Command line printing the sampled values as `name=value` pairs separated by spaces, commas
+    /// or line breaks (e.g. `temperature=61.5 load=0.8`), the program being first, run from the
+    /// test suite directory with the environment of the test case
+    pub command: Vec<String>,
+    /// Time between two samples, the first one being taken when the test case starts; a sample
+    /// still running after it is abandoned
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub interval: Duration,
+}
+
+/// A service started by batrun for the test cases, its output being captured in
+/// `<name>.service.log` in the output directory of the run
+#[derive(Debug, Deserialize, Clone)]
+pub struct ServiceConfig {
+    /// Name of the service in the messages and of its log file
+    pub name: String,
+    /// Command line running the service in the foreground, the program being first, run from the
+    /// test suite directory with the environment of the test suite
+    pub command: Vec<String>,
+    /// Command line run until it succeeds to check that the service is ready (e.g. `pg_isready`),
+    /// with `BATRUN_SERVICE_PID` and `BATRUN_SERVICE_LOG` set; the service is considered ready as
+    /// soon as started if not set
+    #[serde(rename = "ready-command")]
+    pub ready_command: Option<Vec<String>>,
+    /// How long to wait for the service to be ready
+    #[serde(
+        rename = "ready-timeout",
+        default = "ServiceConfig::default_ready_timeout",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub ready_timeout: Duration,
+    /// Command line stopping the service gracefully, with `BATRUN_SERVICE_PID` and
+    /// `BATRUN_SERVICE_LOG` set; the service is killed if not set
+    #[serde(rename = "stop-command")]
+    pub stop_command: Option<Vec<String>>,
+    /// How long to wait for the service to exit once the stop command ran before killing it
+    #[serde(
+        rename = "stop-timeout",
+        default = "ServiceConfig::default_stop_timeout",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub stop_timeout: Duration,
+}
+
+/// How the test cases isolated from the network of the host still reach it
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct NetworkIsolationConfig {
+    /// TCP ports of the loopback interface of the namespaces forwarded to the same ports of the
+    /// loopback interface of the host, e.g. for the test cases to reach the services of the test
+    /// suite
+    #[serde(rename = "forward-ports", default)]
+    pub forward_ports: Vec<u16>,
+}
+
+impl ServiceConfig {
+    fn default_ready_timeout() -> Duration {
+        Duration::from_secs(30)
+    }
+
+    fn default_stop_timeout() -> Duration {
+        Duration::from_secs(10)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SimulatorConfig {
+    /// Command line starting the simulator, the program being first, run from the test suite
+    /// directory
+    pub command: Vec<String>,
+    /// Text printed by the simulator on its console once it is ready to run test cases; the
+    /// simulator is considered ready as soon as started if not set
+    #[serde(rename = "ready-pattern")]
+    pub ready_pattern: Option<String>,
+    /// How long to wait for the simulator to be ready
+    #[serde(
+        rename = "ready-timeout",
+        default = "SimulatorConfig::default_ready_timeout",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub ready_timeout: Duration,
+    /// Environment variables passed to the test cases, e.g. to tell them how to reach the
+    /// simulator
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl SimulatorConfig {
+    fn default_ready_timeout() -> Duration {
+        Duration::from_secs(60)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct KubernetesConfig {
+    /// Container image the job runs
+    pub image: String,
+    /// Command run in the container instead of the image entrypoint, if not empty
+    #[serde(default)]
+    pub command: Vec<String>,
+    /// Namespace the job is created in, the current one of kubectl if not set
+    pub namespace: Option<String>,
+    /// How long to wait for the pod of the job to be ready
+    #[serde(
+        rename = "ready-timeout",
+        default = "KubernetesConfig::default_ready_timeout",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub ready_timeout: Duration,
+    /// The kubectl program to use, run from the test suite directory
+    #[serde(default = "KubernetesConfig::default_kubectl")]
+    pub kubectl: String,
+    /// Environment variables passed to the test cases
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl KubernetesConfig {
+    fn default_ready_timeout() -> Duration {
+        Duration::from_secs(300)
+    }
+
+    fn default_kubectl() -> String {
+        "kubectl".to_string()
+    }
+}
+
+/// Device farm integration, through commands talking to the device farm API
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeviceFarmConfig {
+    /// Command reserving a device, run from the test suite directory with the target name in
+    /// `BATRUN_TARGET`; it prints the lease metadata on its standard output as `KEY=VALUE` lines
+    /// (e.g. the lease id and the device address), which are passed to the test cases as
+    /// environment variables
+    #[serde(rename = "lease-command")]
+    pub lease_command: Vec<String>,
+    /// Command releasing the device, run with the lease metadata as environment variables
+    #[serde(rename = "release-command")]
+    pub release_command: Vec<String>,
+    /// Command capturing the state of the device right after a test case failed (e.g. a
+    /// screenshot with adb, the console buffer over serial or ssh), run with the lease metadata
+    /// as environment variables; its output is stored next to the test case log, and it may
+    /// write other files starting with `BATRUN_ARTIFACT_PREFIX`
+    #[serde(rename = "capture-command")]
+    pub capture_command: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LabLocksConfig {
+    /// Where the locks are stored
+    pub backend: LockBackendConfig,
+    /// Names of the resources locked by the targets, keyed by glob patterns matched against the
+    /// target names
+    pub resources: BTreeMap<String, Vec<String>>,
+    /// How long to wait for a resource locked by someone else before giving up on the target
+    #[serde(
+        default = "LabLocksConfig::default_timeout",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub timeout: Duration,
+}
+
+impl LabLocksConfig {
+    fn default_timeout() -> Duration {
+        Duration::from_secs(3600)
+    }
+
+    /// The resources locked by the given target, sorted so that all batrun instances lock them
+    /// in the same order
+    pub fn resources_of(&self, target: &str) -> Vec<String> {
+        let resources = self
+            .resources
+            .iter()
+            .filter(|(targets, _)| TestSuiteConfig::matches(targets, target))
+            .flat_map(|(_, resources)| resources.iter().cloned())
+            .collect::<std::collections::BTreeSet<_>>();
+        resources.into_iter().collect()
+    }
+}
+
+/// A target supported by the test suite
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(from = "TargetConfigRepr")]
+pub struct TargetConfig {
+    pub name: String,
+    /// Environment variables passed as is to the test processes run on this target
+    pub env: Env,
+    /// Arbitrary properties of the target (e.g. its address or serial port), passed to the test
+    /// processes as `BATRUN_TARGET_<PROPERTY>` environment variables
+    pub properties: BTreeMap<String, String>,
+}
+
+/// A target as written in the test suite config: its name only, or an object
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TargetConfigRepr {
+    Name(String),
+    Object {
+        name: String,
+        #[serde(default)]
+        env: Env,
+        #[serde(default, deserialize_with = "deserialize_properties")]
+        properties: BTreeMap<String, String>,
+    },
+}
+
+impl From<TargetConfigRepr> for TargetConfig {
+    fn from(repr: TargetConfigRepr) -> Self {
+        match repr {
+            TargetConfigRepr::Name(name) => Self {
+                name,
+                env: Env::new(),
+                properties: BTreeMap::new(),
+            },
+            TargetConfigRepr::Object {
+                name,
+                env,
+                properties,
+            } => Self {
+                name,
+                env,
+                properties,
+            },
+        }
+    }
+}
+
+impl TargetConfig {
+    const PROPERTY_ENV_VAR_PREFIX: &str = "BATRUN_TARGET_";
+
+    /// The environment variables of the test processes run on this target: its own ones, then
+    /// its properties
+    pub fn env(&self) -> Env {
+        let mut env = self.env.clone();
+        env.extend(
+            self.properties
+                .iter()
+                .map(|(property, value)| (Self::property_env_var(property), value.clone())),
+        );
+        env
+    }
+
+    /// Name of the environment variable holding a property, e.g. `BATRUN_TARGET_SERIAL_PORT` for
+    /// `serial-port`
+    fn property_env_var(property: &str) -> String {
+        let property = property
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' => c.to_ascii_uppercase(),
+                _ => '_',
+            })
+            .collect::<String>();
+        format!("{}{property}", Self::PROPERTY_ENV_VAR_PREFIX)
+    }
+}
+
+/// Test cases known not to work on some targets (e.g. a board lacking a peripheral)
+#[derive(Debug, Deserialize, Clone)]
+pub struct SkipConfig {
+    /// Glob pattern matched against the test case ids
+    pub test: String,
+    /// Glob patterns of the targets the test cases are skipped on
+    pub targets: Vec<String>,
+    /// Why the test cases do not work on these targets, shown in the reports
+    pub reason: String,
+}
+
+/// Test cases expected to fail
+#[derive(Debug, Deserialize, Clone)]
+pub struct XFailConfig {
+    /// Glob pattern matched against the test case ids
+    pub test: String,
+    /// Glob patterns of the targets the test cases are expected to fail on, all targets if empty
+    #[serde(default)]
     pub targets: Vec<String>,
+    /// Why the test cases are expected to fail (e.g. a bug reference), shown in the reports
+    pub reason: String,
+}
+
+/// Where the log files and artifacts of the test cases are stored
+/// The object storage backends are only available if batrun is built with the feature of the
+/// same name.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ArtifactStorageConfig {
+    /// Only in the output directory
+    #[default]
+    Local,
+    /// Uploaded to an Amazon S3 bucket with the `aws` CLI
+    #[cfg(feature = "s3")]
+    S3(ObjectStorageConfig),
+    /// Uploaded to a Google Cloud Storage bucket with the `gcloud` CLI
+    #[cfg(feature = "gcs")]
+    Gcs(ObjectStorageConfig),
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ObjectStorageConfig {
+    pub bucket: String,
+    /// Prepended to the path of the files relative to the output directory to make their object
+    /// names (e.g. `lab-a/nightly/`)
+    #[serde(default)]
+    pub prefix: String,
+    /// Keep the files in the output directory once uploaded, rather than removing them
+    #[serde(
+        rename = "keep-local",
+        default = "ObjectStorageConfig::default_keep_local"
+    )]
+    pub keep_local: bool,
+    /// The CLI used to upload the files, if not the default one of the storage in `PATH`
+    pub program: Option<String>,
+}
+
+impl ObjectStorageConfig {
+    fn default_keep_local() -> bool {
+        true
+    }
+}
+
+/// Where the secrets passed to the test processes come from
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SecretsConfig {
+    /// A file of `NAME=VALUE` lines, relative to the test suite directory if not absolute, which
+    /// must not be accessible to other users than its owner
+    EnvFile { path: PathBuf },
+    /// A command printing `NAME=VALUE` lines on its standard output (e.g. a vault client), run
+    /// from the test suite directory
+    Command { command: Vec<String> },
+}
+
+/// Where lab locks are stored
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum LockBackendConfig {
+    /// Lock files in a directory shared by the hosts (e.g. on NFS)
+    File {
+        /// The shared directory, relative to the output directory of the run if not absolute
+        directory: PathBuf,
+    },
+    /// Commands talking to a lock service (e.g. Redis or an HTTP lock service)
+    Command {
+        /// Command locking the resource named in `BATRUN_LOCK`, run from the test suite directory
+        /// with the target name in `BATRUN_TARGET`; it must fail if the resource is already
+        /// locked, and is retried until the lock timeout
+        #[serde(rename = "lock-command")]
+        lock_command: Vec<String>,
+        /// Command unlocking the resource named in `BATRUN_LOCK`
+        #[serde(rename = "unlock-command")]
+        unlock_command: Vec<String>,
+    },
+}
+
+/// Deserialize a human readable duration such as `90s` or `1m 30s`
+fn deserialize_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Duration, D::Error> {
+    let duration = String::deserialize(deserializer)?;
+    time::parse(&duration)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid duration `{duration}`")))
+}
+
+pub(crate) fn deserialize_optional_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Duration>, D::Error> {
+    deserialize_duration(deserializer).map(Some)
+}
+
+/// Properties are strings, numbers or booleans, all passed as strings to the test processes
+fn deserialize_properties<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<BTreeMap<String, String>, D::Error> {
+    BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(property, value)| match value {
+            serde_json::Value::String(value) => Ok((property, value)),
+            serde_json::Value::Number(_) | serde_json::Value::Bool(_) => {
+                Ok((property, value.to_string()))
+            }
+            _ => Err(serde::de::Error::custom(format!(
+                "invalid value of target property `{property}`, expected a string, a number or \
+                 a boolean"
+            ))),
+        })
+        .collect()
+}
+
+fn deserialize_regexes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<Regex>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+/// A stage groups test files that must all be run before the test files of the next stage
+#[derive(Debug, Deserialize, Clone)]
+pub struct StageConfig {
+    pub name: String,
+    /// Test files selected by this stage, matched against their path relative to the test suite
+    /// directory; a stage without pattern selects all test files not selected by previous stages
+    #[serde(rename = "test-file-patterns", default)]
+    pub test_file_patterns: Vec<String>,
+    #[serde(rename = "on-failure", default)]
+    pub on_failure: StageFailurePolicy,
+}
+
+/// What to do with the next stages of a target when a test case of a stage fails on it
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StageFailurePolicy {
+    /// Run the next stages anyway
+    #[default]
+    Continue,
+    /// Skip the next stages for this target
+    Stop,
+}
+
+/// Bounds a metric emitted by test cases must stay within for them to pass
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct MetricThreshold {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl MetricThreshold {
+    /// Return a description of the violation if the value is out of bounds
+    pub fn check(&self, metric: &str, value: f64) -> Option<String> {
+        match (self.min, self.max) {
+            (Some(min), _) if value < min => Some(format!("{metric}={value} is below {min}")),
+            (_, Some(max)) if value > max => Some(format!("{metric}={value} is above {max}")),
+            _ => None,
+        }
+    }
 }
 
 impl TestSuiteConfig {
+    /// Name of the config file, at the root of the test suite directory
+    /// The config can also be written in TOML (`test-suite.toml`) or YAML (`test-suite.yaml`),
+    /// with batrun built with the `toml` or `yaml` feature, the JSON one coming first.
+    pub const FILE_NAME: &str = "test-suite.json";
+
+    fn default_xtrace() -> bool {
+        true
+    }
+
+    /// Load the config of a test suite, migrating its deprecated keys, which are recorded in
+    /// [`Self::deprecations`]
     pub fn load(test_suite_dir: &Path) -> Result<Self> {
-        let config_path = test_suite_dir.join("test-suite.json");
-        let mut file = File::open(&config_path).map_err(|io_err| error::kind::SuiteConfigIo {
+        let (config_path, format) = Self::find(test_suite_dir);
+        let contents = Self::read_as_json(&config_path, format)?;
+        let (contents, deprecations) = migration::migrate(&contents);
+        let mut config = Self::parse(test_suite_dir, &config_path, &contents).map_err(|error| {
+            match (error, format) {
+                // The issues are located in the contents converted to JSON, not in the file
+                (error::Error::SuiteConfigValidation(mut error), Some(_)) => {
+                    for issue in &mut error.issues {
+                        issue.position = None;
+                    }
+                    error.into()
+                }
+                (error, _) => error,
+            }
+        })?;
+        config.deprecations = deprecations;
+        Ok(config)
+    }
+
+    /// The path of the config file of a test suite, in JSON or else in one of the other formats
+    pub fn path_in(test_suite_dir: &Path) -> PathBuf {
+        Self::find(test_suite_dir).0
+    }
+
+    /// The config file of a test suite along with its format, `None` for JSON, the JSON one being
+    /// returned if there is none
+    fn find(test_suite_dir: &Path) -> (PathBuf, Option<Format>) {
+        let json_path = test_suite_dir.join(Self::FILE_NAME);
+        if json_path.exists() {
+            return (json_path, None);
+        }
+        Format::FILE_NAMES
+            .iter()
+            .map(|(file_name, format)| (test_suite_dir.join(file_name), Some(*format)))
+            .find(|(path, _)| path.is_file())
+            .unwrap_or((json_path, None))
+    }
+
+    /// Read a config file, converted to JSON if in another format
+    fn read_as_json(config_path: &Path, format: Option<Format>) -> Result<String> {
+        let contents = Self::read(config_path)?;
+        match format {
+            None => Ok(contents),
+            Some(format) => format.to_json(&contents).map_err(|issue| {
+                error::kind::SuiteConfigValidation {
+                    filename: config_path.to_path_buf(),
+                    issues: vec![issue],
+                }
+                .into()
+            }),
+        }
+    }
+
+    /// Rewrite the config file of a test suite in place without its deprecated keys, returning
+    /// the ones migrated
+    /// The config file is left untouched if it has no deprecated keys, or if some of them cannot
+    /// be migrated as their replacement is set too, the config being invalid then.
+    /// Only JSON config files are rewritten, the deprecated keys of the other ones being reported
+    /// as issues to fix by hand.
+    pub fn migrate(test_suite_dir: &Path) -> Result<Vec<Deprecation>> {
+        let (config_path, format) = Self::find(test_suite_dir);
+        let contents = Self::read_as_json(&config_path, format)?;
+        let (migrated, deprecations) = migration::migrate(&contents);
+        if let Some(format) = format
+            && !deprecations.is_empty()
+        {
+            let renames = deprecations
+                .iter()
+                .map(|deprecation| {
+                    format!("`{}` to `{}`", deprecation.key, deprecation.replacement)
+                })
+                .collect::<Vec<_>>();
+            return Err(error::kind::SuiteConfigValidation {
+                filename: config_path,
+                issues: vec![error::kind::ConfigIssue {
+                    message: format!("{} test suite configs cannot be migrated", format.name()),
+                    hint: Some(format!("rename {} by hand", renames.join(", "))),
+                    position: None,
+                }],
+            }
+            .into());
+        }
+        let mut validator = Validator::new(&contents);
+        for deprecation in deprecations
+            .iter()
+            .filter(|deprecation| deprecation.ignored)
+        {
+            validator.issue(
+                deprecation.key,
+                None,
+                format!(
+                    "both `{}` and `{}` are set",
+                    deprecation.key, deprecation.replacement
+                ),
+                Some(&format!("remove `{}`", deprecation.key)),
+            );
+        }
+        if validator.has_issues() {
+            return Err(error::kind::SuiteConfigValidation {
+                filename: config_path,
+                issues: validator.into_issues(),
+            }
+            .into());
+        }
+        // The migrated config must still load, so that a broken config is not rewritten
+        Self::parse(test_suite_dir, &config_path, &migrated)?;
+        if !deprecations.is_empty() {
+            std::fs::write(&config_path, migrated).map_err(|io_err| {
+                error::kind::SuiteConfigIo {
+                    filename: config_path.to_path_buf(),
+                    source: io_err,
+                }
+            })?;
+        }
+        Ok(deprecations)
+    }
+
+    fn read(config_path: &Path) -> Result<String> {
+        let mut file = File::open(config_path).map_err(|io_err| error::kind::SuiteConfigIo {
             filename: config_path.to_path_buf(),
             source: io_err,
         })?;
@@ -32,12 +739,402 @@ impl TestSuiteConfig {
                 filename: config_path.to_path_buf(),
                 source: io_err,
             })?;
-        let config: TestSuiteConfig = serde_json::from_str(&contents).map_err(|serde_err| {
-            error::kind::InvalidSuiteConfig {
+        Ok(contents)
+    }
+
+    /// Parse and validate the contents of a config file, reporting all the issues found at once
+    /// The keys are checked first, then the values serde checks by deserializing the config,
+    /// then the values serde cannot check by itself.
+    fn parse(test_suite_dir: &Path, config_path: &Path, contents: &str) -> Result<Self> {
+        let mut validator = Validator::new(contents);
+        let complete = match serde_json::from_str::<serde_json::Value>(contents) {
+            Err(serde_err) => {
+                validator.serde_issue(&serde_err);
+                false
+            }
+            Ok(serde_json::Value::Object(_)) => validator.check_keys(),
+            Ok(_) => {
+                validator.issue(
+                    "",
+                    None,
+                    "the config must be a JSON object".to_string(),
+                    None,
+                );
+                false
+            }
+        };
+        // Unknown keys are ignored by serde, the config being deserialized to report the other
+        // issues along with them
+        let config = complete
+            .then(|| serde_json::from_str::<Self>(contents))
+            .and_then(|config| {
+                config
+                    .map_err(|serde_err| validator.serde_issue(&serde_err))
+                    .ok()
+            });
+        if let Some(config) = &config {
+            config.validate(test_suite_dir, &mut validator);
+        }
+        match config {
+            Some(config) if !validator.has_issues() => Ok(config),
+            _ => Err(error::kind::SuiteConfigValidation {
                 filename: config_path.to_path_buf(),
-                source: serde_err,
+                issues: validator.into_issues(),
             }
-        })?;
-        Ok(config)
+            .into()),
+        }
+    }
+
+    /// Check the values serde cannot check by itself
+    fn validate(&self, test_suite_dir: &Path, validator: &mut Validator) {
+        const GLOB_HINT: &str = "special characters are escaped with brackets, e.g. `[*]`";
+        const COMMAND_HINT: &str = "give the program and its arguments as a list of strings";
+        let check_pattern = |validator: &mut Validator, key: &str, pattern: &str| {
+            if let Err(error) = glob::Pattern::new(pattern) {
+                validator.issue(
+                    key,
+                    Some(pattern),
+                    format!("invalid pattern `{pattern}` in `{key}`: {error}"),
+                    Some(GLOB_HINT),
+                );
+            }
+        };
+        if let Some(global_fixture) = &self.global_fixture
+            && !test_suite_dir.join(global_fixture).is_file()
+        {
+            validator.issue(
+                "global-fixture",
+                Some(global_fixture),
+                format!("global fixture `{global_fixture}` not found"),
+                Some("its path is relative to the test suite directory"),
+            );
+        }
+        for (index, target) in self.targets.iter().enumerate() {
+            if target.name.is_empty() {
+                validator.issue(
+                    "targets",
+                    Some(""),
+                    "empty target name in `targets`".to_string(),
+                    None,
+                );
+            } else if self.targets[..index]
+                .iter()
+                .any(|other| other.name == target.name)
+            {
+                validator.issue(
+                    "targets",
+                    Some(&target.name),
+                    format!("target `{}` declared twice in `targets`", target.name),
+                    Some("remove one of the declarations"),
+                );
+            }
+        }
+        let patterns = self
+            .only_targets
+            .iter()
+            .flat_map(|(test_cases, targets)| std::iter::once(test_cases).chain(targets));
+        for pattern in patterns {
+            check_pattern(validator, "only-targets", pattern);
+        }
+        let patterns = self
+            .skip
+            .iter()
+            .flat_map(|skip| std::iter::once(&skip.test).chain(&skip.targets));
+        for pattern in patterns {
+            check_pattern(validator, "skip", pattern);
+        }
+        for (pattern, locks) in &self.locks {
+            check_pattern(validator, "locks", pattern);
+            if locks.iter().any(String::is_empty) {
+                validator.issue(
+                    "locks",
+                    Some(""),
+                    "empty lock name in `locks`".to_string(),
+                    None,
+                );
+            }
+        }
+        for pattern in self.ports.keys() {
+            check_pattern(validator, "ports", pattern);
+        }
+        for (pattern, params) in &self.params {
+            check_pattern(validator, "params", pattern);
+            for params in params
+                .iter()
+                .filter(|params| !TestCase::is_valid_params(params))
+            {
+                validator.issue(
+                    "params",
+                    Some(params),
+                    format!("invalid parameters `{params}` in `params`"),
+                    Some("they must not be empty nor contain brackets or line breaks"),
+                );
+            }
+        }
+        let patterns = self
+            .xfail
+            .iter()
+            .flat_map(|xfail| std::iter::once(&xfail.test).chain(&xfail.targets));
+        for pattern in patterns {
+            check_pattern(validator, "xfail", pattern);
+        }
+        for pattern in &self.test_file_patterns {
+            check_pattern(validator, "test-file-patterns", pattern);
+        }
+        for pattern in self
+            .stages
+            .iter()
+            .flat_map(|stage| &stage.test_file_patterns)
+        {
+            check_pattern(validator, "stages", pattern);
+        }
+        for (target, backend) in &self.target_backends {
+            let commands = match backend {
+                TargetBackendConfig::Simulator(simulator) => vec![&simulator.command],
+                TargetBackendConfig::Kubernetes(_) => vec![],
+                TargetBackendConfig::DeviceFarm(device_farm) => {
+                    let mut commands =
+                        vec![&device_farm.lease_command, &device_farm.release_command];
+                    commands.extend(&device_farm.capture_command);
+                    commands
+                }
+            };
+            if commands.iter().any(|command| command.is_empty()) {
+                validator.issue(
+                    "target-backends",
+                    Some(target),
+                    format!("empty command in the target backend of target `{target}`"),
+                    Some(COMMAND_HINT),
+                );
+            }
+        }
+        if self.on_failure.as_ref().is_some_and(Vec::is_empty) {
+            validator.issue(
+                "on-failure",
+                None,
+                "empty command in `on-failure`".to_string(),
+                Some(COMMAND_HINT),
+            );
+        }
+        if let Some(telemetry) = &self.telemetry {
+            if telemetry.command.is_empty() {
+                validator.issue(
+                    "telemetry",
+                    None,
+                    "empty command in `telemetry`".to_string(),
+                    Some(COMMAND_HINT),
+                );
+            }
+            if telemetry.interval.is_zero() {
+                validator.issue(
+                    "telemetry",
+                    None,
+                    "null `interval` in `telemetry`".to_string(),
+                    Some("give a duration such as `5s`"),
+                );
+            }
+        }
+        for (index, service) in self.services.iter().enumerate() {
+            if service.name.is_empty() || service.name.contains(['/', '\\']) {
+                validator.issue(
+                    "services",
+                    Some(&service.name),
+                    format!("invalid service name `{}` in `services`", service.name),
+                    Some("give a name usable as a file name"),
+                );
+            } else if self.services[..index]
+                .iter()
+                .any(|other| other.name == service.name)
+            {
+                validator.issue(
+                    "services",
+                    Some(&service.name),
+                    format!("service `{}` declared twice in `services`", service.name),
+                    Some("rename one of the services"),
+                );
+            }
+            let mut commands = std::iter::once(&service.command)
+                .chain(&service.ready_command)
+                .chain(&service.stop_command);
+            if commands.any(Vec::is_empty) {
+                validator.issue(
+                    "services",
+                    Some(&service.name),
+                    format!("empty command in service `{}`", service.name),
+                    Some(COMMAND_HINT),
+                );
+            }
+        }
+        if let Some(network_isolation) = &self.network_isolation {
+            for (index, port) in network_isolation.forward_ports.iter().enumerate() {
+                if *port == 0 {
+                    validator.issue(
+                        "network-isolation",
+                        None,
+                        "invalid port 0 in `forward-ports`".to_string(),
+                        Some("give the port of the host to forward"),
+                    );
+                } else if network_isolation.forward_ports[..index].contains(port) {
+                    validator.issue(
+                        "network-isolation",
+                        None,
+                        format!("port {port} forwarded twice in `forward-ports`"),
+                        Some("remove one of them"),
+                    );
+                }
+            }
+        }
+        if let Some(SecretsConfig::Command { command }) = &self.secrets
+            && command.is_empty()
+        {
+            validator.issue(
+                "secrets",
+                None,
+                "empty command in `secrets`".to_string(),
+                Some(COMMAND_HINT),
+            );
+        }
+        for name in self
+            .env_pass
+            .iter()
+            .filter(|name| name.is_empty() || name.contains('='))
+        {
+            validator.issue(
+                "env-pass",
+                Some(name),
+                format!("invalid environment variable name `{name}` in `env-pass`"),
+                Some("give the names of the variables only, without `=` nor value"),
+            );
+        }
+        if let Some(lab_locks) = &self.lab_locks {
+            if let LockBackendConfig::Command {
+                lock_command,
+                unlock_command,
+            } = &lab_locks.backend
+                && (lock_command.is_empty() || unlock_command.is_empty())
+            {
+                validator.issue(
+                    "lab-locks",
+                    None,
+                    "empty command in `lab-locks`".to_string(),
+                    Some(COMMAND_HINT),
+                );
+            }
+            for pattern in lab_locks.resources.keys() {
+                check_pattern(validator, "lab-locks", pattern);
+            }
+        }
+    }
+
+    /// Check whether the given test case may run on the given target, according to the test
+    /// suite config and the test case metadata, returning the reason why not if it may not
+    pub fn check_only_targets(&self, test_case: &TestCase, target: &str) -> Option<String> {
+        let id = test_case.id().to_string();
+        let metadata_targets = &test_case.metadata().only_targets;
+        let restrictions = self
+            .only_targets
+            .iter()
+            .filter(|(test_cases, _)| Self::matches(test_cases, &id))
+            .map(|(_, targets)| targets)
+            .chain(Some(metadata_targets).filter(|targets| !targets.is_empty()));
+        for targets in restrictions {
+            if !targets.iter().any(|pattern| Self::matches(pattern, target)) {
+                return Some(format!("only runs on targets `{}`", targets.join("`, `")));
+            }
+        }
+        None
+    }
+
+    /// Return the reason given by the test suite config for skipping the given test case on the
+    /// given target, if the config declares them incompatible
+    pub fn check_skip(&self, test_case: &TestCase, target: &str) -> Option<&str> {
+        let id = test_case.id().to_string();
+        self.skip
+            .iter()
+            .find(|skip| {
+                Self::matches(&skip.test, &id)
+                    && skip
+                        .targets
+                        .iter()
+                        .any(|pattern| Self::matches(pattern, target))
+            })
+            .map(|skip| skip.reason.as_str())
+    }
+
+    /// The target of the given name declared in the test suite config, if any
+    pub fn target(&self, name: &str) -> Option<&TargetConfig> {
+        self.targets.iter().find(|target| target.name == name)
+    }
+
+    /// The parameters given to the given test case by the test suite config, if any
+    pub fn params_of(&self, test_case: &TestCase) -> Option<&[String]> {
+        let id = test_case.id().to_string();
+        self.params
+            .iter()
+            .find(|(pattern, _)| Self::matches(pattern, &id))
+            .map(|(_, params)| params.as_slice())
+    }
+
+    /// Return the reason given by the test suite config for expecting the given test case to fail
+    /// on the given target, if it is expected to
+    pub fn check_xfail(&self, test_case: &TestCase, target: &str) -> Option<&str> {
+        let id = test_case.id().to_string();
+        self.xfail
+            .iter()
+            .find(|xfail| {
+                Self::matches(&xfail.test, &id)
+                    && (xfail.targets.is_empty()
+                        || xfail
+                            .targets
+                            .iter()
+                            .any(|pattern| Self::matches(pattern, target)))
+            })
+            .map(|xfail| xfail.reason.as_str())
+    }
+
+    /// The locks held by the given test case while it runs, declared by its annotations or by the
+    /// test suite config, sorted so that they are always taken in the same order
+    pub fn locks_of(&self, test_case: &TestCase) -> Vec<String> {
+        let id = test_case.id().to_string();
+        let mut locks = self
+            .locks
+            .iter()
+            .filter(|(pattern, _)| Self::matches(pattern, &id))
+            .flat_map(|(_, locks)| locks)
+            .chain(&test_case.metadata().locks)
+            .cloned()
+            .collect::<Vec<_>>();
+        locks.sort();
+        locks.dedup();
+        locks
+    }
+
+    /// The number of ports allocated to the given test case while it runs, declared by its
+    /// annotations or else by the first pattern of the test suite config matching it
+    pub fn ports_of(&self, test_case: &TestCase) -> usize {
+        let id = test_case.id().to_string();
+        test_case
+            .metadata()
+            .ports
+            .or_else(|| {
+                self.ports
+                    .iter()
+                    .find(|(pattern, _)| Self::matches(pattern, &id))
+                    .map(|(_, ports)| *ports)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Check whether two distinct targets are declared as not running at the same time
+    pub fn are_exclusive(&self, target: &str, other_target: &str) -> bool {
+        target != other_target
+            && self.exclusive_targets.iter().any(|group| {
+                group.iter().any(|t| t == target) && group.iter().any(|t| t == other_target)
+            })
+    }
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        // Patterns are validated when loading the config or the test case metadata
+        glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(name))
     }
 }
@@ -0,0 +1,152 @@
+use crate::error::kind::ConfigIssue;
+
+/// A format of the test suite config other than JSON, converted to JSON to be loaded like a JSON
+/// config.
+/// Each format needs batrun to be built with the Cargo feature named after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Format {
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// Names of the config files in the other formats, looked for in order when a test suite has
+    /// no JSON config file
+    pub(super) const FILE_NAMES: &[(&str, Format)] = &[
+        ("test-suite.toml", Format::Toml),
+        ("test-suite.yaml", Format::Yaml),
+        ("test-suite.yml", Format::Yaml),
+    ];
+
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Format::Toml => "TOML",
+            Format::Yaml => "YAML",
+        }
+    }
+
+    /// Convert the contents of a config file in this format to JSON, located issues being
+    /// located in the original contents
+    pub(super) fn to_json(self, contents: &str) -> Result<String, ConfigIssue> {
+        let value = match self {
+            Format::Toml => toml_to_value(contents)?,
+            Format::Yaml => yaml_to_value(contents)?,
+        };
+        // UNWRAP: a JSON value only contains strings, numbers and maps with string keys
+        Ok(serde_json::to_string_pretty(&value).unwrap())
+    }
+
+    #[cfg(not(all(feature = "toml", feature = "yaml")))]
+    fn unsupported(self) -> ConfigIssue {
+        let feature = match self {
+            Format::Toml => "toml",
+            Format::Yaml => "yaml",
+        };
+        ConfigIssue {
+            message: format!(
+                "{} test suite configs are not supported by this build of batrun",
+                self.name()
+            ),
+            hint: Some(format!(
+                "build batrun with the `{feature}` feature, or convert it to JSON as `{}`",
+                super::TestSuiteConfig::FILE_NAME
+            )),
+            position: None,
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+fn toml_to_value(contents: &str) -> Result<serde_json::Value, ConfigIssue> {
+    toml::from_str(contents).map_err(|toml_err| ConfigIssue {
+        message: toml_err.message().trim_end().to_string(),
+        hint: None,
+        position: toml_err
+            .span()
+            .map(|span| super::validation::position_of(contents, span.start)),
+    })
+}
+
+#[cfg(not(feature = "toml"))]
+fn toml_to_value(_contents: &str) -> Result<serde_json::Value, ConfigIssue> {
+    Err(Format::Toml.unsupported())
+}
+
+#[cfg(feature = "yaml")]
+fn yaml_to_value(contents: &str) -> Result<serde_json::Value, ConfigIssue> {
+    serde_yaml::from_str(contents).map_err(|yaml_err| {
+        let position = yaml_err
+            .location()
+            .map(|location| super::validation::position_of(contents, location.index()));
+        let message = yaml_err.to_string();
+        // The location is given apart from the message
+        let message = match message.split_once(" at line ") {
+            Some((message, _)) => message.to_string(),
+            None => message,
+        };
+        ConfigIssue {
+            message,
+            hint: None,
+            position,
+        }
+    })
+}
+
+#[cfg(not(feature = "yaml"))]
+fn yaml_to_value(_contents: &str) -> Result<serde_json::Value, ConfigIssue> {
+    Err(Format::Yaml.unsupported())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_to_json() {
+        let contents = "name = \"foo\"\ntargets = [\"a\", { name = \"b\" }]\n\n[env]\nA = \"1\"\n";
+        let json = Format::Toml.to_json(contents).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!({
+                "name": "foo",
+                "targets": ["a", {"name": "b"}],
+                "env": {"A": "1"},
+            })
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn invalid_toml_located() {
+        let issue = Format::Toml
+            .to_json("name = \"foo\"\ntargets = [\n")
+            .unwrap_err();
+        assert_eq!(issue.position.map(|(line, _)| line), Some(2));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_to_json() {
+        let contents = "name: foo\ntargets:\n  - a\n  - name: b\nretries: 2\n";
+        let json = Format::Yaml.to_json(contents).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!({
+                "name": "foo",
+                "targets": ["a", {"name": "b"}],
+                "retries": 2,
+            })
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn invalid_yaml_located() {
+        let issue = Format::Yaml
+            .to_json("name: foo\ntargets: [a\n")
+            .unwrap_err();
+        assert!(!issue.message.contains(" at line "), "{}", issue.message);
+        assert_eq!(issue.position.map(|(line, _)| line), Some(3));
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Serialize;
+
+use std::fmt::Display;
+use std::ops::Range;
+
+/// A key of the test suite config renamed in a version of batrun, the old key still being
+/// accepted with a deprecation warning
+struct RenamedKey {
+    old: &'static str,
+    new: &'static str,
+    since: &'static str,
+}
+
+/// The renamed keys of the test suite config, all at the top level of the config
+const RENAMED_KEYS: &[RenamedKey] = &[RenamedKey {
+    old: "test-file-pattern",
+    new: "test-file-patterns",
+    since: "2.0.0",
+}];
+
+/// A deprecated key found in a test suite config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Deprecation {
+    pub key: &'static str,
+    pub replacement: &'static str,
+    /// Version of batrun deprecating the key
+    pub since: &'static str,
+    /// Whether the key is ignored rather than migrated, its replacement being in the config too
+    pub ignored: bool,
+}
+
+impl Display for Deprecation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}` is deprecated since batrun {}, use `{}` instead",
+            self.key, self.since, self.replacement
+        )?;
+        if self.ignored {
+            write!(f, " (ignored as `{}` is set too)", self.replacement)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rename the deprecated keys of the contents of a test suite config, keeping its formatting
+/// Returns the migrated contents along with the deprecated keys found. The deprecated keys whose
+/// replacement is in the config too are left as they are, to be ignored.
+pub fn migrate(contents: &str) -> (String, Vec<Deprecation>) {
+    let keys = top_level_keys(contents);
+    let mut renames = Vec::new();
+    let mut deprecations = Vec::new();
+    for renamed_key in RENAMED_KEYS {
+        let Some((range, _)) = keys.iter().find(|(_, key)| *key == renamed_key.old) else {
+            continue;
+        };
+        let ignored = keys.iter().any(|(_, key)| *key == renamed_key.new);
+        if !ignored {
+            renames.push((range.clone(), renamed_key.new));
+        }
+        deprecations.push(Deprecation {
+            key: renamed_key.old,
+            replacement: renamed_key.new,
+            since: renamed_key.since,
+            ignored,
+        });
+    }
+    // Renamed from the end so that the ranges of the keys before stay valid
+    renames.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut migrated = contents.to_string();
+    for (range, new) in renames {
+        migrated.replace_range(range, &format!("\"{new}\""));
+    }
+    (migrated, deprecations)
+}
+
+/// Whether a key of the test suite config is a deprecated one, renamed since
+pub(super) fn is_renamed(key: &str) -> bool {
+    RENAMED_KEYS
+        .iter()
+        .any(|renamed_key| renamed_key.old == key)
+}
+
+/// The keys of the top-level object of a JSON document along with their byte ranges, quotes
+/// included
+/// The document is expected to be valid JSON, anything else giving meaningless keys.
+pub(super) fn top_level_keys(contents: &str) -> Vec<(Range<usize>, &str)> {
+    let mut keys = Vec::new();
+    let mut depth = 0usize;
+    let mut string_start = None;
+    let mut escaped = false;
+    let mut chars = contents.char_indices();
+    while let Some((index, c)) = chars.next() {
+        if let Some(start) = string_start {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    string_start = None;
+                    let is_key = chars.as_str().trim_start().starts_with(':');
+                    if depth == 1 && is_key {
+                        keys.push((start..index + 1, &contents[start + 1..index]));
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => string_start = Some(index),
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_renamed_key() {
+        let (migrated, deprecations) =
+            migrate("{\n    \"name\": \"foo\",\n    \"test-file-pattern\": [\"*.sh\"]\n}\n");
+        assert_eq!(
+            migrated,
+            "{\n    \"name\": \"foo\",\n    \"test-file-patterns\": [\"*.sh\"]\n}\n"
+        );
+        assert_eq!(
+            deprecations,
+            [Deprecation {
+                key: "test-file-pattern",
+                replacement: "test-file-patterns",
+                since: "2.0.0",
+                ignored: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn migrate_up_to_date_config() {
+        let contents = "{ \"name\": \"foo\", \"test-file-patterns\": [\"*.sh\"] }";
+        assert_eq!(migrate(contents), (contents.to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn migrate_ignores_nested_keys_and_values() {
+        let contents = r#"{
+            "env": { "test-file-pattern": "x" },
+            "description": "test-file-pattern",
+            "stages": [{ "test-file-pattern": "y" }]
+        }"#;
+        assert_eq!(migrate(contents), (contents.to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn migrate_ignores_key_with_replacement() {
+        let contents = r#"{ "test-file-pattern": ["*.sh"], "test-file-patterns": ["*.bash"] }"#;
+        let (migrated, deprecations) = migrate(contents);
+        assert_eq!(migrated, contents);
+        assert!(deprecations[0].ignored);
+    }
+
+    #[test]
+    fn top_level_keys_skip_escaped_quotes() {
+        let keys = top_level_keys(r#"{ "a\"b": "c\\", "d": 1 }"#);
+        let keys = keys.into_iter().map(|(_, key)| key).collect::<Vec<_>>();
+        assert_eq!(keys, [r#"a\"b"#, "d"]);
+    }
+}
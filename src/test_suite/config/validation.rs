@@ -0,0 +1,279 @@
+use crate::error::kind::ConfigIssue;
+
+use super::migration;
+
+use std::ops::Range;
+
+/// The top-level keys of the test suite config, deprecated ones excepted
+const KNOWN_KEYS: &[&str] = &[
+    "name",
+    "description",
+    "version",
+    "driver",
+    "driver-options",
+    "test-file-patterns",
+    "global-fixture",
+    "targets",
+    "metric-thresholds",
+    "stages",
+    "only-targets",
+    "skip",
+    "params",
+    "xfail",
+    "target-backends",
+    "exclusive-targets",
+    "lab-locks",
+    "locks",
+    "ports",
+    "retries",
+    "timeout",
+    "fixture-timeout",
+    "on-failure",
+    "telemetry",
+    "services",
+    "network-isolation",
+    "clean-env",
+    "xtrace",
+    "env-pass",
+    "env",
+    "secrets",
+    "redact",
+    "artifact-storage",
+];
+
+/// The top-level keys of the test suite config without default value
+const REQUIRED_KEYS: &[&str] = &["name", "description", "version", "driver", "targets"];
+
+/// Collects the issues of a test suite config, locating them in its contents
+pub(super) struct Validator<'a> {
+    contents: &'a str,
+    keys: Vec<(Range<usize>, &'a str)>,
+    issues: Vec<ConfigIssue>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(contents: &'a str) -> Self {
+        Self {
+            contents,
+            keys: migration::top_level_keys(contents),
+            issues: Vec::new(),
+        }
+    }
+
+    pub fn into_issues(self) -> Vec<ConfigIssue> {
+        self.issues
+    }
+
+    pub fn has_issues(&self) -> bool {
+        !self.issues.is_empty()
+    }
+
+    /// Add an issue about the value of a top-level key, located at the given string in the value
+    /// if found (its last occurrence, so that a duplicated value is located at the duplicate), or
+    /// at the key otherwise
+    pub fn issue(&mut self, key: &str, value: Option<&str>, message: String, hint: Option<&str>) {
+        let offset = value
+            .and_then(|value| self.find_in_value_of(key, value))
+            .or_else(|| self.range_of_key(key).map(|range| range.start));
+        self.issues.push(ConfigIssue {
+            message,
+            hint: hint.map(str::to_string),
+            position: offset.map(|offset| self.position_of(offset)),
+        });
+    }
+
+    /// Add the issue of a JSON error, located where serde located it
+    pub fn serde_issue(&mut self, error: &serde_json::Error) {
+        let message = error.to_string();
+        let suffix = format!(" at line {} column {}", error.line(), error.column());
+        let message = message.strip_suffix(&suffix).unwrap_or(&message);
+        self.issues.push(ConfigIssue {
+            message: message.to_string(),
+            hint: None,
+            position: (error.line() != 0).then(|| (error.line(), error.column())),
+        });
+    }
+
+    /// Check that the top-level keys are known and that the required ones are set, returning
+    /// whether they are all set
+    pub fn check_keys(&mut self) -> bool {
+        let unknown_keys = self
+            .keys
+            .iter()
+            .filter(|(_, key)| !KNOWN_KEYS.contains(key) && !migration::is_renamed(key))
+            .map(|(range, key)| (range.start, *key))
+            .collect::<Vec<_>>();
+        for (offset, key) in unknown_keys {
+            let hint =
+                closest_known_key(key).map(|known_key| format!("did you mean `{known_key}`?"));
+            self.issues.push(ConfigIssue {
+                message: format!("unknown key `{key}`"),
+                hint: Some(hint.unwrap_or_else(|| "remove it".to_string())),
+                position: Some(self.position_of(offset)),
+            });
+        }
+        let object_start = self.contents.find('{').unwrap_or_default();
+        let mut complete = true;
+        for required_key in REQUIRED_KEYS {
+            if self.range_of_key(required_key).is_none() {
+                self.issues.push(ConfigIssue {
+                    message: format!("missing key `{required_key}`"),
+                    hint: Some("add it to the top-level object".to_string()),
+                    position: Some(self.position_of(object_start)),
+                });
+                complete = false;
+            }
+        }
+        complete
+    }
+
+    fn range_of_key(&self, key: &str) -> Option<&Range<usize>> {
+        self.keys
+            .iter()
+            .find(|(_, other)| *other == key)
+            .map(|(range, _)| range)
+    }
+
+    /// The offset of the last occurrence of a JSON string in the value of a top-level key
+    fn find_in_value_of(&self, key: &str, value: &str) -> Option<usize> {
+        let start = self.range_of_key(key)?.end;
+        let end = self
+            .keys
+            .iter()
+            .map(|(range, _)| range.start)
+            .find(|other_start| *other_start > start)
+            .unwrap_or(self.contents.len());
+        // UNWRAP: serializing a string cannot fail
+        let value = serde_json::to_string(value).unwrap();
+        self.contents[start..end]
+            .rfind(&value)
+            .map(|offset| start + offset)
+    }
+
+    fn position_of(&self, offset: usize) -> (usize, usize) {
+        position_of(self.contents, offset)
+    }
+}
+
+/// The line and column of a byte offset of some contents, both starting from 1
+pub(super) fn position_of(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// The known key close enough to an unknown one to likely be what was meant
+fn closest_known_key(key: &str) -> Option<&'static str> {
+    KNOWN_KEYS
+        .iter()
+        .map(|known_key| (edit_distance(key, known_key), *known_key))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known_key)| known_key)
+}
+
+/// The Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+    "name": "foo",
+    "description": "bar",
+    "version": "1.0",
+    "driver": "bash",
+    "targets": ["a", "b", "a"]
+}"#;
+
+    #[test]
+    fn valid_keys() {
+        let mut validator = Validator::new(CONFIG);
+        assert!(validator.check_keys());
+        assert!(!validator.has_issues());
+    }
+
+    #[test]
+    fn unknown_key_with_hint() {
+        let contents = CONFIG.replace("\"version\"", "\"test-file-patern\": [], \"version\"");
+        let mut validator = Validator::new(&contents);
+        assert!(validator.check_keys());
+        assert_eq!(
+            validator.into_issues(),
+            [ConfigIssue {
+                message: "unknown key `test-file-patern`".to_string(),
+                hint: Some("did you mean `test-file-patterns`?".to_string()),
+                position: Some((4, 5)),
+            }]
+        );
+    }
+
+    #[test]
+    fn deprecated_key_is_known() {
+        let contents = CONFIG.replace("\"version\"", "\"test-file-pattern\": [], \"version\"");
+        let mut validator = Validator::new(&contents);
+        validator.check_keys();
+        assert!(!validator.has_issues());
+    }
+
+    #[test]
+    fn missing_key() {
+        let contents = CONFIG.replace("\"driver\": \"bash\",", "");
+        let mut validator = Validator::new(&contents);
+        assert!(!validator.check_keys());
+        let issues = validator.into_issues();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].message, "missing key `driver`");
+        assert_eq!(issues[0].position, Some((1, 1)));
+    }
+
+    #[test]
+    fn issue_located_at_last_occurrence_of_value() {
+        let mut validator = Validator::new(CONFIG);
+        validator.issue("targets", Some("a"), "duplicate".to_string(), None);
+        validator.issue("targets", Some("c"), "missing".to_string(), None);
+        let positions = validator
+            .into_issues()
+            .into_iter()
+            .map(|issue| issue.position)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [Some((6, 27)), Some((6, 5))]);
+    }
+
+    #[test]
+    fn serde_issue_without_position_suffix() {
+        let error = serde_json::from_str::<serde_json::Value>("{\n  \"a\": }").unwrap_err();
+        let mut validator = Validator::new("");
+        validator.serde_issue(&error);
+        assert_eq!(
+            validator.into_issues(),
+            [ConfigIssue {
+                message: "expected value".to_string(),
+                hint: None,
+                position: Some((2, 8)),
+            }]
+        );
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("targets", "targets"), 0);
+        assert_eq!(edit_distance("target", "targets"), 1);
+        assert_eq!(edit_distance("xtarce", "xtrace"), 2);
+    }
+}
@@ -0,0 +1,306 @@
+use crate::test_suite::id::TestCaseId;
+
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Selection of the test files of a test suite by their path
+/// A test file is selected if it matches any of the include patterns (or if there is none), and
+/// none of the exclude patterns. A pattern matching a directory matches all the files it contains.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    patterns: Vec<PathPattern>,
+}
+
+/// Glob pattern matched against the path of test files relative to the test suite directory
+/// Patterns starting with `!` exclude the matching files. `*` matches any sequence of characters,
+/// including `/`.
+#[derive(Debug, Clone)]
+pub struct PathPattern {
+    exclude: bool,
+    pattern: glob::Pattern,
+}
+
+/// Selection of the test cases of a test suite by their id
+/// A test case is selected if it matches any of the include patterns (or if there is none), and
+/// none of the exclude patterns.
+#[derive(Debug, Clone, Default)]
+pub struct TestFilter {
+    patterns: Vec<TestPattern>,
+}
+
+/// Pattern matched against test case ids (`path::name`)
+/// Patterns starting with `!` exclude the matching test cases. Patterns containing glob special
+/// characters (`*`, `?` or `[`) must match the whole id, others match any id containing them.
+#[derive(Debug, Clone)]
+pub struct TestPattern {
+    exclude: bool,
+    matcher: TestMatcher,
+}
+
+#[derive(Debug, Clone)]
+enum TestMatcher {
+    Glob(glob::Pattern),
+    Substring(String),
+}
+
+/// Selection of the test cases by their tags (see
+/// [`TestCaseMetadata::tags`](crate::test_suite::metadata::TestCaseMetadata::tags))
+/// A test case is selected if it has any of the included tags (or if there is none), and none of
+/// the excluded tags.
+#[derive(Debug, Clone, Default)]
+pub struct TagFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+/// Selection of the test cases by how long they usually take on previous runs, test cases which
+/// never passed being considered quick
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpeedFilter {
+    #[default]
+    All,
+    /// Only the test cases usually taking less than the slow threshold
+    Quick,
+    /// Only the test cases usually taking more than the slow threshold
+    Slow,
+}
+
+impl SpeedFilter {
+    /// Whether a test case is selected, given whether it is slow
+    pub fn matches(self, is_slow: bool) -> bool {
+        match self {
+            SpeedFilter::All => true,
+            SpeedFilter::Quick => !is_slow,
+            SpeedFilter::Slow => is_slow,
+        }
+    }
+}
+
+impl TagFilter {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        Self { include, exclude }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn include(&self) -> &[String] {
+        &self.include
+    }
+
+    pub fn exclude(&self) -> &[String] {
+        &self.exclude
+    }
+
+    /// Whether a test case with the given tags is selected
+    pub fn matches(&self, tags: &[String]) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|tag| tags.contains(tag));
+        included && !self.exclude.iter().any(|tag| tags.contains(tag))
+    }
+}
+
+impl TestFilter {
+    pub fn new(patterns: Vec<TestPattern>) -> Self {
+        Self { patterns }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn patterns(&self) -> &[TestPattern] {
+        &self.patterns
+    }
+
+    /// Whether the test case with the given id is selected
+    /// The instances of a parameterized test case are selected by the patterns matching either
+    /// their own id or the id of the parameterized test case.
+    pub fn matches(&self, id: &TestCaseId) -> bool {
+        let ids = match id.params() {
+            Some(_) => vec![id.to_string(), id.without_params().to_string()],
+            None => vec![id.to_string()],
+        };
+        let matches = |p: &TestPattern| ids.iter().any(|id| p.matches(id));
+        let mut includes = self.patterns.iter().filter(|p| !p.exclude).peekable();
+        let included = includes.peek().is_none() || includes.any(matches);
+        included && !self.patterns.iter().filter(|p| p.exclude).any(matches)
+    }
+}
+
+impl TestPattern {
+    fn matches(&self, id: &str) -> bool {
+        match &self.matcher {
+            // Also matched literally, as the brackets of the parameters of a test case id are
+            // read as a character class
+            TestMatcher::Glob(pattern) => pattern.matches(id) || id.contains(pattern.as_str()),
+            TestMatcher::Substring(substring) => id.contains(substring.as_str()),
+        }
+    }
+}
+
+impl FromStr for TestPattern {
+    type Err = String;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let (exclude, matcher) = match pattern.strip_prefix('!') {
+            Some(matcher) => (true, matcher),
+            None => (false, pattern),
+        };
+        let matcher = if matcher.contains(['*', '?', '[']) {
+            TestMatcher::Glob(
+                glob::Pattern::new(matcher)
+                    .map_err(|error| format!("invalid test pattern `{pattern}`: {error}"))?,
+            )
+        } else {
+            TestMatcher::Substring(matcher.to_string())
+        };
+        Ok(Self { exclude, matcher })
+    }
+}
+
+impl Display for TestPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let exclude = if self.exclude { "!" } else { "" };
+        match &self.matcher {
+            TestMatcher::Glob(pattern) => write!(f, "{exclude}{pattern}"),
+            TestMatcher::Substring(substring) => write!(f, "{exclude}{substring}"),
+        }
+    }
+}
+
+impl PathFilter {
+    pub fn new(patterns: Vec<PathPattern>) -> Self {
+        Self { patterns }
+    }
+
+    pub fn patterns(&self) -> &[PathPattern] {
+        &self.patterns
+    }
+
+    /// Whether the file at the given suite-relative path is selected
+    pub fn matches(&self, path: &Path) -> bool {
+        let mut includes = self.patterns.iter().filter(|p| !p.exclude).peekable();
+        let included = includes.peek().is_none() || includes.any(|p| p.matches(path));
+        included && !self.excludes(path)
+    }
+
+    /// Whether the file or directory at the given suite-relative path is excluded
+    pub fn excludes(&self, path: &Path) -> bool {
+        self.patterns
+            .iter()
+            .filter(|p| p.exclude)
+            .any(|p| p.matches(path))
+    }
+}
+
+impl PathPattern {
+    fn matches(&self, path: &Path) -> bool {
+        path.ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| self.pattern.matches_path(ancestor))
+    }
+
+    /// Replace the `**` not forming a whole path component (e.g. `net/**.sh`), rejected by the
+    /// glob crate, by `*` which has the same meaning in our case
+    fn normalize(pattern: &str) -> String {
+        let chars = pattern.chars().collect::<Vec<_>>();
+        let mut normalized = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i..].starts_with(&['*', '*']) {
+                let starts_component = i == 0 || chars[i - 1] == '/';
+                let ends_component = i + 2 == chars.len() || chars[i + 2] == '/';
+                normalized.push_str(if starts_component && ends_component {
+                    "**"
+                } else {
+                    "*"
+                });
+                i += 2;
+            } else {
+                normalized.push(chars[i]);
+                i += 1;
+            }
+        }
+        normalized
+    }
+}
+
+impl FromStr for PathPattern {
+    type Err = String;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let (exclude, glob) = match pattern.strip_prefix('!') {
+            Some(glob) => (true, glob),
+            None => (false, pattern),
+        };
+        let pattern = glob::Pattern::new(&Self::normalize(glob))
+            .map_err(|error| format!("invalid path pattern `{pattern}`: {error}"))?;
+        Ok(Self { exclude, pattern })
+    }
+}
+
+impl Display for PathPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let exclude = if self.exclude { "!" } else { "" };
+        write!(f, "{exclude}{}", self.pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(patterns: &[&str]) -> PathFilter {
+        PathFilter::new(patterns.iter().map(|p| p.parse().unwrap()).collect())
+    }
+
+    #[test]
+    fn test_matches() {
+        let path = Path::new("network/wifi/scan.sh");
+        assert!(filter(&[]).matches(path));
+        assert!(filter(&["network/**.sh"]).matches(path));
+        assert!(filter(&["network/**/scan.sh"]).matches(path));
+        assert!(filter(&["network"]).matches(path));
+        assert!(!filter(&["storage", "network/*.py"]).matches(path));
+        assert!(!filter(&["!network/wifi"]).matches(path));
+        assert!(!filter(&["network", "!**/scan.sh"]).matches(path));
+        assert!(filter(&["!storage"]).matches(path));
+    }
+
+    #[test]
+    fn test_test_filter_matches() {
+        let filter = |patterns: &[&str]| {
+            TestFilter::new(patterns.iter().map(|p| p.parse().unwrap()).collect())
+        };
+        let id = "network/dhcp.sh::test_lease".parse().unwrap();
+        assert!(filter(&[]).matches(&id));
+        assert!(filter(&["lease"]).matches(&id));
+        assert!(filter(&["network/*::test_*"]).matches(&id));
+        assert!(!filter(&["network/*::setup"]).matches(&id));
+        assert!(!filter(&["dhcp", "!lease"]).matches(&id));
+
+        let id = "network/ping.sh::test_ping[eth0]".parse().unwrap();
+        assert!(filter(&["network/ping.sh::test_ping"]).matches(&id));
+        assert!(filter(&["*::test_ping"]).matches(&id));
+        assert!(filter(&["test_ping[eth0]"]).matches(&id));
+        assert!(!filter(&["test_ping[wlan0]"]).matches(&id));
+        assert!(!filter(&["ping", "!*::test_ping"]).matches(&id));
+    }
+
+    #[test]
+    fn test_tag_filter_matches() {
+        let filter = |include: &[&str], exclude: &[&str]| {
+            let tags = |tags: &[&str]| tags.iter().map(ToString::to_string).collect();
+            TagFilter::new(tags(include), tags(exclude))
+        };
+        let tags = ["network".to_string(), "slow".to_string()];
+        assert!(filter(&[], &[]).matches(&tags));
+        assert!(filter(&[], &[]).matches(&[]));
+        assert!(filter(&["wifi", "network"], &[]).matches(&tags));
+        assert!(!filter(&["wifi"], &[]).matches(&tags));
+        assert!(!filter(&["network"], &["slow"]).matches(&tags));
+        assert!(filter(&[], &["wifi"]).matches(&[]));
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Identifier of a test case, stable across runs
+///
+/// Its string representation is `<path>::<name>` or `<path>::<name>[<params>]` where:
+/// - `path` is the path of the test file relative to the test suite directory, using `/` as
+///   separator whatever the platform,
+/// - `name` is the name of the test case in the test file (e.g. the bash function name),
+/// - `params` identifies the parameters of a parameterized test case.
+///
+/// This representation is used in reports, filters and persisted files (history, manifests).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TestCaseId {
+    path: String,
+    name: String,
+    params: Option<String>,
+}
+
+impl TestCaseId {
+    const SEPARATOR: &str = "::";
+
+    pub fn new(path: &Path, name: &str) -> Self {
+        let path = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        Self {
+            path,
+            name: name.to_string(),
+            params: None,
+        }
+    }
+
+    pub fn with_params(mut self, params: &str) -> Self {
+        self.params = Some(params.to_string());
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn params(&self) -> Option<&str> {
+        self.params.as_deref()
+    }
+
+    /// The id of the test case a parameterized test case is an instance of
+    pub fn without_params(&self) -> Self {
+        Self {
+            params: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl Display for TestCaseId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.path, Self::SEPARATOR, self.name)?;
+        if let Some(params) = &self.params {
+            write!(f, "[{params}]")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TestCaseId {
+    type Err = String;
+
+    /// The path may contain the separator (e.g. a `a::b` directory), so the id is split at the
+    /// last separator, except for the ones in the params
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid test case id `{id}`, expected `<path>::<name>`");
+        for (index, _) in id.rmatch_indices(Self::SEPARATOR) {
+            let path = &id[..index];
+            let name = &id[index + Self::SEPARATOR.len()..];
+            let (name, params) = match name.split_once('[') {
+                Some((name, params)) => (name, Some(params.strip_suffix(']').ok_or_else(invalid)?)),
+                None => (name, None),
+            };
+            // A `]` in the name means that the separator is in the params
+            if name.contains(']') {
+                continue;
+            }
+            if path.is_empty() || name.is_empty() {
+                return Err(invalid());
+            }
+            return Ok(Self {
+                path: path.to_string(),
+                name: name.to_string(),
+                params: params.map(str::to_string),
+            });
+        }
+        Err(invalid())
+    }
+}
+
+impl Serialize for TestCaseId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TestCaseId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        id.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let id = TestCaseId::new(Path::new("network/dhcp.sh"), "test_lease");
+        assert_eq!(id.to_string(), "network/dhcp.sh::test_lease");
+        assert_eq!(
+            id.with_params("eth0").to_string(),
+            "network/dhcp.sh::test_lease[eth0]"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let id: TestCaseId = "network/dhcp.sh::test_lease[eth0]".parse().unwrap();
+        assert_eq!(id.path(), "network/dhcp.sh");
+        assert_eq!(id.name(), "test_lease");
+        assert_eq!(id.params(), Some("eth0"));
+        assert!("network/dhcp.sh".parse::<TestCaseId>().is_err());
+    }
+
+    #[test]
+    fn test_parse_separator_in_path_and_params() {
+        let id: TestCaseId = "a::b/tests.sh::test_01".parse().unwrap();
+        assert_eq!(id.path(), "a::b/tests.sh");
+        assert_eq!(id.name(), "test_01");
+        assert_eq!(id.params(), None);
+        let id: TestCaseId = "a::b/tests.sh::test_01[x::y]".parse().unwrap();
+        assert_eq!(id.path(), "a::b/tests.sh");
+        assert_eq!(id.name(), "test_01");
+        assert_eq!(id.params(), Some("x::y"));
+        assert_eq!(id.to_string(), "a::b/tests.sh::test_01[x::y]");
+        assert!("tests.sh::".parse::<TestCaseId>().is_err());
+        assert!("::test_01".parse::<TestCaseId>().is_err());
+    }
+}
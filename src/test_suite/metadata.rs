@@ -0,0 +1,159 @@
+use crate::time;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Per test case metadata, declared by the test author (e.g. through annotations in test files)
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct TestCaseMetadata {
+    /// Duration the test case is expected to take on a healthy target
+    pub expected_duration: Option<Duration>,
+    /// Glob patterns of the targets the test case may run on, all targets if empty
+    pub only_targets: Vec<String>,
+    /// Timeout declared for the test case itself
+    pub timeout: Option<Duration>,
+    /// Timeout declared for all the test cases of its test file
+    pub file_timeout: Option<Duration>,
+    /// Files the test case needs to run, relative to the test suite directory
+    pub needed_files: Vec<PathBuf>,
+    /// Tags categorizing the test case (e.g. `network`), used to select the test cases to run
+    pub tags: Vec<String>,
+    /// Options of the shell running the test case (e.g. `errexit`), for the test drivers running
+    /// test cases in a shell
+    /// Declared with annotations, overriding the options of the test suite; the test driver
+    /// records the effective ones when discovering the test case.
+    pub shell_options: Option<Vec<String>>,
+    /// Whether the test case does not depend on the test cases of other test files, nor on the
+    /// target state they leave, so that its test file may run concurrently with other ones
+    pub independent: bool,
+    /// Names of the shared resources (e.g. a bus or an instrument) the test case uses, no two test
+    /// cases holding the same lock running at the same time, even on different targets
+    pub locks: Vec<String>,
+    /// Number of TCP ports the test case needs (e.g. to start servers), allocated by batrun so
+    /// that no two test cases running at the same time get the same port
+    pub ports: Option<usize>,
+}
+
+impl TestCaseMetadata {
+    const EXPECTED_DURATION: &str = "expected-duration";
+    const ONLY_TARGETS: &str = "only-targets";
+    const TIMEOUT: &str = "timeout";
+    const NEEDS_FILE: &str = "needs-file";
+    const TAGS: &str = "tags";
+    const SHELL_OPTIONS: &str = "shell-options";
+    const INDEPENDENT: &str = "independent";
+    const LOCKS: &str = "locks";
+    const PORTS: &str = "ports";
+    /// Value of the `shell-options` annotation disabling all the options
+    const NO_SHELL_OPTIONS: &str = "none";
+
+    /// Build the metadata from the file-level and test-case-level lists of `key: value`
+    /// annotations
+    /// Later annotations override earlier ones, test-case-level ones overriding file-level ones,
+    /// except for the timeout which is kept for both levels and the needed files, tags and locks
+    /// which add up.
+    /// Unknown keys are ignored.
+    pub fn from_annotations<'a>(
+        file_annotations: impl IntoIterator<Item = &'a (String, String)>,
+        test_case_annotations: impl IntoIterator<Item = &'a (String, String)>,
+    ) -> std::result::Result<Self, String> {
+        let mut metadata = Self::default();
+        for (key, value) in file_annotations {
+            if key == Self::TIMEOUT {
+                metadata.file_timeout = Some(Self::parse_duration(key, value)?);
+            } else {
+                metadata.apply(key, value)?;
+            }
+        }
+        for (key, value) in test_case_annotations {
+            if key == Self::TIMEOUT {
+                metadata.timeout = Some(Self::parse_duration(key, value)?);
+            } else {
+                metadata.apply(key, value)?;
+            }
+        }
+        Ok(metadata)
+    }
+
+    fn apply(&mut self, key: &str, value: &str) -> std::result::Result<(), String> {
+        if key == Self::EXPECTED_DURATION {
+            self.expected_duration = Some(Self::parse_duration(key, value)?);
+        } else if key == Self::ONLY_TARGETS {
+            self.only_targets = Self::parse_target_patterns(key, value)?;
+        } else if key == Self::NEEDS_FILE {
+            if value.is_empty() {
+                return Err(format!("missing file path for `{key}`"));
+            }
+            self.needed_files.push(PathBuf::from(value));
+        } else if key == Self::TAGS {
+            for tag in value.split([',', ' ']).filter(|tag| !tag.is_empty()) {
+                if !self.tags.iter().any(|known| known == tag) {
+                    self.tags.push(tag.to_string());
+                }
+            }
+        } else if key == Self::LOCKS {
+            let locks = value
+                .split([',', ' '])
+                .filter(|lock| !lock.is_empty())
+                .collect::<Vec<_>>();
+            if locks.is_empty() {
+                return Err(format!("missing lock names for `{key}`"));
+            }
+            for lock in locks {
+                if !self.locks.iter().any(|known| known == lock) {
+                    self.locks.push(lock.to_string());
+                }
+            }
+        } else if key == Self::PORTS {
+            self.ports = Some(
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid port count `{value}` for `{key}`"))?,
+            );
+        } else if key == Self::SHELL_OPTIONS {
+            let options = value
+                .split([',', ' '])
+                .filter(|option| !option.is_empty())
+                .map(String::from)
+                .collect::<Vec<_>>();
+            self.shell_options = match options.as_slice() {
+                [] => {
+                    return Err(format!(
+                        "missing options for `{key}`, `none` to disable them"
+                    ));
+                }
+                [option] if option == Self::NO_SHELL_OPTIONS => Some(Vec::new()),
+                _ => Some(options),
+            };
+        } else if key == Self::INDEPENDENT {
+            self.independent = match value {
+                "true" => true,
+                "false" => false,
+                _ => {
+                    return Err(format!(
+                        "invalid value `{value}` for `{key}`, expected `true` or `false`"
+                    ));
+                }
+            };
+        }
+        Ok(())
+    }
+
+    fn parse_duration(key: &str, value: &str) -> std::result::Result<Duration, String> {
+        time::parse(value).ok_or_else(|| format!("invalid duration `{value}` for `{key}`"))
+    }
+
+    /// Parse a list of glob patterns separated by commas or spaces
+    fn parse_target_patterns(key: &str, value: &str) -> std::result::Result<Vec<String>, String> {
+        value
+            .split([',', ' '])
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| match glob::Pattern::new(pattern) {
+                Ok(_) => Ok(pattern.to_string()),
+                Err(error) => Err(format!(
+                    "invalid target pattern `{pattern}` for `{key}`: {error}"
+                )),
+            })
+            .collect()
+    }
+}
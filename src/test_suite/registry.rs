@@ -9,14 +9,43 @@ type TestSuiteMap = HashMap<PathBuf, TestSuite>;
 
 pub(crate) struct TestSuiteRegistry {
     test_suites: TestSuiteMap,
+    /// Name of the output directory of each test suite, in the output directory of the run
+    out_dir_names: HashMap<PathBuf, String>,
 }
 impl TestSuiteRegistry {
     pub(crate) fn new() -> Self {
         Self {
             test_suites: TestSuiteMap::new(),
+            out_dir_names: HashMap::new(),
         }
     }
 
+    /// The name of the output directory of a test suite about to be inserted: its name, suffixed
+    /// with `-2`, `-3`... if another test suite inserted before already uses it, so that the
+    /// outputs of test suites with the same name do not collide
+    pub(crate) fn new_out_dir_name(&self, test_suite_dir: &Path, name: &str) -> String {
+        if let Some(out_dir_name) = self.out_dir_names.get(test_suite_dir) {
+            return out_dir_name.clone();
+        }
+        let is_used = |out_dir_name: &str| {
+            self.out_dir_names
+                .values()
+                .any(|used_name| used_name == out_dir_name)
+        };
+        std::iter::once(name.to_string())
+            .chain((2..).map(|suffix| format!("{name}-{suffix}")))
+            .find(|out_dir_name| !is_used(out_dir_name))
+            // UNWRAP: the candidate names are infinite while the used ones are not
+            .unwrap()
+    }
+
+    pub(crate) fn out_dir_name(&self, test_suite_dir: &Path) -> Result<&str> {
+        self.out_dir_names
+            .get(test_suite_dir)
+            .map(String::as_str)
+            .ok_or_else(|| Error::UnknownTestSuite(test_suite_dir.to_path_buf()))
+    }
+
     pub(crate) fn get(&self, test_suite_dir: &Path) -> Result<&TestSuite> {
         let test_suite = self.test_suites.get(test_suite_dir);
         match test_suite {
@@ -25,8 +54,15 @@ impl TestSuiteRegistry {
         }
     }
 
-    pub(crate) fn insert(&mut self, test_suite_dir: &Path, test_suite: TestSuite) {
+    pub(crate) fn insert(
+        &mut self,
+        test_suite_dir: &Path,
+        test_suite: TestSuite,
+        out_dir_name: String,
+    ) {
         self.test_suites
             .insert(test_suite_dir.to_path_buf(), test_suite);
+        self.out_dir_names
+            .insert(test_suite_dir.to_path_buf(), out_dir_name);
     }
 }
@@ -0,0 +1,85 @@
+use super::config::{StageConfig, StageFailurePolicy};
+use super::{TestFile, TestSuite, TestSuiteFixture};
+
+use std::path::Path;
+
+/// A part of a test suite run after the previous stages completed
+/// Its test suite contains only the test files of the stage and no fixture, the test suite
+/// fixture being run once before the first stage and after the last one.
+#[derive(Debug)]
+pub struct Stage {
+    pub name: String,
+    pub on_failure: StageFailurePolicy,
+    pub test_suite: TestSuite,
+}
+
+impl Stage {
+    /// Name of the implicit stage gathering the test files selected by no declared stage
+    pub const DEFAULT_NAME: &str = "default";
+}
+
+impl TestSuite {
+    /// Split the test files of the test suite into the stages declared in its config
+    /// Test files not selected by any stage are gathered in an implicit last stage.
+    pub fn stages(&self) -> Vec<Stage> {
+        let mut remaining_files = self.test_files.clone();
+        let mut stages = Vec::new();
+        for stage_config in &self.config.stages {
+            let (selected, remaining): (Vec<_>, Vec<_>) = remaining_files
+                .into_iter()
+                .partition(|test_file| Self::stage_selects(stage_config, &test_file.path));
+            remaining_files = remaining;
+            stages.push(self.stage(&stage_config.name, stage_config.on_failure, selected));
+        }
+        if !remaining_files.is_empty() {
+            stages.push(self.stage(
+                Stage::DEFAULT_NAME,
+                StageFailurePolicy::default(),
+                remaining_files,
+            ));
+        }
+        stages
+    }
+
+    /// A test suite containing only the test suite setup
+    pub fn setup_only(&self) -> TestSuite {
+        self.with_files_and_fixture(
+            Vec::new(),
+            TestSuiteFixture {
+                setup_test_case: self.fixture.setup_test_case.clone(),
+                teardown_test_case: None,
+            },
+        )
+    }
+
+    /// A test suite containing only the test suite teardown
+    pub fn teardown_only(&self) -> TestSuite {
+        self.with_files_and_fixture(
+            Vec::new(),
+            TestSuiteFixture {
+                setup_test_case: None,
+                teardown_test_case: self.fixture.teardown_test_case.clone(),
+            },
+        )
+    }
+
+    fn stage(&self, name: &str, on_failure: StageFailurePolicy, files: Vec<TestFile>) -> Stage {
+        Stage {
+            name: name.to_string(),
+            on_failure,
+            test_suite: self.with_files_and_fixture(files, TestSuiteFixture::default()),
+        }
+    }
+
+    fn with_files_and_fixture(&self, files: Vec<TestFile>, fixture: TestSuiteFixture) -> TestSuite {
+        TestSuite::new(&self.path, self.config.clone(), files, fixture)
+    }
+
+    fn stage_selects(stage_config: &StageConfig, test_file: &Path) -> bool {
+        stage_config.test_file_patterns.is_empty()
+            || stage_config.test_file_patterns.iter().any(|pattern| {
+                // UNWRAP: the patterns are checked when loading the test suite config
+                glob::Pattern::new(pattern).unwrap().matches_path(test_file)
+            })
+    }
+}
@@ -1,44 +1,231 @@
-#[derive(Debug, Default, Clone)]
+use serde::{Deserialize, Serialize};
+
+use std::time::Duration;
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Statistics {
     pub passed: usize,
     pub failed: usize,
     pub runner_failed: usize,
     pub skipped: usize,
+    /// Test cases expected to fail which did fail
+    pub xfailed: usize,
+    /// Test cases expected to fail which passed
+    pub xpassed: usize,
+    /// Test cases deselected by the filters of the run (e.g. `--filter` or tags), which are not
+    /// part of the total so that they do not weigh on the pass rate
+    pub filtered_out: usize,
 }
 impl Statistics {
     pub fn total(&self) -> usize {
-        self.passed + self.failed + self.runner_failed + self.skipped
+        self.passed + self.failed + self.runner_failed + self.skipped + self.xfailed + self.xpassed
     }
 
     pub fn max(&self) -> usize {
         // Safeguard in case of change of struct fields
-        match *self {
-            Statistics {
-                passed,
-                failed,
-                runner_failed,
-                skipped,
-            } => [passed, failed, runner_failed, skipped]
-                .into_iter()
-                .max()
-                .unwrap_or(0),
-        }
+        let Statistics {
+            passed,
+            failed,
+            runner_failed,
+            skipped,
+            xfailed,
+            xpassed,
+            // Not part of the total
+            filtered_out: _,
+        } = *self;
+        [passed, failed, runner_failed, skipped, xfailed, xpassed]
+            .into_iter()
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl std::ops::AddAssign<&Statistics> for Statistics {
+    fn add_assign(&mut self, other: &Statistics) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.runner_failed += other.runner_failed;
+        self.skipped += other.skipped;
+        self.xfailed += other.xfailed;
+        self.xpassed += other.xpassed;
+        self.filtered_out += other.filtered_out;
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum SkipReason {
     TestCaseSpecificReason(String),
+    /// The test case is restricted to other targets
+    NotSelectedForTarget(String),
+    /// The test suite config declares the test case as not working on the target
+    IncompatibleWithTarget(String),
+    /// Files needed by the test case are missing
+    MissingFiles(String),
     TestCaseSetupError,
+    PreviousStageFailed(String),
     TestSuiteSetupError,
+    /// A test case failed before this one was run, and the run stops at the first failure
+    AbortedByFailFast,
+    /// The run was interrupted (e.g. with Ctrl-C) before or while this test case was run
+    Interrupted,
+    /// The run was paused (e.g. with a pause file) before this test case was run, which runs when
+    /// the run is resumed
+    Paused,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FailReason {
+    /// The test case reported a failure itself (e.g. non-zero exit status)
+    TestCaseFailure,
+    /// A metric emitted by the test case is outside the thresholds declared in the test suite
+    /// config
+    MetricThresholdExceeded(String),
+    /// The test case was killed after running for longer than its timeout
+    Timeout(Duration),
+    /// The test case was skipped for the given reason, by itself or because files it needs are
+    /// missing, while the run requires all test cases to actually run
+    UnexpectedSkip(String),
+    /// The test case left the given processes running once done, while the run requires test
+    /// cases to clean up after themselves
+    OrphanedProcesses(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TestCaseStatus {
     NotRun,
     Running,
-    Failed,
+    Failed(FailReason),
     Passed,
     Skipped(SkipReason),
+    /// The test case failed, as expected for the given reason
+    XFailed(String),
+    /// The test case passed although it is expected to fail for the given reason
+    XPassed(String),
     DryRun,
 }
+
+impl TestCaseStatus {
+    /// Name of the status in the machine-readable outputs (e.g. `passed` or `not-run`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            TestCaseStatus::NotRun => "not-run",
+            TestCaseStatus::Running => "running",
+            TestCaseStatus::Failed(_) => "failed",
+            TestCaseStatus::Passed => "passed",
+            TestCaseStatus::Skipped(_) => "skipped",
+            TestCaseStatus::XFailed(_) => "xfailed",
+            TestCaseStatus::XPassed(_) => "xpassed",
+            TestCaseStatus::DryRun => "dry-run",
+        }
+    }
+}
+
+impl SkipReason {
+    /// Kind of the reason in the machine-readable outputs (e.g. `test-suite-setup-error`)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SkipReason::TestCaseSpecificReason(_) => "test-case-specific-reason",
+            SkipReason::NotSelectedForTarget(_) => "not-selected-for-target",
+            SkipReason::IncompatibleWithTarget(_) => "incompatible-with-target",
+            SkipReason::MissingFiles(_) => "missing-files",
+            SkipReason::TestCaseSetupError => "test-case-setup-error",
+            SkipReason::PreviousStageFailed(_) => "previous-stage-failed",
+            SkipReason::TestSuiteSetupError => "test-suite-setup-error",
+            SkipReason::AbortedByFailFast => "aborted-by-fail-fast",
+            SkipReason::Interrupted => "interrupted",
+            SkipReason::Paused => "paused",
+        }
+    }
+
+    /// Details of the reason, if its kind has some
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            SkipReason::TestCaseSpecificReason(details)
+            | SkipReason::NotSelectedForTarget(details)
+            | SkipReason::IncompatibleWithTarget(details)
+            | SkipReason::MissingFiles(details)
+            | SkipReason::PreviousStageFailed(details) => Some(details),
+            SkipReason::TestCaseSetupError
+            | SkipReason::TestSuiteSetupError
+            | SkipReason::AbortedByFailFast
+            | SkipReason::Interrupted
+            | SkipReason::Paused => None,
+        }
+    }
+}
+
+/// The reason in the machine-readable outputs: its kind, followed by `: ` and its details if any
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_reason(f, self.kind(), self.details())
+    }
+}
+
+impl FailReason {
+    /// Kind of the reason in the machine-readable outputs (e.g. `timeout`)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FailReason::TestCaseFailure => "test-case-failure",
+            FailReason::MetricThresholdExceeded(_) => "metric-threshold-exceeded",
+            FailReason::Timeout(_) => "timeout",
+            FailReason::UnexpectedSkip(_) => "unexpected-skip",
+            FailReason::OrphanedProcesses(_) => "orphaned-processes",
+        }
+    }
+
+    /// Details of the reason, if its kind has some, the timeouts being in seconds
+    pub fn details(&self) -> Option<String> {
+        match self {
+            FailReason::TestCaseFailure => None,
+            FailReason::MetricThresholdExceeded(details)
+            | FailReason::UnexpectedSkip(details)
+            | FailReason::OrphanedProcesses(details) => Some(details.clone()),
+            FailReason::Timeout(timeout) => Some(format!("{}s", timeout.as_secs_f64())),
+        }
+    }
+}
+
+/// The reason in the machine-readable outputs: its kind, followed by `: ` and its details if any
+impl std::fmt::Display for FailReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_reason(f, self.kind(), self.details().as_deref())
+    }
+}
+
+fn write_reason(
+    f: &mut std::fmt::Formatter<'_>,
+    kind: &str,
+    details: Option<&str>,
+) -> std::fmt::Result {
+    match details {
+        Some(details) => write!(f, "{kind}: {details}"),
+        None => f.write_str(kind),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reasons_as_kind_and_details() {
+        assert_eq!(FailReason::TestCaseFailure.to_string(), "test-case-failure");
+        assert_eq!(
+            FailReason::Timeout(Duration::from_millis(1500)).to_string(),
+            "timeout: 1.5s"
+        );
+        assert_eq!(
+            FailReason::OrphanedProcesses("sleep (42)".to_string()).to_string(),
+            "orphaned-processes: sleep (42)"
+        );
+        assert_eq!(
+            SkipReason::TestSuiteSetupError.to_string(),
+            "test-suite-setup-error"
+        );
+        assert_eq!(
+            SkipReason::IncompatibleWithTarget("not supported".to_string()).to_string(),
+            "incompatible-with-target: not supported"
+        );
+    }
+}
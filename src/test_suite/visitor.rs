@@ -13,44 +13,122 @@ impl<T: FnMut(&TestCase, ShouldSkip)> VisitorFnMutOk for T {}
 /// It implements internally a state machine described below
 pub struct Visitor<'ts> {
     test_suite: &'ts TestSuite,
+    /// The test files visited, all the ones of the test suite unless visiting part of it
+    test_files: &'ts [TestFile],
+    /// Whether the setup and teardown of the test suite are visited
+    suite_fixture: bool,
     state: State,
     test_file_iter: std::iter::Peekable<std::slice::Iter<'ts, TestFile>>,
     test_case_iter: std::slice::Iter<'ts, TestCase>,
     should_skip: ShouldSkip,
+    /// Why the visit was aborted, if it was
+    abort_reason: Option<SkipReason>,
+    /// Whether the visit was aborted before the test suite was set up, in which case it must not
+    /// be torn down
+    suite_aborted: bool,
+    /// Whether the visit was aborted before the current test file was set up, in which case it
+    /// must not be torn down
+    file_aborted: bool,
 }
 
 impl<'ts> Visitor<'ts> {
     pub fn new(test_suite: &'ts TestSuite) -> Self {
         Self {
             test_suite,
+            test_files: &test_suite.test_files,
+            suite_fixture: true,
             state: State::TestSuiteSetup,
             test_file_iter: std::slice::Iter::default().peekable(),
             test_case_iter: std::slice::Iter::default(),
             should_skip: ShouldSkip::No,
+            abort_reason: None,
+            suite_aborted: false,
+            file_aborted: false,
         }
     }
 
-    pub fn visit_next<E>(&mut self, f: impl VisitorFnMut<E>) -> bool {
-        let mut done = false;
-        let next_state = match self.state {
-            State::TestSuiteSetup => self.visit_test_suite_setup(f),
-            State::TestCaseSetup => self.visit_test_case_setup(f),
-            State::TestCase => self.visit_test_case(f),
-            State::TestCaseTeardown => self.visit_test_case_teardown(f),
-            State::TestSuiteTeardown => self.visit_test_suite_teardown(f),
-            // Treat all other states as a state machine termination point
-            termination_state => {
-                done = true;
-                termination_state
-            }
+    /// A visitor of the setup and teardown of the test suite only, the test files being visited
+    /// by other visitors in between (see [`Self::of_test_file`])
+    pub fn of_suite_fixture(test_suite: &'ts TestSuite) -> Self {
+        Self {
+            test_files: &[],
+            ..Self::new(test_suite)
+        }
+    }
+
+    /// A visitor of a single test file of the test suite, run once the test suite is set up,
+    /// with the skip advice of the visitor of the test suite fixture at that point
+    pub fn of_test_file(
+        test_suite: &'ts TestSuite,
+        test_file: &'ts TestFile,
+        should_skip: ShouldSkip,
+    ) -> Self {
+        let test_files = std::slice::from_ref(test_file);
+        Self {
+            test_files,
+            suite_fixture: false,
+            state: State::TestCaseSetup,
+            test_file_iter: test_files.iter().peekable(),
+            should_skip,
+            ..Self::new(test_suite)
+        }
+    }
+
+    /// Whether the next test cases visited will be skipped, and why
+    pub fn skip_advice(&self) -> ShouldSkip {
+        self.should_skip()
+    }
+
+    /// Skip all the test cases visited from now on with the given reason, except the teardowns
+    /// of the test suite and test file already set up, and end the visit in the `Aborted` state
+    pub fn abort(&mut self, reason: SkipReason) {
+        if self.abort_reason.is_none() {
+            self.abort_reason = Some(reason);
+        }
+    }
+
+    /// The skip advice for the next test case, accounting for the visit being aborted
+    fn should_skip(&self) -> ShouldSkip {
+        let mut should_skip = self.should_skip.clone();
+        if let Some(reason) = &self.abort_reason {
+            should_skip.skip_with_reason(reason.clone());
+        }
+        should_skip
+    }
+
+    pub fn visit_next<E>(&mut self, mut f: impl VisitorFnMut<E>) -> bool {
+        let Some(step) = self.next_step() else {
+            return true;
         };
-        self.state = next_state;
-        done
+        if let Some((test_case, should_skip)) = step.visit {
+            let result = f(test_case, should_skip);
+            self.end_step(result.is_ok(), step.skip_on_error);
+        }
+        self.state = step.next_state;
+        false
+    }
+
+    /// Same as [`Self::visit_next`], the test case being visited by a future
+    /// The visit of a test suite by several visitors can then be interleaved cooperatively by an
+    /// executor of futures (see [`crate::simple_executor`]).
+    pub async fn visit_next_async<E>(
+        &mut self,
+        mut f: impl AsyncFnMut(&TestCase, ShouldSkip) -> Result<(), E>,
+    ) -> bool {
+        let Some(step) = self.next_step() else {
+            return true;
+        };
+        if let Some((test_case, should_skip)) = step.visit {
+            let result = f(test_case, should_skip).await;
+            self.end_step(result.is_ok(), step.skip_on_error);
+        }
+        self.state = step.next_state;
+        false
     }
 
     pub fn visit_next_ok(&mut self, mut f: impl VisitorFnMutOk) -> bool {
         let mut f_wrapped = |test_case: &TestCase, should_skip: ShouldSkip| -> Result<(), ()> {
-            f(&test_case, should_skip);
+            f(test_case, should_skip);
             Ok(())
         };
         self.visit_next(&mut f_wrapped)
@@ -67,7 +145,7 @@ impl<'ts> Visitor<'ts> {
 
     pub fn visit_all_ok(&mut self, mut f: impl VisitorFnMutOk) {
         let mut f_wrapped = |test_case: &TestCase, should_skip: ShouldSkip| -> Result<(), ()> {
-            f(&test_case, should_skip);
+            f(test_case, should_skip);
             Ok(())
         };
         loop {
@@ -78,76 +156,149 @@ impl<'ts> Visitor<'ts> {
         }
     }
 
-    fn visit_test_suite_setup<E>(&mut self, mut f: impl VisitorFnMut<E>) -> State {
-        if let Some(tc) = &self.test_suite.fixture.setup_test_case {
-            if let Err(_) = f(tc, self.should_skip.clone()) {
-                self.should_skip
-                    .skip_with_reason(SkipReason::TestSuiteSetupError);
-            }
+    /// The next step of the state machine, or `None` if the visit is over
+    fn next_step(&mut self) -> Option<Step<'ts>> {
+        match self.state {
+            State::TestSuiteSetup => Some(self.visit_test_suite_setup()),
+            State::TestCaseSetup => Some(self.visit_test_case_setup()),
+            State::TestCase => Some(self.visit_test_case()),
+            State::TestCaseTeardown => Some(self.visit_test_case_teardown()),
+            State::TestSuiteTeardown => Some(self.visit_test_suite_teardown()),
+            // Treat all other states as a state machine termination point
+            State::Done | State::Aborted => None,
+        }
+    }
+
+    /// Account for the result of the test case visited by a step
+    fn end_step(&mut self, succeeded: bool, skip_on_error: Option<SkipReason>) {
+        if !succeeded && let Some(reason) = skip_on_error {
+            self.should_skip.skip_with_reason(reason);
         }
-        self.test_file_iter = self.test_suite.test_files.iter().peekable();
-        State::TestCaseSetup
     }
 
-    fn visit_test_case_setup<E>(&mut self, mut f: impl VisitorFnMut<E>) -> State {
+    fn visit_test_suite_setup(&mut self) -> Step<'ts> {
+        self.suite_aborted = self.abort_reason.is_some();
+        self.test_file_iter = self.test_files.iter().peekable();
+        let visit = self
+            .test_suite
+            .fixture
+            .setup_test_case
+            .as_ref()
+            .filter(|_| self.suite_fixture)
+            .map(|tc| (tc, self.should_skip()));
+        Step {
+            visit,
+            skip_on_error: Some(SkipReason::TestSuiteSetupError),
+            next_state: State::TestCaseSetup,
+        }
+    }
+
+    fn visit_test_case_setup(&mut self) -> Step<'ts> {
         // Reset the should_skip status if the stored advise was to skip the test cases from the
         // previous test file due to setup failure
         if let ShouldSkip::Yes(SkipReason::TestCaseSetupError) = self.should_skip {
             self.should_skip = ShouldSkip::No;
         }
+        let mut visit = None;
         if let Some(test_file) = self.test_file_iter.peek() {
             self.test_case_iter = test_file.test_cases.iter();
-            if let Some(tc) = &test_file.setup_test_case {
-                if let Err(_) = f(tc, self.should_skip.clone()) {
-                    self.should_skip
-                        .skip_with_reason(SkipReason::TestCaseSetupError);
-                }
-            }
+            self.file_aborted = self.abort_reason.is_some();
+            visit = test_file.setup_test_case.as_ref();
+        }
+        Step {
+            visit: visit.map(|tc| (tc, self.should_skip())),
+            skip_on_error: Some(SkipReason::TestCaseSetupError),
+            next_state: State::TestCase,
         }
-        State::TestCase
     }
 
-    fn visit_test_case<E>(&mut self, mut f: impl VisitorFnMut<E>) -> State {
-        if let Some(test_case) = self.test_case_iter.next() {
-            let _ = f(test_case, self.should_skip.clone());
-            State::TestCase
-        } else {
-            State::TestCaseTeardown
+    fn visit_test_case(&mut self) -> Step<'ts> {
+        match self.test_case_iter.next() {
+            Some(test_case) => Step {
+                visit: Some((test_case, self.should_skip())),
+                skip_on_error: None,
+                next_state: State::TestCase,
+            },
+            None => Step {
+                visit: None,
+                skip_on_error: None,
+                next_state: State::TestCaseTeardown,
+            },
         }
     }
 
-    fn visit_test_case_teardown<E>(&mut self, mut f: impl VisitorFnMut<E>) -> State {
-        if let Some(test_file) = self.test_file_iter.next() {
-            if let Some(tc) = &test_file.teardown_test_case {
-                let _ = f(tc, self.should_skip.clone());
-            }
-            State::TestCaseSetup
+    fn visit_test_case_teardown(&mut self) -> Step<'ts> {
+        let Some(test_file) = self.test_file_iter.next() else {
+            return Step {
+                visit: None,
+                skip_on_error: None,
+                next_state: State::TestSuiteTeardown,
+            };
+        };
+        let should_skip = if self.file_aborted {
+            self.should_skip()
         } else {
-            State::TestSuiteTeardown
+            self.should_skip.clone()
+        };
+        Step {
+            visit: test_file
+                .teardown_test_case
+                .as_ref()
+                .map(|tc| (tc, should_skip)),
+            skip_on_error: None,
+            next_state: State::TestCaseSetup,
         }
     }
 
-    fn visit_test_suite_teardown<E>(&mut self, mut f: impl VisitorFnMut<E>) -> State {
-        if let Some(tc) = &self.test_suite.fixture.teardown_test_case {
-            let _ = f(tc, self.should_skip.clone());
+    fn visit_test_suite_teardown(&mut self) -> Step<'ts> {
+        let should_skip = if self.suite_aborted {
+            self.should_skip()
+        } else {
+            self.should_skip.clone()
+        };
+        let visit = self
+            .test_suite
+            .fixture
+            .teardown_test_case
+            .as_ref()
+            .filter(|_| self.suite_fixture)
+            .map(|tc| (tc, should_skip));
+        let next_state = if self.abort_reason.is_some() {
+            State::Aborted
+        } else {
+            State::Done
+        };
+        Step {
+            visit,
+            skip_on_error: None,
+            next_state,
         }
-        State::Done
     }
 }
 
+/// A transition of the state machine, visiting at most one test case
+struct Step<'ts> {
+    /// The test case visited and whether it should be skipped
+    visit: Option<(&'ts TestCase, ShouldSkip)>,
+    /// Why the next test cases are skipped if visiting the test case fails
+    skip_on_error: Option<SkipReason>,
+    next_state: State,
+}
+
 /// The state of the state machine
+/// The visit ends in `Aborted` instead of `Done` if it was aborted with [`Visitor::abort`].
 ///
 /// ┌────────────────┐    ┌───────────────┐    ┌──────────┐    ┌──────────────────┐    ┌───────────────────┐    ┌──────┐    ///
 /// │                │    │               │    │          │    │                  │    │                   │    │      │    ///
 /// │ TestSuiteSetup │───>│ TestCaseSetup │───>│ TestCase │───>│ TestCaseTeardown │───>│ TestSuiteTeardown │───>│ Done │    ///
 /// │                │    │               │    │          │    │                  │    │                   │    │      │    ///
-/// └────────────────┘    └───────────────┘    └──────────┘    └──────────────────┘    └───────────────────┘    └──────┘    ///
-///                               ^              ^      │                 │                                                 ///
-///                               │              └──────┘                 │                     ┌───────┐    ┌─────────┐    ///
-///                               └───────────────────────────────────────┘                     │       │    │         │    ///
-///                                                                                             │ <All> │───>│ Aborted │    ///
-///                                                                                             │       │    │         │    ///
-///                                                                                             └───────┘    └─────────┘    ///
+/// └────────────────┘    └───────────────┘    └──────────┘    └──────────────────┘    └─────────┬─────────┘    └──────┘    ///
+///                               ^              ^      │                 │                      │                          ///
+///                               │              └──────┘                 │                      │     ┌─────────┐          ///
+///                               └───────────────────────────────────────┘                      │     │         │          ///
+///                                                                                              └────>│ Aborted │          ///
+///                                                                                                    │         │          ///
+///                                                                                                    └─────────┘          ///
 #[derive(Clone, Copy)]
 pub enum State {
     TestSuiteSetup,
@@ -176,3 +327,170 @@ impl ShouldSkip {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_executor::SimpleExecutor;
+    use crate::test_executor::AbortSignal;
+    use crate::test_suite::TestSuiteFixture;
+
+    use std::path::Path;
+
+    fn test_suite() -> TestSuite {
+        let config = serde_json::from_str(
+            r#"{"name": "suite", "description": "", "version": "1.0", "driver": "bash", "targets": []}"#,
+        )
+        .unwrap();
+        let test_file = |name: &str| TestFile {
+            path: name.into(),
+            setup_test_case: Some(TestCase::new(Path::new(name), "setup")),
+            teardown_test_case: Some(TestCase::new(Path::new(name), "teardown")),
+            test_cases: vec![
+                TestCase::new(Path::new(name), "test_01"),
+                TestCase::new(Path::new(name), "test_02"),
+            ],
+        };
+        let fixture = TestSuiteFixture {
+            setup_test_case: Some(TestCase::new(Path::new("global.sh"), "setup")),
+            teardown_test_case: Some(TestCase::new(Path::new("global.sh"), "teardown")),
+        };
+        TestSuite::new(
+            Path::new("suite"),
+            config,
+            vec![test_file("a.sh"), test_file("b.sh")],
+            fixture,
+        )
+    }
+
+    /// Visit a test case, the setup of `a.sh` failing
+    fn visit(
+        visited: &mut Vec<String>,
+        test_case: &TestCase,
+        should_skip: ShouldSkip,
+    ) -> Result<(), ()> {
+        let skipped = match should_skip {
+            ShouldSkip::No => "",
+            ShouldSkip::Yes(_) => " (skipped)",
+        };
+        visited.push(format!("{}{skipped}", test_case.id()));
+        if test_case.id().to_string() == "a.sh::setup" {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn async_visit_same_as_sync_visit() {
+        let test_suite = test_suite();
+        let mut visited = Vec::new();
+        Visitor::new(&test_suite)
+            .visit_all(|test_case, should_skip| visit(&mut visited, test_case, should_skip));
+        assert_eq!(
+            visited,
+            [
+                "global.sh::setup",
+                "a.sh::setup",
+                "a.sh::test_01 (skipped)",
+                "a.sh::test_02 (skipped)",
+                "a.sh::teardown (skipped)",
+                "b.sh::setup",
+                "b.sh::test_01",
+                "b.sh::test_02",
+                "b.sh::teardown",
+                "global.sh::teardown",
+            ]
+        );
+
+        let mut visited_async = Vec::new();
+        let mut executor = SimpleExecutor::new();
+        executor.spawn(async {
+            let mut visitor = Visitor::new(&test_suite);
+            while !visitor
+                .visit_next_async(async |test_case, should_skip| {
+                    visit(&mut visited_async, test_case, should_skip)
+                })
+                .await
+            {}
+        });
+        executor.run();
+        drop(executor);
+        assert_eq!(visited_async, visited);
+    }
+
+    /// Visit all the test cases, recording why they are skipped, the visit being aborted with the
+    /// reason of the abort signal once the given test case is visited
+    fn visit_aborted_after(test_suite: &TestSuite, abort_after: &str) -> Vec<String> {
+        let abort_signal = AbortSignal::default();
+        let mut visited = Vec::new();
+        let mut visitor = Visitor::new(test_suite);
+        loop {
+            if let Some(reason) = abort_signal.reason() {
+                visitor.abort(reason);
+            }
+            let is_done = visitor.visit_next_ok(|test_case, should_skip| {
+                visited.push(match should_skip {
+                    ShouldSkip::No => test_case.id().to_string(),
+                    ShouldSkip::Yes(reason) => format!("{} ({reason})", test_case.id()),
+                });
+                if test_case.id().to_string() == abort_after {
+                    abort_signal.abort(SkipReason::AbortedByFailFast);
+                    // Only the first reason is kept
+                    abort_signal.abort(SkipReason::Paused);
+                }
+            });
+            if is_done {
+                break;
+            }
+        }
+        assert!(matches!(visitor.state, State::Aborted));
+        visited
+    }
+
+    #[test]
+    fn abort_tears_down_what_is_set_up() {
+        let test_suite = test_suite();
+        assert_eq!(
+            visit_aborted_after(&test_suite, "a.sh::test_01"),
+            [
+                "global.sh::setup",
+                "a.sh::setup",
+                "a.sh::test_01",
+                "a.sh::test_02 (aborted-by-fail-fast)",
+                "a.sh::teardown",
+                "b.sh::setup (aborted-by-fail-fast)",
+                "b.sh::test_01 (aborted-by-fail-fast)",
+                "b.sh::test_02 (aborted-by-fail-fast)",
+                "b.sh::teardown (aborted-by-fail-fast)",
+                "global.sh::teardown",
+            ]
+        );
+    }
+
+    #[test]
+    fn abort_before_setup() {
+        let test_suite = test_suite();
+        let mut visited = Vec::new();
+        let mut visitor = Visitor::new(&test_suite);
+        visitor.abort(SkipReason::Interrupted);
+        visitor.abort(SkipReason::AbortedByFailFast);
+        assert_eq!(
+            visitor.skip_advice(),
+            ShouldSkip::Yes(SkipReason::Interrupted)
+        );
+        visitor.visit_all_ok(|test_case, should_skip| {
+            assert_eq!(should_skip, ShouldSkip::Yes(SkipReason::Interrupted));
+            visited.push(test_case.id().to_string());
+        });
+        assert!(matches!(visitor.state, State::Aborted));
+        assert_eq!(
+            visited.first().map(String::as_str),
+            Some("global.sh::setup")
+        );
+        assert_eq!(
+            visited.last().map(String::as_str),
+            Some("global.sh::teardown")
+        );
+    }
+}
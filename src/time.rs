@@ -1,4 +1,4 @@
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 #[derive(Debug, Clone)]
 pub struct TimeInterval {
@@ -6,6 +6,12 @@ pub struct TimeInterval {
     end_time: Option<Instant>,
 }
 
+impl Default for TimeInterval {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TimeInterval {
     pub fn new() -> Self {
         Self {
@@ -14,6 +20,15 @@ impl TimeInterval {
         }
     }
 
+    /// A finished interval of the given duration, ending now
+    pub fn ending_now(duration: Duration) -> Self {
+        let end_time = Instant::now();
+        Self {
+            start_time: end_time.checked_sub(duration).unwrap_or(end_time),
+            end_time: Some(end_time),
+        }
+    }
+
     pub fn stop(&mut self) -> Duration {
         self.end_time = Some(Instant::now());
         // UNWRAP: Both start_time and end_time are guaranteed to be set at this point
@@ -23,6 +38,13 @@ impl TimeInterval {
     pub fn elapsed(&self) -> Option<Duration> {
         self.end_time.map(|end| end.duration_since(self.start_time))
     }
+
+    /// The point in time the interval started at
+    pub fn started_at(&self) -> SystemTime {
+        SystemTime::now()
+            .checked_sub(self.start_time.elapsed())
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    }
 }
 
 pub fn format(duration: Duration) -> String {
@@ -34,14 +56,98 @@ pub fn format(duration: Duration) -> String {
     let minutes = minutes % 60;
 
     if hours > 0 {
-        return format!("{}h {}m {}s", hours, minutes, seconds);
+        format!("{}h {}m {}s", hours, minutes, seconds)
     } else if minutes > 0 {
-        return format!("{}m {}s", minutes, seconds);
+        format!("{}m {}s", minutes, seconds)
+    } else if seconds == 0 {
+        format!("{}ms", duration.as_millis())
     } else {
-        return format!("{}s", seconds);
+        format!("{}s", seconds)
     }
 }
 
+/// Format a point in time as an RFC 3339 timestamp in UTC, as `git log --date=iso-strict` does,
+/// e.g. `2024-03-01T12:30:00+00:00`
+pub fn format_timestamp(time: SystemTime) -> String {
+    let (year, month, day, hours, minutes, seconds) = utc(time);
+    format!("{year:04}-{month:02}-{day:02}T{hours:02}:{minutes:02}:{seconds:02}+00:00")
+}
+
+/// Format a point in time as a compact ISO 8601 timestamp in UTC, usable in file names on all
+/// platforms, e.g. `20240301T123000Z`
+pub fn format_compact_timestamp(time: SystemTime) -> String {
+    let (year, month, day, hours, minutes, seconds) = utc(time);
+    format!("{year:04}{month:02}{day:02}T{hours:02}{minutes:02}{seconds:02}Z")
+}
+
+/// The UTC year, month, day, hours, minutes and seconds of a point in time
+fn utc(time: SystemTime) -> (u64, u64, u64, u64, u64, u64) {
+    let seconds = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    // Civil date from the number of days since 1970-01-01, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+    )
+}
+
+/// Parse a human readable duration such as `90s`, `2m`, `1h 30m` or `500ms`
+/// A number without unit is interpreted as seconds
+/// Durations which are negative, not a number or too long to be represented are invalid.
+pub fn parse(duration: &str) -> Option<Duration> {
+    let duration = duration.trim();
+    if duration.is_empty() {
+        return None;
+    }
+    if let Ok(seconds) = duration.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+    let mut total = Duration::ZERO;
+    let mut rest = duration;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let value = rest[..number_len].parse::<f64>().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let factor = match &rest[..unit_len] {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return None,
+        };
+        total = total.checked_add(Duration::try_from_secs_f64(value * factor).ok()?)?;
+        rest = rest[unit_len..].trim_start();
+    }
+    Some(total)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,4 +169,43 @@ mod tests {
         let s = Duration::from_secs(1);
         assert_eq!(format(s), "1s");
     }
+
+    #[test]
+    fn test_ms_only() {
+        let ms = Duration::from_millis(200);
+        assert_eq!(format(ms), "200ms");
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let timestamp =
+            |seconds| format_timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!(timestamp(0), "1970-01-01T00:00:00+00:00");
+        assert_eq!(timestamp(951782400), "2000-02-29T00:00:00+00:00");
+        assert_eq!(timestamp(1709296200), "2024-03-01T12:30:00+00:00");
+        let compact =
+            format_compact_timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(1709296200));
+        assert_eq!(compact, "20240301T123000Z");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("42"), Some(Duration::from_secs(42)));
+        assert_eq!(parse("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse("1h 1m 1s"), Some(Duration::from_secs(3661)));
+        assert_eq!(parse("1m30s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse("12 parsecs"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn test_parse_unrepresentable() {
+        assert_eq!(parse("inf"), None);
+        assert_eq!(parse("NaN"), None);
+        assert_eq!(parse("1e30"), None);
+        assert_eq!(parse("-1"), None);
+        assert_eq!(parse("100000000000000000000h"), None);
+        assert_eq!(parse("5000000000000000h 5000000000000000h"), None);
+    }
 }
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+function test_01_uploaded {
+    echo "This log is stored in the bucket"
+}
+
+function test_02_fail {
+    return 1
+}
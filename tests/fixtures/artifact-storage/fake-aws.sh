@@ -0,0 +1,9 @@
+#!/bin/bash
+
+# Mimic `aws s3 cp --only-show-errors <file> s3://<bucket>/<object>`, without S3, the bucket being
+# a directory next to the output directory of the test suite
+set -e
+[ "$1 $2 $3" = "s3 cp --only-show-errors" ]
+object="${4%/batrun-fixture-artifact-storage/*}/bucket/${5#s3://}"
+mkdir -p "$(dirname "$object")"
+cp "$4" "$object"
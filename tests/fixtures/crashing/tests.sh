@@ -0,0 +1,13 @@
+#!/bin/bash
+
+function test_01_ok {
+    return 0
+}
+
+# Waits for batrun to be killed the first time, passing once resumed
+function test_02_killed {
+    if [ ! -e "$2/../killed" ]; then
+        touch "$2/../killed"
+        sleep 5
+    fi
+}
@@ -0,0 +1,5 @@
+#!/bin/bash
+
+function test_01_not_selected {
+    return 1
+}
@@ -0,0 +1,6 @@
+#!/bin/bash
+
+function test_01_ok {
+    echo "Test 01 on target $1"
+    return 0
+}
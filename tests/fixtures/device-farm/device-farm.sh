@@ -0,0 +1,16 @@
+#!/bin/bash
+
+# Mimic the client of a device farm API, without a device farm
+case "$1" in
+    lease)
+        [ "$BATRUN_TARGET" != "farm-exhausted" ] || { echo "no device available" >&2; exit 1; }
+        echo "DEVICE_FARM_LEASE_ID=lease-42"
+        echo "DEVICE_ADDRESS=192.0.2.42"
+        ;;
+    release)
+        [ "$DEVICE_FARM_LEASE_ID" = "lease-42" ]
+        ;;
+    capture)
+        echo "fake screenshot of $DEVICE_ADDRESS" > "$BATRUN_ARTIFACT_PREFIX.screenshot.png"
+        ;;
+esac
@@ -0,0 +1,10 @@
+#!/bin/bash
+
+function test_01_env_points_to_device {
+    [ "$DEVICE_ADDRESS" = "192.0.2.42" ]
+}
+
+# The capture command collects the state of the device
+function test_02_fail {
+    return 1
+}
@@ -0,0 +1,10 @@
+#!/bin/bash
+
+# @expected-duration: 90s
+function test_01_known_duration {
+    return 1
+}
+
+function test_02_unknown_duration {
+    return 1
+}
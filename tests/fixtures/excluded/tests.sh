@@ -0,0 +1,10 @@
+#!/bin/bash
+
+function test_01_everywhere {
+    return 0
+}
+
+# Would fail on the boards, but is skipped on them by the test suite config
+function test_02_emulator_only {
+    [ "$1" = "emulator" ]
+}
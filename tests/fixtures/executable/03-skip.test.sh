@@ -0,0 +1,4 @@
+#!/bin/sh
+
+# Exit code 77 marks the test case as skipped
+exit 77
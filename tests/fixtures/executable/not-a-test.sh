@@ -0,0 +1,4 @@
+#!/bin/sh
+
+# Not matching the test file patterns of the driver, so never run
+exit 1
@@ -0,0 +1,19 @@
+#!/bin/bash
+
+function setup {
+    return 0
+}
+
+function test_01_fail {
+    return 1
+}
+
+function test_02_ok {
+    return 0
+}
+
+# Still run when the run stops at the first failure, as the test file was set up
+function teardown {
+    echo "Tearing down a.sh"
+    return 0
+}
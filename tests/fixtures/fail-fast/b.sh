@@ -0,0 +1,13 @@
+#!/bin/bash
+
+function setup {
+    return 0
+}
+
+function test_01_ok {
+    return 0
+}
+
+function teardown {
+    return 0
+}
@@ -0,0 +1,10 @@
+#!/bin/bash
+
+function setup {
+    return 0
+}
+
+function teardown {
+    echo "Tearing down the test suite"
+    return 0
+}
@@ -0,0 +1,10 @@
+#!/bin/bash
+
+function test_01_ok {
+    return 0
+}
+
+function test_02_fail {
+    echo "Test 02 failing on target $1"
+    return 1
+}
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+function setup {
+    return 1
+}
+
+function teardown {
+    return 0
+}
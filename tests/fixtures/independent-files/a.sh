@@ -0,0 +1,23 @@
+#!/bin/bash
+# @independent: true
+
+function setup {
+    touch "$2/../a.ready"
+}
+
+# Passes only if the test file b.sh runs at the same time
+function test_01_concurrent_with_b {
+    for _ in $(seq 20); do
+        [ -e "$2/../b.ready" ] && return 0
+        sleep 0.1
+    done
+    return 1
+}
+
+function test_02_after {
+    return 0
+}
+
+function teardown {
+    return 0
+}
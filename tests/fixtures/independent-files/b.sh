@@ -0,0 +1,23 @@
+#!/bin/bash
+# @independent: true
+
+function setup {
+    touch "$2/../b.ready"
+}
+
+# Passes only if the test file a.sh runs at the same time
+function test_01_concurrent_with_a {
+    for _ in $(seq 20); do
+        [ -e "$2/../a.ready" ] && return 0
+        sleep 0.1
+    done
+    return 1
+}
+
+function test_02_after {
+    return 0
+}
+
+function teardown {
+    return 0
+}
@@ -0,0 +1,14 @@
+#!/bin/bash
+
+# Mimic the kubectl commands used by batrun, without a cluster
+if [ "$1" = "--namespace" ]; then
+    shift 2
+fi
+case "$1" in
+    create) [[ "$*" != *"--image=broken"* ]] || { echo "image not found" >&2; exit 1; } ;;
+    wait) ;;
+    get) echo "fake-pod" ;;
+    logs) echo "fake simulator ready"; sleep 60 ;;
+    delete) ;;
+    *) echo "unexpected kubectl command: $*" >&2; exit 1 ;;
+esac
@@ -0,0 +1,6 @@
+#!/bin/bash
+
+# The lock directory is relative to the output directory
+function test_01_bench_locked {
+    grep "^$1 on " "$2/../../../../lab-locks/bench.lock"
+}
@@ -0,0 +1,22 @@
+#!/bin/bash
+
+# Attaches the targets `late` and `later` to the targets file given as TARGETS_FILE, `late`
+# being listed twice
+function test_01_attach {
+    if [ "$1" = local ]; then
+        printf 'late\nlater\nlate\n' >> "$TARGETS_FILE"
+    fi
+}
+
+# Passes on `local` once `late` started, which it must do while `local` is still running
+function test_02_wait_late {
+    if [ "$1" = late ]; then
+        touch "$TARGETS_FILE.late"
+    elif [ "$1" = local ]; then
+        for _ in $(seq 50); do
+            [ -e "$TARGETS_FILE.late" ] && return 0
+            sleep 0.1
+        done
+        return 1
+    fi
+}
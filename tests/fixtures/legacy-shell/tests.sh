@@ -0,0 +1,14 @@
+#!/bin/bash
+
+# Passes without `errexit` and `nounset`, as set in the test suite config
+function test_01_legacy {
+    false
+    echo "Unset variable: ${UNSET_VARIABLE}"
+    return 0
+}
+
+# @shell-options: errexit
+function test_02_errexit {
+    false
+    return 0
+}
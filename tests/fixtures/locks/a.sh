@@ -0,0 +1,10 @@
+#!/bin/bash
+# @independent: true
+
+# Fails if the test case of b.sh uses the bus at the same time
+# @locks: bus
+function test_01_use_bus {
+    mkdir "$2/../bus.lock" || return 1
+    sleep 0.5
+    rmdir "$2/../bus.lock"
+}
@@ -0,0 +1,10 @@
+#!/bin/bash
+# @independent: true
+
+# Fails if the test case of a.sh uses the bus at the same time, its lock being declared in the
+# test suite config
+function test_01_use_bus {
+    mkdir "$2/../bus.lock" || return 1
+    sleep 0.5
+    rmdir "$2/../bus.lock"
+}
@@ -0,0 +1,18 @@
+#!/bin/bash
+# @independent: true
+
+function test_01_fixed_port {
+    python3 -c '
+import socket, time
+server = socket.socket()
+server.bind(("127.0.0.1", 8080))
+server.listen()
+time.sleep(0.5)
+'
+}
+
+function test_02_forwarded_port {
+    exec 3<>/dev/tcp/127.0.0.1/28417
+    read -r greeting <&3
+    [ "$greeting" = "hello from the host" ]
+}
@@ -0,0 +1,14 @@
+#!/bin/bash
+
+# Greets each client connecting to port 28417 of the host
+exec python3 -c '
+import socket
+server = socket.socket()
+server.setsockopt(socket.SOL_SOCKET, socket.SO_REUSEADDR, 1)
+server.bind(("127.0.0.1", 28417))
+server.listen()
+while True:
+    client, _ = server.accept()
+    client.sendall(b"hello from the host\n")
+    client.close()
+'
@@ -0,0 +1,15 @@
+#!/bin/bash
+
+# @only-targets: a
+function test_01_only_on_a {
+    [ "$1" = "a" ]
+}
+
+# Restricted to target b by the test suite config
+function test_02_only_on_b {
+    [ "$1" = "b" ]
+}
+
+function test_03_everywhere {
+    return 0
+}
@@ -0,0 +1,11 @@
+#!/bin/bash
+
+function test_01_leaves_process {
+    setsid sleep 30 >/dev/null 2>&1 &
+}
+
+function test_02_cleans_up {
+    sleep 30 >/dev/null 2>&1 &
+    kill $!
+    wait $! || true
+}
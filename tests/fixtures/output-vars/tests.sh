@@ -0,0 +1,32 @@
+#!/bin/bash
+
+function test_01_message_and_metrics {
+    export BATRUN_MESSAGE="Booted in 8.5s"
+    export BATRUN_METRICS="boot_time=8.5,memory=512"
+    return 0
+}
+
+# The artifacts outside of the output directory of the test case are copied into it
+function test_02_artifacts {
+    echo "result" > "$2/result.txt"
+    mkdir -p "$2/reports"
+    echo "report" > "$2/reports/report.txt"
+    export BATRUN_ARTIFACTS="result.txt:$2/reports/report.txt:missing.txt"
+    return 0
+}
+
+# The files dropped into the artifacts directory are collected, failed test cases listing them
+function test_03_artifacts_dir {
+    echo "core" > "$BATRUN_ARTIFACTS_DIR/core"
+    mkdir -p "$BATRUN_ARTIFACTS_DIR/traces"
+    echo "trace" > "$BATRUN_ARTIFACTS_DIR/traces/trace.txt"
+    return 1
+}
+
+# The test driver warns about the output env vars it does not know and the invalid metrics, each
+# being a warning of its own
+function test_04_unknown_output_var {
+    export BATRUN_MESAGE="typo"
+    export BATRUN_METRICS="boot_time=fast"
+    return 0
+}
@@ -0,0 +1,20 @@
+#!/bin/bash
+
+# Passes once both targets reached it, which they only do in time if run at the same time, the
+# targets meeting in the MEET_DIR directory
+function test_01_meet {
+    mkdir -p "$MEET_DIR"
+    touch "$MEET_DIR/$1"
+    for _ in $(seq "${MEET_TRIES:-50}"); do
+        [ -e "$MEET_DIR/a" ] && [ -e "$MEET_DIR/b" ] && return 0
+        sleep 0.1
+    done
+    return 1
+}
+
+# Prints long lines at the same time on both targets
+function test_02_output {
+    for i in $(seq 200); do
+        echo "target $1 line $i $(printf '%0200d' 0)"
+    done
+}
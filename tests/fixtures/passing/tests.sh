@@ -0,0 +1,11 @@
+#!/bin/bash
+
+function test_01_ok {
+    echo "Test 01 on target $1"
+    return 0
+}
+
+function test_02_ok {
+    echo "Test 02 on target $1"
+    return 0
+}
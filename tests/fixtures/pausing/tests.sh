@@ -0,0 +1,18 @@
+#!/bin/bash
+
+function setup {
+    return 0
+}
+
+# Creates the pause file given to batrun, in its output directory
+function test_01_pause {
+    touch "$2/../../../pause"
+}
+
+function test_02_after_pause {
+    return 0
+}
+
+function teardown {
+    return 0
+}
@@ -0,0 +1,8 @@
+#!/bin/bash
+# @independent: true
+
+# @ports: 2
+function test_01_server {
+    export BATRUN_MESSAGE="$BATRUN_PORTS"
+    sleep 0.3
+}
@@ -0,0 +1,7 @@
+#!/bin/bash
+# @independent: true
+
+function test_01_server {
+    export BATRUN_MESSAGE="$BATRUN_PORTS"
+    sleep 0.3
+}
@@ -0,0 +1,8 @@
+#!/bin/bash
+
+trap 'echo stopping; exit 0' TERM
+sleep 0.2
+echo listening
+while true; do
+    sleep 0.1
+done
@@ -0,0 +1,5 @@
+#!/bin/bash
+
+function test_01_ok {
+    return 0
+}
@@ -0,0 +1,21 @@
+#!/bin/bash
+
+function setup {
+    export BATRUN_EXPORT_PORT="$((40000 + RANDOM % 1000))"
+    return 0
+}
+
+# Gets the values exported by the test suite setup and by the setup of its test file
+function test_01_exports {
+    [ "$SERVER_URL" = "http://localhost:8080" ] && [ -n "$PORT" ]
+}
+
+# Only setups can export values, the export being ignored with a warning
+function test_02_export_from_test_case {
+    export BATRUN_EXPORT_OTHER="ignored"
+    return 0
+}
+
+function teardown {
+    [ -n "$PORT" ]
+}
@@ -0,0 +1,6 @@
+#!/bin/bash
+
+# Does not get the values exported by the setup of another test file
+function test_01_no_file_exports {
+    [ "$SERVER_URL" = "http://localhost:8080" ] && [ -z "${PORT:-}" ]
+}
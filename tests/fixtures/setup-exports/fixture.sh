@@ -0,0 +1,6 @@
+#!/bin/bash
+
+function setup {
+    export BATRUN_EXPORT_SERVER_URL="http://localhost:8080"
+    return 0
+}
@@ -0,0 +1,7 @@
+#!/bin/bash
+
+# Mimic a simulator running its board in a process of its own, stopped along with it
+sleep 60 &
+echo "board pid: $!"
+echo "fake-board login:"
+wait
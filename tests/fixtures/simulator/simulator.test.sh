@@ -0,0 +1,5 @@
+#!/bin/bash
+
+function test_01_console_captured {
+    grep "login:" "$BATRUN_SIMULATOR_CONSOLE_LOG"
+}
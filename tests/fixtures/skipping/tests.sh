@@ -0,0 +1,15 @@
+#!/bin/bash
+
+function test_01_ok {
+    return 0
+}
+
+# @tags: optional
+function test_02_skipped {
+    export BATRUN_SKIPPED="Nothing to test on this target"
+    return 0
+}
+
+function test_03_skipped_by_config {
+    return 1
+}
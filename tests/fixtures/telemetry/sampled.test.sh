@@ -0,0 +1,6 @@
+#!/bin/bash
+
+# Runs for long enough to be sampled several times
+function test_01_sampled {
+    sleep 0.5
+}
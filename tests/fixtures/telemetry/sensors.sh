@@ -0,0 +1,3 @@
+#!/bin/bash
+# Prints the values of the sensors of the target, the same at each sample
+echo "temperature=61.5 load=0.8"
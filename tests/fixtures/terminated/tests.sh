@@ -0,0 +1,20 @@
+#!/bin/bash
+
+function setup {
+    return 0
+}
+
+# Runs without timeout until killed, with the process it started, once batrun gets SIGTERM
+function test_01_long {
+    touch "$STARTED_FILE"
+    sleep 30
+}
+
+function test_02_after {
+    return 0
+}
+
+# Still run once batrun got SIGTERM, as the test file was set up
+function teardown {
+    return 0
+}
@@ -0,0 +1,15 @@
+#!/bin/bash
+
+function test_01_exits_on_sigterm {
+    sleep 30
+}
+
+function test_02_ignores_sigterm {
+    trap '' TERM
+    sleep 30
+}
+
+function test_03_leaves_process_ignoring_sigterm {
+    (trap '' TERM; sleep 30) &
+    sleep 30
+}
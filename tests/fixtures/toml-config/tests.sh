@@ -0,0 +1,10 @@
+#!/bin/bash
+
+function test_01_ok {
+    echo "Test 01 on target $1"
+    return 0
+}
+
+function test_02_env {
+    [ "$GREETING" = "hello from TOML" ]
+}
@@ -0,0 +1,11 @@
+#!/bin/bash
+
+# The log of this test case is uploaded, then removed from the output directory
+function test_01_log_uploaded {
+    echo "This log is stored in the bucket"
+}
+
+# Expected to fail, the reported log location is the one in the bucket
+function test_02_failure_log_location {
+    false
+}
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+# Mimic `aws s3 cp --only-show-errors <file> s3://<bucket>/<object>`, without S3, the bucket being
+# a temporary directory
+set -e
+[ "$1 $2 $3" = "s3 cp --only-show-errors" ]
+object="${TMPDIR:-/tmp}/batrun-fake-s3/${5#s3://}"
+mkdir -p "$(dirname "$object")"
+cp "$4" "$object"
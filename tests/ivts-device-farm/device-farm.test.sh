@@ -0,0 +1,14 @@
+#!/bin/bash
+
+function test_01_env_points_to_device {
+    [ "$DEVICE_ADDRESS" = "192.0.2.42" ]
+}
+
+function test_02_lease_recorded {
+    grep "lease-42" "$2/../device-farm.lease.json"
+}
+
+# Expected to fail, the capture command collects the state of the device
+function test_03_device_state_captured_on_failure {
+    false
+}
@@ -0,0 +1,18 @@
+#!/bin/bash
+
+# Mimic the client of a device farm API, without a device farm
+case "$1" in
+    lease)
+        [ "$BATRUN_TARGET" != "farm-exhausted" ] || { echo "no device available" >&2; exit 1; }
+        echo "Reserving a device for $BATRUN_TARGET..."
+        echo "DEVICE_FARM_LEASE_ID=lease-42"
+        echo "DEVICE_ADDRESS=192.0.2.42"
+        ;;
+    release)
+        [ "$DEVICE_FARM_LEASE_ID" = "lease-42" ]
+        ;;
+    capture)
+        echo "Capturing the state of device $DEVICE_ADDRESS..."
+        echo "fake screenshot" > "$BATRUN_ARTIFACT_PREFIX.screenshot.png"
+        ;;
+esac
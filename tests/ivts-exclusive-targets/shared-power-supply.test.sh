@@ -0,0 +1,15 @@
+#!/bin/bash
+
+function test_01_power_supply_not_in_use {
+    local -r DEVICE="$1"
+    local -r OUT_DIR="$2"
+    # Directory shared by all the targets of the run, standing for their shared power supply
+    local -r POWER_SUPPLY="$OUT_DIR/../../power-supply.lock"
+
+    if [ "$DEVICE" = "bench-c" ]; then
+        return 0
+    fi
+    mkdir "$POWER_SUPPLY" || { echo "Power supply already used by another target"; return 1; }
+    sleep 1
+    rmdir "$POWER_SUPPLY"
+}
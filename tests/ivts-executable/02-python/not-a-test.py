@@ -0,0 +1,4 @@
+#!/usr/bin/env python3
+
+# Not matching the default test file patterns, so not discovered
+raise SystemExit(1)
@@ -0,0 +1,7 @@
+#!/usr/bin/env python3
+
+import sys
+
+target, out_dir = sys.argv[1:]
+print(f"Running on {target}, writing to {out_dir}")
+sys.exit(0 if target in ("foo", "bar") else 1)
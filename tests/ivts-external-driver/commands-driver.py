@@ -0,0 +1,45 @@
+#!/usr/bin/env python3
+
+# External batrun driver for test files made of `name: shell command` lines, each line being a
+# test case (or a fixture if named setup or teardown)
+
+import json
+import os
+import subprocess
+import sys
+
+
+def commands(test_suite_dir, test_file):
+    with open(os.path.join(test_suite_dir, test_file)) as file:
+        lines = [line.split(":", 1) for line in file if ":" in line and not line.startswith("#")]
+    return {name.strip(): command.strip() for name, command in lines}
+
+
+def discover(request):
+    response = {"test-files": []}
+    fixture = request["global-fixture"]
+    if fixture:
+        names = commands(request["test-suite-dir"], fixture)
+        response["fixture"] = {name: name for name in ("setup", "teardown") if name in names}
+    for test_file in request["test-files"]:
+        names = commands(request["test-suite-dir"], test_file)
+        discovered = {name: name for name in ("setup", "teardown") if name in names}
+        discovered["path"] = test_file
+        discovered["test-cases"] = [name for name in names if name not in ("setup", "teardown")]
+        response["test-files"].append(discovered)
+    return response
+
+
+def run(request):
+    command = commands(request["test-suite-dir"], request["test-file"])[request["test-case"]]
+    env = dict(os.environ, TARGET=request["target"], OUT_DIR=request["out-dir"])
+    exit_code = subprocess.call(["sh", "-c", command], env=env, stdout=sys.stderr)
+    if exit_code == 77:
+        return {"status": "skipped", "reason": "not supported by the target"}
+    if exit_code != 0:
+        return {"status": "failed"}
+    return {"status": "passed", "metrics": {"exit_code": exit_code}}
+
+
+request = json.load(sys.stdin)
+json.dump({"discover": discover, "run": run}[sys.argv[1]](request), sys.stdout)
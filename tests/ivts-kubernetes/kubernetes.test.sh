@@ -0,0 +1,11 @@
+#!/bin/bash
+
+function test_01_env_points_to_pod {
+    [ "$BATRUN_K8S_NAMESPACE" = "ci" ]
+    [ "$BATRUN_K8S_POD" = "fake-pod" ]
+    [[ "$BATRUN_K8S_JOB" = batrun-k8s-ok-* ]]
+}
+
+function test_02_pod_logs_streamed {
+    grep "fake simulator ready" "$2/../kubernetes.pod.log"
+}
@@ -0,0 +1,6 @@
+#!/bin/bash
+
+# The lock directory is relative to the output directory given with --out-dir
+function test_01_instrument_locked_while_running {
+    grep "bench-scope" "$2/../../../lab-locks/oscilloscope.lock"
+}
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+# Mimic the console of an emulated board
+echo "Booting fake board..."
+case "$1" in
+    ok) echo "fake-board login:" ;;
+    crash) echo "Kernel panic"; exit 1 ;;
+esac
+sleep 60
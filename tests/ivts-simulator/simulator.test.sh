@@ -0,0 +1,14 @@
+#!/bin/bash
+
+function test_01_env_points_to_simulator {
+    [ "$SIMULATOR_PORT" = "2222" ]
+}
+
+function test_02_console_captured {
+    grep "login:" "$BATRUN_SIMULATOR_CONSOLE_LOG"
+}
+
+# Expected to fail, the end of the simulator console is copied next to its log
+function test_03_console_copied_on_failure {
+    false
+}
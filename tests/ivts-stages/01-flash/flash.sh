@@ -0,0 +1,7 @@
+#!/bin/bash
+
+# Flashing fails on target bar, which must skip the next stages for bar only
+function test_flash {
+    local -r DEVICE="$1"
+    [[ "$DEVICE" != "bar" ]]
+}
@@ -0,0 +1,5 @@
+#!/bin/bash
+
+function test_full {
+    return 0
+}
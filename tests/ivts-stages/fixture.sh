@@ -0,0 +1,9 @@
+#!/bin/bash
+
+function setup {
+    return 0
+}
+
+function teardown {
+    return 0
+}
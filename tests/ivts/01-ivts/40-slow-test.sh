@@ -0,0 +1,7 @@
+#!/bin/bash
+
+# @expected-duration: 200ms
+function test_slower_than_expected {
+    sleep 1
+    return 0
+}
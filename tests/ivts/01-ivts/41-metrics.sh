@@ -0,0 +1,16 @@
+#!/bin/bash
+
+function test_01_metric_within_threshold {
+    export BATRUN_METRIC_boot_time=8.5
+    return 0
+}
+
+function test_02_metric_above_threshold {
+    export BATRUN_METRIC_boot_time=12.3
+    return 0
+}
+
+function test_03_metric_not_a_number {
+    export BATRUN_METRIC_boot_time=fast
+    return 0
+}
@@ -0,0 +1,11 @@
+#!/bin/bash
+
+# @only-targets: foo
+function test_01_only_on_foo {
+    [ "$1" = "foo" ]
+}
+
+# Restricted to target bar by the test suite config
+function test_02_only_on_bar {
+    [ "$1" = "bar" ]
+}
@@ -0,0 +1,13 @@
+#!/bin/bash
+
+# @timeout: 5s
+
+function test_01_within_file_timeout {
+    sleep 0.1
+}
+
+# Expected to fail, killed once its own timeout is reached
+# @timeout: 500ms
+function test_02_killed_after_test_case_timeout {
+    sleep 30
+}
@@ -0,0 +1,12 @@
+#!/bin/bash
+
+# @needs-file: fixture.sh
+function test_01_needed_file_exists {
+    true
+}
+
+# Expected to be skipped, the file it needs does not exist
+# @needs-file: data/missing-input.bin
+function test_02_needed_file_missing {
+    false
+}
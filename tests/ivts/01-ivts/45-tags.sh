@@ -0,0 +1,15 @@
+#!/bin/bash
+
+# Select these test cases with `--include-tag tags`, or skip the network ones with
+# `--exclude-tag network`
+# @tags: tags
+
+# @tags: network
+function test_01_network {
+    true
+}
+
+# @tags: storage, slow
+function test_02_storage {
+    true
+}
@@ -0,0 +1,10 @@
+#!/bin/bash
+
+# Declared as not working on target baz by the test suite config, so skipped there
+function test_01_not_on_baz {
+    [ "$1" != "baz" ]
+}
+
+function test_02_everywhere {
+    true
+}
@@ -0,0 +1,13 @@
+#!/bin/bash
+
+# Declares itself as expected to fail, reported as XFAILED
+function test_01_known_bug {
+    export BATRUN_XFAIL="known bug in the frobnicator"
+    false
+}
+
+# Expected to fail according to the test suite config but passes, reported as XPASSED (which
+# fails the run with `--strict-xfail`)
+function test_02_fixed_bug {
+    true
+}
@@ -0,0 +1,17 @@
+#!/bin/bash
+
+# Run once per parameter printed by test_01_interface_params, reported as
+# `test_01_interface[lo]` and `test_01_interface[eth0]`
+function test_01_interface {
+    [[ "$BATRUN_PARAM" == "lo" || "$BATRUN_PARAM" == "eth0" ]]
+}
+
+function test_01_interface_params {
+    echo lo
+    echo eth0
+}
+
+# Run once per parameter given by the test suite config
+function test_02_baudrate {
+    [[ "$BATRUN_PARAM" =~ ^[0-9]+$ ]]
+}
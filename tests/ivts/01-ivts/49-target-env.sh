@@ -0,0 +1,15 @@
+#!/bin/bash
+
+# The environment variables and properties of the target declared in the test suite config are
+# passed to the test processes
+function test_01_target_env {
+    local target=$1
+    if [[ "$target" == "bar" ]]; then
+        [[ "$LOG_LEVEL" == "debug" ]] &&
+            [[ "$BATRUN_TARGET_ADDRESS" == "192.0.2.2" ]] &&
+            [[ "$BATRUN_TARGET_SERIAL_PORT" == "/dev/ttyUSB0" ]] &&
+            [[ "$BATRUN_TARGET_BAUD_RATE" == "115200" ]]
+    else
+        [[ -z "${BATRUN_TARGET_ADDRESS:-}" ]]
+    fi
+}
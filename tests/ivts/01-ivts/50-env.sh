@@ -0,0 +1,11 @@
+#!/bin/bash
+
+# The environment variables of the test suite config are overridden by the ones of the target,
+# themselves overridden by the ones given with `--env`
+function test_01_env {
+    local target=$1
+    case "$target" in
+        bar) [[ "$LOG_LEVEL" == "debug" ]] ;;
+        *) [[ "$LOG_LEVEL" == "info" ]] ;;
+    esac
+}
@@ -0,0 +1,27 @@
+#!/bin/bash
+
+# Reported with its message and metrics
+function test_01_message_and_metrics {
+    export BATRUN_MESSAGE="Booted in 8.5s"
+    export BATRUN_METRICS="boot_time=8.5 memory=512"
+    return 0
+}
+
+# Collects an artifact written to the output directory, warns about the missing one
+function test_02_artifacts {
+    echo "Output of test 02" > "$2/test_02.txt"
+    export BATRUN_ARTIFACTS="test_02.txt:missing.txt"
+    return 0
+}
+
+# One of the metrics is not a number and is ignored with a warning
+function test_03_invalid_metrics {
+    export BATRUN_METRICS="boot_time=8.5 memory=lots"
+    return 0
+}
+
+# Collects the files dropped into its artifacts directory
+function test_04_artifacts_dir {
+    echo "Dump of test 04" > "$BATRUN_ARTIFACTS_DIR/dump.txt"
+    return 0
+}
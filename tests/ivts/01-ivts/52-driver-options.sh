@@ -0,0 +1,9 @@
+#!/bin/bash
+
+# Only parses with the `extglob` shell option enabled by the `driver-options` of the test suite
+function test_01_extglob {
+    case "$1" in
+        @(foo|bar|baz)) return 0 ;;
+        *) return 1 ;;
+    esac
+}
@@ -0,0 +1,14 @@
+#!/bin/bash
+
+# Runs in the strict mode of the test suite, exiting at the first failing command
+function test_01_strict_mode {
+    [[ $- == *e* ]]
+    [[ $- == *u* ]]
+}
+
+# @shell-options: none
+function test_02_legacy {
+    echo "Unset variable: ${UNSET_VARIABLE}"
+    false
+    return 0
+}